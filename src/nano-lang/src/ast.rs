@@ -20,6 +20,7 @@ use std::{fmt, ops::Range, sync::Arc};
 use vec1::Vec1;
 
 pub const ASSERT_VARIABLE: &str = "_try";
+pub const BACKPASS_VARIABLE: &str = "_backpass";
 pub const CAPTURE_VARIABLE: &str = "_capture";
 pub const PIPE_VARIABLE: &str = "_pipe";
 pub const TRY_VARIABLE: &str = "_try";
@@ -117,6 +118,8 @@ fn str_to_keyword(word: &str) -> Option<Token> {
         "const" => Some(Token::Const),
         "fn" => Some(Token::Fn),
         "if" => Some(Token::If),
+        "impl" => Some(Token::Impl),
+        "interface" => Some(Token::Interface),
         "use" => Some(Token::Use),
         "let" => Some(Token::Let),
         "opaque" => Some(Token::Opaque),
@@ -144,9 +147,36 @@ pub struct Function<T, Expr> {
     pub public: bool,
     pub return_annotation: Option<Annotation>,
     pub return_type: T,
+    pub type_parameters: Vec<TypeParameter>,
     pub end_position: usize,
 }
 
+/// A type variable declared ahead of a function's arguments, along with the
+/// interfaces it must implement, as in `fn max<a: Ord>(left: a, right: a)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub location: Span,
+    pub name: String,
+    pub constraints: Vec<Constraint>,
+}
+
+/// An interface, possibly qualified by the module it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub location: Span,
+    pub module: Option<String>,
+    pub name: String,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{module}.{}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
 pub type TypedTypeAlias = TypeAlias<Arc<Type>>;
 pub type UntypedTypeAlias = TypeAlias<()>;
 
@@ -235,6 +265,7 @@ impl TypedDataType {
                     sugar: false,
                 },
             ],
+            derive: vec![],
             doc: None,
             location: Span::empty(),
             name: "Ordering".to_string(),
@@ -272,6 +303,7 @@ impl TypedDataType {
                     sugar: false,
                 },
             ],
+            derive: vec![],
             doc: None,
             location: Span::empty(),
             name: "Option".to_string(),
@@ -281,6 +313,42 @@ impl TypedDataType {
             typed_parameters: vec![tipo],
         }
     }
+
+    /// Fractions in lowest terms, with a positive denominator. The
+    /// constructor isn't exposed, so values only come from normalising
+    /// prelude functions and literals.
+    pub fn rational() -> Self {
+        let field = |label: &str| RecordConstructorArg {
+            label: Some(label.to_string()),
+            annotation: Annotation::Constructor {
+                location: Span::empty(),
+                module: None,
+                name: "Int".to_string(),
+                arguments: vec![],
+            },
+            location: Span::empty(),
+            tipo: builtins::int(),
+            doc: None,
+        };
+
+        DataType {
+            constructors: vec![RecordConstructor {
+                location: Span::empty(),
+                name: "Rational".to_string(),
+                arguments: vec![field("numerator"), field("denominator")],
+                doc: None,
+                sugar: false,
+            }],
+            derive: vec![],
+            doc: None,
+            location: Span::empty(),
+            name: "Rational".to_string(),
+            opaque: true,
+            parameters: vec![],
+            public: true,
+            typed_parameters: vec![],
+        }
+    }
 }
 
 pub type UntypedDataType = DataType<()>;
@@ -288,6 +356,9 @@ pub type UntypedDataType = DataType<()>;
 #[derive(Debug, Clone, PartialEq)]
 pub struct DataType<T> {
     pub constructors: Vec<RecordConstructor<T>>,
+    /// Prelude interfaces implemented by generated code, as in
+    /// `derive(Eq, Ord)`.
+    pub derive: Vec<Constraint>,
     pub doc: Option<String>,
     pub location: Span,
     pub name: String,
@@ -309,20 +380,56 @@ pub struct Use<PackageName> {
     pub unqualified: Vec<UnqualifiedImport>,
 }
 
-pub type TypedModuleConstant = ModuleConstant<Arc<Type>>;
-pub type UntypedModuleConstant = ModuleConstant<()>;
+pub type TypedModuleConstant = ModuleConstant<Arc<Type>, TypedExpr>;
+pub type UntypedModuleConstant = ModuleConstant<(), UntypedExpr>;
 
+/// A constant whose value is any expression evaluable at compile-time, from
+/// literals to calls of functions on other constants.
 #[derive(Debug, Clone, PartialEq)]
-pub struct ModuleConstant<T> {
+pub struct ModuleConstant<T, Expr> {
     pub doc: Option<String>,
     pub location: Span,
     pub public: bool,
     pub name: String,
     pub annotation: Option<Annotation>,
-    pub value: Box<Constant>,
+    pub value: Box<Expr>,
     pub tipo: T,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub doc: Option<String>,
+    pub end_position: usize,
+    pub location: Span,
+    pub methods: Vec<InterfaceMethod>,
+    pub name: String,
+    pub parameter: String,
+    pub public: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceMethod {
+    pub arguments: Vec<UntypedArg>,
+    pub doc: Option<String>,
+    pub location: Span,
+    pub name: String,
+    pub return_annotation: Annotation,
+}
+
+pub type TypedImplementation = Implementation<Arc<Type>, TypedExpr>;
+pub type UntypedImplementation = Implementation<(), UntypedExpr>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Implementation<T, Expr> {
+    pub annotation: Annotation,
+    pub doc: Option<String>,
+    pub end_position: usize,
+    pub interface: Constraint,
+    pub location: Span,
+    pub methods: Vec<Function<T, Expr>>,
+    pub type_parameters: Vec<TypeParameter>,
+}
+
 pub type TypedValidator = Validator<Arc<Type>, TypedExpr>;
 pub type UntypedValidator = Validator<(), UntypedExpr>;
 
@@ -331,11 +438,74 @@ pub struct Validator<T, Expr> {
     pub doc: Option<String>,
     pub end_position: usize,
     pub fun: Function<T, Expr>,
-    pub other_fun: Option<Function<T, Expr>>,
+    /// The handlers of the other purposes of a multi-validator.
+    pub other_funs: Vec<Function<T, Expr>>,
     pub location: Span,
     pub params: Vec<Arg<T>>,
 }
 
+impl<T, Expr> Validator<T, Expr> {
+    /// Every function of the validator, along with the purpose it handles.
+    pub fn handlers(&self) -> impl Iterator<Item = (Purpose, &Function<T, Expr>)> {
+        std::iter::once(&self.fun)
+            .chain(&self.other_funs)
+            .map(|fun| (Purpose::of(fun), fun))
+    }
+}
+
+/// What a script is run for, as told by the `ScriptPurpose` of its context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Purpose {
+    Mint,
+    Spend,
+    Withdraw,
+    Certify,
+}
+
+impl Purpose {
+    /// The purpose a handler is named after or else, going by its arguments,
+    /// spending for handlers given a datum and minting for the others.
+    pub fn of<T, Expr>(fun: &Function<T, Expr>) -> Self {
+        match fun.name.as_str() {
+            "mint" => Purpose::Mint,
+            "spend" => Purpose::Spend,
+            "withdraw" => Purpose::Withdraw,
+            "certify" => Purpose::Certify,
+            _ if fun.arguments.len() == 3 => Purpose::Spend,
+            _ => Purpose::Mint,
+        }
+    }
+
+    /// The index of the matching `ScriptPurpose` constructor.
+    pub fn constr_index(&self) -> usize {
+        match self {
+            Purpose::Mint => 0,
+            Purpose::Spend => 1,
+            Purpose::Withdraw => 2,
+            Purpose::Certify => 3,
+        }
+    }
+
+    /// Spending handlers take a datum ahead of the redeemer and the context.
+    pub fn arity(&self) -> usize {
+        match self {
+            Purpose::Spend => 3,
+            Purpose::Mint | Purpose::Withdraw | Purpose::Certify => 2,
+        }
+    }
+}
+
+impl fmt::Display for Purpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Purpose::Mint => "mint",
+            Purpose::Spend => "spend",
+            Purpose::Withdraw => "withdraw",
+            Purpose::Certify => "certify",
+        })
+    }
+}
+
 pub type TypedDefinition = Definition<Arc<Type>, TypedExpr, String>;
 pub type UntypedDefinition = Definition<(), UntypedExpr, ()>;
 
@@ -349,11 +519,15 @@ pub enum Definition<T, Expr, PackageName> {
 
     Use(Use<PackageName>),
 
-    ModuleConstant(ModuleConstant<T>),
+    ModuleConstant(ModuleConstant<T, Expr>),
 
     Test(Function<T, Expr>),
 
     Validator(Validator<T, Expr>),
+
+    Interface(Interface),
+
+    Implementation(Implementation<T, Expr>),
 }
 
 impl<A, B, C> Definition<A, B, C> {
@@ -365,6 +539,8 @@ impl<A, B, C> Definition<A, B, C> {
             | Definition::DataType(DataType { location, .. })
             | Definition::ModuleConstant(ModuleConstant { location, .. })
             | Definition::Validator(Validator { location, .. })
+            | Definition::Interface(Interface { location, .. })
+            | Definition::Implementation(Implementation { location, .. })
             | Definition::Test(Function { location, .. }) => *location,
         }
     }
@@ -377,6 +553,8 @@ impl<A, B, C> Definition<A, B, C> {
             | Definition::DataType(DataType { doc, .. })
             | Definition::ModuleConstant(ModuleConstant { doc, .. })
             | Definition::Validator(Validator { doc, .. })
+            | Definition::Interface(Interface { doc, .. })
+            | Definition::Implementation(Implementation { doc, .. })
             | Definition::Test(Function { doc, .. }) => {
                 let _ = std::mem::replace(doc, Some(new_doc));
             }
@@ -391,6 +569,8 @@ impl<A, B, C> Definition<A, B, C> {
             | Definition::DataType(DataType { doc, .. })
             | Definition::ModuleConstant(ModuleConstant { doc, .. })
             | Definition::Validator(Validator { doc, .. })
+            | Definition::Interface(Interface { doc, .. })
+            | Definition::Implementation(Implementation { doc, .. })
             | Definition::Test(Function { doc, .. }) => doc.clone(),
         }
     }
@@ -398,14 +578,32 @@ impl<A, B, C> Definition<A, B, C> {
 
 impl TypedDefinition {
     pub fn find_node(&self, byte_index: usize) -> Option<Located<'_>> {
-        if let Definition::Fn(Function { body, .. })
-        | Definition::Validator(Validator {
-            fun: Function { body, .. },
-            ..
-        })
-        | Definition::Test(Function { body, .. }) = self
-        {
-            if let Some(expression) = body.find_node(byte_index) {
+        if let Definition::Fn(fun) | Definition::Test(fun) = self {
+            if let Some(expression) = fun.body.find_node(byte_index) {
+                return Some(Located::Expression(expression));
+            }
+        }
+
+        if let Definition::Validator(validator) = self {
+            if let Some(expression) = validator
+                .handlers()
+                .find_map(|(_, fun)| fun.body.find_node(byte_index))
+            {
+                return Some(Located::Expression(expression));
+            }
+        }
+
+        if let Definition::ModuleConstant(ModuleConstant { value, .. }) = self {
+            if let Some(expression) = value.find_node(byte_index) {
+                return Some(Located::Expression(expression));
+            }
+        }
+
+        if let Definition::Implementation(Implementation { methods, .. }) = self {
+            if let Some(expression) = methods
+                .iter()
+                .find_map(|method| method.body.find_node(byte_index))
+            {
                 return Some(Located::Expression(expression));
             }
         }
@@ -789,6 +987,24 @@ pub enum Pattern<Constructor, Type> {
         value: String,
     },
 
+    /// Integers from `start` to `end`, both included.
+    IntRange {
+        location: Span,
+        start: String,
+        end: String,
+    },
+
+    String {
+        location: Span,
+        value: String,
+    },
+
+    ByteArray {
+        location: Span,
+        value: Vec<u8>,
+        preferred_format: ByteArrayFormatPreference,
+    },
+
     Var {
         location: Span,
         name: String,
@@ -833,6 +1049,9 @@ impl<A, B> Pattern<A, B> {
         match self {
             Pattern::Assign { pattern, .. } => pattern.location(),
             Pattern::Int { location, .. }
+            | Pattern::IntRange { location, .. }
+            | Pattern::String { location, .. }
+            | Pattern::ByteArray { location, .. }
             | Pattern::Var { location, .. }
             | Pattern::List { location, .. }
             | Pattern::Discard { location, .. }
@@ -848,6 +1067,26 @@ impl<A, B> Pattern<A, B> {
     pub fn is_var(&self) -> bool {
         matches!(self, Self::Var { .. })
     }
+
+    /// The first literal, or range of literals, found in the pattern.
+    pub fn find_literal(&self) -> Option<&Self> {
+        match self {
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => Some(self),
+            Pattern::Var { .. } | Pattern::Discard { .. } => None,
+            Pattern::Assign { pattern, .. } => pattern.find_literal(),
+            Pattern::List { elements, tail, .. } => elements
+                .iter()
+                .chain(tail.as_deref())
+                .find_map(Pattern::find_literal),
+            Pattern::Constructor { arguments, .. } => arguments
+                .iter()
+                .find_map(|argument| argument.value.find_literal()),
+            Pattern::Tuple { elems, .. } => elems.iter().find_map(Pattern::find_literal),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -861,11 +1100,18 @@ pub enum ByteArrayFormatPreference {
 pub enum AssignmentKind {
     Let,
     Expect,
+    /// `let pattern <- value`, where the rest of the sequence becomes a
+    /// callback given to `value` as its last argument.
+    Backpass,
 }
 
 impl AssignmentKind {
     pub fn is_let(&self) -> bool {
-        matches!(self, AssignmentKind::Let)
+        matches!(self, AssignmentKind::Let | AssignmentKind::Backpass)
+    }
+
+    pub fn is_backpass(&self) -> bool {
+        matches!(self, AssignmentKind::Backpass)
     }
 
     pub fn is_expect(&self) -> bool {
@@ -874,7 +1120,7 @@ impl AssignmentKind {
 
     pub fn location_offset(&self) -> usize {
         match self {
-            AssignmentKind::Let => 3,
+            AssignmentKind::Let | AssignmentKind::Backpass => 3,
             AssignmentKind::Expect => 6,
         }
     }
@@ -9,12 +9,16 @@
 */
 
 use crate::{
-    ast::{Arg, ArgName, CallArg, Function, ModuleKind, Span, TypedDataType, TypedFunction, UnOp},
+    ast::{
+        Arg, ArgName, AssignmentKind, BinOp, CallArg, Function, IfBranch, ModuleKind, Pattern,
+        Span, TypedArg, TypedDataType, TypedFunction, UnOp,
+    },
     expr::TypedExpr,
     gen_uplc::builder::{DataTypeKey, FunctionAccessKey},
     tipo::{
-        fields::FieldMap, Type, TypeConstructor, TypeInfo, TypeVar, ValueConstructor,
-        ValueConstructorVariant,
+        fields::FieldMap, ImplementationConstructor, ImplementationKey, InterfaceConstructor,
+        InterfaceKey, ModuleValueConstructor, Type, TypeConstructor, TypeInfo, TypeVar,
+        ValueConstructor, ValueConstructorVariant,
     },
     IdGenerator,
 };
@@ -22,6 +26,7 @@ use indexmap::IndexMap;
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 use strum::IntoEnumIterator;
 use untyped_plutus_core::builtins::DefaultFunction;
+use vec1::vec1;
 
 pub const BYTE_ARRAY: &str = "ByteArray";
 pub const BOOL: &str = "Bool";
@@ -33,7 +38,16 @@ pub const RESULT: &str = "Result";
 pub const STRING: &str = "String";
 pub const OPTION: &str = "Option";
 pub const ORDERING: &str = "Ordering";
+pub const RATIONAL: &str = "Rational";
 pub const REDEEMER_WRAPPER: &str = "RedeemerWrapper";
+pub const EQ: &str = "Eq";
+pub const ORD: &str = "Ord";
+pub const SERIALISE: &str = "Serialise";
+
+/// The alias every module has the prelude imported under, which generated
+/// code uses to refer to it whatever the module defines. It isn't a valid
+/// module name, so source code can neither use nor shadow it.
+pub const PRELUDE_ALIAS: &str = "@nano";
 
 pub fn prelude(id_gen: &IdGenerator) -> TypeInfo {
     let mut prelude = TypeInfo {
@@ -44,6 +58,10 @@ pub fn prelude(id_gen: &IdGenerator) -> TypeInfo {
         types_constructors: HashMap::new(),
         values: HashMap::new(),
         accessors: HashMap::new(),
+        interfaces: HashMap::new(),
+        implementations: HashMap::new(),
+        constraints: HashMap::new(),
+        type_parameters: HashMap::new(),
     };
 
     prelude.types.insert(
@@ -364,7 +382,171 @@ pub fn prelude(id_gen: &IdGenerator) -> TypeInfo {
         ),
     );
 
+    prelude.types.insert(
+        RATIONAL.to_string(),
+        TypeConstructor {
+            location: Span::empty(),
+            parameters: vec![],
+            tipo: rational(),
+            module: "".to_string(),
+            public: true,
+        },
+    );
+
     prelude
+        .types_constructors
+        .insert(RATIONAL.to_string(), vec![RATIONAL.to_string()]);
+
+    for (name, tipo) in rational_functions() {
+        let arity = tipo.arg_types().map(|args| args.len()).unwrap_or_default();
+
+        prelude.values.insert(
+            name.to_string(),
+            ValueConstructor::public(
+                tipo,
+                ValueConstructorVariant::ModuleFn {
+                    name: name.to_string(),
+                    field_map: None,
+                    module: "".to_string(),
+                    arity,
+                    location: Span::empty(),
+                    builtin: None,
+                },
+            ),
+        );
+    }
+
+    let eq_parameter = id_gen.next();
+    let eq_var = generic_var(eq_parameter);
+    prelude_interface(
+        &mut prelude,
+        EQ,
+        eq_parameter,
+        &[("equals", function(vec![eq_var.clone(), eq_var], bool()))],
+        &[INT, BYTE_ARRAY, STRING, BOOL, DATA, RATIONAL],
+    );
+
+    let ord_parameter = id_gen.next();
+    let ord_var = generic_var(ord_parameter);
+    prelude_interface(
+        &mut prelude,
+        ORD,
+        ord_parameter,
+        &[(
+            "compare",
+            function(vec![ord_var.clone(), ord_var], ordering()),
+        )],
+        &[INT, BYTE_ARRAY, RATIONAL],
+    );
+
+    let serialise_parameter = id_gen.next();
+    let serialise_var = generic_var(serialise_parameter);
+    prelude_interface(
+        &mut prelude,
+        SERIALISE,
+        serialise_parameter,
+        &[
+            ("to_data", function(vec![serialise_var.clone()], data())),
+            ("from_data", function(vec![data()], serialise_var)),
+        ],
+        &[INT, BYTE_ARRAY, STRING, BOOL, DATA, RATIONAL],
+    );
+
+    prelude
+}
+
+/// Signatures of the prelude functions on `Rational`, defined in
+/// [`prelude_functions`].
+fn rational_functions() -> Vec<(&'static str, Arc<Type>)> {
+    let binary = || function(vec![rational(), rational()], rational());
+    let rounding = || function(vec![rational()], int());
+
+    vec![
+        ("rational", function(vec![int(), int()], rational())),
+        ("rational_from_int", function(vec![int()], rational())),
+        ("rational_numerator", rounding()),
+        ("rational_denominator", rounding()),
+        ("rational_add", binary()),
+        ("rational_sub", binary()),
+        ("rational_mul", binary()),
+        ("rational_div", binary()),
+        ("rational_negate", function(vec![rational()], rational())),
+        ("rational_floor", rounding()),
+        ("rational_ceil", rounding()),
+        ("rational_truncate", rounding()),
+        ("rational_round", rounding()),
+    ]
+}
+
+/// Registers a prelude interface along with its implementations for prelude
+/// types, see [`prelude_functions`].
+fn prelude_interface(
+    prelude: &mut TypeInfo,
+    name: &str,
+    parameter: u64,
+    methods: &[(&str, Arc<Type>)],
+    implemented_by: &[&str],
+) {
+    let key = InterfaceKey {
+        module_name: "".to_string(),
+        interface_name: name.to_string(),
+    };
+
+    for (method, tipo) in methods {
+        let arity = match tipo.as_ref() {
+            Type::Fn { args, .. } => args.len(),
+            _ => unreachable!("interface methods are functions"),
+        };
+
+        prelude.values.insert(
+            method.to_string(),
+            ValueConstructor::public(
+                tipo.clone(),
+                ValueConstructorVariant::ModuleFn {
+                    name: method.to_string(),
+                    field_map: None,
+                    module: "".to_string(),
+                    arity,
+                    location: Span::empty(),
+                    builtin: None,
+                },
+            ),
+        );
+
+        prelude
+            .constraints
+            .insert(method.to_string(), vec![(parameter, key.clone())]);
+    }
+
+    prelude.interfaces.insert(
+        name.to_string(),
+        InterfaceConstructor {
+            public: true,
+            location: Span::empty(),
+            module: "".to_string(),
+            name: name.to_string(),
+            parameter,
+            methods: methods
+                .iter()
+                .map(|(method, tipo)| (method.to_string(), tipo.clone()))
+                .collect(),
+        },
+    );
+
+    for type_name in implemented_by {
+        prelude.implementations.insert(
+            ImplementationKey {
+                interface: key.clone(),
+                module_name: "".to_string(),
+                type_name: type_name.to_string(),
+            },
+            ImplementationConstructor {
+                location: Span::empty(),
+                module: "".to_string(),
+                arguments: vec![],
+            },
+        );
+    }
 }
 
 pub fn plutus(id_gen: &IdGenerator) -> TypeInfo {
@@ -376,6 +558,10 @@ pub fn plutus(id_gen: &IdGenerator) -> TypeInfo {
         types_constructors: HashMap::new(),
         values: HashMap::new(),
         accessors: HashMap::new(),
+        interfaces: HashMap::new(),
+        implementations: HashMap::new(),
+        constraints: HashMap::new(),
+        type_parameters: HashMap::new(),
     };
 
     for builtin in DefaultFunction::iter() {
@@ -678,6 +864,7 @@ pub fn prelude_functions(id_gen: &IdGenerator) -> IndexMap<FunctionAccessKey, Ty
             public: true,
             return_annotation: None,
             return_type: bool(),
+            type_parameters: vec![],
             end_position: 0,
             body: TypedExpr::UnOp {
                 location: Span::empty(),
@@ -735,6 +922,7 @@ pub fn prelude_functions(id_gen: &IdGenerator) -> IndexMap<FunctionAccessKey, Ty
             public: true,
             return_annotation: None,
             return_type: a_var,
+            type_parameters: vec![],
             end_position: 0,
         },
     );
@@ -789,6 +977,7 @@ pub fn prelude_functions(id_gen: &IdGenerator) -> IndexMap<FunctionAccessKey, Ty
             public: true,
             return_annotation: None,
             return_type: a_var,
+            type_parameters: vec![],
             end_position: 0,
         },
     );
@@ -901,13 +1090,715 @@ pub fn prelude_functions(id_gen: &IdGenerator) -> IndexMap<FunctionAccessKey, Ty
             public: true,
             return_annotation: None,
             return_type,
+            type_parameters: vec![],
             end_position: 0,
         },
     );
 
+    let eq = InterfaceKey {
+        module_name: "".to_string(),
+        interface_name: EQ.to_string(),
+    };
+
+    for tipo in [int(), byte_array(), string(), bool(), data(), rational()] {
+        let equals = TypedExpr::BinOp {
+            location: Span::empty(),
+            tipo: bool(),
+            name: BinOp::Eq,
+            left: local_var("left", tipo.clone()).into(),
+            right: local_var("right", tipo.clone()).into(),
+        };
+
+        insert_implementation(
+            &mut functions,
+            &eq,
+            "equals",
+            tipo.clone(),
+            &[("left", tipo.clone()), ("right", tipo)],
+            bool(),
+            equals,
+        );
+    }
+
+    let ord = InterfaceKey {
+        module_name: "".to_string(),
+        interface_name: ORD.to_string(),
+    };
+
+    let less_than_int = TypedExpr::BinOp {
+        location: Span::empty(),
+        tipo: bool(),
+        name: BinOp::LtInt,
+        left: local_var("left", int()).into(),
+        right: local_var("right", int()).into(),
+    };
+
+    let equals_int = TypedExpr::BinOp {
+        location: Span::empty(),
+        tipo: bool(),
+        name: BinOp::Eq,
+        left: local_var("left", int()).into(),
+        right: local_var("right", int()).into(),
+    };
+
+    insert_implementation(
+        &mut functions,
+        &ord,
+        "compare",
+        int(),
+        &[("left", int()), ("right", int())],
+        ordering(),
+        compare_with(less_than_int, equals_int),
+    );
+
+    let less_than_bytearray = builtin_call(
+        DefaultFunction::LessThanByteString,
+        vec![
+            local_var("left", byte_array()),
+            local_var("right", byte_array()),
+        ],
+        bool(),
+    );
+
+    let equals_bytearray = TypedExpr::BinOp {
+        location: Span::empty(),
+        tipo: bool(),
+        name: BinOp::Eq,
+        left: local_var("left", byte_array()).into(),
+        right: local_var("right", byte_array()).into(),
+    };
+
+    insert_implementation(
+        &mut functions,
+        &ord,
+        "compare",
+        byte_array(),
+        &[("left", byte_array()), ("right", byte_array())],
+        ordering(),
+        compare_with(less_than_bytearray, equals_bytearray),
+    );
+
+    // Denominators are positive, so fractions compare like their cross
+    // products.
+    let cross_products = || {
+        let left = local_var("left", rational());
+        let right = local_var("right", rational());
+
+        (
+            int_op(
+                BinOp::MultInt,
+                rational_field(left.clone(), 0),
+                rational_field(right.clone(), 1),
+            ),
+            int_op(
+                BinOp::MultInt,
+                rational_field(right, 0),
+                rational_field(left, 1),
+            ),
+        )
+    };
+
+    let (left, right) = cross_products();
+    let less_than_rational = int_op(BinOp::LtInt, left, right);
+
+    let (left, right) = cross_products();
+    let equals_rational = int_op(BinOp::Eq, left, right);
+
+    insert_implementation(
+        &mut functions,
+        &ord,
+        "compare",
+        rational(),
+        &[("left", rational()), ("right", rational())],
+        ordering(),
+        compare_with(less_than_rational, equals_rational),
+    );
+
+    let serialise = InterfaceKey {
+        module_name: "".to_string(),
+        interface_name: SERIALISE.to_string(),
+    };
+
+    // Conversions go through assignments to and from `Data`, which share
+    // their encoding with every other value crossing that boundary.
+    for tipo in [int(), byte_array(), string(), bool(), data(), rational()] {
+        let to_data = TypedExpr::Sequence {
+            location: Span::empty(),
+            expressions: vec![
+                TypedExpr::Assignment {
+                    location: Span::empty(),
+                    tipo: data(),
+                    value: local_var("value", tipo.clone()).into(),
+                    pattern: Pattern::Var {
+                        location: Span::empty(),
+                        name: "data".to_string(),
+                    },
+                    kind: AssignmentKind::Let,
+                },
+                local_var("data", data()),
+            ],
+        };
+
+        insert_implementation(
+            &mut functions,
+            &serialise,
+            "to_data",
+            tipo.clone(),
+            &[("value", tipo.clone())],
+            data(),
+            to_data,
+        );
+
+        let from_data = TypedExpr::Sequence {
+            location: Span::empty(),
+            expressions: vec![
+                TypedExpr::Assignment {
+                    location: Span::empty(),
+                    tipo: tipo.clone(),
+                    value: local_var("data", data()).into(),
+                    pattern: Pattern::Var {
+                        location: Span::empty(),
+                        name: "value".to_string(),
+                    },
+                    kind: AssignmentKind::Expect,
+                },
+                // Decoded fractions may come in any terms, and with a zero or
+                // negative denominator.
+                if tipo == rational() {
+                    prelude_call(
+                        "rational",
+                        vec![
+                            rational_field(local_var("value", tipo.clone()), 0),
+                            rational_field(local_var("value", tipo.clone()), 1),
+                        ],
+                        rational(),
+                    )
+                } else {
+                    local_var("value", tipo.clone())
+                },
+            ],
+        };
+
+        insert_implementation(
+            &mut functions,
+            &serialise,
+            "from_data",
+            tipo.clone(),
+            &[("data", data())],
+            tipo,
+            from_data,
+        );
+    }
+
+    rational_prelude_functions(&mut functions);
+
     functions
 }
 
+/// Fractions are kept in lowest terms with a positive denominator, so that
+/// they can be compared structurally and rounded with integer division.
+fn rational_prelude_functions(functions: &mut IndexMap<FunctionAccessKey, TypedFunction>) {
+    let value = || local_var("self", rational());
+    let numerator = || rational_field(value(), 0);
+    let denominator = || rational_field(value(), 1);
+
+    // Euclid's algorithm, where `%` takes the sign of the divisor. The result
+    // is positive unless both arguments are zero.
+    let a = || local_var("a", int());
+    let b = || local_var("b", int());
+
+    insert_function(
+        functions,
+        "rational_gcd",
+        &[("a", int()), ("b", int())],
+        int(),
+        if_else(
+            int_op(BinOp::Eq, b(), int_literal("0")),
+            if_else(
+                int_op(BinOp::LtInt, a(), int_literal("0")),
+                negate(a()),
+                a(),
+            ),
+            prelude_call(
+                "rational_gcd",
+                vec![b(), int_op(BinOp::ModInt, a(), b())],
+                int(),
+            ),
+        ),
+    );
+
+    let n = || local_var("numerator", int());
+    let d = || local_var("denominator", int());
+    let divisor = || local_var("divisor", int());
+
+    insert_function(
+        functions,
+        "rational",
+        &[("numerator", int()), ("denominator", int())],
+        rational(),
+        if_else(
+            int_op(BinOp::Eq, d(), int_literal("0")),
+            TypedExpr::ErrorTerm {
+                location: Span::empty(),
+                tipo: rational(),
+            },
+            TypedExpr::Sequence {
+                location: Span::empty(),
+                expressions: vec![
+                    let_(
+                        "divisor",
+                        if_else(
+                            int_op(BinOp::LtInt, d(), int_literal("0")),
+                            negate(prelude_call("rational_gcd", vec![n(), d()], int())),
+                            prelude_call("rational_gcd", vec![n(), d()], int()),
+                        ),
+                    ),
+                    rational_constructor(
+                        Span::empty(),
+                        int_op(BinOp::DivInt, n(), divisor()),
+                        int_op(BinOp::DivInt, d(), divisor()),
+                    ),
+                ],
+            },
+        ),
+    );
+
+    insert_function(
+        functions,
+        "rational_from_int",
+        &[("self", int())],
+        rational(),
+        rational_constructor(Span::empty(), local_var("self", int()), int_literal("1")),
+    );
+
+    insert_function(
+        functions,
+        "rational_numerator",
+        &[("self", rational())],
+        int(),
+        numerator(),
+    );
+
+    insert_function(
+        functions,
+        "rational_denominator",
+        &[("self", rational())],
+        int(),
+        denominator(),
+    );
+
+    let left = |index| rational_field(local_var("left", rational()), index);
+    let right = |index| rational_field(local_var("right", rational()), index);
+
+    let binary = [
+        (
+            "rational_add",
+            int_op(
+                BinOp::AddInt,
+                int_op(BinOp::MultInt, left(0), right(1)),
+                int_op(BinOp::MultInt, right(0), left(1)),
+            ),
+            int_op(BinOp::MultInt, left(1), right(1)),
+        ),
+        (
+            "rational_sub",
+            int_op(
+                BinOp::SubInt,
+                int_op(BinOp::MultInt, left(0), right(1)),
+                int_op(BinOp::MultInt, right(0), left(1)),
+            ),
+            int_op(BinOp::MultInt, left(1), right(1)),
+        ),
+        (
+            "rational_mul",
+            int_op(BinOp::MultInt, left(0), right(0)),
+            int_op(BinOp::MultInt, left(1), right(1)),
+        ),
+        (
+            "rational_div",
+            int_op(BinOp::MultInt, left(0), right(1)),
+            int_op(BinOp::MultInt, left(1), right(0)),
+        ),
+    ];
+
+    for (name, numerator, denominator) in binary {
+        insert_function(
+            functions,
+            name,
+            &[("left", rational()), ("right", rational())],
+            rational(),
+            prelude_call("rational", vec![numerator, denominator], rational()),
+        );
+    }
+
+    insert_function(
+        functions,
+        "rational_negate",
+        &[("self", rational())],
+        rational(),
+        rational_constructor(Span::empty(), negate(numerator()), denominator()),
+    );
+
+    // Half-way values round away from zero.
+    let half_up = |numerator| {
+        int_op(
+            BinOp::DivInt,
+            int_op(
+                BinOp::AddInt,
+                int_op(BinOp::MultInt, int_literal("2"), numerator),
+                denominator(),
+            ),
+            int_op(BinOp::MultInt, int_literal("2"), denominator()),
+        )
+    };
+
+    let rounding = [
+        (
+            "rational_floor",
+            int_op(BinOp::DivInt, numerator(), denominator()),
+        ),
+        (
+            "rational_ceil",
+            negate(int_op(BinOp::DivInt, negate(numerator()), denominator())),
+        ),
+        (
+            "rational_truncate",
+            builtin_call(
+                DefaultFunction::QuotientInteger,
+                vec![numerator(), denominator()],
+                int(),
+            ),
+        ),
+        (
+            "rational_round",
+            if_else(
+                int_op(BinOp::LtInt, numerator(), int_literal("0")),
+                negate(half_up(negate(numerator()))),
+                half_up(numerator()),
+            ),
+        ),
+    ];
+
+    for (name, body) in rounding {
+        insert_function(functions, name, &[("self", rational())], int(), body);
+    }
+}
+
+/// Defines the method of a prelude implementation for the given type.
+fn insert_implementation(
+    functions: &mut IndexMap<FunctionAccessKey, TypedFunction>,
+    interface: &InterfaceKey,
+    method: &str,
+    tipo: Arc<Type>,
+    arguments: &[(&str, Arc<Type>)],
+    return_type: Arc<Type>,
+    body: TypedExpr,
+) {
+    let Type::App { name, .. } = tipo.as_ref() else {
+        unreachable!("prelude implementations are for type constructors")
+    };
+
+    let name = ImplementationKey {
+        interface: interface.clone(),
+        module_name: "".to_string(),
+        type_name: name.clone(),
+    }
+    .method_name(method);
+
+    insert_function(functions, &name, arguments, return_type, body);
+}
+
+fn insert_function(
+    functions: &mut IndexMap<FunctionAccessKey, TypedFunction>,
+    name: &str,
+    arguments: &[(&str, Arc<Type>)],
+    return_type: Arc<Type>,
+    body: TypedExpr,
+) {
+    let arguments = arguments
+        .iter()
+        .map(|(name, tipo)| TypedArg {
+            arg_name: ArgName::Named {
+                name: name.to_string(),
+                label: name.to_string(),
+                location: Span::empty(),
+                is_validator_param: false,
+            },
+            location: Span::empty(),
+            annotation: None,
+            tipo: tipo.clone(),
+        })
+        .collect();
+
+    functions.insert(
+        FunctionAccessKey {
+            module_name: "".to_string(),
+            function_name: name.to_string(),
+            variant_name: "".to_string(),
+        },
+        Function {
+            arguments,
+            body,
+            doc: None,
+            location: Span::empty(),
+            name: name.to_string(),
+            public: true,
+            return_annotation: None,
+            return_type,
+            type_parameters: vec![],
+            end_position: 0,
+        },
+    );
+}
+
+/// `if less_than { Less } else if equals { Equal } else { Greater }`
+fn compare_with(less_than: TypedExpr, equals: TypedExpr) -> TypedExpr {
+    let ordering_constructor = |name: &str| TypedExpr::Var {
+        location: Span::empty(),
+        constructor: ValueConstructor::public(
+            ordering(),
+            ValueConstructorVariant::Record {
+                module: "".into(),
+                name: name.to_string(),
+                field_map: None::<FieldMap>,
+                arity: 0,
+                location: Span::empty(),
+                constructors_count: 3,
+            },
+        ),
+        name: name.to_string(),
+    };
+
+    TypedExpr::If {
+        location: Span::empty(),
+        branches: vec1![
+            IfBranch {
+                condition: less_than,
+                body: ordering_constructor("Less"),
+                location: Span::empty(),
+            },
+            IfBranch {
+                condition: equals,
+                body: ordering_constructor("Equal"),
+                location: Span::empty(),
+            },
+        ],
+        final_else: ordering_constructor("Greater").into(),
+        tipo: ordering(),
+    }
+}
+
+fn local_var(name: &str, tipo: Arc<Type>) -> TypedExpr {
+    TypedExpr::Var {
+        location: Span::empty(),
+        constructor: ValueConstructor {
+            public: true,
+            tipo,
+            variant: ValueConstructorVariant::LocalVariable {
+                location: Span::empty(),
+            },
+        },
+        name: name.to_string(),
+    }
+}
+
+fn int_literal(value: &str) -> TypedExpr {
+    TypedExpr::Int {
+        location: Span::empty(),
+        tipo: int(),
+        value: value.to_string(),
+    }
+}
+
+/// Arithmetic or comparison on integers.
+fn int_op(name: BinOp, left: TypedExpr, right: TypedExpr) -> TypedExpr {
+    let tipo = match name {
+        BinOp::AddInt | BinOp::SubInt | BinOp::MultInt | BinOp::DivInt | BinOp::ModInt => int(),
+        _ => bool(),
+    };
+
+    TypedExpr::BinOp {
+        location: Span::empty(),
+        tipo,
+        name,
+        left: left.into(),
+        right: right.into(),
+    }
+}
+
+fn negate(value: TypedExpr) -> TypedExpr {
+    TypedExpr::UnOp {
+        location: Span::empty(),
+        value: value.into(),
+        tipo: int(),
+        op: UnOp::Negate,
+    }
+}
+
+fn if_else(condition: TypedExpr, then: TypedExpr, otherwise: TypedExpr) -> TypedExpr {
+    let tipo = then.tipo();
+
+    TypedExpr::If {
+        location: Span::empty(),
+        branches: vec1![IfBranch {
+            condition,
+            body: then,
+            location: Span::empty(),
+        }],
+        final_else: otherwise.into(),
+        tipo,
+    }
+}
+
+fn let_(name: &str, value: TypedExpr) -> TypedExpr {
+    TypedExpr::Assignment {
+        location: Span::empty(),
+        tipo: value.tipo(),
+        value: value.into(),
+        pattern: Pattern::Var {
+            location: Span::empty(),
+            name: name.to_string(),
+        },
+        kind: AssignmentKind::Let,
+    }
+}
+
+fn call_args(args: Vec<TypedExpr>) -> Vec<CallArg<TypedExpr>> {
+    args.into_iter()
+        .map(|value| CallArg {
+            label: None,
+            location: Span::empty(),
+            value,
+        })
+        .collect()
+}
+
+/// Calls a function defined in [`prelude_functions`].
+fn prelude_call(name: &str, args: Vec<TypedExpr>, return_type: Arc<Type>) -> TypedExpr {
+    let tipo = function(
+        args.iter().map(TypedExpr::tipo).collect(),
+        return_type.clone(),
+    );
+
+    TypedExpr::Call {
+        location: Span::empty(),
+        tipo: return_type,
+        fun: TypedExpr::Var {
+            location: Span::empty(),
+            constructor: ValueConstructor::public(
+                tipo,
+                ValueConstructorVariant::ModuleFn {
+                    name: name.to_string(),
+                    field_map: None,
+                    module: "".to_string(),
+                    arity: args.len(),
+                    location: Span::empty(),
+                    builtin: None,
+                },
+            ),
+            name: name.to_string(),
+        }
+        .into(),
+        args: call_args(args),
+    }
+}
+
+fn builtin_call(
+    builtin: DefaultFunction,
+    args: Vec<TypedExpr>,
+    return_type: Arc<Type>,
+) -> TypedExpr {
+    let tipo = function(
+        args.iter().map(TypedExpr::tipo).collect(),
+        return_type.clone(),
+    );
+
+    TypedExpr::Call {
+        location: Span::empty(),
+        tipo: return_type,
+        fun: TypedExpr::ModuleSelect {
+            location: Span::empty(),
+            tipo,
+            label: builtin.nano_name(),
+            module_name: "nano/builtin".to_string(),
+            module_alias: "builtin".to_string(),
+            constructor: ModuleValueConstructor::Fn {
+                location: Span::empty(),
+                module: "".to_string(),
+                name: builtin.nano_name(),
+            },
+        }
+        .into(),
+        args: call_args(args),
+    }
+}
+
+/// The numerator (0) or denominator (1) of a `Rational`.
+fn rational_field(record: TypedExpr, index: u64) -> TypedExpr {
+    let label = if index == 0 {
+        "numerator"
+    } else {
+        "denominator"
+    };
+
+    TypedExpr::RecordAccess {
+        location: Span::empty(),
+        tipo: int(),
+        label: label.to_string(),
+        index,
+        record: record.into(),
+    }
+}
+
+/// Builds a `Rational` from parts that are already in lowest terms, with a
+/// positive denominator.
+pub fn rational_constructor(
+    location: Span,
+    numerator: TypedExpr,
+    denominator: TypedExpr,
+) -> TypedExpr {
+    TypedExpr::Call {
+        location,
+        tipo: rational(),
+        fun: TypedExpr::Var {
+            location,
+            constructor: ValueConstructor::public(
+                function(vec![int(), int()], rational()),
+                ValueConstructorVariant::Record {
+                    module: "".into(),
+                    name: RATIONAL.to_string(),
+                    field_map: None::<FieldMap>,
+                    arity: 2,
+                    location: Span::empty(),
+                    constructors_count: 1,
+                },
+            ),
+            name: RATIONAL.to_string(),
+        }
+        .into(),
+        args: call_args(vec![numerator, denominator]),
+    }
+}
+
+/// Builds a `Rational` from any parts, normalised when the program runs.
+pub fn rational_normalised(
+    location: Span,
+    numerator: TypedExpr,
+    denominator: TypedExpr,
+) -> TypedExpr {
+    match prelude_call("rational", vec![numerator, denominator], rational()) {
+        TypedExpr::Call {
+            tipo, fun, args, ..
+        } => TypedExpr::Call {
+            location,
+            tipo,
+            fun,
+            args,
+        },
+        _ => unreachable!(),
+    }
+}
+
 pub fn prelude_data_types(id_gen: &IdGenerator) -> IndexMap<DataTypeKey, TypedDataType> {
     let mut data_types = IndexMap::new();
 
@@ -929,6 +1820,14 @@ pub fn prelude_data_types(id_gen: &IdGenerator) -> IndexMap<DataTypeKey, TypedDa
         option_data_type,
     );
 
+    data_types.insert(
+        DataTypeKey {
+            module_name: "".to_string(),
+            defined_type: RATIONAL.to_string(),
+        },
+        TypedDataType::rational(),
+    );
+
     data_types
 }
 
@@ -1026,6 +1925,16 @@ pub fn ordering() -> Arc<Type> {
     })
 }
 
+#[allow(clippy::arc_with_non_send_sync)]
+pub fn rational() -> Arc<Type> {
+    Arc::new(Type::App {
+        public: true,
+        name: RATIONAL.to_string(),
+        module: "".to_string(),
+        args: vec![],
+    })
+}
+
 pub fn function(args: Vec<Arc<Type>>, ret: Arc<Type>) -> Arc<Type> {
     Arc::new(Type::Fn { ret, args })
 }
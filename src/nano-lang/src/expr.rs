@@ -15,12 +15,14 @@ use vec1::Vec1;
 
 use crate::{
     ast::{
-        Annotation, Arg, AssignmentKind, BinOp, ByteArrayFormatPreference, CallArg,
+        Annotation, Arg, AssignmentKind, BinOp, ByteArrayFormatPreference, CallArg, Constant,
         DefinitionLocation, IfBranch, Pattern, RecordUpdateSpread, Span, TraceKind, TypedClause,
         TypedRecordUpdateArg, UnOp, UntypedClause, UntypedRecordUpdateArg,
     },
     builtins::void,
-    tipo::{ModuleValueConstructor, PatternConstructor, Type, ValueConstructor},
+    tipo::{
+        ModuleValueConstructor, PatternConstructor, Type, ValueConstructor, ValueConstructorVariant,
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -212,6 +214,32 @@ impl TypedExpr {
         matches!(self, Self::Assignment { .. })
     }
 
+    /// The literal this expression stands for, when there is one.
+    pub fn literal(&self) -> Option<Constant> {
+        match self {
+            Self::Int {
+                location, value, ..
+            } => Some(Constant::Int {
+                location: *location,
+                value: value.clone(),
+            }),
+            Self::String {
+                location, value, ..
+            } => Some(Constant::String {
+                location: *location,
+                value: value.clone(),
+            }),
+            Self::ByteArray {
+                location, bytes, ..
+            } => Some(Constant::ByteArray {
+                location: *location,
+                bytes: bytes.clone(),
+                preferred_format: ByteArrayFormatPreference::HexadecimalString,
+            }),
+            _ => None,
+        }
+    }
+
     pub fn definition_location(&self) -> Option<DefinitionLocation<'_>> {
         match self {
             TypedExpr::Fn { .. }
@@ -395,6 +423,189 @@ impl TypedExpr {
             TypedExpr::UnOp { value, .. } => value.find_node(byte_index).or(Some(self)),
         }
     }
+
+    /// The types at which the local variable defined at `definition` is used.
+    pub fn local_variable_uses(&self, definition: Span, uses: &mut Vec<Arc<Type>>) {
+        match self {
+            TypedExpr::Var { constructor, .. } => {
+                if let ValueConstructorVariant::LocalVariable { location } = &constructor.variant {
+                    if *location == definition {
+                        uses.push(constructor.tipo.clone());
+                    }
+                }
+            }
+
+            TypedExpr::ErrorTerm { .. }
+            | TypedExpr::Int { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::ByteArray { .. }
+            | TypedExpr::ModuleSelect { .. } => (),
+
+            TypedExpr::Trace { text, then, .. } => {
+                text.local_variable_uses(definition, uses);
+                then.local_variable_uses(definition, uses);
+            }
+
+            TypedExpr::Pipeline { expressions, .. }
+            | TypedExpr::Sequence { expressions, .. }
+            | TypedExpr::Tuple {
+                elems: expressions, ..
+            } => expressions
+                .iter()
+                .for_each(|e| e.local_variable_uses(definition, uses)),
+
+            TypedExpr::List { elements, tail, .. } => {
+                elements
+                    .iter()
+                    .for_each(|e| e.local_variable_uses(definition, uses));
+
+                if let Some(tail) = tail {
+                    tail.local_variable_uses(definition, uses);
+                }
+            }
+
+            TypedExpr::Call { fun, args, .. } => {
+                fun.local_variable_uses(definition, uses);
+                args.iter()
+                    .for_each(|arg| arg.value.local_variable_uses(definition, uses));
+            }
+
+            TypedExpr::BinOp { left, right, .. } => {
+                left.local_variable_uses(definition, uses);
+                right.local_variable_uses(definition, uses);
+            }
+
+            TypedExpr::When {
+                subject, clauses, ..
+            } => {
+                subject.local_variable_uses(definition, uses);
+                clauses
+                    .iter()
+                    .for_each(|clause| clause.then.local_variable_uses(definition, uses));
+            }
+
+            TypedExpr::If {
+                branches,
+                final_else,
+                ..
+            } => {
+                for branch in branches {
+                    branch.condition.local_variable_uses(definition, uses);
+                    branch.body.local_variable_uses(definition, uses);
+                }
+
+                final_else.local_variable_uses(definition, uses);
+            }
+
+            TypedExpr::RecordUpdate { spread, args, .. } => {
+                spread.local_variable_uses(definition, uses);
+                args.iter()
+                    .for_each(|arg| arg.value.local_variable_uses(definition, uses));
+            }
+
+            TypedExpr::Fn { body: value, .. }
+            | TypedExpr::Assignment { value, .. }
+            | TypedExpr::RecordAccess { record: value, .. }
+            | TypedExpr::TupleIndex { tuple: value, .. }
+            | TypedExpr::UnOp { value, .. } => value.local_variable_uses(definition, uses),
+        }
+    }
+
+    /// Every `when` and `if` in the expression, outermost first, along with
+    /// the bodies of the clauses or branches it chooses between.
+    pub fn branch_points(&self, points: &mut Vec<(Span, Vec<Span>)>) {
+        match self {
+            TypedExpr::ErrorTerm { .. }
+            | TypedExpr::Var { .. }
+            | TypedExpr::Int { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::ByteArray { .. }
+            | TypedExpr::ModuleSelect { .. } => (),
+
+            TypedExpr::Trace { text, then, .. } => {
+                text.branch_points(points);
+                then.branch_points(points);
+            }
+
+            TypedExpr::Pipeline { expressions, .. }
+            | TypedExpr::Sequence { expressions, .. }
+            | TypedExpr::Tuple {
+                elems: expressions, ..
+            } => expressions.iter().for_each(|e| e.branch_points(points)),
+
+            TypedExpr::List { elements, tail, .. } => {
+                elements.iter().for_each(|e| e.branch_points(points));
+
+                if let Some(tail) = tail {
+                    tail.branch_points(points);
+                }
+            }
+
+            TypedExpr::Call { fun, args, .. } => {
+                fun.branch_points(points);
+                args.iter().for_each(|arg| arg.value.branch_points(points));
+            }
+
+            TypedExpr::BinOp { left, right, .. } => {
+                left.branch_points(points);
+                right.branch_points(points);
+            }
+
+            TypedExpr::When {
+                location,
+                subject,
+                clauses,
+                ..
+            } => {
+                points.push((
+                    *location,
+                    clauses
+                        .iter()
+                        .map(|clause| clause.then.location())
+                        .collect(),
+                ));
+
+                subject.branch_points(points);
+                clauses
+                    .iter()
+                    .for_each(|clause| clause.then.branch_points(points));
+            }
+
+            TypedExpr::If {
+                location,
+                branches,
+                final_else,
+                ..
+            } => {
+                points.push((
+                    *location,
+                    branches
+                        .iter()
+                        .map(|branch| branch.body.location())
+                        .chain([final_else.location()])
+                        .collect(),
+                ));
+
+                for branch in branches {
+                    branch.condition.branch_points(points);
+                    branch.body.branch_points(points);
+                }
+
+                final_else.branch_points(points);
+            }
+
+            TypedExpr::RecordUpdate { spread, args, .. } => {
+                spread.branch_points(points);
+                args.iter().for_each(|arg| arg.value.branch_points(points));
+            }
+
+            TypedExpr::Fn { body: value, .. }
+            | TypedExpr::Assignment { value, .. }
+            | TypedExpr::RecordAccess { record: value, .. }
+            | TypedExpr::TupleIndex { tuple: value, .. }
+            | TypedExpr::UnOp { value, .. } => value.branch_points(points),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -404,6 +615,13 @@ pub enum UntypedExpr {
         value: String,
     },
 
+    /// A `Rational` literal such as `3/4r`.
+    Rational {
+        location: Span,
+        numerator: String,
+        denominator: String,
+    },
+
     String {
         location: Span,
         value: String,
@@ -477,6 +695,13 @@ pub enum UntypedExpr {
         value: Box<Self>,
     },
 
+    /// A named value applied to explicit types, as in `f<Int>`.
+    TypeApplication {
+        location: Span,
+        value: Box<Self>,
+        arguments: Vec<Annotation>,
+    },
+
     When {
         location: Span,
         subject: Box<Self>,
@@ -606,9 +831,11 @@ impl UntypedExpr {
             Self::PipeLine { expressions, .. } => expressions.last().location(),
             Self::Trace { then, .. } => then.location(),
             Self::TraceIfFalse { location, .. }
+            | Self::TypeApplication { location, .. }
             | Self::Fn { location, .. }
             | Self::Var { location, .. }
             | Self::Int { location, .. }
+            | Self::Rational { location, .. }
             | Self::ErrorTerm { location, .. }
             | Self::When { location, .. }
             | Self::Call { location, .. }
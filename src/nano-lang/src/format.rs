@@ -16,11 +16,12 @@ use vec1::Vec1;
 use crate::{
     ast::{
         Annotation, Arg, ArgName, AssignmentKind, BinOp, ByteArrayFormatPreference, CallArg,
-        ClauseGuard, Constant, DataType, Definition, Function, IfBranch, ModuleConstant, Pattern,
-        RecordConstructor, RecordConstructorArg, RecordUpdateSpread, Span, TraceKind, TypeAlias,
-        TypedArg, UnOp, UnqualifiedImport, UntypedArg, UntypedClause, UntypedClauseGuard,
-        UntypedDefinition, UntypedFunction, UntypedModule, UntypedPattern, UntypedRecordUpdateArg,
-        Use, Validator, CAPTURE_VARIABLE,
+        ClauseGuard, Constant, Constraint, DataType, Definition, Function, IfBranch, Interface,
+        ModuleConstant, Pattern, RecordConstructor, RecordConstructorArg, RecordUpdateSpread, Span,
+        TraceKind, TypeAlias, TypeParameter, TypedArg, UnOp, UnqualifiedImport, UntypedArg,
+        UntypedClause, UntypedClauseGuard, UntypedDefinition, UntypedFunction,
+        UntypedImplementation, UntypedModule, UntypedPattern, UntypedRecordUpdateArg, Use,
+        Validator, CAPTURE_VARIABLE,
     },
     docvec,
     expr::{UntypedExpr, DEFAULT_ERROR_STR, DEFAULT_TODO_STR},
@@ -226,12 +227,14 @@ impl<'comments> Formatter<'comments> {
                 body,
                 public,
                 return_annotation,
+                type_parameters,
                 end_position,
                 ..
             }) => self.definition_fn(
                 public,
                 "fn",
                 name,
+                type_parameters,
                 args,
                 return_annotation,
                 body,
@@ -241,10 +244,10 @@ impl<'comments> Formatter<'comments> {
             Definition::Validator(Validator {
                 end_position,
                 fun,
-                other_fun,
+                other_funs,
                 params,
                 ..
-            }) => self.definition_validator(params, fun, other_fun, *end_position),
+            }) => self.definition_validator(params, fun, other_funs, *end_position),
 
             Definition::Test(Function {
                 name,
@@ -252,7 +255,7 @@ impl<'comments> Formatter<'comments> {
                 body,
                 end_position,
                 ..
-            }) => self.definition_fn(&false, "test", name, args, &None, body, *end_position),
+            }) => self.definition_fn(&false, "test", name, &[], args, &None, body, *end_position),
 
             Definition::TypeAlias(TypeAlias {
                 alias,
@@ -269,11 +272,23 @@ impl<'comments> Formatter<'comments> {
                 constructors,
                 location,
                 opaque,
+                derive,
                 ..
-            }) => self.data_type(*public, *opaque, name, parameters, constructors, location),
+            }) => derive_(derive).append(self.data_type(
+                *public,
+                *opaque,
+                name,
+                parameters,
+                constructors,
+                location,
+            )),
 
             Definition::Use(import) => self.import(import),
 
+            Definition::Interface(interface) => self.interface(interface),
+
+            Definition::Implementation(implementation) => self.implementation(implementation),
+
             Definition::ModuleConstant(ModuleConstant {
                 public,
                 name,
@@ -286,11 +301,7 @@ impl<'comments> Formatter<'comments> {
                     None => head,
                     Some(t) => head.append(": ").append(self.annotation(t)),
                 };
-                head.append(" =")
-                    .append(line())
-                    .append(self.const_expr(value))
-                    .nest(INDENT)
-                    .group()
+                head.append(" =").append(self.case_clause_value(value))
             }
         }
     }
@@ -343,13 +354,21 @@ impl<'comments> Formatter<'comments> {
         }
     }
 
-    pub fn docs_const_expr<'a>(&mut self, name: &'a str, value: &'a Constant) -> Document<'a> {
+    /// Constants computed from an expression only show their type, since
+    /// their value is only known once evaluated.
+    pub fn docs_const_expr<'a>(
+        &mut self,
+        name: &'a str,
+        tipo: &Type,
+        literal: Option<&'a Constant>,
+    ) -> Document<'a> {
         let mut printer = tipo::pretty::Printer::new();
-        name.to_doc()
-            .append(": ")
-            .append(printer.print(&value.tipo()))
-            .append(" = ")
-            .append(self.const_expr(value))
+        let doc = name.to_doc().append(": ").append(printer.print(tipo));
+
+        match literal {
+            Some(literal) => doc.append(" = ").append(self.const_expr(literal)),
+            None => doc,
+        }
     }
 
     fn documented_definition<'a>(&mut self, s: &'a UntypedDefinition) -> Document<'a> {
@@ -466,6 +485,7 @@ impl<'comments> Formatter<'comments> {
         public: &'a bool,
         keyword: &'a str,
         name: &'a str,
+        type_parameters: &'a [TypeParameter],
         args: &'a [UntypedArg],
         return_annotation: &'a Option<Annotation>,
         body: &'a UntypedExpr,
@@ -475,6 +495,7 @@ impl<'comments> Formatter<'comments> {
             .append(keyword)
             .append(" ")
             .append(name)
+            .append(self.type_parameters(type_parameters))
             .append(wrap_args(args.iter().map(|e| (self.fn_arg(e), false))));
 
         let head = match return_annotation {
@@ -496,54 +517,161 @@ impl<'comments> Formatter<'comments> {
             .append("}")
     }
 
+    fn type_parameters<'a>(&mut self, type_parameters: &'a [TypeParameter]) -> Document<'a> {
+        if type_parameters.is_empty() {
+            return nil();
+        }
+
+        wrap_generics(type_parameters.iter().map(|parameter| {
+            let name = parameter.name.to_doc();
+
+            if parameter.constraints.is_empty() {
+                name
+            } else {
+                let constraints = parameter
+                    .constraints
+                    .iter()
+                    .map(|constraint| Document::String(constraint.to_string()));
+
+                name.append(": ").append(join(constraints, " + ".to_doc()))
+            }
+        }))
+        .group()
+    }
+
+    fn interface<'a>(
+        &mut self,
+        Interface {
+            end_position,
+            methods,
+            name,
+            parameter,
+            public,
+            ..
+        }: &'a Interface,
+    ) -> Document<'a> {
+        let head = pub_(*public)
+            .append("interface ")
+            .append(name.as_str())
+            .append(wrap_generics([parameter.to_doc()]));
+
+        let mut signatures = Vec::with_capacity(methods.len());
+
+        for method in methods {
+            let comments = self.pop_comments(method.location.start);
+            let doc_comments = self.doc_comments(method.location.start);
+
+            let signature = "fn "
+                .to_doc()
+                .append(method.name.as_str())
+                .append(wrap_args(
+                    method.arguments.iter().map(|e| (self.fn_arg(e), false)),
+                ))
+                .append(" -> ")
+                .append(self.annotation(&method.return_annotation))
+                .group();
+
+            signatures.push(commented(doc_comments.append(signature).group(), comments));
+        }
+
+        let body = join(signatures, line());
+
+        let body = match printed_comments(self.pop_comments(*end_position), false) {
+            Some(comments) => body.append(line()).append(comments),
+            None => body,
+        };
+
+        if body.is_empty() {
+            return head.append(" {}");
+        }
+
+        head.append(" {")
+            .append(line().append(body).nest(INDENT))
+            .append(line())
+            .append("}")
+    }
+
+    fn implementation<'a>(&mut self, implementation: &'a UntypedImplementation) -> Document<'a> {
+        let head = "impl"
+            .to_doc()
+            .append(self.type_parameters(&implementation.type_parameters))
+            .append(" ")
+            .append(Document::String(implementation.interface.to_string()))
+            .append(wrap_generics([self.annotation(&implementation.annotation)]));
+
+        let mut methods = Vec::with_capacity(implementation.methods.len());
+
+        for method in &implementation.methods {
+            let comments = self.pop_comments(method.location.start);
+            let doc_comments = self.doc_comments(method.location.start);
+
+            let method_doc = self
+                .definition_fn(
+                    &method.public,
+                    "fn",
+                    &method.name,
+                    &method.type_parameters,
+                    &method.arguments,
+                    &method.return_annotation,
+                    &method.body,
+                    method.end_position,
+                )
+                .group();
+
+            methods.push(commented(doc_comments.append(method_doc).group(), comments));
+        }
+
+        let body = join(methods, lines(2));
+
+        let body = match printed_comments(self.pop_comments(implementation.end_position), false) {
+            Some(comments) => body.append(lines(2)).append(comments),
+            None => body,
+        };
+
+        if body.is_empty() {
+            return head.append(" {}");
+        }
+
+        head.append(" {")
+            .append(line().append(body).nest(INDENT))
+            .append(line())
+            .append("}")
+    }
+
     fn definition_validator<'a>(
         &mut self,
         params: &'a [UntypedArg],
         fun: &'a UntypedFunction,
-        other_fun: &'a Option<UntypedFunction>,
+        other_funs: &'a [UntypedFunction],
         end_position: usize,
     ) -> Document<'a> {
-        let fun_comments = self.pop_comments(fun.location.start);
-        let fun_doc_comments = self.doc_comments(fun.location.start);
-        let first_fn = self
-            .definition_fn(
-                &false,
-                "fn",
-                &fun.name,
-                &fun.arguments,
-                &fun.return_annotation,
-                &fun.body,
-                fun.end_position,
-            )
-            .group();
-        let first_fn = commented(fun_doc_comments.append(first_fn).group(), fun_comments);
-
-        let other_fn = match other_fun {
-            None => nil(),
-            Some(other) => {
-                let other_comments = self.pop_comments(other.location.start);
-                let other_doc_comments = self.doc_comments(other.location.start);
-
-                let other_fn = self
-                    .definition_fn(
-                        &false,
-                        "fn",
-                        &other.name,
-                        &other.arguments,
-                        &other.return_annotation,
-                        &other.body,
-                        other.end_position,
-                    )
-                    .group();
+        let mut v_body = line();
+
+        for (index, fun) in std::iter::once(fun).chain(other_funs).enumerate() {
+            let fun_comments = self.pop_comments(fun.location.start);
+            let fun_doc_comments = self.doc_comments(fun.location.start);
+            let fun_doc = self
+                .definition_fn(
+                    &false,
+                    "fn",
+                    &fun.name,
+                    &fun.type_parameters,
+                    &fun.arguments,
+                    &fun.return_annotation,
+                    &fun.body,
+                    fun.end_position,
+                )
+                .group();
 
-                commented(other_doc_comments.append(other_fn).group(), other_comments)
+            if index > 0 {
+                v_body = v_body.append(lines(2));
             }
-        };
 
-        let v_body = line()
-            .append(first_fn)
-            .append(if other_fun.is_some() { lines(2) } else { nil() })
-            .append(other_fn);
+            v_body = v_body.append(commented(
+                fun_doc_comments.append(fun_doc).group(),
+                fun_comments,
+            ));
+        }
 
         let v_body = match printed_comments(self.pop_comments(end_position), false) {
             Some(comments) => v_body.append(lines(2)).append(comments).nest(INDENT),
@@ -617,9 +745,10 @@ impl<'comments> Formatter<'comments> {
     ) -> Document<'a> {
         self.pop_empty_lines(pattern.location().end);
 
-        let keyword = match kind {
-            AssignmentKind::Let => "let ",
-            AssignmentKind::Expect => "expect ",
+        let (keyword, operator) = match kind {
+            AssignmentKind::Let => ("let ", " ="),
+            AssignmentKind::Expect => ("expect ", " ="),
+            AssignmentKind::Backpass => ("let ", " <-"),
         };
 
         let pattern = self.pattern(pattern);
@@ -631,7 +760,7 @@ impl<'comments> Formatter<'comments> {
         keyword
             .to_doc()
             .append(pattern.append(annotation).group())
-            .append(" =")
+            .append(operator)
             .append(self.case_clause_value(value))
     }
 
@@ -686,6 +815,12 @@ impl<'comments> Formatter<'comments> {
 
             UntypedExpr::Int { value, .. } => value.to_doc(),
 
+            UntypedExpr::Rational {
+                numerator,
+                denominator,
+                ..
+            } => docvec![numerator.as_str(), "/", denominator.as_str(), "r"],
+
             UntypedExpr::String { value, .. } => self.string(value),
 
             UntypedExpr::Sequence { expressions, .. } => self.sequence(expressions),
@@ -763,6 +898,10 @@ impl<'comments> Formatter<'comments> {
             UntypedExpr::ErrorTerm { .. } => "error".to_doc(),
 
             UntypedExpr::TraceIfFalse { value, .. } => self.trace_if_false(value),
+
+            UntypedExpr::TypeApplication {
+                value, arguments, ..
+            } => self.expr(value).append(self.type_arguments(arguments)),
         };
 
         commented(document, comments)
@@ -1487,6 +1626,16 @@ impl<'comments> Formatter<'comments> {
         let doc = match pattern {
             Pattern::Int { value, .. } => value.to_doc(),
 
+            Pattern::IntRange { start, end, .. } => docvec![start.as_str(), "..", end.as_str()],
+
+            Pattern::String { value, .. } => self.string(value),
+
+            Pattern::ByteArray {
+                value,
+                preferred_format,
+                ..
+            } => self.bytearray(value, preferred_format),
+
             Pattern::Var { name, .. } => name.to_doc(),
 
             Pattern::Assign { name, pattern, .. } => {
@@ -1630,6 +1779,22 @@ fn pub_(public: bool) -> Document<'static> {
     }
 }
 
+fn derive_(derive: &[Constraint]) -> Document<'_> {
+    if derive.is_empty() {
+        return nil();
+    }
+
+    let interfaces = derive
+        .iter()
+        .map(|constraint| Document::String(constraint.to_string()));
+
+    "derive("
+        .to_doc()
+        .append(join(interfaces, ", ".to_doc()))
+        .append(")")
+        .append(line())
+}
+
 impl<'a> Documentable<'a> for &'a UnqualifiedImport {
     fn to_doc(self) -> Document<'a> {
         self.name.to_doc().append(match &self.as_name {
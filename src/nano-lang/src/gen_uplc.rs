@@ -18,7 +18,7 @@ use untyped_plutus_core::{
     },
     builder::{CONSTR_FIELDS_EXPOSER, CONSTR_GET_FIELD, CONSTR_INDEX_EXPOSER, EXPECT_ON_LIST},
     builtins::DefaultFunction,
-    machine::cost_model::ExBudget,
+    machine::{self, cost_model::ExBudget},
     optimize::nano_optimize_and_intern,
     parser::interner::Interner,
 };
@@ -26,29 +26,37 @@ use untyped_plutus_core::{
 use crate::{
     ast::{
         ArgName, AssignmentKind, BinOp, Pattern, Span, TypedArg, TypedClause, TypedDataType,
-        TypedFunction, TypedValidator, UnOp,
+        TypedFunction, TypedModuleConstant, TypedValidator, UnOp,
     },
     builtins::{bool, data, void},
     expr::TypedExpr,
     gen_uplc::builder::{find_and_replace_generics, get_generic_id_and_type, get_variant_name},
     tipo::{
-        ModuleValueConstructor, PatternConstructor, Type, TypeInfo, ValueConstructor,
-        ValueConstructorVariant,
+        ImplementationKey, ModuleValueConstructor, PatternConstructor, Type, TypeInfo,
+        ValueConstructor, ValueConstructorVariant,
     },
     IdGenerator,
 };
 
 pub mod air;
 pub mod builder;
+pub mod schema;
 pub mod scope;
+pub mod source_map;
 pub mod stack;
 
 use air::Air;
 use builder::{
-    AssignmentProperties, ClauseProperties, DataTypeKey, FuncComponents, FunctionAccessKey,
+    AssignmentProperties, ClauseProperties, ConstantAccessKey, DataTypeKey, FuncComponents,
+    FunctionAccessKey,
 };
 
-use self::{builder::replace_opaque_type, scope::Scope, stack::AirStack};
+use self::{
+    builder::replace_opaque_type,
+    scope::Scope,
+    source_map::{Recorder, SourceMap},
+    stack::AirStack,
+};
 
 #[derive(Clone, Debug)]
 pub enum CodeGenFunction {
@@ -56,17 +64,46 @@ pub enum CodeGenFunction {
     Link(String),
 }
 
+/// Why a module constant couldn't be evaluated at compile-time.
+#[derive(Debug, thiserror::Error)]
+pub enum ConstantEvaluationError {
+    #[error("{error}{}", logs.iter().map(|log| format!("\n{log}")).collect::<String>())]
+    Failed {
+        error: machine::Error,
+        logs: Vec<String>,
+    },
+
+    #[error("The evaluation ended on {term}, which isn't a value.")]
+    NotAValue { term: String },
+
+    #[error("It uses the constant {module}.{name}, which couldn't be evaluated either.")]
+    Dependency { module: String, name: String },
+}
+
 #[derive(Clone)]
 pub struct CodeGenerator<'a> {
     defined_functions: IndexMap<FunctionAccessKey, ()>,
     functions: IndexMap<FunctionAccessKey, &'a TypedFunction>,
     data_types: IndexMap<DataTypeKey, &'a TypedDataType>,
     module_types: IndexMap<&'a String, &'a TypeInfo>,
+    constants: IndexMap<ConstantAccessKey, &'a TypedModuleConstant>,
+    /// The module constants evaluated so far, failures included so that they
+    /// are reported rather than evaluated again.
+    evaluated_constants:
+        IndexMap<ConstantAccessKey, Result<Rc<UplcConstant>, Rc<ConstantEvaluationError>>>,
+    /// The first constant referred to by the program being generated that
+    /// couldn't be evaluated.
+    failed_constant: Option<ConstantAccessKey>,
     id_gen: Rc<IdGenerator>,
     needs_field_access: bool,
     code_gen_functions: IndexMap<String, CodeGenFunction>,
     zero_arg_functions: IndexMap<FunctionAccessKey, Vec<Air>>,
     uplc_to_function: IndexMap<Program<DeBruijn>, FunctionAccessKey>,
+    /// Where the generic functions bound by `let` are defined. Each is
+    /// specialised to the types it is used at.
+    local_generic_functions: Vec<Span>,
+    /// Where the terms come from, when asked for.
+    source_map: Option<Recorder>,
 }
 
 impl<'a> CodeGenerator<'a> {
@@ -74,17 +111,23 @@ impl<'a> CodeGenerator<'a> {
         functions: IndexMap<FunctionAccessKey, &'a TypedFunction>,
         data_types: IndexMap<DataTypeKey, &'a TypedDataType>,
         module_types: IndexMap<&'a String, &'a TypeInfo>,
+        constants: IndexMap<ConstantAccessKey, &'a TypedModuleConstant>,
     ) -> Self {
         CodeGenerator {
             defined_functions: IndexMap::new(),
             functions,
             data_types,
             module_types,
+            constants,
+            evaluated_constants: IndexMap::new(),
+            failed_constant: None,
             needs_field_access: false,
             id_gen: IdGenerator::new().into(),
             code_gen_functions: IndexMap::new(),
             zero_arg_functions: IndexMap::new(),
             uplc_to_function: IndexMap::new(),
+            local_generic_functions: vec![],
+            source_map: None,
         }
     }
 
@@ -95,17 +138,45 @@ impl<'a> CodeGenerator<'a> {
         self.needs_field_access = false;
         self.defined_functions = IndexMap::new();
         self.uplc_to_function = IndexMap::new();
+        self.local_generic_functions = vec![];
+
+        if let Some(recorder) = &mut self.source_map {
+            recorder.forget_air();
+        }
     }
 
-    pub fn generate(
-        &mut self,
-        TypedValidator {
-            fun,
-            other_fun,
-            params,
-            ..
-        }: &TypedValidator,
-    ) -> Program<Name> {
+    pub fn generate(&mut self, validator: &TypedValidator) -> Program<Name> {
+        let mut handlers = vec![];
+
+        for (index, (purpose, fun)) in validator.handlers().enumerate() {
+            if index > 0 {
+                self.reset();
+            }
+
+            handlers.push((purpose, self.generate_handler(fun)));
+        }
+
+        let mut term = if handlers.len() == 1 {
+            handlers.remove(0).1
+        } else {
+            let term = builder::wrap_as_multi_validator(handlers);
+
+            self.needs_field_access = true;
+
+            term
+        };
+
+        term = builder::wrap_validator_args(term, &validator.params);
+
+        self.finalize(term)
+    }
+
+    /// Compiles a function of a validator, which fails unless it returns true.
+    fn generate_handler(&mut self, fun: &TypedFunction) -> Term<Name> {
+        if let Some(recorder) = &mut self.source_map {
+            recorder.function = fun.name.clone();
+        }
+
         let mut ir_stack = AirStack::new(self.id_gen.clone());
 
         ir_stack.noop();
@@ -132,71 +203,168 @@ impl<'a> CodeGenerator<'a> {
 
         self.convert_opaque_type_to_inner_ir(&mut ir_stack);
 
-        let mut term = self.uplc_code_gen(&mut ir_stack);
+        self.uplc_code_gen(&mut ir_stack)
+    }
 
-        if let Some(other) = other_fun {
-            self.reset();
+    /// Compiles a validator along with where its terms come from.
+    pub fn generate_with_source_map(
+        &mut self,
+        module_name: &str,
+        validator: &TypedValidator,
+    ) -> (Program<Name>, SourceMap) {
+        self.with_source_map(module_name, &validator.fun.name, |generator| {
+            generator.generate(validator)
+        })
+    }
 
-            let mut other_ir_stack = AirStack::new(self.id_gen.clone());
+    /// Compiles the body of a test along with where its terms come from.
+    pub fn generate_test_with_source_map(
+        &mut self,
+        module_name: &str,
+        test_name: &str,
+        test_body: &TypedExpr,
+        arguments: &[TypedArg],
+    ) -> (Program<Name>, SourceMap) {
+        self.with_source_map(module_name, test_name, |generator| {
+            generator.generate_test(test_body, arguments)
+        })
+    }
 
-            other_ir_stack.noop();
+    fn with_source_map(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        generate: impl FnOnce(&mut Self) -> Program<Name>,
+    ) -> (Program<Name>, SourceMap) {
+        self.source_map = Some(Recorder::new(module_name, function_name));
 
-            let mut args_stack = other_ir_stack.empty_with_scope();
-            let mut body_stack = other_ir_stack.empty_with_scope();
-            let mut unit_stack = other_ir_stack.empty_with_scope();
-            let mut error_stack = other_ir_stack.empty_with_scope();
+        let program = generate(self);
 
-            self.wrap_validator_args(&mut args_stack, &other.arguments, true);
+        let source_map = self
+            .source_map
+            .take()
+            .map(Recorder::source_map)
+            .unwrap_or_default();
 
-            self.build(&other.body, &mut body_stack);
+        (program, source_map)
+    }
 
-            unit_stack.void();
-            error_stack.error(void());
+    /// Compiles the body of a test. Tests taking arguments compile to a
+    /// function expecting each argument as `Data`.
+    pub fn generate_test(
+        &mut self,
+        test_body: &TypedExpr,
+        arguments: &[TypedArg],
+    ) -> Program<Name> {
+        let mut ir_stack = AirStack::new(self.id_gen.clone());
 
-            other_ir_stack.merge_child(args_stack);
-            other_ir_stack.if_branch(bool(), body_stack, unit_stack);
-            other_ir_stack.merge_child(error_stack);
+        ir_stack.noop();
 
-            let mut other_ir_stack = other_ir_stack.complete();
+        if arguments.is_empty() {
+            self.build(test_body, &mut ir_stack);
+        } else {
+            let mut args_stack = ir_stack.empty_with_scope();
+            let mut body_stack = ir_stack.empty_with_scope();
 
-            self.define_ir(&mut other_ir_stack);
+            self.wrap_validator_args(&mut args_stack, arguments, false);
 
-            self.convert_opaque_type_to_inner_ir(&mut other_ir_stack);
+            self.build(test_body, &mut body_stack);
 
-            let other_term = self.uplc_code_gen(&mut other_ir_stack);
+            ir_stack.merge_child(args_stack);
+            ir_stack.merge_child(body_stack);
+        }
 
-            let (spend, mint) = if other.arguments.len() > fun.arguments.len() {
-                (other_term, term)
-            } else {
-                (term, other_term)
-            };
+        let mut ir_stack = ir_stack.complete();
 
-            term = builder::wrap_as_multi_validator(spend, mint);
+        self.define_ir(&mut ir_stack);
 
-            self.needs_field_access = true;
-        }
+        self.convert_opaque_type_to_inner_ir(&mut ir_stack);
 
-        term = builder::wrap_validator_args(term, params);
+        let term = self.uplc_code_gen(&mut ir_stack);
 
         self.finalize(term)
     }
 
-    pub fn generate_test(&mut self, test_body: &TypedExpr) -> Program<Name> {
-        let mut ir_stack = AirStack::new(self.id_gen.clone());
+    /// Evaluates a module constant computed from an expression. Results are
+    /// kept across resets, so each constant is evaluated once however many
+    /// programs refer to it. A constant referring to another one that can't be
+    /// evaluated fails too.
+    pub fn evaluate_constant(
+        &mut self,
+        module_name: &str,
+        constant_name: &str,
+    ) -> Result<Rc<UplcConstant>, Rc<ConstantEvaluationError>> {
+        let key = ConstantAccessKey {
+            module_name: module_name.to_string(),
+            constant_name: constant_name.to_string(),
+        };
 
-        ir_stack.noop();
+        if let Some(result) = self.evaluated_constants.get(&key) {
+            return result.clone();
+        }
 
-        self.build(test_body, &mut ir_stack);
+        let result = self.run_constant(&key);
 
-        let mut ir_stack = ir_stack.complete();
+        self.evaluated_constants.insert(key, result.clone());
 
-        self.define_ir(&mut ir_stack);
+        result
+    }
 
-        self.convert_opaque_type_to_inner_ir(&mut ir_stack);
+    /// Compiles the expression of a constant and runs it.
+    fn run_constant(
+        &mut self,
+        key: &ConstantAccessKey,
+    ) -> Result<Rc<UplcConstant>, Rc<ConstantEvaluationError>> {
+        let ConstantAccessKey {
+            module_name,
+            constant_name,
+        } = key;
+
+        let constant = *self.constants.get(key).unwrap_or_else(|| {
+            unreachable!("Missing module constant {module_name}.{constant_name}")
+        });
+
+        // The current program may be half-way through generation, so the
+        // constant is compiled by a fresh copy of the generator.
+        let mut generator = self.clone();
+        generator.reset();
+        generator.source_map = None;
+        generator.failed_constant = None;
+
+        let program = generator.generate_test(&constant.value, &[]);
+
+        self.evaluated_constants = generator.evaluated_constants;
+
+        if let Some(ConstantAccessKey {
+            module_name,
+            constant_name,
+        }) = generator.failed_constant
+        {
+            return Err(ConstantEvaluationError::Dependency {
+                module: module_name,
+                name: constant_name,
+            }
+            .into());
+        }
 
-        let term = self.uplc_code_gen(&mut ir_stack);
+        let program: Program<NamedDeBruijn> = program.try_into().unwrap();
 
-        self.finalize(term)
+        let mut eval_result = program.eval(ExBudget::default());
+
+        let logs = eval_result.logs();
+
+        let value = match eval_result.result() {
+            Ok(Term::Constant(value)) => value,
+            Ok(term) => {
+                return Err(ConstantEvaluationError::NotAValue {
+                    term: term.to_string(),
+                }
+                .into())
+            }
+            Err(error) => return Err(ConstantEvaluationError::Failed { error, logs }.into()),
+        };
+
+        Ok(value)
     }
 
     fn finalize(&mut self, term: Term<Name>) -> Program<Name> {
@@ -214,14 +382,77 @@ impl<'a> CodeGenerator<'a> {
             term,
         };
 
-        program = nano_optimize_and_intern(program);
+        program = match &mut self.source_map {
+            Some(recorder) => recorder.optimise(program),
+            None => nano_optimize_and_intern(program),
+        };
 
         self.reset();
 
         program
     }
 
+    /// A generic function bound by `let`, defined once for each of the types
+    /// it is used at.
+    fn local_generic_function(
+        &mut self,
+        name: &str,
+        value: &TypedExpr,
+        uses: Vec<Arc<Type>>,
+        ir_stack: &mut AirStack,
+    ) -> Vec<AirStack> {
+        let generic = value.tipo();
+
+        let mut ids = vec![];
+        generic.collect_generics(&mut ids);
+
+        let mut variants = IndexMap::new();
+
+        for instance in uses {
+            let mut variant = String::new();
+            builder::get_local_variant_name(&mut variant, &instance);
+
+            variants.entry(variant).or_insert(instance);
+        }
+
+        variants
+            .into_iter()
+            .map(|(variant, instance)| {
+                let mono_types = ids
+                    .iter()
+                    .filter_map(|id| {
+                        builder::find_generic(&generic, &instance, *id).map(|tipo| (*id, tipo))
+                    })
+                    .collect();
+
+                let mut value_stack = ir_stack.empty_with_scope();
+
+                self.build(value, &mut value_stack);
+
+                let (_, air) = builder::monomorphize(value_stack.air, mono_types, &instance);
+
+                value_stack.air = air;
+
+                let mut stack = ir_stack.empty_with_scope();
+
+                stack.let_assignment(format!("{name}{variant}"), value_stack);
+
+                stack
+            })
+            .collect()
+    }
+
     pub(crate) fn build(&mut self, body: &TypedExpr, ir_stack: &mut AirStack) {
+        let start = ir_stack.air.len();
+
+        self.build_expr(body, ir_stack);
+
+        if let (Some(recorder), Some(head)) = (&mut self.source_map, ir_stack.air.get(start)) {
+            recorder.locate(head, body.location());
+        }
+    }
+
+    fn build_expr(&mut self, body: &TypedExpr, ir_stack: &mut AirStack) {
         match body {
             TypedExpr::Int { value, .. } => ir_stack.integer(value.to_string()),
             TypedExpr::String { value, .. } => ir_stack.string(value.to_string()),
@@ -229,7 +460,29 @@ impl<'a> CodeGenerator<'a> {
             TypedExpr::Pipeline { expressions, .. } | TypedExpr::Sequence { expressions, .. } => {
                 let mut stacks = Vec::new();
 
-                for expr in expressions.iter() {
+                for (index, expr) in expressions.iter().enumerate() {
+                    if let TypedExpr::Assignment {
+                        value,
+                        pattern: Pattern::Var { name, location },
+                        kind: AssignmentKind::Let,
+                        ..
+                    } = expr
+                    {
+                        if matches!(**value, TypedExpr::Fn { .. }) && value.tipo().is_generic() {
+                            let mut uses = vec![];
+
+                            for later in &expressions[index + 1..] {
+                                later.local_variable_uses(*location, &mut uses);
+                            }
+
+                            self.local_generic_functions.push(*location);
+
+                            stacks.extend(self.local_generic_function(name, value, uses, ir_stack));
+
+                            continue;
+                        }
+                    }
+
                     let mut stack = ir_stack.empty_with_scope();
                     self.build(expr, &mut stack);
                     stacks.push(stack);
@@ -240,15 +493,35 @@ impl<'a> CodeGenerator<'a> {
             TypedExpr::Var {
                 constructor, name, ..
             } => match &constructor.variant {
-                ValueConstructorVariant::ModuleConstant { literal, .. } => {
+                ValueConstructorVariant::ModuleConstant {
+                    literal: Some(literal),
+                    ..
+                } => {
                     builder::constants_ir(literal, ir_stack);
                 }
+                ValueConstructorVariant::ModuleConstant {
+                    literal: None,
+                    module,
+                    name,
+                    ..
+                } => {
+                    self.build_evaluated_constant(module, name, constructor.tipo.clone(), ir_stack);
+                }
                 ValueConstructorVariant::ModuleFn {
                     builtin: Some(builtin),
                     ..
                 } => {
                     ir_stack.builtin(*builtin, constructor.tipo.clone(), vec![]);
                 }
+                ValueConstructorVariant::LocalVariable { location }
+                    if self.local_generic_functions.contains(location) =>
+                {
+                    let mut variant = String::new();
+
+                    builder::get_local_variant_name(&mut variant, &constructor.tipo);
+
+                    ir_stack.var(constructor.clone(), format!("{name}{variant}"), "");
+                }
                 _ => {
                     ir_stack.var(constructor.clone(), name, "");
                 }
@@ -521,11 +794,13 @@ impl<'a> CodeGenerator<'a> {
                     pattern_stack.merge_child(value_stack);
                     ir_stack.merge(pattern_stack);
                 } else {
+                    let clauses = builder::guard_literal_patterns(clauses, &self.id_gen);
+
                     // TODO: go over rearrange clauses
                     let clauses = if subject_tipo.is_list() {
-                        builder::rearrange_clauses(clauses.clone())
+                        builder::rearrange_clauses(clauses)
                     } else {
-                        clauses.clone()
+                        clauses
                     };
 
                     if let Some((last_clause, clauses)) = clauses.split_last() {
@@ -651,38 +926,48 @@ impl<'a> CodeGenerator<'a> {
                 tipo,
                 ..
             } => match constructor {
-                ModuleValueConstructor::Record { .. } => {
-                    todo!("Records from modules not yet implemented.")
+                ModuleValueConstructor::Record { name, .. } => {
+                    let type_info = self.module_types.get(module_name).unwrap();
+                    let value = type_info.values.get(name).unwrap();
+
+                    ir_stack.var(
+                        ValueConstructor::public(tipo.clone(), value.variant.clone()),
+                        name,
+                        "",
+                    );
                 }
                 ModuleValueConstructor::Fn { name, module, .. } => {
-                    let func = self.functions.get(&FunctionAccessKey {
-                        module_name: module_name.clone(),
-                        function_name: name.clone(),
-                        variant_name: String::new(),
-                    });
-
                     let type_info = self.module_types.get(module_name).unwrap();
                     let value = type_info.values.get(name).unwrap();
 
-                    if let Some(_func) = func {
+                    if let ValueConstructorVariant::ModuleFn {
+                        builtin: Some(builtin),
+                        ..
+                    } = &value.variant
+                    {
+                        ir_stack.builtin(*builtin, tipo.clone(), vec![]);
+                    } else {
                         ir_stack.var(
                             ValueConstructor::public(tipo.clone(), value.variant.clone()),
                             format!("{module}_{name}"),
                             "",
                         );
-                    } else {
-                        let ValueConstructorVariant::ModuleFn {
-                            builtin: Some(builtin), ..
-                        } = &value.variant else {
-                            unreachable!()
-                        };
-
-                        ir_stack.builtin(*builtin, tipo.clone(), vec![]);
                     }
                 }
-                ModuleValueConstructor::Constant { literal, .. } => {
+                ModuleValueConstructor::Constant {
+                    literal: Some(literal),
+                    ..
+                } => {
                     builder::constants_ir(literal, ir_stack);
                 }
+                ModuleValueConstructor::Constant {
+                    literal: None,
+                    module,
+                    name,
+                    ..
+                } => {
+                    self.build_evaluated_constant(module, name, tipo.clone(), ir_stack);
+                }
             },
             TypedExpr::RecordUpdate {
                 spread, args, tipo, ..
@@ -757,6 +1042,53 @@ impl<'a> CodeGenerator<'a> {
         }
     }
 
+    /// Functions are located in the module defining them, whichever module
+    /// uses them.
+    fn build_function_body(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        body: &TypedExpr,
+        ir_stack: &mut AirStack,
+    ) {
+        let Some(recorder) = &mut self.source_map else {
+            return self.build(body, ir_stack);
+        };
+
+        let caller = (
+            std::mem::replace(&mut recorder.module, module_name.to_string()),
+            std::mem::replace(&mut recorder.function, function_name.to_string()),
+        );
+
+        self.build(body, ir_stack);
+
+        if let Some(recorder) = &mut self.source_map {
+            (recorder.module, recorder.function) = caller;
+        }
+    }
+
+    /// A constant that can't be evaluated is noted as such and fails when the
+    /// program runs.
+    fn build_evaluated_constant(
+        &mut self,
+        module_name: &str,
+        constant_name: &str,
+        tipo: Arc<Type>,
+        ir_stack: &mut AirStack,
+    ) {
+        match self.evaluate_constant(module_name, constant_name) {
+            Ok(value) => ir_stack.constant(tipo, value),
+            Err(_) => {
+                self.failed_constant.get_or_insert(ConstantAccessKey {
+                    module_name: module_name.to_string(),
+                    constant_name: constant_name.to_string(),
+                });
+
+                ir_stack.error(tipo);
+            }
+        }
+    }
+
     fn handle_each_clause(
         &mut self,
         ir_stack: &mut AirStack,
@@ -990,6 +1322,19 @@ impl<'a> CodeGenerator<'a> {
 
                 pattern_stack.merge_child(value_stack);
             }
+            Pattern::String { value, .. } => {
+                pattern_stack.string(value);
+
+                pattern_stack.merge_child(value_stack);
+            }
+            Pattern::ByteArray { value, .. } => {
+                pattern_stack.byte_array(value.clone());
+
+                pattern_stack.merge_child(value_stack);
+            }
+            Pattern::IntRange { .. } => {
+                unreachable!("Ranges are matched in clause guards")
+            }
             Pattern::Var { name, .. } => {
                 pattern_stack.void();
 
@@ -997,7 +1342,7 @@ impl<'a> CodeGenerator<'a> {
 
                 var_stack.local_var(
                     tipo.clone().into(),
-                    clause_properties.original_subject_name(),
+                    builder::whole_subject_name(tipo, clause_properties),
                 );
 
                 pattern_stack.let_assignment(name, var_stack);
@@ -1009,7 +1354,7 @@ impl<'a> CodeGenerator<'a> {
 
                 new_stack.local_var(
                     tipo.clone().into(),
-                    clause_properties.original_subject_name(),
+                    builder::whole_subject_name(tipo, clause_properties),
                 );
 
                 let mut let_stack = pattern_stack.empty_with_scope();
@@ -1139,7 +1484,10 @@ impl<'a> CodeGenerator<'a> {
         tipo: &Type,
     ) {
         match pattern {
-            Pattern::Int { .. } => unreachable!(),
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => unreachable!(),
             Pattern::Var { .. } => unreachable!(),
             Pattern::Assign { .. } => todo!("Nested assign not yet implemented"),
             Pattern::Discard { .. } => {
@@ -1625,7 +1973,10 @@ impl<'a> CodeGenerator<'a> {
 
                 inner_name
             }
-            Pattern::Int { .. } => {
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => {
                 let error_message = "Nested pattern-match on integers isn't implemented yet. Use when clause-guard as an alternative, or break down the pattern.";
                 todo!("{}", error_message)
             }
@@ -1661,7 +2012,10 @@ impl<'a> CodeGenerator<'a> {
         };
 
         match pattern {
-            Pattern::Int { .. } => todo!(),
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => unreachable!(),
             Pattern::Var { name, .. } => {
                 let expect_value_stack = value_stack.empty_with_scope();
                 pattern_stack.let_assignment(name, value_stack);
@@ -1765,7 +2119,10 @@ impl<'a> CodeGenerator<'a> {
         assignment_properties: AssignmentProperties,
     ) {
         match pattern {
-            Pattern::Int { .. } => todo!(),
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => unreachable!(),
             Pattern::Var { .. } => todo!(),
             Pattern::Assign { .. } => todo!(),
             Pattern::Discard { .. } => todo!(),
@@ -1802,7 +2159,10 @@ impl<'a> CodeGenerator<'a> {
 
                             elements_stack.merge(element_stack);
                         }
-                        Pattern::Int { .. } => todo!(),
+                        Pattern::Int { .. }
+                        | Pattern::IntRange { .. }
+                        | Pattern::String { .. }
+                        | Pattern::ByteArray { .. } => unreachable!(),
                         Pattern::Assign { .. } => todo!(),
                         Pattern::Discard { .. } => {
                             names.push("_".to_string());
@@ -1887,7 +2247,7 @@ impl<'a> CodeGenerator<'a> {
                 let mut expect_stack = pattern_stack.empty_with_scope();
 
                 match assignment_properties.kind {
-                    AssignmentKind::Let => {
+                    AssignmentKind::Let | AssignmentKind::Backpass => {
                         expect_stack.merge_child(value_stack);
                     }
                     AssignmentKind::Expect => {
@@ -2012,7 +2372,10 @@ impl<'a> CodeGenerator<'a> {
         assignment_properties: AssignmentProperties,
     ) {
         match pattern {
-            Pattern::Int { .. } => unreachable!(),
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => unreachable!(),
             Pattern::Var { name, .. } => {
                 expect_stack.merge(value_stack);
 
@@ -2712,7 +3075,10 @@ impl<'a> CodeGenerator<'a> {
 
                 Some(tuple_name)
             }
-            Pattern::Int { .. } => todo!(),
+            Pattern::Int { .. }
+            | Pattern::IntRange { .. }
+            | Pattern::String { .. }
+            | Pattern::ByteArray { .. } => unreachable!(),
             Pattern::Assign { .. } => todo!(),
         }
     }
@@ -2731,6 +3097,8 @@ impl<'a> CodeGenerator<'a> {
             false,
         );
 
+        builder::merge_cyclic_functions(&mut function_definitions, &mut func_index_map);
+
         let mut final_func_dep_ir = IndexMap::new();
         let mut to_be_defined = IndexMap::new();
 
@@ -2916,7 +3284,9 @@ impl<'a> CodeGenerator<'a> {
                             air: vec![],
                         };
 
-                        if func_comp.is_code_gen_func {
+                        if !func_comp.cycle.is_empty() {
+                            builder::define_cyclic_functions(&mut func_stack, &func_comp);
+                        } else if func_comp.is_code_gen_func {
                             func_stack = recursion_stack
                         } else {
                             func_stack.define_func(
@@ -2950,56 +3320,36 @@ impl<'a> CodeGenerator<'a> {
         ir_stack: &mut [Air],
         func_components: &mut IndexMap<FunctionAccessKey, FuncComponents>,
         func_index_map: &mut IndexMap<FunctionAccessKey, Scope>,
-        mut recursion_func_map: IndexMap<FunctionAccessKey, ()>,
+        recursion_func_map: IndexMap<FunctionAccessKey, ()>,
         in_zero_arg_func: bool,
     ) {
         self.define_ir_processor(ir_stack, func_components, func_index_map, in_zero_arg_func);
 
-        let mut recursion_func_map_to_add = recursion_func_map.clone();
-
         for func_index in func_index_map.clone().iter() {
             let func = func_index.0;
 
             let function_components = func_components.get_mut(func).unwrap();
             let mut function_ir = function_components.ir.clone();
             let in_zero_arg = function_components.args.is_empty() || in_zero_arg_func;
-            let mut skip = false;
-
-            for ir in function_ir.clone() {
-                let Air::Var {  constructor,  variant_name, .. } = ir
-                else {
-                    continue;
-                };
 
-                let ValueConstructorVariant::ModuleFn { name: func_name,  module, builtin: None, .. } = constructor.variant
-                else {
-                    continue;
-                };
-
-                let ir_function_key = FunctionAccessKey {
-                    module_name: module.clone(),
-                    function_name: func_name.clone(),
-                    variant_name: variant_name.clone(),
-                };
-
-                if recursion_func_map.contains_key(&ir_function_key) && func == &ir_function_key {
-                    skip = true;
-                } else if func == &ir_function_key {
-                    recursion_func_map_to_add.insert(ir_function_key, ());
-                }
-            }
+            // A function whose body is being processed further up is called back,
+            // by itself or by the functions it calls. Its dependencies are
+            // collected up there.
+            let skip = recursion_func_map.contains_key(func);
 
-            recursion_func_map = recursion_func_map_to_add.clone();
             if !skip {
                 let mut inner_func_components = IndexMap::new();
 
                 let mut inner_func_index_map = IndexMap::new();
 
+                let mut inner_recursion_func_map = recursion_func_map.clone();
+                inner_recursion_func_map.insert(func.clone(), ());
+
                 self.define_ir_recurse(
                     &mut function_ir,
                     &mut inner_func_components,
                     &mut inner_func_index_map,
-                    recursion_func_map.clone(),
+                    inner_recursion_func_map,
                     in_zero_arg,
                 );
 
@@ -3034,6 +3384,9 @@ impl<'a> CodeGenerator<'a> {
         in_zero_arg_func: bool,
     ) {
         let mut to_be_defined_map: IndexMap<FunctionAccessKey, Scope> = IndexMap::new();
+
+        self.resolve_implementations(ir_stack);
+
         for (index, ir) in ir_stack.to_vec().iter().enumerate().rev() {
             // I tried putting the 2 let else together, but then formatting stopped working
             #[rustfmt::skip]
@@ -3063,7 +3416,7 @@ impl<'a> CodeGenerator<'a> {
             if let Some(function) = self.functions.get(&non_variant_function_key).cloned() {
                 let mut func_stack = AirStack::with_scope(self.id_gen.clone(), scope.clone());
 
-                self.build(&function.body, &mut func_stack);
+                self.build_function_body(module, name, &function.body, &mut func_stack);
 
                 let func_ir = func_stack.complete();
 
@@ -3091,9 +3444,11 @@ impl<'a> CodeGenerator<'a> {
 
                 mono_types = map.into_iter().collect();
 
-                let (variant_name, func_ir) =
+                let (variant_name, mut func_ir) =
                     builder::monomorphize(func_ir, mono_types, &constructor.tipo);
 
+                self.resolve_implementations(&mut func_ir);
+
                 let function_key = FunctionAccessKey {
                     module_name: module.clone(),
                     function_name: non_variant_function_key.function_name,
@@ -3174,7 +3529,12 @@ impl<'a> CodeGenerator<'a> {
                             let mut func_stack =
                                 AirStack::with_scope(self.id_gen.clone(), scope.clone());
 
-                            self.build(&function.body, &mut func_stack);
+                            self.build_function_body(
+                                &current_func.module_name,
+                                &current_func.function_name,
+                                &function.body,
+                                &mut func_stack,
+                            );
 
                             let temp_ir = func_stack.complete();
 
@@ -3223,6 +3583,7 @@ impl<'a> CodeGenerator<'a> {
                             args,
                             defined_by_zero_arg: in_zero_arg_func,
                             is_code_gen_func: false,
+                            cycle: vec![],
                         },
                     );
                 }
@@ -3273,6 +3634,7 @@ impl<'a> CodeGenerator<'a> {
                         args: vec!["__one".to_string()],
                         defined_by_zero_arg: in_zero_arg_func,
                         is_code_gen_func: true,
+                        cycle: vec![],
                     },
                 );
 
@@ -3290,6 +3652,94 @@ impl<'a> CodeGenerator<'a> {
         }
     }
 
+    /// Points interface methods to the implementation for the type they are
+    /// used at, which is only known once their function is monomorphised.
+    fn resolve_implementations(&self, ir_stack: &mut [Air]) {
+        let prelude = "nano".to_string();
+
+        for ir in ir_stack.iter_mut() {
+            let Air::Var {
+                scope, constructor, ..
+            } = ir
+            else {
+                continue;
+            };
+
+            let ValueConstructorVariant::ModuleFn {
+                name,
+                module,
+                arity,
+                location,
+                builtin: None,
+                ..
+            } = &constructor.variant
+            else {
+                continue;
+            };
+
+            let module_name = if module.is_empty() { &prelude } else { module };
+
+            let Some(interface) = self.module_types.get(module_name).and_then(|info| {
+                info.interfaces
+                    .values()
+                    .find(|interface| interface.methods.contains_key(name))
+            }) else {
+                continue;
+            };
+
+            let implemented = builder::find_generic(
+                &interface.methods[name],
+                &constructor.tipo,
+                interface.parameter,
+            );
+
+            let Some(Type::App {
+                module: type_module,
+                name: type_name,
+                ..
+            }) = implemented.as_deref()
+            else {
+                continue;
+            };
+
+            let key = ImplementationKey {
+                interface: interface.key(),
+                module_name: type_module.clone(),
+                type_name: type_name.clone(),
+            };
+
+            let Some(implementation) = self
+                .module_types
+                .values()
+                .find_map(|info| info.implementations.get(&key))
+            else {
+                continue;
+            };
+
+            let method_name = key.method_name(name);
+
+            let resolved = Air::Var {
+                scope: scope.clone(),
+                constructor: ValueConstructor {
+                    public: false,
+                    variant: ValueConstructorVariant::ModuleFn {
+                        name: method_name.clone(),
+                        field_map: None,
+                        module: implementation.module.clone(),
+                        arity: *arity,
+                        location: *location,
+                        builtin: None,
+                    },
+                    tipo: constructor.tipo.clone(),
+                },
+                name: method_name,
+                variant_name: String::new(),
+            };
+
+            *ir = resolved;
+        }
+    }
+
     fn convert_opaque_type_to_inner_ir(&mut self, ir_stack: &mut Vec<Air>) {
         let mut indices_to_remove = vec![];
         for (index, ir) in ir_stack.clone().into_iter().enumerate() {
@@ -3722,7 +4172,18 @@ impl<'a> CodeGenerator<'a> {
         let mut arg_stack: Vec<Term<Name>> = vec![];
 
         while let Some(ir_element) = ir_stack.pop() {
+            let location = self
+                .source_map
+                .as_ref()
+                .and_then(|recorder| recorder.location_of(&ir_element));
+
             self.gen_uplc(ir_element, &mut arg_stack);
+
+            if let (Some(recorder), Some(location), Some(term)) =
+                (&mut self.source_map, location, arg_stack.last())
+            {
+                recorder.tag(term, location);
+            }
         }
         arg_stack[0].clone()
     }
@@ -3828,6 +4289,7 @@ impl<'a> CodeGenerator<'a> {
                 };
             }
             Air::Void { .. } => arg_stack.push(Term::Constant(UplcConstant::Unit.into())),
+            Air::Constant { value, .. } => arg_stack.push(Term::Constant(value)),
             Air::List {
                 count, tipo, tail, ..
             } => {
@@ -4323,6 +4785,51 @@ impl<'a> CodeGenerator<'a> {
                     arg_stack.push(term);
                 }
             }
+            Air::DefineCyclicFuncs { functions, .. } => {
+                // Like a recursive function, but each function is given all
+                // of them rather than just itself.
+                let names = functions.iter().map(|(name, _)| name).collect_vec();
+
+                let mut bodies = vec![];
+
+                for (_, params) in functions.iter() {
+                    let mut func_body = arg_stack.pop().unwrap();
+
+                    for param in params.iter().rev() {
+                        func_body = func_body.lambda(param.clone());
+                    }
+
+                    for name in names.iter().rev() {
+                        func_body = func_body.lambda(name.to_string());
+                    }
+
+                    bodies.push(func_body);
+                }
+
+                let mut term = arg_stack.pop().unwrap();
+
+                for name in names.iter().rev() {
+                    term = term.lambda(name.to_string());
+                }
+
+                for name in names.iter() {
+                    let instance = names.iter().fold(Term::var(name.to_string()), |instance, other| {
+                        instance.apply(Term::var(other.to_string()))
+                    });
+
+                    term = term.apply(instance);
+                }
+
+                for name in names.iter().rev() {
+                    term = term.lambda(name.to_string());
+                }
+
+                for body in bodies {
+                    term = term.apply(body);
+                }
+
+                arg_stack.push(term);
+            }
             Air::Let { name, .. } => {
                 let arg = arg_stack.pop().unwrap();
 
@@ -4381,17 +4888,11 @@ impl<'a> CodeGenerator<'a> {
             } => {
                 let subject = arg_stack.pop().unwrap();
 
-                let subject = if tipo.is_int()
-                    || tipo.is_bytearray()
-                    || tipo.is_string()
-                    || tipo.is_list()
-                    || tipo.is_tuple()
-                    || tipo.is_bool()
-                {
-                    subject
-                } else {
+                let subject = if builder::matches_on_constr_index(&tipo) {
                     self.needs_field_access = true;
                     Term::var(CONSTR_INDEX_EXPOSER).apply(subject)
+                } else {
+                    subject
                 };
 
                 let mut term = arg_stack.pop().unwrap();
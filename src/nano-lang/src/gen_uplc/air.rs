@@ -9,8 +9,8 @@
 */
 
 use indexmap::IndexSet;
-use std::sync::Arc;
-use untyped_plutus_core::builtins::DefaultFunction;
+use std::{rc::Rc, sync::Arc};
+use untyped_plutus_core::{ast::Constant as UplcConstant, builtins::DefaultFunction};
 use crate::{
     ast::{BinOp, UnOp},
     tipo::{Type, ValueConstructor},
@@ -35,6 +35,12 @@ pub enum Air {
         scope: Scope,
         value: bool,
     },
+    /// The evaluated value of a module constant.
+    Constant {
+        scope: Scope,
+        tipo: Arc<Type>,
+        value: Rc<UplcConstant>,
+    },
     List {
         scope: Scope,
         count: usize,
@@ -69,6 +75,11 @@ pub enum Air {
         recursive: bool,
         variant_name: String,
     },
+    /// Functions calling each other, followed by their bodies in order.
+    DefineCyclicFuncs {
+        scope: Scope,
+        functions: Vec<(String, Vec<String>)>,
+    },
     Fn {
         scope: Scope,
         params: Vec<String>,
@@ -238,12 +249,14 @@ impl Air {
             | Air::String { scope, .. }
             | Air::ByteArray { scope, .. }
             | Air::Bool { scope, .. }
+            | Air::Constant { scope, .. }
             | Air::List { scope, .. }
             | Air::Tuple { scope, .. }
             | Air::Void { scope }
             | Air::Var { scope, .. }
             | Air::Call { scope, .. }
             | Air::DefineFunc { scope, .. }
+            | Air::DefineCyclicFuncs { scope, .. }
             | Air::Fn { scope, .. }
             | Air::Builtin { scope, .. }
             | Air::BinOp { scope, .. }
@@ -283,12 +296,14 @@ impl Air {
             | Air::String { scope, .. }
             | Air::ByteArray { scope, .. }
             | Air::Bool { scope, .. }
+            | Air::Constant { scope, .. }
             | Air::List { scope, .. }
             | Air::Tuple { scope, .. }
             | Air::Void { scope }
             | Air::Var { scope, .. }
             | Air::Call { scope, .. }
             | Air::DefineFunc { scope, .. }
+            | Air::DefineCyclicFuncs { scope, .. }
             | Air::Fn { scope, .. }
             | Air::Builtin { scope, .. }
             | Air::BinOp { scope, .. }
@@ -380,6 +395,7 @@ impl Air {
             ),
             Air::Var { constructor, .. } => Some(constructor.tipo.clone()),
             Air::List { tipo, .. }
+            | Air::Constant { tipo, .. }
             | Air::Tuple { tipo, .. }
             | Air::Call { tipo, .. }
             | Air::Builtin { tipo, .. }
@@ -402,6 +418,7 @@ impl Air {
             | Air::ErrorTerm { tipo, .. }
             | Air::Trace { tipo, .. } => Some(tipo.clone()),
             Air::DefineFunc { .. }
+            | Air::DefineCyclicFuncs { .. }
             | Air::Fn { .. }
             | Air::Let { .. }
             | Air::WrapClause { .. }
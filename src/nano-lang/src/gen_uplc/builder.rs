@@ -23,11 +23,11 @@ use untyped_plutus_core::{
 };
 use crate::{
     ast::{
-        AssignmentKind, BinOp, ClauseGuard, Constant, DataType, Pattern, Span, TypedArg,
-        TypedClause, TypedDataType, UnOp,
+        AssignmentKind, BinOp, CallArg, ClauseGuard, Constant, DataType, Pattern, Purpose, Span,
+        TypedArg, TypedClause, TypedDataType, UnOp,
     },
     expr::TypedExpr,
-    tipo::{PatternConstructor, Type, TypeVar, ValueConstructorVariant},
+    tipo::{PatternConstructor, Type, TypeVar, ValueConstructor, ValueConstructorVariant},
     IdGenerator,
 };
 use super::{air::Air, scope::Scope, stack::AirStack};
@@ -40,6 +40,9 @@ pub struct FuncComponents {
     pub recursive: bool,
     pub defined_by_zero_arg: bool,
     pub is_code_gen_func: bool,
+    /// The functions, this one first, which call each other and so are
+    /// defined together. Empty unless there are several of them.
+    pub cycle: Vec<(FunctionAccessKey, FuncComponents)>,
 }
 
 #[derive(Clone, Eq, Debug, PartialEq, Hash)]
@@ -54,6 +57,12 @@ pub struct DataTypeKey {
     pub defined_type: String,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ConstantAccessKey {
+    pub module_name: String,
+    pub constant_name: String,
+}
+
 pub type ConstrUsageKey = String;
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -194,6 +203,32 @@ impl ClauseProperties {
     }
 }
 
+/// Whether a `when` on values of the type matches on the index of their
+/// constructor rather than on the values themselves.
+pub fn matches_on_constr_index(tipo: &Type) -> bool {
+    !(tipo.is_int()
+        || tipo.is_bytearray()
+        || tipo.is_string()
+        || tipo.is_list()
+        || tipo.is_tuple()
+        || tipo.is_bool())
+}
+
+/// The variable holding the whole subject of a clause, for patterns that
+/// bind it. A subject matched on its constructor index is kept aside in the
+/// clause variable.
+pub fn whole_subject_name(tipo: &Type, clause_properties: &mut ClauseProperties) -> String {
+    if matches!(clause_properties, ClauseProperties::ConstrClause { .. })
+        && matches_on_constr_index(tipo)
+    {
+        *clause_properties.needs_constr_var() = true;
+
+        clause_properties.clause_var_name().clone()
+    } else {
+        clause_properties.original_subject_name().clone()
+    }
+}
+
 pub fn convert_type_to_data(term: Term<Name>, field_type: &Arc<Type>) -> Term<Name> {
     if field_type.is_bytearray() {
         Term::b_data().apply(term)
@@ -284,6 +319,219 @@ pub fn convert_data_to_type(term: Term<Name>, field_type: &Arc<Type>) -> Term<Na
     }
 }
 
+/// Clauses matching a literal at the top of their pattern compare it with the
+/// subject directly. Ranges, and literals nested in other patterns, bind the
+/// value to a variable instead and check it in the clause guard, ahead of any
+/// guard the clause already has.
+pub fn guard_literal_patterns(clauses: &[TypedClause], id_gen: &IdGenerator) -> Vec<TypedClause> {
+    clauses
+        .iter()
+        .map(|clause| {
+            let mut checks = vec![];
+
+            let pattern = guard_literal_pattern(&clause.pattern, true, id_gen, &mut checks);
+
+            let guard = checks
+                .into_iter()
+                .chain(clause.guard.clone())
+                .reduce(|left, right| ClauseGuard::And {
+                    location: clause.location,
+                    left: left.into(),
+                    right: right.into(),
+                });
+
+            TypedClause {
+                location: clause.location,
+                pattern,
+                guard,
+                then: clause.then.clone(),
+            }
+        })
+        .collect()
+}
+
+fn guard_literal_pattern(
+    pattern: &Pattern<PatternConstructor, Arc<Type>>,
+    is_top: bool,
+    id_gen: &IdGenerator,
+    checks: &mut Vec<ClauseGuard<Arc<Type>>>,
+) -> Pattern<PatternConstructor, Arc<Type>> {
+    let (location, tipo, literal) = match pattern {
+        Pattern::IntRange {
+            location,
+            start,
+            end,
+        } => {
+            let (var, value) = pattern_var(*location, crate::builtins::int(), id_gen);
+
+            let bound = |value: &String| {
+                Box::new(ClauseGuard::Constant(Constant::Int {
+                    location: *location,
+                    value: value.clone(),
+                }))
+            };
+
+            checks.push(ClauseGuard::And {
+                location: *location,
+                left: ClauseGuard::LtEqInt {
+                    location: *location,
+                    left: bound(start),
+                    right: value.clone().into(),
+                }
+                .into(),
+                right: ClauseGuard::LtEqInt {
+                    location: *location,
+                    left: value.into(),
+                    right: bound(end),
+                }
+                .into(),
+            });
+
+            return var;
+        }
+
+        Pattern::Int { location, value } if !is_top => (
+            *location,
+            crate::builtins::int(),
+            Constant::Int {
+                location: *location,
+                value: value.clone(),
+            },
+        ),
+
+        Pattern::String { location, value } if !is_top => (
+            *location,
+            crate::builtins::string(),
+            Constant::String {
+                location: *location,
+                value: value.clone(),
+            },
+        ),
+
+        Pattern::ByteArray {
+            location,
+            value,
+            preferred_format,
+        } if !is_top => (
+            *location,
+            crate::builtins::byte_array(),
+            Constant::ByteArray {
+                location: *location,
+                bytes: value.clone(),
+                preferred_format: *preferred_format,
+            },
+        ),
+
+        Pattern::Assign {
+            name,
+            location,
+            pattern,
+        } => {
+            return Pattern::Assign {
+                name: name.clone(),
+                location: *location,
+                pattern: guard_literal_pattern(pattern, is_top, id_gen, checks).into(),
+            }
+        }
+
+        Pattern::List {
+            location,
+            elements,
+            tail,
+        } => {
+            return Pattern::List {
+                location: *location,
+                elements: elements
+                    .iter()
+                    .map(|element| guard_literal_pattern(element, false, id_gen, checks))
+                    .collect(),
+                tail: tail
+                    .as_ref()
+                    .map(|tail| guard_literal_pattern(tail, false, id_gen, checks).into()),
+            }
+        }
+
+        Pattern::Tuple { location, elems } => {
+            return Pattern::Tuple {
+                location: *location,
+                elems: elems
+                    .iter()
+                    .map(|elem| guard_literal_pattern(elem, false, id_gen, checks))
+                    .collect(),
+            }
+        }
+
+        Pattern::Constructor {
+            is_record,
+            location,
+            name,
+            arguments,
+            module,
+            constructor,
+            with_spread,
+            tipo,
+        } => {
+            return Pattern::Constructor {
+                is_record: *is_record,
+                location: *location,
+                name: name.clone(),
+                arguments: arguments
+                    .iter()
+                    .map(|argument| CallArg {
+                        value: guard_literal_pattern(&argument.value, false, id_gen, checks),
+                        ..argument.clone()
+                    })
+                    .collect(),
+                module: module.clone(),
+                constructor: constructor.clone(),
+                with_spread: *with_spread,
+                tipo: tipo.clone(),
+            }
+        }
+
+        Pattern::Int { .. }
+        | Pattern::String { .. }
+        | Pattern::ByteArray { .. }
+        | Pattern::Var { .. }
+        | Pattern::Discard { .. } => return pattern.clone(),
+    };
+
+    let (var, value) = pattern_var(location, tipo, id_gen);
+
+    checks.push(ClauseGuard::Equals {
+        location,
+        left: value.into(),
+        right: ClauseGuard::Constant(literal).into(),
+    });
+
+    var
+}
+
+/// A fresh variable for a clause to bind part of its subject to, and the
+/// guard reading it back.
+fn pattern_var(
+    location: Span,
+    tipo: Arc<Type>,
+    id_gen: &IdGenerator,
+) -> (
+    Pattern<PatternConstructor, Arc<Type>>,
+    ClauseGuard<Arc<Type>>,
+) {
+    let name = format!("__pattern_{}", id_gen.next());
+
+    (
+        Pattern::Var {
+            location,
+            name: name.clone(),
+        },
+        ClauseGuard::Var {
+            location,
+            tipo,
+            name,
+        },
+    )
+}
+
 pub fn rearrange_clauses(clauses: Vec<TypedClause>) -> Vec<TypedClause> {
     let mut sorted_clauses = clauses;
 
@@ -685,7 +933,10 @@ pub fn check_when_pattern_needs(
                 check_when_pattern_needs(element, clause_properties);
             }
         }
-        Pattern::Int { .. } => {
+        Pattern::Int { .. }
+        | Pattern::IntRange { .. }
+        | Pattern::String { .. }
+        | Pattern::ByteArray { .. } => {
             *clause_properties.needs_constr_var() = true;
 
             *clause_properties.is_complex_clause() = true;
@@ -852,6 +1103,49 @@ pub fn get_generic_id_and_type(tipo: &Type, param: &Type) -> Vec<(u64, Arc<Type>
     generics_ids
 }
 
+/// The type a generic stands for in an instance of a type, e.g. `Int` for `a`
+/// in `fn(a, a) -> Bool` instantiated as `fn(Int, Int) -> Bool`.
+pub fn find_generic(tipo: &Type, instance: &Type, id: u64) -> Option<Arc<Type>> {
+    if let Type::Var { tipo: var } = instance {
+        if let TypeVar::Link { tipo: instance } = &*var.borrow() {
+            return find_generic(tipo, instance, id);
+        }
+    }
+
+    match (tipo, instance) {
+        (Type::Var { tipo: var }, _) => match &*var.borrow() {
+            TypeVar::Generic { id: generic } if *generic == id => Some(instance.clone().into()),
+            TypeVar::Link { tipo } => find_generic(tipo, instance, id),
+            _ => None,
+        },
+
+        (
+            Type::App { args, .. },
+            Type::App {
+                args: instances, ..
+            },
+        )
+        | (Type::Tuple { elems: args }, Type::Tuple { elems: instances }) => args
+            .iter()
+            .zip(instances)
+            .find_map(|(tipo, instance)| find_generic(tipo, instance, id)),
+
+        (
+            Type::Fn { args, ret },
+            Type::Fn {
+                args: instances,
+                ret: instance_ret,
+            },
+        ) => args
+            .iter()
+            .chain([ret])
+            .zip(instances.iter().chain([instance_ret]))
+            .find_map(|(tipo, instance)| find_generic(tipo, instance, id)),
+
+        _ => None,
+    }
+}
+
 pub fn get_variant_name(new_name: &mut String, t: &Arc<Type>) {
     new_name.push_str(&if t.is_string() {
         "_string".to_string()
@@ -902,6 +1196,47 @@ pub fn get_variant_name(new_name: &mut String, t: &Arc<Type>) {
     });
 }
 
+/// Like `get_variant_name`, but for the instances of generic local functions
+/// which may still mention the generic types of the enclosing function. Those
+/// are named after their id, so instances stay apart until the enclosing
+/// function is itself monomorphized.
+pub fn get_local_variant_name(new_name: &mut String, t: &Arc<Type>) {
+    if !t.is_generic() {
+        return get_variant_name(new_name, t);
+    }
+
+    match &**t {
+        Type::Var { tipo } => match &*tipo.borrow() {
+            TypeVar::Link { tipo } => get_local_variant_name(new_name, tipo),
+            TypeVar::Generic { id } => new_name.push_str(&format!("_generic{id}")),
+            TypeVar::Unbound { .. } => new_name.push_str("_unbound"),
+        },
+        Type::App { name, args, .. } => {
+            new_name.push_str(&format!("_{}", name.to_lowercase()));
+
+            for arg in args {
+                get_local_variant_name(new_name, arg);
+            }
+        }
+        Type::Tuple { elems } => {
+            new_name.push_str("_tuple");
+
+            for elem in elems {
+                get_local_variant_name(new_name, elem);
+            }
+        }
+        Type::Fn { args, ret } => {
+            new_name.push_str("_fn");
+
+            for arg in args {
+                get_local_variant_name(new_name, arg);
+            }
+
+            get_local_variant_name(new_name, ret);
+        }
+    }
+}
+
 pub fn convert_constants_to_data(constants: Vec<Rc<UplcConstant>>) -> Vec<UplcConstant> {
     let mut new_constants = vec![];
     for constant in constants {
@@ -976,20 +1311,57 @@ pub fn wrap_validator_args(term: Term<Name>, arguments: &[TypedArg]) -> Term<Nam
     term
 }
 
-pub fn wrap_as_multi_validator(spend: Term<Name>, mint: Term<Name>) -> Term<Name> {
-    Term::equals_integer()
-        .apply(Term::integer(0.into()))
-        .apply(Term::var(CONSTR_INDEX_EXPOSER).apply(Term::var("__second_arg")))
-        .delayed_if_else(
-            mint.apply(Term::var("__first_arg"))
-                .apply(Term::var("__second_arg")),
-            spend.apply(Term::var("__first_arg")).apply(
-                Term::head_list()
+/// Runs the handler of the purpose a multi-validator is run for, failing if
+/// it has none. Only spending comes with a third argument, so the redeemer
+/// given to a spending handler is wrapped in a constructor other than the
+/// first, to tell it apart from a script context.
+pub fn wrap_as_multi_validator(handlers: Vec<(Purpose, Term<Name>)>) -> Term<Name> {
+    let mut spend = None;
+    let mut other = Term::Error;
+
+    for (purpose, handler) in handlers.into_iter().rev() {
+        if purpose == Purpose::Spend {
+            spend = Some(handler);
+
+            continue;
+        }
+
+        other = Term::equals_integer()
+            .apply(Term::integer(purpose.constr_index().into()))
+            .apply(Term::var("__purpose_index"))
+            .delayed_if_else(
+                handler
+                    .apply(Term::var("__first_arg"))
+                    .apply(Term::var("__second_arg")),
+                other,
+            );
+    }
+
+    // The purpose comes second among the fields of the script context.
+    let other = other.lambda("__purpose_index").apply(
+        Term::var(CONSTR_INDEX_EXPOSER).apply(
+            Term::head_list().apply(
+                Term::tail_list()
                     .apply(Term::var(CONSTR_FIELDS_EXPOSER).apply(Term::var("__second_arg"))),
             ),
-        )
-        .lambda("__second_arg")
-        .lambda("__first_arg")
+        ),
+    );
+
+    let term = match spend {
+        Some(spend) => Term::equals_integer()
+            .apply(Term::integer(0.into()))
+            .apply(Term::var(CONSTR_INDEX_EXPOSER).apply(Term::var("__second_arg")))
+            .delayed_if_else(
+                other,
+                spend.apply(Term::var("__first_arg")).apply(
+                    Term::head_list()
+                        .apply(Term::var(CONSTR_FIELDS_EXPOSER).apply(Term::var("__second_arg"))),
+                ),
+            ),
+        None => other,
+    };
+
+    term.lambda("__second_arg").lambda("__first_arg")
 }
 
 pub fn monomorphize(
@@ -1514,7 +1886,9 @@ pub fn handle_func_dependencies(
                 air: recursion_ir,
             };
 
-            if depend_comp.is_code_gen_func {
+            if !depend_comp.cycle.is_empty() {
+                define_cyclic_functions(&mut temp_stack, depend_comp);
+            } else if depend_comp.is_code_gen_func {
                 temp_stack = recursion_stack;
             } else {
                 temp_stack.define_func(
@@ -1542,6 +1916,266 @@ pub fn handle_func_dependencies(
     }
 }
 
+/// Merges each group of functions calling each other, directly or not, into
+/// the first of them, so that the dependencies between functions are acyclic.
+pub fn merge_cyclic_functions(
+    func_components: &mut IndexMap<FunctionAccessKey, FuncComponents>,
+    func_index_map: &mut IndexMap<FunctionAccessKey, Scope>,
+) {
+    for mut cycle in strongly_connected_functions(func_components) {
+        if cycle.len() < 2 {
+            continue;
+        }
+
+        cycle.sort_by_key(|key| func_components.get_index_of(key));
+
+        let members = cycle
+            .iter()
+            .map(|key| (key.clone(), func_components.shift_remove(key).unwrap()))
+            .collect_vec();
+
+        let mut scope = func_index_map.get(&cycle[0]).cloned().unwrap_or_default();
+
+        for key in &cycle {
+            if let Some(member_scope) = func_index_map.shift_remove(key) {
+                scope = scope.common_ancestor(&member_scope);
+            }
+        }
+
+        let (_, first) = &members[0];
+
+        let merged = FuncComponents {
+            ir: first.ir.clone(),
+            dependencies: members
+                .iter()
+                .flat_map(|(_, member)| member.dependencies.clone())
+                .filter(|dependency| !cycle.contains(dependency))
+                .unique()
+                .collect_vec(),
+            args: first.args.clone(),
+            recursive: false,
+            defined_by_zero_arg: members.iter().all(|(_, member)| member.defined_by_zero_arg),
+            is_code_gen_func: false,
+            cycle: members.clone(),
+        };
+
+        func_components.insert(cycle[0].clone(), merged);
+        func_index_map.insert(cycle[0].clone(), scope);
+
+        for component in func_components.values_mut() {
+            component.dependencies = component
+                .dependencies
+                .iter()
+                .map(|dependency| {
+                    if cycle.contains(dependency) {
+                        cycle[0].clone()
+                    } else {
+                        dependency.clone()
+                    }
+                })
+                .unique()
+                .collect_vec();
+        }
+
+        if let Some(merged) = func_components.get_mut(&cycle[0]) {
+            merged
+                .dependencies
+                .retain(|dependency| dependency != &cycle[0]);
+        }
+    }
+}
+
+/// The groups of functions which call each other, following Tarjan's
+/// algorithm.
+fn strongly_connected_functions(
+    func_components: &IndexMap<FunctionAccessKey, FuncComponents>,
+) -> Vec<Vec<FunctionAccessKey>> {
+    struct Tarjan<'a> {
+        func_components: &'a IndexMap<FunctionAccessKey, FuncComponents>,
+        indices: IndexMap<FunctionAccessKey, usize>,
+        low_links: IndexMap<FunctionAccessKey, usize>,
+        stack: Vec<FunctionAccessKey>,
+        on_stack: IndexSet<FunctionAccessKey>,
+        components: Vec<Vec<FunctionAccessKey>>,
+    }
+
+    impl Tarjan<'_> {
+        fn connect(&mut self, key: &FunctionAccessKey) {
+            let index = self.indices.len();
+
+            self.indices.insert(key.clone(), index);
+            self.low_links.insert(key.clone(), index);
+            self.stack.push(key.clone());
+            self.on_stack.insert(key.clone());
+
+            let func_components = self.func_components;
+
+            for dependency in &func_components[key].dependencies {
+                if !func_components.contains_key(dependency) {
+                    continue;
+                }
+
+                let low_link = if !self.indices.contains_key(dependency) {
+                    self.connect(dependency);
+
+                    self.low_links[dependency]
+                } else if self.on_stack.contains(dependency) {
+                    self.indices[dependency]
+                } else {
+                    continue;
+                };
+
+                if low_link < self.low_links[key] {
+                    self.low_links.insert(key.clone(), low_link);
+                }
+            }
+
+            if self.low_links[key] == index {
+                let mut component = vec![];
+
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+
+                    let is_root = &member == key;
+
+                    component.push(member);
+
+                    if is_root {
+                        break;
+                    }
+                }
+
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        func_components,
+        indices: IndexMap::new(),
+        low_links: IndexMap::new(),
+        stack: vec![],
+        on_stack: IndexSet::new(),
+        components: vec![],
+    };
+
+    for key in func_components.keys() {
+        if !tarjan.indices.contains_key(key) {
+            tarjan.connect(key);
+        }
+    }
+
+    tarjan.components
+}
+
+/// The name a function is bound to in the generated code.
+pub fn function_access_name(key: &FunctionAccessKey) -> String {
+    if key.module_name.is_empty() {
+        format!("{}{}", key.function_name, key.variant_name)
+    } else {
+        format!(
+            "{}_{}{}",
+            key.module_name, key.function_name, key.variant_name
+        )
+    }
+}
+
+/// Defines functions calling each other at once. Each takes every function
+/// of the cycle before its own parameters, which it passes along whenever it
+/// refers to one of them.
+pub fn define_cyclic_functions(stack: &mut AirStack, func_comp: &FuncComponents) {
+    let cycle = func_comp
+        .cycle
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect_vec();
+
+    let (functions, bodies) = func_comp
+        .cycle
+        .iter()
+        .map(|(key, component)| {
+            let body = AirStack {
+                id_gen: stack.id_gen.clone(),
+                scope: stack.scope.clone(),
+                air: handle_cyclic_recursion_ir(&cycle, component),
+            };
+
+            ((function_access_name(key), component.args.clone()), body)
+        })
+        .unzip();
+
+    stack.define_cyclic_funcs(functions, bodies);
+}
+
+/// Passes the functions of the cycle to each reference to one of them, so
+/// `is_odd(n)` becomes `(is_odd is_even is_odd)(n)`.
+fn handle_cyclic_recursion_ir(cycle: &[FunctionAccessKey], func_comp: &FuncComponents) -> Vec<Air> {
+    let mut recursion_ir = func_comp.ir.clone();
+
+    for index in (0..recursion_ir.len()).rev() {
+        let Air::Var {
+            scope,
+            constructor,
+            variant_name,
+            ..
+        } = &recursion_ir[index]
+        else {
+            continue;
+        };
+
+        let ValueConstructorVariant::ModuleFn {
+            name,
+            module,
+            builtin: None,
+            ..
+        } = &constructor.variant
+        else {
+            continue;
+        };
+
+        let key = FunctionAccessKey {
+            module_name: module.clone(),
+            function_name: name.clone(),
+            variant_name: variant_name.clone(),
+        };
+
+        if !cycle.contains(&key) {
+            continue;
+        }
+
+        let scope = scope.clone();
+        let tipo = constructor.tipo.clone();
+
+        for (offset, member) in cycle.iter().enumerate() {
+            recursion_ir.insert(
+                index + 1 + offset,
+                Air::Var {
+                    scope: scope.clone(),
+                    constructor: ValueConstructor::public(
+                        tipo.clone(),
+                        ValueConstructorVariant::LocalVariable {
+                            location: Span::empty(),
+                        },
+                    ),
+                    name: function_access_name(member),
+                    variant_name: String::new(),
+                },
+            );
+        }
+
+        recursion_ir.insert(
+            index,
+            Air::Call {
+                scope,
+                count: cycle.len(),
+                tipo,
+            },
+        );
+    }
+
+    recursion_ir
+}
+
 pub fn handle_recursion_ir(
     func_key: &FunctionAccessKey,
     func_comp: &FuncComponents,
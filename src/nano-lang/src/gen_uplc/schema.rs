@@ -0,0 +1,494 @@
+/*
+ * @file schema.rs
+ * @author Krisna Pranav
+ * @version 0.1
+ * @date 2023-05-30
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+use std::{collections::BTreeMap, fmt, ops::Deref, sync::Arc};
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+
+use crate::{
+    ast::{TypedArg, TypedDataType},
+    gen_uplc::builder::{find_and_replace_generics, get_generic_id_and_type, DataTypeKey},
+    tipo::{Type, TypeVar},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
+pub struct Annotated<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub annotated: T,
+}
+
+impl<T> From<T> for Annotated<T> {
+    fn from(annotated: T) -> Self {
+        Annotated {
+            title: None,
+            description: None,
+            annotated,
+        }
+    }
+}
+
+/// How a value of some type is laid out as `PlutusData`. This follows the
+/// conversions performed by `convert_type_to_data` and `convert_data_to_type`:
+///
+/// - `Int` is an integer, `ByteArray` are bytes and `String` are UTF-8 bytes;
+/// - `List<a>` is a list, unless its elements are pairs, which makes it a map;
+/// - tuples (pairs included) are lists of their elements;
+/// - `Void`, `Bool` and custom types are constructors, whose index is the
+///   position of the constructor in the type definition and whose fields are
+///   in declaration order (e.g. `Some` is 0 and `None` is 1);
+/// - opaque types wrapping a single value are erased to that value.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Schema {
+    Integer,
+    Bytes,
+    List(Items),
+    Map(Box<Annotated<Schema>>, Box<Annotated<Schema>>),
+    AnyOf(Vec<Annotated<Constructor>>),
+    /// A custom type, described in the [`Definitions`] under that key.
+    Ref(String),
+    /// Any `PlutusData`.
+    Opaque,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Items {
+    One(Box<Annotated<Schema>>),
+    Many(Vec<Annotated<Schema>>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Constructor {
+    pub index: usize,
+    pub fields: Vec<Annotated<Schema>>,
+}
+
+/// Schemas of custom types, indexed by a key derived from the type name and
+/// its arguments. Types are referred to by key from
+/// other schemas, which also makes recursive types representable.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Definitions {
+    inner: BTreeMap<String, Option<Annotated<Schema>>>,
+}
+
+impl Definitions {
+    pub fn new() -> Self {
+        Definitions::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn lookup(&self, key: &str) -> Option<&Annotated<Schema>> {
+        self.inner.get(key).and_then(|schema| schema.as_ref())
+    }
+
+    fn register<F>(&mut self, key: String, build_schema: F) -> Result<Schema, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<Annotated<Schema>, Error>,
+    {
+        if !self.inner.contains_key(&key) {
+            self.inner.insert(key.clone(), None);
+
+            let schema = build_schema(self)?;
+
+            self.inner.insert(key.clone(), Some(schema));
+        }
+
+        Ok(Schema::Ref(key))
+    }
+}
+
+impl Annotated<Schema> {
+    pub fn from_arg(
+        arg: &TypedArg,
+        data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+        definitions: &mut Definitions,
+    ) -> Result<Self, Error> {
+        let schema = Annotated::from_type(&arg.tipo, data_types, definitions)?;
+
+        Ok(Annotated {
+            title: Some(arg.arg_name.get_label()),
+            ..schema
+        })
+    }
+
+    pub fn from_type(
+        tipo: &Arc<Type>,
+        data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+        definitions: &mut Definitions,
+    ) -> Result<Self, Error> {
+        match tipo.as_ref() {
+            Type::App {
+                module, name, args, ..
+            } if module.is_empty() => match name.as_str() {
+                "Int" => Ok(Schema::Integer.into()),
+
+                "ByteArray" => Ok(Schema::Bytes.into()),
+
+                "String" => Ok(Annotated {
+                    title: Some("String".to_string()),
+                    description: Some("An UTF-8 encoded text.".to_string()),
+                    annotated: Schema::Bytes,
+                }),
+
+                "Data" => Ok(Annotated {
+                    title: Some("Data".to_string()),
+                    description: Some("Any Plutus data.".to_string()),
+                    annotated: Schema::Opaque,
+                }),
+
+                "Void" => Ok(Annotated {
+                    title: Some("Unit".to_string()),
+                    description: Some("The nullary constructor.".to_string()),
+                    annotated: Schema::AnyOf(vec![Constructor {
+                        index: 0,
+                        fields: vec![],
+                    }
+                    .into()]),
+                }),
+
+                "Bool" => Ok(Annotated {
+                    title: Some("Bool".to_string()),
+                    description: None,
+                    annotated: Schema::AnyOf(vec![
+                        Annotated {
+                            title: Some("False".to_string()),
+                            description: None,
+                            annotated: Constructor {
+                                index: 0,
+                                fields: vec![],
+                            },
+                        },
+                        Annotated {
+                            title: Some("True".to_string()),
+                            description: None,
+                            annotated: Constructor {
+                                index: 1,
+                                fields: vec![],
+                            },
+                        },
+                    ]),
+                }),
+
+                "List" if tipo.is_map() => {
+                    let elems = args[0].get_inner_types();
+
+                    let keys = Annotated::from_type(&elems[0], data_types, definitions)
+                        .map_err(|e| e.breadcrumb(tipo))?;
+
+                    let values = Annotated::from_type(&elems[1], data_types, definitions)
+                        .map_err(|e| e.breadcrumb(tipo))?;
+
+                    Ok(Schema::Map(keys.into(), values.into()).into())
+                }
+
+                "List" => {
+                    let items = Annotated::from_type(&args[0], data_types, definitions)
+                        .map_err(|e| e.breadcrumb(tipo))?;
+
+                    Ok(Schema::List(Items::One(items.into())).into())
+                }
+
+                _ => Annotated::from_data_type(tipo, module, name, args, data_types, definitions),
+            },
+
+            Type::App {
+                module, name, args, ..
+            } => Annotated::from_data_type(tipo, module, name, args, data_types, definitions),
+
+            Type::Tuple { elems } => {
+                let items = elems
+                    .iter()
+                    .map(|elem| Annotated::from_type(elem, data_types, definitions))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.breadcrumb(tipo))?;
+
+                Ok(Annotated {
+                    title: Some("Tuple".to_string()),
+                    description: None,
+                    annotated: Schema::List(Items::Many(items)),
+                })
+            }
+
+            Type::Var { tipo: var } => match var.borrow().deref() {
+                TypeVar::Link { tipo } => Annotated::from_type(tipo, data_types, definitions),
+                TypeVar::Generic { .. } => Err(Error::new(ErrorContext::FreeTypeVar, tipo)),
+                TypeVar::Unbound { .. } => Err(Error::new(ErrorContext::UnboundTypeVar, tipo)),
+            },
+
+            Type::Fn { .. } => Err(Error::new(ErrorContext::UnexpectedFunction, tipo)),
+        }
+    }
+
+    fn from_data_type(
+        tipo: &Arc<Type>,
+        module: &str,
+        name: &str,
+        args: &[Arc<Type>],
+        data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+        definitions: &mut Definitions,
+    ) -> Result<Self, Error> {
+        let data_type = data_types
+            .get(&DataTypeKey {
+                module_name: module.to_string(),
+                defined_type: name.to_string(),
+            })
+            .ok_or_else(|| Error::new(ErrorContext::UnknownDataType, tipo))?;
+
+        let mut mono_types = IndexMap::new();
+
+        for (param, arg) in data_type.typed_parameters.iter().zip(args) {
+            mono_types.extend(get_generic_id_and_type(param, arg));
+        }
+
+        // Opaque types wrapping a single value are erased by the code generator
+        // and represented as the value they wrap.
+        if data_type.opaque
+            && data_type.constructors.len() == 1
+            && data_type.constructors[0].arguments.len() == 1
+        {
+            let mut inner = data_type.constructors[0].arguments[0].tipo.clone();
+
+            find_and_replace_generics(&mut inner, &mono_types);
+
+            return Annotated::from_type(&inner, data_types, definitions)
+                .map_err(|e| e.breadcrumb(tipo));
+        }
+
+        let schema = definitions.register(definition_key(tipo), |definitions| {
+            let mut constructors = vec![];
+
+            for (index, constructor) in data_type.constructors.iter().enumerate() {
+                let mut fields = vec![];
+
+                for field in constructor.arguments.iter() {
+                    let mut field_type = field.tipo.clone();
+
+                    find_and_replace_generics(&mut field_type, &mono_types);
+
+                    let schema = Annotated::from_type(&field_type, data_types, definitions)
+                        .map_err(|e| e.breadcrumb(tipo))?;
+
+                    fields.push(Annotated {
+                        title: field.label.clone().or(schema.title),
+                        description: field.doc.clone().map(|doc| doc.trim().to_string()),
+                        annotated: schema.annotated,
+                    });
+                }
+
+                constructors.push(Annotated {
+                    title: Some(constructor.name.clone()),
+                    description: constructor.doc.clone().map(|doc| doc.trim().to_string()),
+                    annotated: Constructor { index, fields },
+                });
+            }
+
+            Ok(Annotated {
+                title: Some(data_type.name.clone()),
+                description: data_type.doc.clone().map(|doc| doc.trim().to_string()),
+                annotated: Schema::AnyOf(constructors),
+            })
+        })?;
+
+        Ok(schema.into())
+    }
+}
+
+/// Builds the key under which a type is stored in the definitions, e.g.
+/// `Int`, `types/Datum` or `Option$types/Datum`.
+fn definition_key(tipo: &Type) -> String {
+    match tipo {
+        Type::App {
+            module, name, args, ..
+        } => {
+            let mut key = if module.is_empty() {
+                name.clone()
+            } else {
+                format!("{module}/{name}")
+            };
+
+            if !args.is_empty() {
+                key.push('$');
+                key.push_str(&args.iter().map(|arg| definition_key(arg)).join("_"));
+            }
+
+            key
+        }
+        Type::Tuple { elems } => format!(
+            "Tuple${}",
+            elems.iter().map(|elem| definition_key(elem)).join("_")
+        ),
+        Type::Var { tipo } => match tipo.borrow().deref() {
+            TypeVar::Link { tipo } => definition_key(tipo),
+            TypeVar::Generic { id } | TypeVar::Unbound { id } => format!("{id}"),
+        },
+        Type::Fn { .. } => "Fn".to_string(),
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Schema::Integer => {
+                let mut s = serializer.serialize_struct("Integer", 1)?;
+                s.serialize_field("dataType", "integer")?;
+                s.end()
+            }
+            Schema::Bytes => {
+                let mut s = serializer.serialize_struct("Bytes", 1)?;
+                s.serialize_field("dataType", "bytes")?;
+                s.end()
+            }
+            Schema::List(items) => {
+                let mut s = serializer.serialize_struct("List", 2)?;
+                s.serialize_field("dataType", "list")?;
+                s.serialize_field("items", items)?;
+                s.end()
+            }
+            Schema::Map(keys, values) => {
+                let mut s = serializer.serialize_struct("Map", 3)?;
+                s.serialize_field("dataType", "map")?;
+                s.serialize_field("keys", keys)?;
+                s.serialize_field("values", values)?;
+                s.end()
+            }
+            Schema::AnyOf(constructors) => {
+                let mut s = serializer.serialize_struct("AnyOf", 1)?;
+                s.serialize_field("anyOf", constructors)?;
+                s.end()
+            }
+            Schema::Ref(key) => {
+                let mut s = serializer.serialize_struct("Ref", 1)?;
+                s.serialize_field(
+                    "$ref",
+                    &format!(
+                        "#/definitions/{}",
+                        key.replace('~', "~0").replace('/', "~1")
+                    ),
+                )?;
+                s.end()
+            }
+            Schema::Opaque => serializer.serialize_struct("Opaque", 0)?.end(),
+        }
+    }
+}
+
+impl Serialize for Items {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Items::One(item) => item.serialize(serializer),
+            Items::Many(items) => items.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Constructor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Constructor", 3)?;
+        s.serialize_field("dataType", "constructor")?;
+        s.serialize_field("index", &self.index)?;
+        s.serialize_field("fields", &self.fields)?;
+        s.end()
+    }
+}
+
+impl Serialize for Definitions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.inner.len()))?;
+
+        for (key, schema) in self.inner.iter() {
+            if let Some(schema) = schema {
+                map.serialize_entry(key, schema)?;
+            }
+        }
+
+        map.end()
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{context}")]
+pub struct Error {
+    context: ErrorContext,
+    breadcrumbs: Vec<Type>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ErrorContext {
+    #[error("I found a type variable that was never bound in a type serialised as data.")]
+    UnboundTypeVar,
+
+    #[error("I found a generic type variable in a type serialised as data.")]
+    FreeTypeVar,
+
+    #[error("I found a function in a type serialised as data.")]
+    UnexpectedFunction,
+
+    #[error("I couldn't find the definition of a data type serialised as data.")]
+    UnknownDataType,
+}
+
+impl Error {
+    pub fn new(context: ErrorContext, tipo: &Type) -> Self {
+        Error {
+            context,
+            breadcrumbs: vec![tipo.clone()],
+        }
+    }
+
+    pub fn breadcrumb(self, tipo: &Type) -> Self {
+        let mut breadcrumbs = vec![tipo.clone()];
+
+        breadcrumbs.extend(self.breadcrumbs);
+
+        Error {
+            context: self.context,
+            breadcrumbs,
+        }
+    }
+
+    pub fn help(&self) -> String {
+        let hint = match self.context {
+            ErrorContext::UnboundTypeVar | ErrorContext::FreeTypeVar => {
+                "Values serialised as Plutus data must have a fully known type. Try adding an annotation to narrow down the types involved."
+            }
+            ErrorContext::UnexpectedFunction => {
+                "Plutus data cannot hold functions."
+            }
+            ErrorContext::UnknownDataType => {
+                "This is likely a compiler bug, please report it along with the code involved."
+            }
+        };
+
+        format!(
+            "{hint}\n\nHere's the path I followed to get there:\n\n{}",
+            Breadcrumbs(&self.breadcrumbs)
+        )
+    }
+}
+
+struct Breadcrumbs<'a>(&'a [Type]);
+
+impl fmt::Display for Breadcrumbs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (depth, tipo) in self.0.iter().enumerate() {
+            writeln!(f, "{}╰─▶ {}", "  ".repeat(depth + 1), tipo.to_pretty(0))?;
+        }
+
+        Ok(())
+    }
+}
@@ -26,6 +26,12 @@ impl Scope {
         self.0.is_empty()
     }
 
+    /// The id pushed last. Air pushed onto a stack in a scope of its own keeps
+    /// that id wherever it is moved.
+    pub fn last(&self) -> Option<u64> {
+        self.0.last().copied()
+    }
+
     pub fn replace(&mut self, mut replacement: Scope) {
         let common = self.common_ancestor(&replacement);
 
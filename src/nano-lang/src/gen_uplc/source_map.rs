@@ -0,0 +1,238 @@
+/*
+ * @file source_map.rs
+ * @author Krisna Pranav
+ * @version 0.1
+ * @date 2023-05-11
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::{self, Discriminant},
+    rc::Rc,
+};
+use untyped_plutus_core::{
+    ast::{Name, Program, Term},
+    optimize::{nano_optimize_and_intern_labelled, shrinker::Labels},
+};
+use crate::ast::Span;
+use super::air::Air;
+
+/// Where a term of a generated program comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub module: String,
+    /// The function, test or validator the term is part of.
+    pub function: String,
+    pub span: Span,
+}
+
+impl Location {
+    /// The line the location starts on, counting from 1.
+    pub fn line(&self, code: &str) -> usize {
+        code.bytes()
+            .take(self.span.start)
+            .filter(|byte| *byte == b'\n')
+            .count()
+            + 1
+    }
+}
+
+/// The locations of the terms of a program, by their position as given by
+/// `Term::positions`.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    locations: BTreeMap<usize, Location>,
+    /// The parent of each term of the program.
+    parents: Vec<Option<usize>>,
+}
+
+impl SourceMap {
+    pub fn get(&self, position: usize) -> Option<&Location> {
+        self.locations.get(&position)
+    }
+
+    /// The location of a term, or else of the closest term around it that has
+    /// one. Builtins and errors only ever have the latter.
+    pub fn enclosing(&self, position: usize) -> Option<&Location> {
+        let mut position = Some(position);
+
+        while let Some(current) = position {
+            if let Some(location) = self.locations.get(&current) {
+                return Some(location);
+            }
+
+            position = self.parents.get(current).copied().flatten();
+        }
+
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Location)> {
+        self.locations
+            .iter()
+            .map(|(position, location)| (*position, location))
+    }
+
+    /// Where a breakpoint on a line of a module stops, counting lines from 1:
+    /// the outermost terms starting first on that line.
+    pub fn positions_on_line(&self, module: &str, code: &str, line: usize) -> Vec<usize> {
+        let start: usize = code
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum();
+
+        let end = code[start..]
+            .find('\n')
+            .map_or(code.len(), |offset| start + offset);
+
+        let on_line: Vec<_> = self
+            .iter()
+            .filter(|(_, location)| {
+                location.module == module && (start..=end).contains(&location.span.start)
+            })
+            .collect();
+
+        let Some(first) = on_line
+            .iter()
+            .map(|(_, location)| location.span.start)
+            .min()
+        else {
+            return vec![];
+        };
+
+        let outermost = on_line
+            .iter()
+            .filter(|(_, location)| location.span.start == first)
+            .map(|(_, location)| location.span.end)
+            .max();
+
+        on_line
+            .into_iter()
+            .filter(|(_, location)| {
+                location.span.start == first && Some(location.span.end) == outermost
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+/// Terms tagged with their location while a program is generated, until they
+/// find their place in it. A term is known by the address of its first shared
+/// field, which stays the same however the term is moved around.
+#[derive(Clone, Default)]
+pub(crate) struct Recorder {
+    /// The module and function whose code is being built.
+    pub module: String,
+    pub function: String,
+    /// The locations of the Air built for each expression, until it is turned
+    /// into a term. Air is known by the id of its scope and by its kind, as
+    /// the passes over Air move, copy and insert it but keep its scope's id.
+    air: HashMap<(u64, Discriminant<Air>), Location>,
+    /// Tagged terms are kept so that their addresses can't be reused.
+    tags: HashMap<(u8, usize), (Term<Name>, Location)>,
+    source_map: SourceMap,
+}
+
+impl Recorder {
+    pub fn new(module: &str, function: &str) -> Self {
+        Recorder {
+            module: module.to_string(),
+            function: function.to_string(),
+            ..Recorder::default()
+        }
+    }
+
+    pub fn location(&self, span: Span) -> Location {
+        Location {
+            module: self.module.clone(),
+            function: self.function.clone(),
+            span,
+        }
+    }
+
+    /// Locates the Air an expression starts with, unless an expression it
+    /// starts with too was located already.
+    pub fn locate(&mut self, air: &Air, span: Span) {
+        if let Some(key) = air_key(air) {
+            let location = self.location(span);
+
+            self.air.entry(key).or_insert(location);
+        }
+    }
+
+    pub fn location_of(&self, air: &Air) -> Option<Location> {
+        air_key(air).and_then(|key| self.air.get(&key).cloned())
+    }
+
+    /// Scope ids start over with each program, or each handler of a validator.
+    pub fn forget_air(&mut self) {
+        self.air.clear();
+    }
+
+    /// Tags a term, unless it was given a more precise location already.
+    pub fn tag(&mut self, term: &Term<Name>, location: Location) {
+        if let Some(key) = key(term) {
+            self.tags
+                .entry(key)
+                .or_insert_with(|| (term.clone(), location));
+        }
+    }
+
+    /// Optimises a finished program, whose tagged terms are followed through
+    /// to the optimised program.
+    pub fn optimise(&mut self, program: Program<Name>) -> Program<Name> {
+        let mut locations = vec![];
+        let mut labels = Labels::new();
+
+        for (position, (term, _)) in program.term.positions().into_iter().enumerate() {
+            if let Some((_, location)) = key(term).and_then(|key| self.tags.get(&key)) {
+                labels.insert(position, locations.len());
+                locations.push(location.clone());
+            }
+        }
+
+        let program = nano_optimize_and_intern_labelled(program, &mut labels);
+
+        self.source_map = SourceMap {
+            locations: labels
+                .into_iter()
+                .map(|(position, label)| (position, locations[label].clone()))
+                .collect(),
+            parents: program
+                .term
+                .positions()
+                .into_iter()
+                .map(|(_, parent)| parent)
+                .collect(),
+        };
+
+        program
+    }
+
+    pub fn source_map(self) -> SourceMap {
+        self.source_map
+    }
+}
+
+/// Builtins and errors share nothing, and aren't worth a location anyway.
+fn key(term: &Term<Name>) -> Option<(u8, usize)> {
+    let key = match term {
+        Term::Var(name) => (0, Rc::as_ptr(name) as usize),
+        Term::Delay(body) => (1, Rc::as_ptr(body) as usize),
+        Term::Lambda { body, .. } => (2, Rc::as_ptr(body) as usize),
+        Term::Apply { function, .. } => (3, Rc::as_ptr(function) as usize),
+        Term::Constant(constant) => (4, Rc::as_ptr(constant) as usize),
+        Term::Force(body) => (5, Rc::as_ptr(body) as usize),
+        Term::Error | Term::Builtin(_) => return None,
+    };
+
+    Some(key)
+}
+
+fn air_key(air: &Air) -> Option<(u64, Discriminant<Air>)> {
+    air.scope().last().map(|id| (id, mem::discriminant(air)))
+}
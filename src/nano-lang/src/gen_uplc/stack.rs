@@ -10,7 +10,9 @@
 
 use std::{rc::Rc, sync::Arc};
 use indexmap::IndexSet;
-use untyped_plutus_core::{builder::EXPECT_ON_LIST, builtins::DefaultFunction};
+use untyped_plutus_core::{
+    ast::Constant as UplcConstant, builder::EXPECT_ON_LIST, builtins::DefaultFunction,
+};
 use crate::{
     ast::Span,
     builtins::{data, list, void},
@@ -620,6 +622,16 @@ impl AirStack {
         });
     }
 
+    pub fn constant(&mut self, tipo: Arc<Type>, value: Rc<UplcConstant>) {
+        self.new_scope();
+
+        self.air.push(Air::Constant {
+            scope: self.scope.clone(),
+            tipo,
+            value,
+        });
+    }
+
     pub fn clause_guard(
         &mut self,
         subject_name: impl ToString,
@@ -701,6 +713,21 @@ impl AirStack {
         self.merge_child(body_stack);
     }
 
+    /// Functions calling each other, as `(name, params)`, with their bodies in
+    /// the same order.
+    pub fn define_cyclic_funcs(
+        &mut self,
+        functions: Vec<(String, Vec<String>)>,
+        bodies: Vec<AirStack>,
+    ) {
+        self.air.push(Air::DefineCyclicFuncs {
+            scope: self.scope.clone(),
+            functions,
+        });
+
+        self.merge_children(bodies);
+    }
+
     pub fn noop(&mut self) {
         self.new_scope();
 
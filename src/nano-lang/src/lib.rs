@@ -23,6 +23,9 @@ pub mod parser;
 pub mod pretty;
 pub mod tipo;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug, Default, Clone)]
 pub struct IdGenerator {
     id: Arc<AtomicU64>,
@@ -104,6 +104,8 @@ fn module_parser() -> impl Parser<Token, Vec<UntypedDefinition>, Error = ParseEr
         fn_parser(),
         test_parser(),
         constant_parser(),
+        interface_parser(),
+        implementation_parser(),
     ))
     .repeated()
     .then_ignore(end())
@@ -201,13 +203,13 @@ pub fn data_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = Parse
         }]
     });
 
-    pub_parser()
+    let data_type = pub_parser()
         .then(just(Token::Opaque).ignored().or_not())
         .or_not()
         .then(type_name_with_args())
         .then(choice((constructors, record_sugar)))
-        .map_with_span(|((pub_opaque, (name, parameters)), constructors), span| {
-            ast::UntypedDefinition::DataType(ast::DataType {
+        .map_with_span(
+            |((pub_opaque, (name, parameters)), constructors), span| ast::DataType {
                 location: span,
                 constructors: constructors
                     .into_iter()
@@ -227,10 +229,30 @@ pub fn data_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = Parse
                 parameters: parameters.unwrap_or_default(),
                 public: pub_opaque.is_some(),
                 typed_parameters: vec![],
+                derive: vec![],
+            },
+        );
+
+    derive_parser()
+        .or_not()
+        .then(data_type)
+        .map(|(derive, data_type)| {
+            ast::UntypedDefinition::DataType(ast::DataType {
+                derive: derive.unwrap_or_default(),
+                ..data_type
             })
         })
 }
 
+fn derive_parser() -> impl Parser<Token, Vec<ast::Constraint>, Error = ParseError> {
+    just(Token::Derive).ignore_then(
+        constraint_parser()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .delimited_by(just(Token::LeftParen), just(Token::RightParen)),
+    )
+}
+
 pub fn type_alias_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
     pub_parser()
         .or_not()
@@ -264,7 +286,7 @@ pub fn validator_parser() -> impl Parser<Token, ast::UntypedDefinition, Error =
             fn_parser()
                 .repeated()
                 .at_least(1)
-                .at_most(2)
+                .at_most(4)
                 .delimited_by(just(Token::LeftBrace), just(Token::RightBrace))
                 .map(|defs| {
                     defs.into_iter().map(|def| {
@@ -290,7 +312,7 @@ pub fn validator_parser() -> impl Parser<Token, ast::UntypedDefinition, Error =
                 fun: functions
                     .next()
                     .expect("unwrapping safe because there's 'at_least(1)' function"),
-                other_fun: functions.next(),
+                other_funs: functions.collect(),
                 location: Span {
                     start: span.start,
                     end: params_span.end,
@@ -306,6 +328,7 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
         .or_not()
         .then_ignore(just(Token::Fn))
         .then(select! {Token::Name {name} => name})
+        .then(type_parameters_parser().or_not())
         .then(
             fn_param_parser(false)
                 .separated_by(just(Token::Comma))
@@ -320,7 +343,11 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
                 .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
         )
         .map_with_span(
-            |((((opt_pub, name), (arguments, args_span)), return_annotation), body), span| {
+            |(
+                ((((opt_pub, name), type_parameters), (arguments, args_span)), return_annotation),
+                body,
+            ),
+             span| {
                 ast::UntypedDefinition::Fn(ast::Function {
                     arguments,
                     body: body.unwrap_or_else(|| expr::UntypedExpr::todo(span, None)),
@@ -337,6 +364,127 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
                     public: opt_pub.is_some(),
                     return_annotation,
                     return_type: (),
+                    type_parameters: type_parameters.unwrap_or_default(),
+                })
+            },
+        )
+}
+
+pub fn type_parameters_parser() -> impl Parser<Token, Vec<ast::TypeParameter>, Error = ParseError> {
+    select! {Token::Name {name} => name}
+        .then(
+            just(Token::Colon)
+                .ignore_then(
+                    constraint_parser()
+                        .separated_by(just(Token::Plus))
+                        .at_least(1),
+                )
+                .or_not(),
+        )
+        .map_with_span(|(name, constraints), span| ast::TypeParameter {
+            location: span,
+            name,
+            constraints: constraints.unwrap_or_default(),
+        })
+        .separated_by(just(Token::Comma))
+        .allow_trailing()
+        .delimited_by(just(Token::Less), just(Token::Greater))
+}
+
+fn constraint_parser() -> impl Parser<Token, ast::Constraint, Error = ParseError> {
+    select! {Token::Name {name} => name}
+        .then_ignore(just(Token::Dot))
+        .or_not()
+        .then(select! {Token::UpName {name} => name})
+        .map_with_span(|(module, name), span| ast::Constraint {
+            location: span,
+            module,
+            name,
+        })
+}
+
+pub fn interface_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
+    let method = just(Token::Fn)
+        .ignore_then(select! {Token::Name {name} => name})
+        .then(
+            fn_param_parser(false)
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen)),
+        )
+        .then_ignore(just(Token::RArrow))
+        .then(type_parser())
+        .map_with_span(
+            |((name, arguments), return_annotation), span| ast::InterfaceMethod {
+                arguments,
+                doc: None,
+                location: span,
+                name,
+                return_annotation,
+            },
+        );
+
+    pub_parser()
+        .or_not()
+        .then_ignore(just(Token::Interface))
+        .then(select! {Token::UpName {name} => name})
+        .then(
+            select! {Token::Name {name} => name}
+                .delimited_by(just(Token::Less), just(Token::Greater)),
+        )
+        .map_with_span(|header, span| (header, span))
+        .then(
+            method
+                .repeated()
+                .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
+        )
+        .map_with_span(
+            |((((opt_pub, name), parameter), header_span), methods), span| {
+                ast::UntypedDefinition::Interface(ast::Interface {
+                    doc: None,
+                    end_position: span.end - 1,
+                    location: header_span,
+                    methods,
+                    name,
+                    parameter,
+                    public: opt_pub.is_some(),
+                })
+            },
+        )
+}
+
+pub fn implementation_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
+    just(Token::Impl)
+        .ignore_then(type_parameters_parser().or_not())
+        .then(constraint_parser())
+        .then(type_parser().delimited_by(just(Token::Less), just(Token::Greater)))
+        .map_with_span(|header, span| (header, span))
+        .then(
+            fn_parser()
+                .repeated()
+                .delimited_by(just(Token::LeftBrace), just(Token::RightBrace))
+                .map(|defs| {
+                    defs.into_iter()
+                        .map(|def| {
+                            let ast::UntypedDefinition::Fn(fun) = def else {
+                                unreachable!("It should be a fn definition");
+                            };
+
+                            fun
+                        })
+                        .collect()
+                }),
+        )
+        .map_with_span(
+            |((((type_parameters, interface), annotation), header_span), methods), span| {
+                ast::UntypedDefinition::Implementation(ast::Implementation {
+                    annotation,
+                    doc: None,
+                    end_position: span.end - 1,
+                    interface,
+                    location: header_span,
+                    methods,
+                    type_parameters: type_parameters.unwrap_or_default(),
                 })
             },
         )
@@ -345,17 +493,21 @@ pub fn fn_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseEr
 pub fn test_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
     just(Token::Test)
         .ignore_then(select! {Token::Name {name} => name})
-        .then_ignore(just(Token::LeftParen))
-        .then_ignore(just(Token::RightParen))
-        .map_with_span(|name, span| (name, span))
+        .then(
+            fn_param_parser(false)
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(just(Token::LeftParen), just(Token::RightParen)),
+        )
+        .map_with_span(|name_and_arguments, span| (name_and_arguments, span))
         .then(
             expr_seq_parser()
                 .or_not()
                 .delimited_by(just(Token::LeftBrace), just(Token::RightBrace)),
         )
-        .map_with_span(|((name, span_end), body), span| {
+        .map_with_span(|(((name, arguments), span_end), body), span| {
             ast::UntypedDefinition::Test(ast::Function {
-                arguments: vec![],
+                arguments,
                 body: body.unwrap_or_else(|| expr::UntypedExpr::todo(span, None)),
                 doc: None,
                 location: span_end,
@@ -364,18 +516,23 @@ pub fn test_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = Parse
                 public: false,
                 return_annotation: None,
                 return_type: (),
+                type_parameters: vec![],
             })
         })
 }
 
 fn constant_parser() -> impl Parser<Token, ast::UntypedDefinition, Error = ParseError> {
+    // A single expression, which may only hold sequences within blocks.
+    let mut sequence = Recursive::declare();
+    sequence.define(expr_seq_parser());
+
     pub_parser()
         .or_not()
         .then_ignore(just(Token::Const))
         .then(select! {Token::Name{name} => name})
         .then(just(Token::Colon).ignore_then(type_parser()).or_not())
         .then_ignore(just(Token::Equal))
-        .then(constant_value_parser())
+        .then(expr_parser(sequence))
         .map_with_span(|(((public, name), annotation), value), span| {
             ast::UntypedDefinition::ModuleConstant(ast::ModuleConstant {
                 doc: None,
@@ -599,6 +756,17 @@ pub fn expr_parser(
             }
         });
 
+        let rational_parser = select! {
+            Token::Rational { numerator, denominator } => (numerator, denominator)
+        }
+        .map_with_span(
+            |(numerator, denominator), span| expr::UntypedExpr::Rational {
+                location: span,
+                numerator,
+                denominator,
+            },
+        );
+
         let record_update_parser = select! {Token::Name { name } => name}
             .map_with_span(|module, span: Span| (module, span))
             .then_ignore(just(Token::Dot))
@@ -982,17 +1150,20 @@ pub fn expr_parser(
         let let_parser = just(Token::Let)
             .ignore_then(pattern_parser())
             .then(just(Token::Colon).ignore_then(type_parser()).or_not())
-            .then_ignore(just(Token::Equal))
+            .then(choice((
+                just(Token::Equal).to(ast::AssignmentKind::Let),
+                just(Token::LArrow).to(ast::AssignmentKind::Backpass),
+            )))
             .then(r.clone())
-            .map_with_span(
-                |((pattern, annotation), value), span| expr::UntypedExpr::Assignment {
+            .map_with_span(|(((pattern, annotation), kind), value), span| {
+                expr::UntypedExpr::Assignment {
                     location: span,
                     value: Box::new(value),
                     pattern,
-                    kind: ast::AssignmentKind::Let,
+                    kind,
                     annotation,
-                },
-            );
+                }
+            });
 
         let expect_parser = just(Token::Expect)
             .ignore_then(pattern_parser())
@@ -1046,6 +1217,7 @@ pub fn expr_parser(
         let expr_unit_parser = choice((
             string_parser,
             int_parser,
+            rational_parser,
             record_update_parser,
             record_parser,
             field_access_constructor,
@@ -1071,7 +1243,7 @@ pub fn expr_parser(
         }
 
         enum Chain {
-            Call(Vec<ParserArg>, Span),
+            Call(Option<(Vec<ast::Annotation>, Span)>, Vec<ParserArg>, Span),
             FieldAccess(String, Span),
             TupleIndex(usize, Span),
         }
@@ -1099,7 +1271,14 @@ pub fn expr_parser(
                 }
             });
 
-        let call_parser = choice((
+        let type_arguments_parser = type_parser()
+            .separated_by(just(Token::Comma))
+            .allow_trailing()
+            .at_least(1)
+            .delimited_by(just(Token::Less), just(Token::Greater))
+            .map_with_span(|arguments, span| (arguments, span));
+
+        let arguments_parser = choice((
             select! { Token::Name { name } => name }
                 .then_ignore(just(Token::Colon))
                 .or_not()
@@ -1122,17 +1301,30 @@ pub fn expr_parser(
         ))
         .separated_by(just(Token::Comma))
         .allow_trailing()
-        .delimited_by(just(Token::LeftParen), just(Token::RightParen))
-        .map_with_span(Chain::Call);
+        .delimited_by(just(Token::LeftParen), just(Token::RightParen));
+
+        let call_parser = type_arguments_parser
+            .or_not()
+            .then(arguments_parser)
+            .map_with_span(|(type_arguments, args), span| Chain::Call(type_arguments, args, span));
 
         let chain = choice((tuple_index_parser, field_access_parser, call_parser));
 
         let chained = expr_unit_parser
             .then(chain.repeated())
             .foldl(|expr, chain| match chain {
-                Chain::Call(args, span) => {
+                Chain::Call(type_arguments, args, span) => {
                     let mut holes = Vec::new();
 
+                    let expr = match type_arguments {
+                        Some((arguments, types_span)) => expr::UntypedExpr::TypeApplication {
+                            location: expr.location().union(types_span),
+                            value: Box::new(expr),
+                            arguments,
+                        },
+                        None => expr,
+                    };
+
                     let args = args
                         .into_iter()
                         .enumerate()
@@ -1244,9 +1436,17 @@ pub fn expr_parser(
             just(Token::Minus).to(BinOp::SubInt),
         ));
 
+        // An operand followed by `..` is the start of a range pattern, as in
+        // `-9..-1` on the clause following `0 -> 0`, not a subtraction.
+        let not_a_range = just(Token::DotDot)
+            .not()
+            .rewind()
+            .ignored()
+            .or(end());
+
         let sum = product
             .clone()
-            .then(op.then(product).repeated())
+            .then(op.then(product).then_ignore(not_a_range).repeated())
             .foldl(|a, (op, b)| expr::UntypedExpr::BinOp {
                 location: a.location().union(b.location()),
                 name: op,
@@ -1563,6 +1763,17 @@ pub fn pub_parser() -> impl Parser<Token, (), Error = ParseError> {
     just(Token::Pub).ignored()
 }
 
+/// A bound of an integer range pattern, which may be negative as in `-5..-1`.
+fn int_bound_parser() -> impl Parser<Token, String, Error = ParseError> {
+    just(Token::Minus)
+        .or_not()
+        .then(select! {Token::Int {value} => value})
+        .map(|(minus, value)| match minus {
+            Some(_) => format!("-{value}"),
+            None => value,
+        })
+}
+
 pub fn pattern_parser() -> impl Parser<Token, ast::UntypedPattern, Error = ParseError> {
     recursive(|r| {
         let record_constructor_pattern_arg_parser = choice((
@@ -1670,12 +1881,33 @@ pub fn pattern_parser() -> impl Parser<Token, ast::UntypedPattern, Error = Parse
                     location: span,
                 }
             }),
+            int_bound_parser()
+                .then_ignore(just(Token::DotDot))
+                .then(int_bound_parser())
+                .map_with_span(|(start, end), span| ast::UntypedPattern::IntRange {
+                    location: span,
+                    start,
+                    end,
+                }),
             select! {Token::Int {value} => value}.map_with_span(|value, span| {
                 ast::UntypedPattern::Int {
                     location: span,
                     value,
                 }
             }),
+            select! {Token::String {value} => value}.map_with_span(|value, span| {
+                ast::UntypedPattern::String {
+                    location: span,
+                    value,
+                }
+            }),
+            bytearray_parser().map_with_span(|(preferred_format, value), span| {
+                ast::UntypedPattern::ByteArray {
+                    location: span,
+                    value,
+                    preferred_format,
+                }
+            }),
             r.clone()
                 .separated_by(just(Token::Comma))
                 .allow_trailing()
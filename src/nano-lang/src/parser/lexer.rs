@@ -16,6 +16,15 @@ use super::{error::ParseError, token::Token};
 pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
     let int = text::int(10).map(|value| Token::Int { value });
 
+    let rational = text::int(10)
+        .then_ignore(just('/'))
+        .then(text::int(10))
+        .then_ignore(just('r'))
+        .map(|(numerator, denominator)| Token::Rational {
+            numerator,
+            denominator,
+        });
+
     let ordinal = text::int(10)
         .then_with(|index: String| {
             choice((just("st"), just("nd"), just("rd"), just("th")))
@@ -49,6 +58,7 @@ pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
         just('?').to(Token::Question),
         choice((
             just("<=").to(Token::LessEqual),
+            just("<-").to(Token::LArrow),
             just('<').to(Token::Less),
             just(">=").to(Token::GreaterEqual),
             just('>').to(Token::Greater),
@@ -110,10 +120,13 @@ pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
         "assert" => Token::Expect,
         "expect" => Token::Expect,
         "const" => Token::Const,
+        "derive" => Token::Derive,
         "fn" => Token::Fn,
         "test" => Token::Test,
         "if" => Token::If,
         "else" => Token::Else,
+        "impl" => Token::Impl,
+        "interface" => Token::Interface,
         "is" => Token::Is,
         "let" => Token::Let,
         "opaque" => Token::Opaque,
@@ -178,7 +191,7 @@ pub fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = ParseError> {
         comment_parser(Token::DocComment),
         comment_parser(Token::Comment),
         choice((
-            ordinal, keyword, int, op, newlines, grouping, bytestring, string,
+            rational, ordinal, keyword, int, op, newlines, grouping, bytestring, string,
         ))
         .or(any().map(Token::Error).validate(|t, span, emit| {
             emit(ParseError::expected_input_found(
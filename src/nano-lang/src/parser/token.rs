@@ -18,6 +18,7 @@ pub enum Token {
     UpName { name: String },
     DiscardName { name: String },
     Int { value: String },
+    Rational { numerator: String, denominator: String },
     ByteString { value: String },
     String { value: String },
 
@@ -62,6 +63,7 @@ pub enum Token {
     Pipe,        
     Dot,         
     RArrow,      
+    LArrow,
     DotDot,      
     EndOfFile,
 
@@ -73,11 +75,14 @@ pub enum Token {
 
     As,
     Const,
+    Derive,
     Fn,
     If,
     Else,
     ErrorTerm,
     Expect,
+    Impl,
+    Interface,
     Is,
     Let,
     Opaque,
@@ -107,6 +112,13 @@ impl fmt::Display for Token {
             Token::UpName { name } => name,
             Token::DiscardName { name } => name,
             Token::Int { value } => value,
+            Token::Rational {
+                numerator,
+                denominator,
+            } => {
+                index_str = format!("{numerator}/{denominator}r");
+                &index_str[..]
+            }
             Token::String { value } => value,
             Token::ByteString { value } => value,
             Token::NewLineLeftParen => "↳(",
@@ -148,6 +160,7 @@ impl fmt::Display for Token {
             Token::Pipe => "|>",
             Token::Dot => ".",
             Token::RArrow => "->",
+            Token::LArrow => "<-",
             Token::DotDot => "..",
             Token::EndOfFile => "EOF",
             Token::Comment => "//",
@@ -160,9 +173,12 @@ impl fmt::Display for Token {
             Token::When => "when",
             Token::Is => "is",
             Token::Const => "const",
+            Token::Derive => "derive",
             Token::Fn => "fn",
             Token::If => "if",
             Token::Else => "else",
+            Token::Impl => "impl",
+            Token::Interface => "interface",
             Token::Use => "import",
             Token::Let => "let",
             Token::Opaque => "opaque",
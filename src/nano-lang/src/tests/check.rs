@@ -0,0 +1,70 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::{ast::ModuleKind, tipo::error::Error};
+
+fn check(code: &str) -> Result<(), Error> {
+    TestProject::new()
+        .check("test_module", ModuleKind::Lib, code)
+        .map(|_| ())
+}
+
+#[test]
+fn expect_string_literal() {
+    let code = indoc! {r#"
+        fn only_abc(s: String) -> Bool {
+          expect @"abc" = s
+          True
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::LiteralPatternInAssignment { is_let: false, .. })
+    ));
+}
+
+#[test]
+fn expect_byte_array_literal() {
+    let code = indoc! {r#"
+        fn only_zero(b: ByteArray) -> Bool {
+          expect #"00" = b
+          True
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::LiteralPatternInAssignment { is_let: false, .. })
+    ));
+}
+
+#[test]
+fn expect_nested_range() {
+    let code = indoc! {r#"
+        fn small_first(pair: (Int, Int)) -> Int {
+          expect (0..9, second) = pair
+          second
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::LiteralPatternInAssignment { is_let: false, .. })
+    ));
+}
+
+#[test]
+fn let_int_literal_in_list() {
+    let code = indoc! {r#"
+        fn head(xs: List<Int>) -> Int {
+          let [1, ..] = xs
+          1
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::LiteralPatternInAssignment { is_let: true, .. })
+    ));
+}
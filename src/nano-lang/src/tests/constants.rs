@@ -0,0 +1,116 @@
+use indoc::indoc;
+
+use super::{eval, TestProject};
+use crate::gen_uplc::ConstantEvaluationError;
+
+#[test]
+fn evaluated_constants() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "consts",
+        indoc! {r#"
+            pub type Point {
+              x: Int,
+              y: Int,
+            }
+
+            fn double(n: Int) -> Int {
+              n * 2
+            }
+
+            pub const xs: List<Int> = [double(1), 2 + 2, 6]
+
+            pub const pair: (Int, ByteArray) = (double(21), #"ff")
+
+            pub const origin: Point = Point { x: double(0), y: 0 }
+
+            const xs_sum: Int = 1 + 2 + 3
+
+            pub const total: Int = double(double(xs_sum))
+
+            test list_constant() {
+              xs == [2, 4, 6]
+            }
+
+            test tuple_constant() {
+              let (n, bytes) = pair
+              n == 42 && bytes == #"ff"
+            }
+
+            test record_constant() {
+              origin.x == 0 && origin.y == 0
+            }
+
+            test call_constant() {
+              total == 24
+            }
+        "#},
+    );
+
+    assert!(project.passes("consts", "list_constant"));
+    assert!(project.passes("consts", "tuple_constant"));
+    assert!(project.passes("consts", "record_constant"));
+    assert!(project.passes("consts", "call_constant"));
+
+    let mut generator = project.generator();
+
+    for name in ["xs", "pair", "origin", "total", "xs_sum"] {
+        assert!(
+            generator.evaluate_constant("consts", name).is_ok(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn failing_constants() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "consts",
+        indoc! {r#"
+            pub const a: Int = 1 / 0
+
+            pub const b: Int = a + 1
+
+            pub const c: Int = b * 2
+
+            test uses_c() {
+              c == 4
+            }
+        "#},
+    );
+
+    let mut generator = project.generator();
+
+    let dependency = |error: &ConstantEvaluationError| match error {
+        ConstantEvaluationError::Dependency { module, name } => {
+            Some((module.clone(), name.clone()))
+        }
+        _ => None,
+    };
+
+    let c = generator.evaluate_constant("consts", "c").unwrap_err();
+    assert_eq!(
+        dependency(&c),
+        Some(("consts".to_string(), "b".to_string()))
+    );
+
+    let b = generator.evaluate_constant("consts", "b").unwrap_err();
+    assert_eq!(
+        dependency(&b),
+        Some(("consts".to_string(), "a".to_string()))
+    );
+
+    let a = generator.evaluate_constant("consts", "a").unwrap_err();
+    assert!(matches!(*a, ConstantEvaluationError::Failed { .. }));
+
+    // The failure is kept rather than evaluated again.
+    assert!(std::rc::Rc::ptr_eq(
+        &a,
+        &generator.evaluate_constant("consts", "a").unwrap_err()
+    ));
+
+    assert!(eval(project.test_program("consts", "uses_c")).failed());
+}
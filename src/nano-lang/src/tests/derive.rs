@@ -0,0 +1,201 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::{ast::ModuleKind, tipo::error::Error};
+
+#[test]
+fn labelled_record() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "points",
+        indoc! {r#"
+            derive(Eq, Ord, Serialise)
+            type Point {
+              x: Int,
+              y: Int,
+            }
+
+            derive(Eq, Ord)
+            type Shape {
+              Dot { at: Point }
+              Segment { from: Point, to: Point }
+            }
+
+            test equal_points() {
+              equals(Point { x: 1, y: 2 }, Point { x: 1, y: 2 }) && !equals(Point { x: 1, y: 2 }, Point { x: 2, y: 1 })
+            }
+
+            test ordered_by_fields() {
+              compare(Point { x: 1, y: 9 }, Point { x: 2, y: 0 }) == Less && compare(Point { x: 1, y: 2 }, Point { x: 1, y: 1 }) == Greater
+            }
+
+            test ordered_by_constructor() {
+              let p = Point { x: 0, y: 0 }
+              compare(Dot { at: p }, Segment { from: p, to: p }) == Less && compare(Segment { from: p, to: p }, Dot { at: p }) == Greater
+            }
+
+            test round_trip() {
+              let p: Point = from_data(to_data(Point { x: 3, y: 4 }))
+              p.x == 3 && p.y == 4
+            }
+        "#},
+    );
+
+    assert!(project.passes("points", "equal_points"));
+    assert!(project.passes("points", "ordered_by_fields"));
+    assert!(project.passes("points", "ordered_by_constructor"));
+    assert!(project.passes("points", "round_trip"));
+}
+
+#[test]
+fn unlabelled_constructors() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "money",
+        indoc! {r#"
+            derive(Eq, Ord, Serialise)
+            type Amount {
+              Lovelace(Int)
+              Token(ByteArray, Int)
+            }
+
+            test ordered_by_constructor() {
+              compare(Lovelace(10), Token(#"00", 1)) == Less && compare(Token(#"00", 1), Lovelace(10)) == Greater
+            }
+
+            test ordered_by_fields() {
+              compare(Token(#"00", 2), Token(#"00", 1)) == Greater && compare(Lovelace(1), Lovelace(1)) == Equal
+            }
+
+            test round_trip() {
+              let amount: Amount = from_data(to_data(Token(#"ff", 5)))
+              equals(amount, Token(#"ff", 5))
+            }
+        "#},
+    );
+
+    assert!(project.passes("money", "ordered_by_constructor"));
+    assert!(project.passes("money", "ordered_by_fields"));
+    assert!(project.passes("money", "round_trip"));
+}
+
+#[test]
+fn nullary_constructors() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "colours",
+        indoc! {r#"
+            derive(Eq, Ord, Serialise)
+            type Colour {
+              Red
+              Green
+              Blue
+            }
+
+            test ordered_by_declaration() {
+              compare(Red, Green) == Less && compare(Blue, Green) == Greater && compare(Blue, Blue) == Equal
+            }
+
+            test round_trip() {
+              let colour: Colour = from_data(to_data(Blue))
+              equals(colour, Blue) && !equals(colour, Red)
+            }
+        "#},
+    );
+
+    assert!(project.passes("colours", "ordered_by_declaration"));
+    assert!(project.passes("colours", "round_trip"));
+}
+
+#[test]
+fn parameterised_type() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "boxes",
+        indoc! {r#"
+            derive(Eq, Ord)
+            type Box<a> {
+              Empty
+              Full(a)
+            }
+
+            test empty_first() {
+              compare(Empty, Full(0)) == Less && compare(Full(0), Empty) == Greater
+            }
+
+            test ordered_by_contents() {
+              compare(Full(1), Full(2)) == Less && compare(Full(#"ff"), Full(#"00")) == Greater
+            }
+
+            test equal_contents() {
+              equals(Full(7), Full(7)) && !equals(Full(7), Empty)
+            }
+        "#},
+    );
+
+    assert!(project.passes("boxes", "empty_first"));
+    assert!(project.passes("boxes", "ordered_by_contents"));
+    assert!(project.passes("boxes", "equal_contents"));
+}
+
+#[test]
+fn shadowed_prelude_names() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "verdicts",
+        indoc! {r#"
+            use nano
+
+            derive(Eq, Ord)
+            type Verdict {
+              Less
+              Equal
+              Greater
+            }
+
+            derive(Ord, Serialise)
+            type Ruling {
+              Ruling(Int, Verdict)
+              Dismissed
+            }
+
+            pub fn compare(left: Int, right: Int) -> Int {
+              left - right
+            }
+
+            test ordered_despite_shadowing() {
+              nano.compare(Ruling(1, Greater), Ruling(1, Less)) == nano.Greater && nano.compare(Ruling(9, Equal), Dismissed) == nano.Less && nano.compare(Dismissed, Dismissed) == nano.Equal && compare(3, 1) == 2
+            }
+
+            test round_trip() {
+              let ruling: Ruling = from_data(to_data(Ruling(2, Equal)))
+              nano.compare(ruling, Ruling(2, Equal)) == nano.Equal
+            }
+        "#},
+    );
+
+    assert!(project.passes("verdicts", "ordered_despite_shadowing"));
+    assert!(project.passes("verdicts", "round_trip"));
+}
+
+#[test]
+fn parameterised_serialise() {
+    let result = TestProject::new().check(
+        "boxes",
+        ModuleKind::Lib,
+        indoc! {r#"
+            derive(Serialise)
+            type Box<a> {
+              Empty
+              Full(a)
+            }
+        "#},
+    );
+
+    assert!(matches!(result, Err(Error::GenericSerialiseDerive { .. })));
+}
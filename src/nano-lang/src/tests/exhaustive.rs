@@ -0,0 +1,146 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::{
+    ast::ModuleKind,
+    tipo::error::{Error, Warning},
+};
+
+fn unmatched(code: &str) -> Vec<String> {
+    match TestProject::new().check("test_module", ModuleKind::Lib, code) {
+        Err(Error::NotExhaustivePatternMatch { unmatched, .. }) => unmatched,
+        result => panic!("Expected a non-exhaustive match, got {result:?}"),
+    }
+}
+
+/// The source of the clauses reported as unreachable.
+fn unreachable(code: &str) -> Vec<&str> {
+    let warnings = TestProject::new()
+        .check("test_module", ModuleKind::Lib, code)
+        .expect("Failed to check module");
+
+    warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            Warning::UnreachableClause { location } => Some(&code[location.start..location.end]),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn missing_nested_constructor() {
+    let code = indoc! {r#"
+        type Shape {
+          Circle(Int)
+          Rect(Option<Int>, Bool)
+        }
+
+        pub fn area(shape: Shape) -> Int {
+          when shape is {
+            Circle(_) -> 0
+            Rect(Some(_), True) -> 1
+            Rect(None, _) -> 2
+          }
+        }
+    "#};
+
+    assert_eq!(unmatched(code), vec!["Rect(Some(_), False)"]);
+}
+
+#[test]
+fn missing_tuple() {
+    let code = indoc! {r#"
+        pub fn pick(pair: (Bool, Option<Int>)) -> Int {
+          when pair is {
+            (True, _) -> 0
+            (False, None) -> 1
+          }
+        }
+    "#};
+
+    assert_eq!(unmatched(code), vec!["(False, Some(_))"]);
+}
+
+#[test]
+fn missing_list_with_tail() {
+    let code = indoc! {r#"
+        pub fn count(xs: List<Bool>) -> Int {
+          when xs is {
+            [] -> 0
+            [True, ..rest] -> 1
+          }
+        }
+    "#};
+
+    assert_eq!(unmatched(code), vec!["[False, ..]"]);
+}
+
+#[test]
+fn guarded_clauses_do_not_count() {
+    let code = indoc! {r#"
+        pub fn larger(xs: List<Int>) -> Int {
+          when xs is {
+            [] -> 0
+            [x] -> x
+            [x, y, ..] if x > y -> x
+          }
+        }
+    "#};
+
+    assert_eq!(unmatched(code), vec!["[_, _, ..]"]);
+
+    let code = indoc! {r#"
+        pub fn positive(o: Option<Int>) -> Int {
+          when o is {
+            Some(x) if x > 0 -> x
+            None -> 0
+          }
+        }
+    "#};
+
+    assert_eq!(unmatched(code), vec!["Some(_)"]);
+}
+
+#[test]
+fn clauses_after_guards_are_reachable() {
+    let code = indoc! {r#"
+        pub fn positive(o: Option<Int>) -> Int {
+          when o is {
+            Some(x) if x > 0 -> x
+            Some(_) -> 1
+            None -> 0
+          }
+        }
+    "#};
+
+    assert!(unreachable(code).is_empty());
+}
+
+#[test]
+fn redundant_clauses() {
+    let code = indoc! {r#"
+        pub fn flag(o: Option<Bool>) -> Int {
+          when o is {
+            Some(_) -> 1
+            Some(True) -> 2
+            None -> 0
+            _ -> 3
+          }
+        }
+    "#};
+
+    assert_eq!(unreachable(code), vec!["Some(True)", "_"]);
+
+    let code = indoc! {r#"
+        pub fn size(xs: List<Int>) -> Int {
+          when xs is {
+            [_, ..] -> 1
+            [] -> 0
+            [x] -> x
+          }
+        }
+    "#};
+
+    assert_eq!(unreachable(code), vec!["[x]"]);
+}
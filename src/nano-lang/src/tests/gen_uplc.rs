@@ -0,0 +1,588 @@
+use indoc::indoc;
+use untyped_plutus_core::{
+    ast::{Data, DeBruijn, NamedDeBruijn, Program},
+    machine::{
+        cost_model::{CostModel, ExBudget},
+        debugger::{Debugger, Status},
+        Machine,
+    },
+    PlutusData,
+};
+
+use super::{eval, TestProject};
+use crate::{
+    ast::{Definition, ModuleKind},
+    format, parser,
+    tipo::error::Error,
+};
+
+#[test]
+fn when_literal_patterns() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "literals",
+        indoc! {r#"
+            fn size(n: Int) -> String {
+              when n is {
+                0 -> @"none"
+                1..9 -> @"few"
+                _ -> @"many"
+              }
+            }
+
+            fn greeting(s: String) -> Int {
+              when s is {
+                @"hello" -> 1
+                @"bonjour" -> 2
+                _ -> 0
+              }
+            }
+
+            fn tag(b: ByteArray) -> Int {
+              when b is {
+                #"00" -> 0
+                #"ff" -> 255
+                _ -> -1
+              }
+            }
+
+            test sizes() {
+              size(0) == @"none" && size(1) == @"few" && size(9) == @"few" && size(10) == @"many" && size(-1) == @"many"
+            }
+
+            test greetings() {
+              greeting(@"hello") == 1 && greeting(@"bonjour") == 2 && greeting(@"hi") == 0
+            }
+
+            test tags() {
+              tag(#"00") == 0 && tag(#"ff") == 255 && tag(#"0000") == -1 && tag(#"") == -1
+            }
+
+            test wrong_size() {
+              size(10) == @"few"
+            }
+        "#},
+    );
+
+    assert!(project.passes("literals", "sizes"));
+    assert!(project.passes("literals", "greetings"));
+    assert!(project.passes("literals", "tags"));
+    assert!(!project.passes("literals", "wrong_size"));
+}
+
+#[test]
+fn when_negative_range_patterns() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "signs",
+        indoc! {r#"
+            fn sign(n: Int) -> Int {
+              when n is {
+                -9..-1 -> -1
+                0 -> 0
+                -10..9 -> 1
+                _ -> 2
+              }
+            }
+
+            test signs() {
+              sign(-9) == -1 && sign(-1) == -1 && sign(-10) == 1 && sign(0) == 0 && sign(9) == 1 && sign(-11) == 2
+            }
+
+            pub const below: Int = 0 - 1
+        "#},
+    );
+
+    assert!(project.passes("signs", "signs"));
+
+    let result = TestProject::new().check(
+        "signs",
+        ModuleKind::Lib,
+        indoc! {r#"
+            fn sign(n: Int) -> Int {
+              when n is {
+                -1..-9 -> -1
+                _ -> 0
+              }
+            }
+        "#},
+    );
+
+    assert!(matches!(result, Err(Error::EmptyIntRange { .. })));
+}
+
+#[test]
+fn expect_compared_literals() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "literals",
+        indoc! {r#"
+            fn only_abc(s: String) -> Bool {
+              expect True = s == @"abc"
+              True
+            }
+
+            test abc() {
+              only_abc(@"abc")
+            }
+
+            test abd() {
+              only_abc(@"abd")
+            }
+        "#},
+    );
+
+    assert!(project.passes("literals", "abc"));
+    assert!(eval(project.test_program("literals", "abd")).failed());
+}
+
+#[test]
+fn local_polymorphism_and_type_arguments() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "generics",
+        indoc! {r#"
+            fn identity(x: a) -> a {
+              x
+            }
+
+            fn empty() -> List<a> {
+              []
+            }
+
+            test local_polymorphism() {
+              let pair = fn(x) { (x, x) }
+              let (a, b) = pair(1)
+              let (c, d) = pair(#"ff")
+              a + b == 2 && c == d
+            }
+
+            test type_arguments() {
+              let xs = empty<Int>()
+              identity<Int>(1) == 1 && xs == [] && identity<ByteArray>(#"00") == #"00"
+            }
+        "#},
+    );
+
+    assert!(project.passes("generics", "local_polymorphism"));
+    assert!(project.passes("generics", "type_arguments"));
+
+    let code = indoc! {r#"
+        fn identity(x: a) -> a {
+          x
+        }
+
+        pub fn wrong() -> Int {
+          identity<ByteArray>(1)
+        }
+    "#};
+
+    assert!(matches!(
+        TestProject::new().check("generics", ModuleKind::Lib, code),
+        Err(Error::CouldNotUnify { .. })
+    ));
+}
+
+fn pretty(code: &str) -> String {
+    let (module, extra) = parser::module(code, ModuleKind::Lib).expect("Failed to parse module");
+
+    let mut formatted = String::new();
+
+    format::pretty(&mut formatted, module, extra, code);
+
+    formatted
+}
+
+#[test]
+fn backpassing() {
+    let code = indoc! {r#"
+        fn and_then(opt: Option<a>, then: fn(a) -> Option<b>) -> Option<b> {
+          when opt is {
+            None -> None
+            Some(a) -> then(a)
+          }
+        }
+
+        fn half(n: Int) -> Option<Int> {
+          if n % 2 == 0 {
+            Some(n / 2)
+          } else {
+            None
+          }
+        }
+
+        fn quarter_sum(pair: Option<(Int, Int)>) -> Option<Int> {
+          let (a, b) <- and_then(pair)
+          let x <- and_then(half(a))
+          let y <- and_then(half(b))
+          Some(x / 2 + y / 2)
+        }
+
+        test chained() {
+          quarter_sum(Some((8, 4))) == Some(3)
+        }
+
+        test short_circuits() {
+          quarter_sum(Some((8, 3))) == None && quarter_sum(None) == None
+        }
+    "#};
+
+    let mut project = TestProject::new();
+
+    project.lib("backpassing", code);
+
+    assert!(project.passes("backpassing", "chained"));
+    assert!(project.passes("backpassing", "short_circuits"));
+
+    let formatted = pretty(code);
+
+    assert_eq!(formatted.matches(" <-").count(), 3);
+    assert_eq!(pretty(&formatted), formatted);
+
+    let code = indoc! {r#"
+        pub fn dangling(opt: Option<Int>) -> Option<Int> {
+          let x <- and_then(opt)
+        }
+    "#};
+
+    assert!(matches!(
+        TestProject::new().check("backpassing", ModuleKind::Lib, code),
+        Err(Error::LastExpressionIsAssignment { .. })
+    ));
+}
+
+#[test]
+fn source_map_follows_recursive_module_functions() {
+    let mut project = TestProject::new();
+
+    project
+        .lib(
+            "math",
+            indoc! {r#"
+                pub fn sum_to(n: Int) -> Int {
+                  if n <= 0 {
+                    0
+                  } else {
+                    n + sum_to(n - 1)
+                  }
+                }
+            "#},
+        )
+        .validator(
+            "checker",
+            indoc! {r#"
+                use math
+
+                validator {
+                  fn spend(_datum: Data, redeemer: Int, _ctx: Data) -> Bool {
+                    math.sum_to(redeemer) == 6
+                  }
+                }
+            "#},
+        );
+
+    let Definition::Validator(validator) = project.definition("checker", "spend") else {
+        unreachable!()
+    };
+
+    let (program, source_map) = project
+        .generator()
+        .generate_with_source_map("checker", validator);
+
+    let program = program
+        .apply_data(Data::integer(0.into()))
+        .apply_data(Data::integer(3.into()))
+        .apply_data(Data::integer(0.into()));
+
+    assert!(!eval(program.clone()).failed());
+
+    let program: Program<DeBruijn> = program.try_into().unwrap();
+    let program: Program<NamedDeBruijn> = program.into();
+
+    let machine = Machine::new(
+        pallas_primitives::babbage::Language::PlutusV2,
+        CostModel::default(),
+        ExBudget::default(),
+        200,
+    );
+
+    let mut debugger = Debugger::new(machine, program).unwrap();
+
+    let functions: Vec<_> = source_map
+        .iter()
+        .filter(|(_, location)| location.module == "math")
+        .map(|(position, _)| position)
+        .collect();
+
+    assert!(!functions.is_empty());
+
+    debugger.breakpoints.extend(functions);
+
+    let mut stops = 0;
+
+    while let Status::Paused = debugger.resume().unwrap() {
+        let location = source_map.enclosing(debugger.position().unwrap()).unwrap();
+
+        assert_eq!(location.module, "math");
+        assert_eq!(location.function, "sum_to");
+
+        stops += 1;
+    }
+
+    assert!(stops > 1);
+}
+
+#[test]
+fn source_map_leaves_programs_unchanged() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "halves",
+        indoc! {r#"
+            fn half(n: Int) -> Rational {
+              rational(n, 2)
+            }
+
+            test half_of_four() {
+              half(4) == rational_from_int(2)
+            }
+        "#},
+    );
+
+    let Definition::Test(test) = project.definition("halves", "half_of_four") else {
+        unreachable!()
+    };
+
+    let (program, source_map) = project.generator().generate_test_with_source_map(
+        "halves",
+        "half_of_four",
+        &test.body,
+        &test.arguments,
+    );
+
+    assert_eq!(program, project.test_program("halves", "half_of_four"));
+
+    assert!(!eval(program).failed());
+
+    assert!(source_map
+        .iter()
+        .any(|(_, location)| location.function == "half"));
+}
+
+/// A script context whose purpose is the constructor `index` of
+/// `ScriptPurpose`.
+fn script_context(index: u64) -> PlutusData {
+    Data::constr(
+        0,
+        vec![
+            Data::integer(0.into()),
+            Data::constr(index, vec![Data::bytestring(vec![0x00])]),
+        ],
+    )
+}
+
+#[test]
+fn multi_validator_purposes() {
+    let mut project = TestProject::new();
+
+    project.validator(
+        "protocol",
+        indoc! {r#"
+            validator {
+              fn spend(_datum: Data, redeemer: Int, _ctx: Data) -> Bool {
+                redeemer == 1
+              }
+
+              fn mint(redeemer: Int, _ctx: Data) -> Bool {
+                redeemer == 0
+              }
+
+              fn withdraw(redeemer: Int, _ctx: Data) -> Bool {
+                redeemer == 2
+              }
+
+              fn certify(redeemer: Int, _ctx: Data) -> Bool {
+                redeemer == 3
+              }
+            }
+        "#},
+    );
+
+    let Definition::Validator(validator) = project.definition("protocol", "spend") else {
+        unreachable!()
+    };
+
+    let program = project.generator().generate(validator);
+
+    // Each handler only accepts the redeemer matching the index of its
+    // purpose, so a handler accepting a redeemer tells which one ran.
+    let run = |index: u64, redeemer: u64| {
+        !eval(
+            program
+                .clone()
+                .apply_data(Data::integer(redeemer.into()))
+                .apply_data(script_context(index)),
+        )
+        .failed()
+    };
+
+    for index in [0, 2, 3] {
+        for redeemer in 0..4 {
+            assert_eq!(
+                run(index, redeemer),
+                index == redeemer,
+                "{index} {redeemer}"
+            );
+        }
+    }
+
+    // Spending handlers are given a datum first, and their redeemer wrapped
+    // in a constructor so as to tell it apart from a script context.
+    let spend = |redeemer: u64| {
+        !eval(
+            program
+                .clone()
+                .apply_data(Data::integer(0.into()))
+                .apply_data(Data::constr(1, vec![Data::integer(redeemer.into())]))
+                .apply_data(script_context(1)),
+        )
+        .failed()
+    };
+
+    assert!(spend(1));
+    assert!(!spend(0));
+}
+
+#[test]
+fn multi_validator_missing_purpose() {
+    let mut project = TestProject::new();
+
+    project.validator(
+        "minter",
+        indoc! {r#"
+            validator {
+              fn mint(_redeemer: Data, _ctx: Data) -> Bool {
+                True
+              }
+
+              fn withdraw(_redeemer: Data, _ctx: Data) -> Bool {
+                True
+              }
+            }
+        "#},
+    );
+
+    let Definition::Validator(validator) = project.definition("minter", "mint") else {
+        unreachable!()
+    };
+
+    let program = project.generator().generate(validator);
+
+    let run = |index: u64| {
+        !eval(
+            program
+                .clone()
+                .apply_data(Data::integer(0.into()))
+                .apply_data(script_context(index)),
+        )
+        .failed()
+    };
+
+    assert!(run(0));
+    assert!(run(2));
+    assert!(!run(1));
+    assert!(!run(3));
+}
+
+#[test]
+fn when_var_pattern_binds_subject() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "shapes",
+        indoc! {r#"
+            type Shape {
+              Circle(Int)
+              Square(Int)
+            }
+
+            fn circles_only(shape: Shape) -> Shape {
+              when shape is {
+                Circle(_) -> shape
+                other -> other
+              }
+            }
+
+            test square_kept() {
+              circles_only(Square(2)) == Square(2)
+            }
+        "#},
+    );
+
+    assert!(project.passes("shapes", "square_kept"));
+}
+
+#[test]
+fn mutual_recursion_budget() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "parity",
+        indoc! {r#"
+            fn is_even(n: Int) -> Bool {
+              if n == 0 {
+                True
+              } else {
+                is_odd(n - 1)
+              }
+            }
+
+            fn is_odd(n: Int) -> Bool {
+              if n == 0 {
+                False
+              } else {
+                is_even(n - 1)
+              }
+            }
+
+            fn parity(n: Int, even: Bool) -> Bool {
+              if n == 0 {
+                even
+              } else {
+                parity(n - 1, !even)
+              }
+            }
+
+            test mutual() {
+              is_even(100)
+            }
+
+            test baseline() {
+              parity(100, True)
+            }
+        "#},
+    );
+
+    assert!(project.passes("parity", "mutual"));
+    assert!(project.passes("parity", "baseline"));
+
+    let mutual = eval(project.test_program("parity", "mutual")).cost();
+    let baseline = eval(project.test_program("parity", "baseline")).cost();
+
+    // Going through the shared fixpoint costs no more than a function
+    // calling itself with its state as an extra argument.
+    assert!(mutual.mem <= baseline.mem, "{mutual:?} > {baseline:?}");
+    assert!(mutual.cpu <= baseline.cpu, "{mutual:?} > {baseline:?}");
+
+    assert_eq!(
+        mutual,
+        ExBudget {
+            mem: 254_902,
+            cpu: 108_398_189,
+        }
+    );
+}
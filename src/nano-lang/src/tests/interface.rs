@@ -0,0 +1,180 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::{ast::ModuleKind, tipo::error::Error};
+
+const SIZE: &str = indoc! {r#"
+    pub interface Size<a> {
+      fn size(x: a) -> Int
+    }
+
+    impl Size<Int> {
+      fn size(x: Int) -> Int {
+        x
+      }
+    }
+"#};
+
+fn check(code: &str) -> Result<(), Error> {
+    TestProject::new()
+        .check("sizes", ModuleKind::Lib, &format!("{SIZE}\n{code}"))
+        .map(|_| ())
+}
+
+#[test]
+fn missing_implementation() {
+    let code = indoc! {r#"
+        pub fn flag() -> Int {
+          size(True)
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::MissingImplementation { interface, .. }) if interface == "Size"
+    ));
+}
+
+#[test]
+fn unconstrained_generic() {
+    let code = indoc! {r#"
+        pub fn twice(x: a) -> Int {
+          size(x) * 2
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::MissingImplementation { interface, .. }) if interface == "Size"
+    ));
+}
+
+#[test]
+fn duplicate_implementation() {
+    let code = indoc! {r#"
+        impl Size<Int> {
+          fn size(x: Int) -> Int {
+            0
+          }
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::DuplicateImplementation { interface, name, .. })
+            if interface == "Size" && name == "Int"
+    ));
+}
+
+#[test]
+fn overlapping_implementation() {
+    let code = indoc! {r#"
+        impl<a> Size<List<a>> {
+          fn size(xs: List<a>) -> Int {
+            0
+          }
+        }
+
+        impl Size<List<Int>> {
+          fn size(xs: List<Int>) -> Int {
+            1
+          }
+        }
+    "#};
+
+    assert!(matches!(
+        check(code),
+        Err(Error::IllegalImplementationType { .. })
+    ));
+}
+
+#[test]
+fn constrained_generic_call() {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "sizes",
+        &format!(
+            "{SIZE}\n{}",
+            indoc! {r#"
+                impl<a: Size> Size<List<a>> {
+                  fn size(xs: List<a>) -> Int {
+                    when xs is {
+                      [] -> 0
+                      [x, ..rest] -> size(x) + size(rest)
+                    }
+                  }
+                }
+
+                pub fn total<a: Size>(xs: List<a>) -> Int {
+                  size(xs) + 1
+                }
+
+                test flat() {
+                  total([1, 2, 3]) == 7
+                }
+
+                test nested() {
+                  total([[1, 2], [], [3]]) == 7
+                }
+            "#}
+        ),
+    );
+
+    assert!(project.passes("sizes", "flat"));
+    assert!(project.passes("sizes", "nested"));
+}
+
+#[test]
+fn method_call_across_modules() {
+    let mut project = TestProject::new();
+
+    project
+        .lib(
+            "sizes",
+            &format!(
+                "{SIZE}\n{}",
+                indoc! {r#"
+                    pub fn total<a: Size>(xs: List<a>) -> Int {
+                      when xs is {
+                        [] -> 0
+                        [x, ..rest] -> size(x) + total(rest)
+                      }
+                    }
+                "#}
+            ),
+        )
+        .lib(
+            "boxes",
+            indoc! {r#"
+                use sizes.{Size}
+
+                pub type Box {
+                  Box(Int)
+                }
+
+                impl Size<Box> {
+                  fn size(b: Box) -> Int {
+                    let Box(n) = b
+                    n * 10
+                  }
+                }
+
+                test own_implementation() {
+                  sizes.size(Box(2)) == 20
+                }
+
+                test imported_implementation() {
+                  sizes.size(3) == 3
+                }
+
+                test through_generic_function() {
+                  sizes.total([Box(1), Box(2)]) == 30
+                }
+            "#},
+        );
+
+    assert!(project.passes("boxes", "own_implementation"));
+    assert!(project.passes("boxes", "imported_implementation"));
+    assert!(project.passes("boxes", "through_generic_function"));
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use untyped_plutus_core::{
+    ast::{DeBruijn, Name, Program, Term},
+    machine::{cost_model::ExBudget, eval_result::EvalResult},
+};
+
+use crate::{
+    ast::{
+        Definition, ModuleKind, Tracing, TypedDataType, TypedDefinition, TypedFunction, TypedModule,
+    },
+    builtins,
+    gen_uplc::{
+        builder::{ConstantAccessKey, DataTypeKey, FunctionAccessKey},
+        CodeGenerator,
+    },
+    parser,
+    tipo::{
+        error::{Error, Warning},
+        TypeInfo,
+    },
+    IdGenerator,
+};
+
+mod check;
+mod constants;
+mod derive;
+mod exhaustive;
+mod gen_uplc;
+mod interface;
+mod rational;
+mod schema;
+
+/// Modules checked one after the other, each seeing the ones before it, as a
+/// project would check them.
+pub struct TestProject {
+    pub id_gen: IdGenerator,
+    pub module_types: HashMap<String, TypeInfo>,
+    pub functions: IndexMap<FunctionAccessKey, TypedFunction>,
+    pub data_types: IndexMap<DataTypeKey, TypedDataType>,
+    pub modules: IndexMap<String, TypedModule>,
+}
+
+impl TestProject {
+    pub fn new() -> Self {
+        let id_gen = IdGenerator::new();
+
+        let mut module_types = HashMap::new();
+
+        module_types.insert("nano".to_string(), builtins::prelude(&id_gen));
+        module_types.insert("nano/builtin".to_string(), builtins::plutus(&id_gen));
+
+        TestProject {
+            functions: builtins::prelude_functions(&id_gen),
+            data_types: builtins::prelude_data_types(&id_gen),
+            id_gen,
+            module_types,
+            modules: IndexMap::new(),
+        }
+    }
+
+    pub fn check(
+        &mut self,
+        name: &str,
+        kind: ModuleKind,
+        code: &str,
+    ) -> Result<Vec<Warning>, Error> {
+        let (mut ast, _) = parser::module(code, kind).expect("Failed to parse module");
+
+        ast.name = name.to_string();
+
+        let mut warnings = vec![];
+
+        let ast = ast.infer(
+            &self.id_gen,
+            kind,
+            "test",
+            &self.module_types,
+            Tracing::KeepTraces,
+            &mut warnings,
+        )?;
+
+        self.module_types
+            .insert(name.to_string(), ast.type_info.clone());
+
+        self.modules.insert(name.to_string(), ast);
+
+        Ok(warnings)
+    }
+
+    pub fn lib(&mut self, name: &str, code: &str) -> &mut Self {
+        if let Err(error) = self.check(name, ModuleKind::Lib, code) {
+            panic!("Failed to check {name}: {error:?}");
+        }
+
+        self
+    }
+
+    pub fn validator(&mut self, name: &str, code: &str) -> &mut Self {
+        if let Err(error) = self.check(name, ModuleKind::Validator, code) {
+            panic!("Failed to check {name}: {error:?}");
+        }
+
+        self
+    }
+
+    pub fn generator(&self) -> CodeGenerator<'_> {
+        let mut functions: IndexMap<_, _> =
+            self.functions.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let mut constants = IndexMap::new();
+
+        for (module_name, module) in &self.modules {
+            for def in module.definitions() {
+                let funcs = match def {
+                    Definition::Fn(func) => std::slice::from_ref(func),
+                    Definition::Implementation(implementation) => &implementation.methods[..],
+                    Definition::ModuleConstant(constant) => {
+                        constants.insert(
+                            ConstantAccessKey {
+                                module_name: module_name.clone(),
+                                constant_name: constant.name.clone(),
+                            },
+                            constant,
+                        );
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                for func in funcs {
+                    functions.insert(
+                        FunctionAccessKey {
+                            module_name: module_name.clone(),
+                            function_name: func.name.clone(),
+                            variant_name: String::new(),
+                        },
+                        func,
+                    );
+                }
+            }
+        }
+
+        CodeGenerator::new(
+            functions,
+            self.all_data_types(),
+            self.module_types.iter().collect(),
+            constants,
+        )
+    }
+
+    /// The data types of the prelude and of every module checked.
+    pub fn all_data_types(&self) -> IndexMap<DataTypeKey, &TypedDataType> {
+        let mut data_types: IndexMap<_, _> = self
+            .data_types
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect();
+
+        for (module_name, module) in &self.modules {
+            for def in module.definitions() {
+                if let Definition::DataType(data_type) = def {
+                    data_types.insert(
+                        DataTypeKey {
+                            module_name: module_name.clone(),
+                            defined_type: data_type.name.clone(),
+                        },
+                        data_type,
+                    );
+                }
+            }
+        }
+
+        data_types
+    }
+
+    /// The definition named `name` of a module, be it a function, test or
+    /// validator.
+    pub fn definition(&self, module: &str, name: &str) -> &TypedDefinition {
+        self.modules[module]
+            .definitions()
+            .find(|def| match def {
+                Definition::Fn(func) | Definition::Test(func) => func.name == name,
+                Definition::Validator(validator) => validator.fun.name == name,
+                _ => false,
+            })
+            .unwrap_or_else(|| panic!("No definition named {name} in {module}"))
+    }
+
+    /// Compiles the test `name` of a module.
+    pub fn test_program(&self, module: &str, name: &str) -> Program<Name> {
+        let Definition::Test(test) = self.definition(module, name) else {
+            panic!("{name} is not a test");
+        };
+
+        self.generator().generate_test(&test.body, &test.arguments)
+    }
+
+    /// Whether the test `name` of a module evaluates to `True`.
+    pub fn passes(&self, module: &str, name: &str) -> bool {
+        eval(self.test_program(module, name))
+            .result()
+            .is_ok_and(|term| term == Term::bool(true))
+    }
+}
+
+pub fn eval(program: Program<Name>) -> EvalResult {
+    let program: Program<DeBruijn> = program
+        .try_into()
+        .expect("Failed to convert program to De Bruijn indices");
+
+    program.eval(ExBudget::default())
+}
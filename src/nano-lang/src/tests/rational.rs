@@ -0,0 +1,111 @@
+use indoc::indoc;
+
+use super::{eval, TestProject};
+use crate::{ast::ModuleKind, tipo::error::Error};
+
+fn rationals() -> TestProject {
+    let mut project = TestProject::new();
+
+    project.lib(
+        "rationals",
+        indoc! {r#"
+            test negative_denominator() {
+              let r = rational(3, -6)
+              rational_numerator(r) == -1 && rational_denominator(r) == 2
+            }
+
+            test both_negative() {
+              let r = rational(-4, -6)
+              rational_numerator(r) == 2 && rational_denominator(r) == 3
+            }
+
+            test equal_once_normalised() {
+              equals(rational(2, -4), rational_negate(1/2r)) && equals(rational(6, 8), 3/4r)
+            }
+
+            test compared_by_value() {
+              compare(1/3r, 1/2r) == Less && compare(rational(-1, 2), rational(1, -3)) == Less && compare(2/4r, 1/2r) == Equal && compare(rational_from_int(1), 99/100r) == Greater
+            }
+
+            test zero_denominator() {
+              rational_numerator(rational(1, 0)) == 1
+            }
+
+            test division_by_zero() {
+              rational_numerator(rational_div(1/2r, rational_from_int(0))) == 1
+            }
+
+            test division() {
+              equals(rational_div(1/2r, rational(-3, 4)), rational(-2, 3))
+            }
+
+            test floor() {
+              rational_floor(7/2r) == 3 && rational_floor(rational(-7, 2)) == -4 && rational_floor(rational(-1, 3)) == -1 && rational_floor(rational(-4, 2)) == -2
+            }
+
+            test ceil() {
+              rational_ceil(7/2r) == 4 && rational_ceil(rational(-7, 2)) == -3 && rational_ceil(rational(-1, 3)) == 0 && rational_ceil(rational(-4, 2)) == -2
+            }
+
+            test truncate() {
+              rational_truncate(7/2r) == 3 && rational_truncate(rational(-7, 2)) == -3 && rational_truncate(rational(-5, 3)) == -1 && rational_truncate(rational(-4, 2)) == -2
+            }
+
+            test round_half_way() {
+              rational_round(1/2r) == 1 && rational_round(5/2r) == 3 && rational_round(rational(-1, 2)) == -1 && rational_round(rational(-5, 2)) == -3
+            }
+
+            test round_negative() {
+              rational_round(rational(-7, 5)) == -1 && rational_round(rational(-8, 5)) == -2 && rational_round(7/5r) == 1 && rational_round(rational(-4, 2)) == -2
+            }
+        "#},
+    );
+
+    project
+}
+
+#[test]
+fn normalisation() {
+    let project = rationals();
+
+    assert!(project.passes("rationals", "negative_denominator"));
+    assert!(project.passes("rationals", "both_negative"));
+    assert!(project.passes("rationals", "equal_once_normalised"));
+}
+
+#[test]
+fn comparison() {
+    assert!(rationals().passes("rationals", "compared_by_value"));
+}
+
+#[test]
+fn rounding() {
+    let project = rationals();
+
+    for test in ["floor", "ceil", "truncate", "round_half_way", "round_negative"] {
+        assert!(project.passes("rationals", test), "{test}");
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    let project = rationals();
+
+    assert!(project.passes("rationals", "division"));
+    assert!(eval(project.test_program("rationals", "zero_denominator")).failed());
+    assert!(eval(project.test_program("rationals", "division_by_zero")).failed());
+}
+
+#[test]
+fn zero_denominator_literal() {
+    let code = indoc! {r#"
+        pub fn infinity() -> Rational {
+          1/0r
+        }
+    "#};
+
+    assert!(matches!(
+        TestProject::new().check("rationals", ModuleKind::Lib, code),
+        Err(Error::ZeroDenominator { .. })
+    ));
+}
@@ -0,0 +1,179 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::{
+    ast::Definition,
+    gen_uplc::schema::{Annotated, Constructor, Definitions, Error, Items, Schema},
+};
+
+const SCHEMAS: &str = indoc! {r#"
+    pub type Datum {
+      Locked(Int)
+      Unlocked
+    }
+
+    pub type Point {
+      x: Int,
+      y: Int,
+    }
+
+    pub opaque type Id {
+      Id(ByteArray)
+    }
+
+    pub type Tree<a> {
+      Leaf(a)
+      Node(List<Tree<a>>)
+    }
+
+    pub fn data(datum: Datum, point: Point, id: Id, owners: List<(ByteArray, Int)>, pair: (Int, String), tree: Tree<Int>) -> Bool {
+      True
+    }
+
+    pub fn callback(callback: fn(Int) -> Int) -> Bool {
+      True
+    }
+
+    pub fn callbacks(callbacks: List<fn(Int) -> Int>) -> Bool {
+      True
+    }
+"#};
+
+/// Derives the schema of each argument of a function of the module.
+fn schemas(function: &str) -> Result<(Vec<Annotated<Schema>>, Definitions), Error> {
+    let mut project = TestProject::new();
+
+    project.lib("schemas", SCHEMAS);
+
+    let Definition::Fn(function) = project.definition("schemas", function) else {
+        panic!("{function} is not a function");
+    };
+
+    let data_types = project.all_data_types();
+
+    let mut definitions = Definitions::new();
+
+    let schemas = function
+        .arguments
+        .iter()
+        .map(|arg| Annotated::from_arg(arg, &data_types, &mut definitions))
+        .collect::<Result<_, _>>()?;
+
+    Ok((schemas, definitions))
+}
+
+fn titled(title: &str, annotated: Schema) -> Annotated<Schema> {
+    Annotated {
+        title: Some(title.to_string()),
+        description: None,
+        annotated,
+    }
+}
+
+fn constructor(
+    title: &str,
+    index: usize,
+    fields: Vec<Annotated<Schema>>,
+) -> Annotated<Constructor> {
+    Annotated {
+        title: Some(title.to_string()),
+        description: None,
+        annotated: Constructor { index, fields },
+    }
+}
+
+#[test]
+fn schema_of_arguments() {
+    let (schemas, _) = schemas("data").unwrap();
+
+    assert_eq!(
+        schemas,
+        [
+            titled("datum", Schema::Ref("schemas/Datum".to_string())),
+            titled("point", Schema::Ref("schemas/Point".to_string())),
+            titled("id", Schema::Bytes),
+            titled(
+                "owners",
+                Schema::Map(
+                    Box::new(Schema::Bytes.into()),
+                    Box::new(Schema::Integer.into())
+                )
+            ),
+            titled(
+                "pair",
+                Schema::List(Items::Many(vec![
+                    Schema::Integer.into(),
+                    Annotated {
+                        title: Some("String".to_string()),
+                        description: Some("An UTF-8 encoded text.".to_string()),
+                        annotated: Schema::Bytes,
+                    },
+                ]))
+            ),
+            titled("tree", Schema::Ref("schemas/Tree$Int".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn schema_of_custom_types() {
+    let (_, definitions) = schemas("data").unwrap();
+
+    assert_eq!(
+        definitions.lookup("schemas/Datum"),
+        Some(&titled(
+            "Datum",
+            Schema::AnyOf(vec![
+                constructor("Locked", 0, vec![Schema::Integer.into()]),
+                constructor("Unlocked", 1, vec![]),
+            ])
+        ))
+    );
+
+    assert_eq!(
+        definitions.lookup("schemas/Point"),
+        Some(&titled(
+            "Point",
+            Schema::AnyOf(vec![constructor(
+                "Point",
+                0,
+                vec![titled("x", Schema::Integer), titled("y", Schema::Integer)]
+            )])
+        ))
+    );
+
+    // Recursive types refer to their own definition.
+    assert_eq!(
+        definitions.lookup("schemas/Tree$Int"),
+        Some(&titled(
+            "Tree",
+            Schema::AnyOf(vec![
+                constructor("Leaf", 0, vec![Schema::Integer.into()]),
+                constructor(
+                    "Node",
+                    1,
+                    vec![Schema::List(Items::One(Box::new(
+                        Schema::Ref("schemas/Tree$Int".to_string()).into()
+                    )))
+                    .into()]
+                ),
+            ])
+        ))
+    );
+
+    // Opaque types wrapping a single value are erased.
+    assert_eq!(definitions.lookup("schemas/Id"), None);
+}
+
+#[test]
+fn schema_rejects_functions() {
+    for function in ["callback", "callbacks"] {
+        let error = schemas(function).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "I found a function in a type serialised as data."
+        );
+        assert!(error.help().contains("fn(Int) -> Int"), "{}", error.help());
+    }
+}
@@ -16,8 +16,10 @@ use crate::{
 use std::{cell::RefCell, collections::HashMap, ops::Deref, sync::Arc};
 use untyped_plutus_core::{ast::Type as UplcType, builtins::DefaultFunction};
 
+mod derive;
 mod environment;
 pub mod error;
+mod exhaustive;
 mod expr;
 pub mod fields;
 mod hydrator;
@@ -217,6 +219,24 @@ impl Type {
         }
     }
 
+    /// The generic types found in the type which aren't in `ids` yet, in the
+    /// order they first appear.
+    pub fn collect_generics(&self, ids: &mut Vec<u64>) {
+        match self {
+            Type::Var { tipo } => match tipo.borrow().deref() {
+                TypeVar::Generic { id } if !ids.contains(id) => ids.push(*id),
+                TypeVar::Link { tipo } => tipo.collect_generics(ids),
+                TypeVar::Generic { .. } | TypeVar::Unbound { .. } => (),
+            },
+            Type::App { args, .. } => args.iter().for_each(|arg| arg.collect_generics(ids)),
+            Type::Fn { args, ret } => {
+                args.iter().for_each(|arg| arg.collect_generics(ids));
+                ret.collect_generics(ids);
+            }
+            Type::Tuple { elems } => elems.iter().for_each(|elem| elem.collect_generics(ids)),
+        }
+    }
+
     pub fn get_inner_types(&self) -> Vec<Arc<Type>> {
         if self.is_list() {
             match self {
@@ -548,7 +568,10 @@ pub enum ValueConstructorVariant {
     ModuleConstant {
         location: Span,
         module: String,
-        literal: Constant,
+        name: String,
+        /// The value of constants defined by a literal. Others are evaluated
+        /// during code generation.
+        literal: Option<Constant>,
     },
 
     ModuleFn {
@@ -593,10 +616,15 @@ impl ValueConstructorVariant {
             },
 
             Self::ModuleConstant {
-                literal, location, ..
+                literal,
+                location,
+                module,
+                name,
             } => ModuleValueConstructor::Constant {
                 literal: literal.clone(),
                 location: *location,
+                module: module.clone(),
+                name: name.clone(),
             },
 
             Self::LocalVariable { location, .. } => ModuleValueConstructor::Fn {
@@ -630,6 +658,12 @@ impl ValueConstructorVariant {
     pub fn is_local_variable(&self) -> bool {
         matches!(self, Self::LocalVariable { .. })
     }
+
+    /// Functions are named in lowercase, but methods of implementations are
+    /// named after their interface, see [`ImplementationKey::method_name`].
+    pub fn is_implementation_method(&self) -> bool {
+        matches!(self, Self::ModuleFn { name, .. } if name.starts_with(char::is_uppercase))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -641,6 +675,74 @@ pub struct TypeInfo {
     pub types_constructors: HashMap<String, Vec<String>>,
     pub values: HashMap<String, ValueConstructor>,
     pub accessors: HashMap<String, AccessorsMap>,
+    pub interfaces: HashMap<String, InterfaceConstructor>,
+    pub implementations: HashMap<ImplementationKey, ImplementationConstructor>,
+    /// The generic types of values which must implement some interface.
+    pub constraints: HashMap<String, Vec<(u64, InterfaceKey)>>,
+    /// The type parameters functions declare, in order.
+    pub type_parameters: HashMap<String, Vec<u64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceConstructor {
+    pub public: bool,
+    pub location: Span,
+    pub module: String,
+    pub name: String,
+    /// The generic type standing for the implementing type in the methods.
+    pub parameter: u64,
+    pub methods: HashMap<String, Arc<Type>>,
+}
+
+impl InterfaceConstructor {
+    pub fn key(&self) -> InterfaceKey {
+        InterfaceKey {
+            module_name: self.module.clone(),
+            interface_name: self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceKey {
+    pub module_name: String,
+    pub interface_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImplementationKey {
+    pub interface: InterfaceKey,
+    pub module_name: String,
+    pub type_name: String,
+}
+
+impl ImplementationKey {
+    /// The name methods of the implementation are defined under. Starting
+    /// with an uppercase letter, it can't clash with any other function.
+    pub fn method_name(&self, method: &str) -> String {
+        if self.module_name.is_empty() {
+            format!(
+                "{}_{}_{method}",
+                self.interface.interface_name, self.type_name
+            )
+        } else {
+            format!(
+                "{}_{}_{}_{method}",
+                self.interface.interface_name,
+                self.module_name.replace('/', "_"),
+                self.type_name
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImplementationConstructor {
+    pub location: Span,
+    pub module: String,
+    /// Interfaces each argument of the type must implement for the
+    /// implementation to apply, e.g. `a: Ord` for `impl<a: Ord> Ord<List<a>>`.
+    pub arguments: Vec<Vec<InterfaceKey>>,
 }
 
 #[derive(Debug, Clone)]
@@ -652,6 +754,15 @@ pub struct TypeConstructor {
     pub tipo: Arc<Type>,
 }
 
+impl TypeConstructor {
+    pub fn definition_location(&self) -> DefinitionLocation<'_> {
+        DefinitionLocation {
+            module: Some(self.module.as_str()),
+            span: self.location,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AccessorsMap {
     pub public: bool,
@@ -691,8 +802,10 @@ pub enum ModuleValueConstructor {
     },
 
     Constant {
-        literal: Constant,
+        literal: Option<Constant>,
         location: Span,
+        module: String,
+        name: String,
     },
 }
 
@@ -0,0 +1,482 @@
+/**
+ * @file derive.rs
+ * @author Krisna Pranav
+ * @brief Implementations generated for derived interfaces
+ * @version 0.1
+ * @date 2023-05-06
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+// Derived interfaces are implemented by plain source code, built here and
+// type-checked like any other implementation. Conversions to and from `Data`
+// are assignments from and to an annotated `Data` value, so they share their
+// encoding with every other value crossing that boundary: constructors are
+// tagged with their index in the declaration and hold their fields in order.
+// Prelude names are all accessed through `PRELUDE_ALIAS`, so that the
+// module's own definitions can't get in the way.
+
+use vec1::vec1;
+
+use crate::{
+    ast::{
+        Annotation, Arg, ArgName, AssignmentKind, BinOp, CallArg, Function, Implementation,
+        Pattern, RecordConstructor, Span, TypeParameter, UntypedClause, UntypedDataType,
+        UntypedFunction, UntypedImplementation, UntypedPattern,
+    },
+    builtins::{BOOL, DATA, EQ, ORD, ORDERING, PRELUDE_ALIAS, SERIALISE},
+    expr::UntypedExpr,
+};
+
+use super::error::Error;
+
+/// Implementations of every interface the data-type derives.
+pub fn implementations(data_type: &UntypedDataType) -> Result<Vec<UntypedImplementation>, Error> {
+    data_type
+        .derive
+        .iter()
+        .map(|interface| {
+            let derive = Derive {
+                data_type,
+                location: interface.location,
+            };
+
+            let (methods, constraints) = match (&interface.module, interface.name.as_str()) {
+                (None, EQ) => (vec![derive.equals()], vec![]),
+                (None, ORD) => (vec![derive.compare()], vec![interface.clone()]),
+                (None, SERIALISE) if !data_type.parameters.is_empty() => {
+                    return Err(Error::GenericSerialiseDerive {
+                        location: interface.location,
+                    })
+                }
+                (None, SERIALISE) => (vec![derive.to_data(), derive.expect_from_data()], vec![]),
+                _ => {
+                    return Err(Error::IllegalDerive {
+                        location: interface.location,
+                        name: interface.to_string(),
+                    })
+                }
+            };
+
+            Ok(Implementation {
+                annotation: derive.self_annotation(),
+                doc: None,
+                end_position: interface.location.end,
+                interface: interface.clone(),
+                location: interface.location,
+                methods,
+                type_parameters: data_type
+                    .parameters
+                    .iter()
+                    .map(|name| TypeParameter {
+                        location: interface.location,
+                        name: name.clone(),
+                        constraints: constraints.clone(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+struct Derive<'a> {
+    data_type: &'a UntypedDataType,
+    /// Where the interface is named in the `derive`, which every generated
+    /// node points at.
+    location: Span,
+}
+
+impl Derive<'_> {
+    /// Values are equal when their `Data` representations are.
+    fn equals(&self) -> UntypedFunction {
+        let body = UntypedExpr::BinOp {
+            location: self.location,
+            name: BinOp::Eq,
+            left: self.var("left").into(),
+            right: self.var("right").into(),
+        };
+
+        self.method(
+            "equals",
+            vec![
+                self.argument("left", self.self_annotation()),
+                self.argument("right", self.self_annotation()),
+            ],
+            self.prelude_annotation(BOOL),
+            body,
+        )
+    }
+
+    /// Values are ordered by the index of their constructor, then by their
+    /// fields from first to last.
+    fn compare(&self) -> UntypedFunction {
+        let constructors = &self.data_type.constructors;
+
+        let by_fields = if constructors.iter().all(|c| c.arguments.is_empty()) {
+            self.prelude_var("Equal")
+        } else {
+            let clauses = constructors
+                .iter()
+                .map(|constructor| {
+                    if constructor.arguments.is_empty() {
+                        return self.clause(
+                            self.constructor_pattern(constructor, "left"),
+                            self.prelude_var("Equal"),
+                        );
+                    }
+
+                    let mut right_clauses = vec![self.clause(
+                        self.constructor_pattern(constructor, "right"),
+                        self.compare_fields(constructor.arguments.len()),
+                    )];
+
+                    // Constructors are known to be the same by now.
+                    if constructors.len() > 1 {
+                        right_clauses.push(self.clause(
+                            Pattern::Discard {
+                                name: "_".to_string(),
+                                location: self.location,
+                            },
+                            self.prelude_var("Equal"),
+                        ));
+                    }
+
+                    self.clause(
+                        self.constructor_pattern(constructor, "left"),
+                        self.when(self.var("right"), right_clauses),
+                    )
+                })
+                .collect();
+
+            self.when(self.var("left"), clauses)
+        };
+
+        let body = if constructors.len() > 1 {
+            let compared = self.call(
+                self.prelude_var("compare"),
+                vec![self.index("left"), self.index("right")],
+            );
+
+            self.then_compare(compared, by_fields)
+        } else {
+            by_fields
+        };
+
+        self.method(
+            "compare",
+            vec![
+                self.argument("left", self.self_annotation()),
+                self.argument("right", self.self_annotation()),
+            ],
+            self.prelude_annotation(ORDERING),
+            body,
+        )
+    }
+
+    /// `when compare(left_0, right_0) is { Equal -> ..., ordering -> ordering }`
+    /// down to the last field.
+    fn compare_fields(&self, arity: usize) -> UntypedExpr {
+        (0..arity)
+            .rev()
+            .fold(None, |rest, field| {
+                let compared = self.call(
+                    self.prelude_var("compare"),
+                    vec![
+                        self.var(&format!("left_{field}")),
+                        self.var(&format!("right_{field}")),
+                    ],
+                );
+
+                Some(match rest {
+                    None => compared,
+                    Some(rest) => self.then_compare(compared, rest),
+                })
+            })
+            .unwrap_or_else(|| self.prelude_var("Equal"))
+    }
+
+    /// `when compared is { Equal -> rest, ordering -> ordering }`
+    fn then_compare(&self, compared: UntypedExpr, rest: UntypedExpr) -> UntypedExpr {
+        self.when(
+            compared,
+            vec![
+                self.clause(self.prelude_constructor("Equal"), rest),
+                self.clause(
+                    Pattern::Var {
+                        location: self.location,
+                        name: "ordering".to_string(),
+                    },
+                    self.var("ordering"),
+                ),
+            ],
+        )
+    }
+
+    /// The index of the constructor of a value, as in
+    /// `when value is { None -> 0, Some(_) -> 1 }`.
+    fn index(&self, value: &str) -> UntypedExpr {
+        let clauses = self
+            .data_type
+            .constructors
+            .iter()
+            .enumerate()
+            .map(|(index, constructor)| {
+                let index = UntypedExpr::Int {
+                    location: self.location,
+                    value: index.to_string(),
+                };
+
+                self.clause(self.spread_pattern(constructor), index)
+            })
+            .collect();
+
+        self.when(self.var(value), clauses)
+    }
+
+    /// `let data: Data = value`
+    fn to_data(&self) -> UntypedFunction {
+        let body = UntypedExpr::Sequence {
+            location: self.location,
+            expressions: vec![
+                UntypedExpr::Assignment {
+                    location: self.location,
+                    value: self.var("value").into(),
+                    pattern: Pattern::Var {
+                        location: self.location,
+                        name: "data".to_string(),
+                    },
+                    kind: AssignmentKind::Let,
+                    annotation: Some(self.prelude_annotation(DATA)),
+                },
+                self.var("data"),
+            ],
+        };
+
+        self.method(
+            "to_data",
+            vec![self.argument("value", self.self_annotation())],
+            self.prelude_annotation(DATA),
+            body,
+        )
+    }
+
+    /// `expect value: Self = data`, which checks the constructor index and
+    /// every field along the way.
+    fn expect_from_data(&self) -> UntypedFunction {
+        let body = UntypedExpr::Sequence {
+            location: self.location,
+            expressions: vec![
+                UntypedExpr::Assignment {
+                    location: self.location,
+                    value: self.var("data").into(),
+                    pattern: Pattern::Var {
+                        location: self.location,
+                        name: "value".to_string(),
+                    },
+                    kind: AssignmentKind::Expect,
+                    annotation: Some(self.self_annotation()),
+                },
+                self.var("value"),
+            ],
+        };
+
+        self.method(
+            "from_data",
+            vec![self.argument("data", self.prelude_annotation(DATA))],
+            self.self_annotation(),
+            body,
+        )
+    }
+
+    fn method(
+        &self,
+        name: &str,
+        arguments: Vec<Arg<()>>,
+        return_annotation: Annotation,
+        body: UntypedExpr,
+    ) -> UntypedFunction {
+        Function {
+            arguments,
+            body,
+            doc: None,
+            location: self.location,
+            name: name.to_string(),
+            public: true,
+            return_annotation: Some(return_annotation),
+            return_type: (),
+            type_parameters: vec![],
+            end_position: self.location.end,
+        }
+    }
+
+    /// The data-type applied to its own parameters.
+    fn self_annotation(&self) -> Annotation {
+        Annotation::Constructor {
+            location: self.location,
+            module: None,
+            name: self.data_type.name.clone(),
+            arguments: self
+                .data_type
+                .parameters
+                .iter()
+                .map(|name| Annotation::Var {
+                    location: self.location,
+                    name: name.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn prelude_annotation(&self, name: &str) -> Annotation {
+        Annotation::Constructor {
+            location: self.location,
+            module: Some(PRELUDE_ALIAS.to_string()),
+            name: name.to_string(),
+            arguments: vec![],
+        }
+    }
+
+    fn argument(&self, name: &str, annotation: Annotation) -> Arg<()> {
+        Arg {
+            arg_name: ArgName::Named {
+                name: name.to_string(),
+                label: name.to_string(),
+                location: self.location,
+                is_validator_param: false,
+            },
+            location: self.location,
+            annotation: Some(annotation),
+            tipo: (),
+        }
+    }
+
+    fn var(&self, name: &str) -> UntypedExpr {
+        UntypedExpr::Var {
+            location: self.location,
+            name: name.to_string(),
+        }
+    }
+
+    /// `@nano.{name}`, see [`PRELUDE_ALIAS`].
+    fn prelude_var(&self, name: &str) -> UntypedExpr {
+        UntypedExpr::FieldAccess {
+            location: self.location,
+            label: name.to_string(),
+            container: self.var(PRELUDE_ALIAS).into(),
+        }
+    }
+
+    fn call(&self, fun: UntypedExpr, arguments: Vec<UntypedExpr>) -> UntypedExpr {
+        UntypedExpr::Call {
+            arguments: arguments
+                .into_iter()
+                .map(|value| CallArg {
+                    label: None,
+                    location: self.location,
+                    value,
+                })
+                .collect(),
+            fun: fun.into(),
+            location: self.location,
+        }
+    }
+
+    fn when(&self, subject: UntypedExpr, clauses: Vec<UntypedClause>) -> UntypedExpr {
+        UntypedExpr::When {
+            location: self.location,
+            subject: subject.into(),
+            clauses,
+        }
+    }
+
+    fn clause(&self, pattern: UntypedPattern, then: UntypedExpr) -> UntypedClause {
+        UntypedClause {
+            location: self.location,
+            patterns: vec1![pattern],
+            guard: None,
+            then,
+        }
+    }
+
+    /// The constructor with each of its fields bound to `{prefix}_{index}`.
+    fn constructor_pattern(
+        &self,
+        constructor: &RecordConstructor<()>,
+        prefix: &str,
+    ) -> UntypedPattern {
+        let arguments = constructor
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| CallArg {
+                label: argument.label.clone(),
+                location: self.location,
+                value: Pattern::Var {
+                    location: self.location,
+                    name: format!("{prefix}_{index}"),
+                },
+            })
+            .collect();
+
+        self.constructor(&constructor.name, arguments, false)
+    }
+
+    /// The constructor whatever its fields, as in `Some { .. }` for records
+    /// or `Some(_)` otherwise, since only records can be spread.
+    fn spread_pattern(&self, constructor: &RecordConstructor<()>) -> UntypedPattern {
+        if constructor
+            .arguments
+            .iter()
+            .any(|argument| argument.label.is_some())
+        {
+            return self.constructor(&constructor.name, vec![], true);
+        }
+
+        let arguments = constructor
+            .arguments
+            .iter()
+            .map(|_| CallArg {
+                label: None,
+                location: self.location,
+                value: Pattern::Discard {
+                    name: "_".to_string(),
+                    location: self.location,
+                },
+            })
+            .collect();
+
+        self.constructor(&constructor.name, arguments, false)
+    }
+
+    fn prelude_constructor(&self, name: &str) -> UntypedPattern {
+        Pattern::Constructor {
+            is_record: false,
+            location: self.location,
+            name: name.to_string(),
+            arguments: vec![],
+            module: Some(PRELUDE_ALIAS.to_string()),
+            constructor: (),
+            with_spread: false,
+            tipo: (),
+        }
+    }
+
+    fn constructor(
+        &self,
+        name: &str,
+        arguments: Vec<CallArg<UntypedPattern>>,
+        with_spread: bool,
+    ) -> UntypedPattern {
+        Pattern::Constructor {
+            is_record: with_spread || arguments.iter().any(|argument| argument.label.is_some()),
+            location: self.location,
+            name: name.to_string(),
+            arguments,
+            module: None,
+            constructor: (),
+            with_spread,
+            tipo: (),
+        }
+    }
+}
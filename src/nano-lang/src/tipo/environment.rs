@@ -18,11 +18,12 @@ use itertools::Itertools;
 
 use crate::{
     ast::{
-        Annotation, CallArg, DataType, Definition, Function, ModuleConstant, ModuleKind, Pattern,
-        RecordConstructor, RecordConstructorArg, Span, TypeAlias, TypedDefinition,
-        UnqualifiedImport, UntypedArg, UntypedDefinition, Use, Validator, PIPE_VARIABLE,
+        Annotation, CallArg, Constraint, DataType, Definition, Function, Implementation, Interface,
+        InterfaceMethod, ModuleConstant, ModuleKind, RecordConstructor, RecordConstructorArg, Span,
+        TypeAlias, TypeParameter, TypedDefinition, UnqualifiedImport, UntypedArg,
+        UntypedDefinition, UntypedImplementation, Use, Validator, PIPE_VARIABLE,
     },
-    builtins::{self, function, generic_var, tuple, unbound_var},
+    builtins::{function, generic_var, tuple, unbound_var, PRELUDE_ALIAS},
     tipo::fields::FieldMap,
     IdGenerator,
 };
@@ -30,8 +31,9 @@ use crate::{
 use super::{
     error::{Error, Snippet, Warning},
     hydrator::Hydrator,
-    AccessorsMap, PatternConstructor, RecordAccessor, Type, TypeConstructor, TypeInfo, TypeVar,
-    ValueConstructor, ValueConstructorVariant,
+    AccessorsMap, ImplementationConstructor, ImplementationKey, InterfaceConstructor, InterfaceKey,
+    RecordAccessor, Type, TypeConstructor, TypeInfo, TypeVar, ValueConstructor,
+    ValueConstructorVariant,
 };
 
 #[derive(Debug)]
@@ -43,6 +45,7 @@ pub struct ScopeResetData {
 pub struct Environment<'a> {
     pub accessors: HashMap<String, AccessorsMap>,
     pub current_module: &'a String,
+    pub current_package: &'a str,
 
     pub entity_usages: Vec<HashMap<String, (EntityKind, Span, bool)>>,
     pub id_gen: IdGenerator,
@@ -57,6 +60,14 @@ pub struct Environment<'a> {
 
     pub module_values: HashMap<String, ValueConstructor>,
 
+    pub module_interfaces: HashMap<String, InterfaceConstructor>,
+
+    pub module_implementations: HashMap<ImplementationKey, ImplementationConstructor>,
+
+    pub module_constraints: HashMap<String, Vec<(u64, InterfaceKey)>>,
+
+    pub module_type_parameters: HashMap<String, Vec<u64>>,
+
     previous_id: u64,
 
     pub scope: HashMap<String, ValueConstructor>,
@@ -82,6 +93,52 @@ impl<'a> Environment<'a> {
         self.scope = data.local_values;
     }
 
+    /// The interfaces the generic types of a value must implement.
+    pub fn constraints_of(&self, variant: &ValueConstructorVariant) -> Vec<(u64, InterfaceKey)> {
+        let ValueConstructorVariant::ModuleFn { name, module, .. } = variant else {
+            return vec![];
+        };
+
+        let constraints = if module == self.current_module {
+            self.module_constraints.get(name)
+        } else {
+            let module = if module.is_empty() { "nano" } else { module };
+
+            self.importable_modules
+                .get(module)
+                .and_then(|info| info.constraints.get(name))
+        };
+
+        constraints.cloned().unwrap_or_default()
+    }
+
+    /// The generic types a value is explicitly applied to, in order: those
+    /// its definition declares, followed by the others as they appear in its
+    /// type.
+    pub fn type_parameters_of(&self, variant: &ValueConstructorVariant, tipo: &Type) -> Vec<u64> {
+        let declared = match variant {
+            ValueConstructorVariant::ModuleFn { name, module, .. }
+                if module == self.current_module =>
+            {
+                self.module_type_parameters.get(name)
+            }
+            ValueConstructorVariant::ModuleFn { name, module, .. } => {
+                let module = if module.is_empty() { "nano" } else { module };
+
+                self.importable_modules
+                    .get(module)
+                    .and_then(|info| info.type_parameters.get(name))
+            }
+            _ => None,
+        };
+
+        let mut parameters = declared.cloned().unwrap_or_default();
+
+        tipo.collect_generics(&mut parameters);
+
+        parameters
+    }
+
     pub fn convert_unused_to_warnings(&mut self) {
         let unused = self
             .entity_usages
@@ -189,6 +246,7 @@ impl<'a> Environment<'a> {
                 body,
                 return_annotation,
                 return_type,
+                type_parameters,
                 end_position,
             }) => {
                 let function = self
@@ -229,6 +287,7 @@ impl<'a> Environment<'a> {
                     arguments: args,
                     return_annotation,
                     return_type,
+                    type_parameters,
                     body,
                     end_position,
                 })
@@ -239,7 +298,75 @@ impl<'a> Environment<'a> {
             | Definition::Use { .. }
             | Definition::Test { .. }
             | Definition::Validator { .. }
-            | Definition::ModuleConstant { .. }) => definition,
+            | Definition::ModuleConstant { .. }
+            | Definition::Interface { .. }
+            | Definition::Implementation { .. }) => definition,
+        }
+    }
+
+    pub fn get_implementation(
+        &self,
+        key: &ImplementationKey,
+    ) -> Option<&ImplementationConstructor> {
+        self.module_implementations.get(key).or_else(|| {
+            self.importable_modules
+                .values()
+                .find_map(|module| module.implementations.get(key))
+        })
+    }
+
+    pub fn get_interface(
+        &mut self,
+        constraint: &Constraint,
+    ) -> Result<&InterfaceConstructor, Error> {
+        let Constraint {
+            location,
+            module,
+            name,
+        } = constraint;
+
+        match module {
+            None => self
+                .module_interfaces
+                .get(name)
+                .ok_or_else(|| Error::UnknownInterface {
+                    location: *location,
+                    name: name.to_string(),
+                    interfaces: self.module_interfaces.keys().cloned().collect(),
+                }),
+
+            Some(m) => {
+                let module = self
+                    .imported_modules
+                    .get(m)
+                    .map(|(_, module)| *module)
+                    .ok_or_else(|| Error::UnknownModule {
+                        location: *location,
+                        name: m.to_string(),
+                        imported_modules: self
+                            .importable_modules
+                            .keys()
+                            .map(|t| t.to_string())
+                            .collect(),
+                    })?;
+
+                self.unused_modules.remove(m);
+
+                module
+                    .interfaces
+                    .get(name)
+                    .filter(|interface| interface.public)
+                    .ok_or_else(|| Error::UnknownInterface {
+                        location: *location,
+                        name: name.to_string(),
+                        interfaces: module
+                            .interfaces
+                            .values()
+                            .filter(|interface| interface.public)
+                            .map(|interface| interface.name.clone())
+                            .collect(),
+                    })
+            }
         }
     }
 
@@ -371,6 +498,47 @@ impl<'a> Environment<'a> {
         }
     }
 
+    /// Implementations are for a type constructor applied to distinct type
+    /// variables, e.g. `Option<a>`, and keyed by that constructor.
+    pub fn implementation_key(
+        &mut self,
+        interface: &InterfaceConstructor,
+        annotation: &Annotation,
+    ) -> Result<ImplementationKey, Error> {
+        let Annotation::Constructor { arguments, .. } = annotation else {
+            return Err(Error::IllegalImplementationType {
+                location: annotation.location(),
+            });
+        };
+
+        let mut seen = HashSet::new();
+
+        for argument in arguments {
+            match argument {
+                Annotation::Var { name, .. } if seen.insert(name) => (),
+                _ => {
+                    return Err(Error::IllegalImplementationType {
+                        location: argument.location(),
+                    })
+                }
+            }
+        }
+
+        let tipo = Hydrator::new().type_from_annotation(annotation, self)?;
+
+        match collapse_links(tipo).deref() {
+            Type::App { module, name, .. } => Ok(ImplementationKey {
+                interface: interface.key(),
+                module_name: module.clone(),
+                type_name: name.clone(),
+            }),
+
+            _ => Err(Error::IllegalImplementationType {
+                location: annotation.location(),
+            }),
+        }
+    }
+
     pub fn in_new_scope<T>(&mut self, process_scope: impl FnOnce(&mut Self) -> T) -> T {
         let initial = self.open_new_scope();
 
@@ -565,6 +733,7 @@ impl<'a> Environment<'a> {
     pub fn new(
         id_gen: IdGenerator,
         current_module: &'a String,
+        current_package: &'a str,
         importable_modules: &'a HashMap<String, TypeInfo>,
         warnings: &'a mut Vec<Warning>,
     ) -> Self {
@@ -579,7 +748,14 @@ impl<'a> Environment<'a> {
             module_types: prelude.types.clone(),
             module_types_constructors: prelude.types_constructors.clone(),
             module_values: HashMap::new(),
-            imported_modules: HashMap::new(),
+            module_interfaces: prelude.interfaces.clone(),
+            module_implementations: HashMap::new(),
+            module_constraints: HashMap::new(),
+            module_type_parameters: HashMap::new(),
+            imported_modules: HashMap::from([(
+                PRELUDE_ALIAS.to_string(),
+                (Span::empty(), prelude),
+            )]),
             unused_modules: HashMap::new(),
             unqualified_imported_names: HashMap::new(),
             accessors: prelude.accessors.clone(),
@@ -587,11 +763,65 @@ impl<'a> Environment<'a> {
             importable_modules,
             imported_types: HashSet::new(),
             current_module,
+            current_package,
             warnings,
             entity_usages: vec![HashMap::new()],
         }
     }
 
+    /// Resolves the module targeted by an import. Modules are referred to
+    /// either by their name alone, or prefixed with the package exporting them
+    /// (e.g. `owner/repo/module`). Modules under an `internal` folder are only
+    /// visible from within their own package.
+    pub fn find_module(&self, module: &[String], location: Span) -> Result<&'a TypeInfo, Error> {
+        let name = module.join("/");
+
+        let unknown_module = || Error::UnknownModule {
+            location,
+            name: name.clone(),
+            imported_modules: self.importable_modules.keys().cloned().collect(),
+        };
+
+        let module_info = match self.importable_modules.get(&name) {
+            Some(module_info) => module_info,
+            None if module.len() > 2 => {
+                let package = module[..2].join("/");
+
+                let module_info = self
+                    .importable_modules
+                    .get(&module[2..].join("/"))
+                    .ok_or_else(unknown_module)?;
+
+                if module_info.package != package {
+                    return Err(Error::ModuleNotInPackage {
+                        location,
+                        name: module_info.name.clone(),
+                        package,
+                        actual_package: module_info.package.clone(),
+                    });
+                }
+
+                module_info
+            }
+            None => return Err(unknown_module()),
+        };
+
+        if module_info.package != self.current_package
+            && module_info
+                .name
+                .split('/')
+                .any(|segment| segment == "internal")
+        {
+            return Err(Error::InternalModuleImported {
+                location,
+                name: module_info.name.clone(),
+                package: module_info.package.clone(),
+            });
+        }
+
+        Ok(module_info)
+    }
+
     pub fn new_generic_var(&mut self) -> Arc<Type> {
         generic_var(self.next_uid())
     }
@@ -627,21 +857,12 @@ impl<'a> Environment<'a> {
                 location,
                 ..
             }) => {
-                let name = module.join("/");
-
-                let module_info =
-                    self.importable_modules
-                        .get(&name)
-                        .ok_or_else(|| Error::UnknownModule {
-                            location: *location,
-                            name: name.clone(),
-                            imported_modules: self.imported_modules.keys().cloned().collect(),
-                        })?;
+                let module_info = self.find_module(module, *location)?;
 
                 if module_info.kind.is_validator() {
                     return Err(Error::ValidatorImported {
                         location: *location,
-                        name,
+                        name: module_info.name.clone(),
                     });
                 }
 
@@ -660,6 +881,7 @@ impl<'a> Environment<'a> {
                 {
                     let mut type_imported = false;
                     let mut value_imported = false;
+                    let mut interface_imported = false;
                     let mut variant = None;
 
                     let imported_name = as_name.as_ref().unwrap_or(name);
@@ -697,6 +919,17 @@ impl<'a> Environment<'a> {
                         type_imported = true;
                     }
 
+                    if let Some(interface) = module_info
+                        .interfaces
+                        .get(name)
+                        .filter(|interface| interface.public)
+                    {
+                        self.module_interfaces
+                            .insert(imported_name.clone(), interface.clone());
+
+                        interface_imported = true;
+                    }
+
                     if value_imported && type_imported {
                         self.init_usage(
                             imported_name.to_string(),
@@ -724,7 +957,7 @@ impl<'a> Environment<'a> {
                                 *location,
                             ),
                         };
-                    } else if !value_imported {
+                    } else if !interface_imported {
                         return Err(Error::UnknownModuleField {
                             location: *location,
                             name: name.clone(),
@@ -769,6 +1002,244 @@ impl<'a> Environment<'a> {
         }
     }
 
+    fn register_implementation(
+        &mut self,
+        Implementation {
+            annotation,
+            interface,
+            location,
+            methods,
+            type_parameters,
+            ..
+        }: &UntypedImplementation,
+        module_name: &String,
+        hydrators: &mut HashMap<String, Hydrator>,
+    ) -> Result<(), Error> {
+        let interface = self.get_interface(interface)?.clone();
+
+        let key = self.implementation_key(&interface, annotation)?;
+
+        if key.interface.module_name != *module_name && key.module_name != *module_name {
+            return Err(Error::OrphanImplementation {
+                location: *location,
+                interface: interface.name,
+                name: key.type_name,
+            });
+        }
+
+        if let Some(previous) = self.get_implementation(&key) {
+            return Err(Error::DuplicateImplementation {
+                location: *location,
+                previous_location: previous.location,
+                interface: interface.name,
+                name: key.type_name,
+            });
+        }
+
+        let Annotation::Constructor { arguments, .. } = annotation else {
+            unreachable!("implementation_key only accepts type constructors")
+        };
+
+        let mut argument_constraints = Vec::with_capacity(arguments.len());
+
+        for argument in arguments {
+            let mut interfaces = vec![];
+
+            for parameter in type_parameters {
+                if matches!(argument, Annotation::Var { name, .. } if *name == parameter.name) {
+                    for constraint in &parameter.constraints {
+                        interfaces.push(self.get_interface(constraint)?.key());
+                    }
+                }
+            }
+
+            argument_constraints.push(interfaces);
+        }
+
+        self.module_implementations.insert(
+            key.clone(),
+            ImplementationConstructor {
+                location: *location,
+                module: module_name.clone(),
+                arguments: argument_constraints,
+            },
+        );
+
+        for (index, method) in methods.iter().enumerate() {
+            if !interface.methods.contains_key(&method.name) {
+                return Err(Error::UnknownInterfaceMethod {
+                    location: method.location,
+                    name: method.name.clone(),
+                    interface: interface.name,
+                    methods: interface.methods.keys().cloned().collect(),
+                });
+            }
+
+            if let Some(previous) = methods[..index].iter().find(|m| m.name == method.name) {
+                return Err(Error::DuplicateName {
+                    location: method.location,
+                    previous_location: previous.location,
+                    name: method.name.clone(),
+                });
+            }
+
+            let type_parameters: Vec<TypeParameter> = type_parameters
+                .iter()
+                .chain(&method.type_parameters)
+                .cloned()
+                .collect();
+
+            self.register_function(
+                &key.method_name(&method.name),
+                &type_parameters,
+                &method.arguments,
+                &method.return_annotation,
+                module_name,
+                hydrators,
+                &method.location,
+            )?;
+        }
+
+        let missing: Vec<String> = interface
+            .methods
+            .keys()
+            .filter(|name| !methods.iter().any(|method| method.name == **name))
+            .cloned()
+            .sorted()
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(Error::MissingInterfaceMethods {
+                location: *location,
+                interface: interface.name,
+                missing,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Registers an interface and its methods, which are values like any
+    /// function but resolved to an implementation once their type is known.
+    pub fn register_interface(
+        &mut self,
+        def: &'a UntypedDefinition,
+        module_name: &String,
+        names: &mut HashMap<&'a str, &'a Span>,
+    ) -> Result<(), Error> {
+        let Definition::Interface(Interface {
+            location,
+            methods,
+            name,
+            parameter,
+            public,
+            ..
+        }) = def
+        else {
+            return Ok(());
+        };
+
+        if let Some(previous) = self
+            .module_interfaces
+            .get(name)
+            .filter(|previous| previous.module == *module_name)
+        {
+            return Err(Error::DuplicateName {
+                location: *location,
+                previous_location: previous.location,
+                name: name.clone(),
+            });
+        }
+
+        let mut hydrator = Hydrator::new();
+
+        let parameter = hydrator
+            .type_from_annotation(
+                &Annotation::Var {
+                    location: *location,
+                    name: parameter.clone(),
+                },
+                self,
+            )?
+            .get_generic()
+            .expect("Type parameters are hydrated to generic types");
+
+        let key = InterfaceKey {
+            module_name: module_name.clone(),
+            interface_name: name.clone(),
+        };
+
+        let mut method_types = HashMap::new();
+
+        for InterfaceMethod {
+            arguments,
+            location,
+            name: method,
+            return_annotation,
+            ..
+        } in methods
+        {
+            assert_unique_value_name(names, method, location)?;
+
+            let scope = hydrator.open_new_scope();
+
+            let mut field_map = FieldMap::new(arguments.len(), true);
+
+            let mut arg_types = Vec::with_capacity(arguments.len());
+
+            for (i, arg) in arguments.iter().enumerate() {
+                field_map.insert(arg.arg_name.get_label().clone(), i, &arg.location)?;
+
+                arg_types.push(hydrator.type_from_option_annotation(&arg.annotation, self)?);
+            }
+
+            let return_type = hydrator.type_from_annotation(return_annotation, self)?;
+
+            hydrator.close_scope(scope);
+
+            let tipo = generalise(function(arg_types, return_type), 0);
+
+            let variant = ValueConstructorVariant::ModuleFn {
+                name: method.clone(),
+                field_map: field_map.into_option(),
+                module: module_name.to_owned(),
+                arity: arguments.len(),
+                location: *location,
+                builtin: None,
+            };
+
+            self.insert_variable(method.clone(), variant.clone(), tipo.clone());
+
+            self.insert_module_value(
+                method,
+                ValueConstructor {
+                    public: *public,
+                    variant,
+                    tipo: tipo.clone(),
+                },
+            );
+
+            self.module_constraints
+                .insert(method.clone(), vec![(parameter, key.clone())]);
+
+            method_types.insert(method.clone(), tipo);
+        }
+
+        self.module_interfaces.insert(
+            name.clone(),
+            InterfaceConstructor {
+                public: *public,
+                location: *location,
+                module: module_name.clone(),
+                name: name.clone(),
+                parameter,
+                methods: method_types,
+            },
+        );
+
+        Ok(())
+    }
+
     pub fn register_types(
         &mut self,
         definitions: Vec<&'a UntypedDefinition>,
@@ -822,7 +1293,9 @@ impl<'a> Environment<'a> {
                             | Definition::Validator { .. }
                             | Definition::Use { .. }
                             | Definition::ModuleConstant { .. }
-                            | Definition::Test { .. } => None,
+                            | Definition::Test { .. }
+                            | Definition::Interface { .. }
+                            | Definition::Implementation { .. } => None,
                         })
                         .collect::<Vec<Snippet>>();
 
@@ -928,7 +1401,9 @@ impl<'a> Environment<'a> {
             | Definition::Validator { .. }
             | Definition::Test { .. }
             | Definition::Use { .. }
-            | Definition::ModuleConstant { .. } => {}
+            | Definition::ModuleConstant { .. }
+            | Definition::Interface { .. }
+            | Definition::Implementation { .. } => {}
         }
 
         Ok(())
@@ -937,16 +1412,14 @@ impl<'a> Environment<'a> {
     #[allow(clippy::too_many_arguments)]
     fn register_function(
         &mut self,
-        name: &'a str,
+        name: &str,
+        type_parameters: &[TypeParameter],
         arguments: &[UntypedArg],
         return_annotation: &Option<Annotation>,
         module_name: &String,
         hydrators: &mut HashMap<String, Hydrator>,
-        names: &mut HashMap<&'a str, &'a Span>,
-        location: &'a Span,
+        location: &Span,
     ) -> Result<(), Error> {
-        assert_unique_value_name(names, name, location)?;
-
         self.ungeneralised_functions.insert(name.to_string());
 
         let mut field_map = FieldMap::new(arguments.len(), true);
@@ -958,6 +1431,44 @@ impl<'a> Environment<'a> {
 
         let mut hydrator = Hydrator::new();
 
+        let mut constraints = vec![];
+        let mut parameters = vec![];
+
+        for TypeParameter {
+            location,
+            name,
+            constraints: interfaces,
+        } in type_parameters
+        {
+            let tipo = hydrator.type_from_annotation(
+                &Annotation::Var {
+                    location: *location,
+                    name: name.clone(),
+                },
+                self,
+            )?;
+
+            let id = tipo
+                .get_generic()
+                .expect("Type parameters are hydrated to generic types");
+
+            parameters.push(id);
+
+            for interface in interfaces {
+                constraints.push((id, self.get_interface(interface)?.key()));
+            }
+        }
+
+        if !constraints.is_empty() {
+            self.module_constraints
+                .insert(name.to_string(), constraints);
+        }
+
+        if !parameters.is_empty() {
+            self.module_type_parameters
+                .insert(name.to_string(), parameters);
+        }
+
         let mut arg_types = Vec::new();
 
         for arg in arguments {
@@ -998,13 +1509,15 @@ impl<'a> Environment<'a> {
     ) -> Result<(), Error> {
         match def {
             Definition::Fn(fun) => {
+                assert_unique_value_name(names, &fun.name, &fun.location)?;
+
                 self.register_function(
                     &fun.name,
+                    &fun.type_parameters,
                     &fun.arguments,
                     &fun.return_annotation,
                     module_name,
                     hydrators,
-                    names,
                     &fun.location,
                 )?;
 
@@ -1015,41 +1528,27 @@ impl<'a> Environment<'a> {
 
             Definition::Validator(Validator {
                 fun,
-                other_fun,
+                other_funs,
                 params,
                 ..
             }) if kind.is_validator() => {
-                let temp_params: Vec<UntypedArg> = params
-                    .iter()
-                    .cloned()
-                    .chain(fun.arguments.clone())
-                    .collect();
-
-                self.register_function(
-                    &fun.name,
-                    &temp_params,
-                    &fun.return_annotation,
-                    module_name,
-                    hydrators,
-                    names,
-                    &fun.location,
-                )?;
-
-                if let Some(other) = other_fun {
+                for fun in std::iter::once(fun).chain(other_funs) {
                     let temp_params: Vec<UntypedArg> = params
                         .iter()
                         .cloned()
-                        .chain(other.arguments.clone())
+                        .chain(fun.arguments.clone())
                         .collect();
 
+                    assert_unique_value_name(names, &fun.name, &fun.location)?;
+
                     self.register_function(
-                        &other.name,
+                        &fun.name,
+                        &fun.type_parameters,
                         &temp_params,
-                        &other.return_annotation,
+                        &fun.return_annotation,
                         module_name,
                         hydrators,
-                        names,
-                        &other.location,
+                        &fun.location,
                     )?;
                 }
             }
@@ -1060,23 +1559,22 @@ impl<'a> Environment<'a> {
                 })
             }
 
-            Definition::Test(Function { name, location, .. }) => {
-                assert_unique_value_name(names, name, location)?;
-                hydrators.insert(name.clone(), Hydrator::new());
-                let arg_types = vec![];
-                let return_type = builtins::bool();
-                self.insert_variable(
-                    name.clone(),
-                    ValueConstructorVariant::ModuleFn {
-                        name: name.clone(),
-                        field_map: None,
-                        module: module_name.to_owned(),
-                        arity: 0,
-                        location: *location,
-                        builtin: None,
-                    },
-                    function(arg_types, return_type),
-                );
+            Definition::Test(test) => {
+                assert_unique_value_name(names, &test.name, &test.location)?;
+
+                self.register_function(
+                    &test.name,
+                    &test.type_parameters,
+                    &test.arguments,
+                    &test.return_annotation,
+                    module_name,
+                    hydrators,
+                    &test.location,
+                )?;
+            }
+
+            Definition::Implementation(implementation) => {
+                self.register_implementation(implementation, module_name, hydrators)?;
             }
 
             Definition::DataType(DataType {
@@ -1178,11 +1676,41 @@ impl<'a> Environment<'a> {
                 assert_unique_const_name(names, name, location)?;
             }
 
-            Definition::Use { .. } | Definition::TypeAlias { .. } => {}
+            Definition::Use { .. }
+            | Definition::TypeAlias { .. }
+            | Definition::Interface { .. } => {}
         }
         Ok(())
     }
 
+    /// The interfaces each type parameter of a function must implement.
+    pub fn type_parameter_constraints(
+        &mut self,
+        type_parameters: &[TypeParameter],
+    ) -> Result<HashMap<String, Vec<InterfaceKey>>, Error> {
+        let mut constraints = HashMap::new();
+
+        for TypeParameter {
+            name,
+            constraints: interfaces,
+            ..
+        } in type_parameters
+        {
+            let mut keys = Vec::with_capacity(interfaces.len());
+
+            for interface in interfaces {
+                keys.push(self.get_interface(interface)?.key());
+            }
+
+            constraints
+                .entry(name.clone())
+                .or_insert_with(Vec::new)
+                .extend(keys);
+        }
+
+        Ok(constraints)
+    }
+
     #[allow(clippy::only_used_in_recursion)]
     pub fn unify(
         &mut self,
@@ -1341,124 +1869,6 @@ impl<'a> Environment<'a> {
         }
     }
 
-    pub fn check_exhaustiveness(
-        &mut self,
-        patterns: Vec<Pattern<PatternConstructor, Arc<Type>>>,
-        value_typ: Arc<Type>,
-        location: Span,
-    ) -> Result<(), Vec<String>> {
-        match &*value_typ {
-            Type::App {
-                name: type_name,
-                module,
-                ..
-            } => {
-                let m = if module.is_empty() || module == self.current_module {
-                    None
-                } else {
-                    Some(module.clone())
-                };
-
-                if type_name == "List" && module.is_empty() {
-                    return self.check_list_pattern_exhaustiveness(patterns);
-                }
-
-                if let Ok(constructors) = self.get_constructors_for_type(&m, type_name, location) {
-                    let mut unmatched_constructors: HashSet<String> =
-                        constructors.iter().cloned().collect();
-
-                    for p in &patterns {
-                        let mut pattern = p;
-                        while let Pattern::Assign {
-                            pattern: assign_pattern,
-                            ..
-                        } = pattern
-                        {
-                            pattern = assign_pattern;
-                        }
-
-                        match pattern {
-                            Pattern::Discard { .. } => return Ok(()),
-                            Pattern::Var { .. } => return Ok(()),
-                            Pattern::Constructor {
-                                constructor: PatternConstructor::Record { name, .. },
-                                ..
-                            } => {
-                                unmatched_constructors.remove(name);
-                            }
-                            _ => return Ok(()),
-                        }
-                    }
-
-                    if !unmatched_constructors.is_empty() {
-                        return Err(unmatched_constructors.into_iter().sorted().collect());
-                    }
-                }
-                Ok(())
-            }
-            _ => Ok(()),
-        }
-    }
-
-    pub fn check_list_pattern_exhaustiveness(
-        &mut self,
-        patterns: Vec<Pattern<PatternConstructor, Arc<Type>>>,
-    ) -> Result<(), Vec<String>> {
-        let mut cover_empty = false;
-        let mut cover_tail = false;
-
-        let patterns = patterns.iter().map(|p| match p {
-            Pattern::Assign { pattern, .. } => pattern,
-            _ => p,
-        });
-
-        for p in patterns {
-            match p {
-                Pattern::Var { .. } => {
-                    cover_empty = true;
-                    cover_tail = true;
-                }
-                Pattern::Discard { .. } => {
-                    cover_empty = true;
-                    cover_tail = true;
-                }
-                Pattern::List { elements, tail, .. } => {
-                    if elements.is_empty() {
-                        cover_empty = true;
-                    }
-                    match tail {
-                        None => {}
-                        Some(p) => match **p {
-                            Pattern::Discard { .. } => {
-                                cover_tail = true;
-                            }
-                            Pattern::Var { .. } => {
-                                cover_tail = true;
-                            }
-                            _ => {
-                                unreachable!()
-                            }
-                        },
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        if cover_empty && cover_tail {
-            Ok(())
-        } else {
-            let mut missing = vec![];
-            if !cover_empty {
-                missing.push("[]".to_owned());
-            }
-            if !cover_tail {
-                missing.push("[_, ..]".to_owned());
-            }
-            Err(missing)
-        }
-    }
-
     pub fn get_constructors_for_type(
         &mut self,
         full_module_name: &Option<String>,
@@ -1683,6 +2093,57 @@ fn get_compatible_record_fields<A>(
     compatible
 }
 
+/// Makes generic the unbound types of a local function which aren't `free`,
+/// that is mentioned by other values in scope.
+pub(crate) fn generalise_local(t: &Type, free: &HashSet<u64>) {
+    match t {
+        Type::Var { tipo } => {
+            let generic = match tipo.borrow().deref() {
+                TypeVar::Unbound { id } if !free.contains(id) => Some(*id),
+                TypeVar::Link { tipo } => {
+                    generalise_local(tipo, free);
+                    None
+                }
+                TypeVar::Unbound { .. } | TypeVar::Generic { .. } => None,
+            };
+
+            if let Some(id) = generic {
+                *tipo.borrow_mut() = TypeVar::Generic { id };
+            }
+        }
+
+        Type::App { args, .. } => args.iter().for_each(|arg| generalise_local(arg, free)),
+
+        Type::Fn { args, ret } => {
+            args.iter().for_each(|arg| generalise_local(arg, free));
+            generalise_local(ret, free);
+        }
+
+        Type::Tuple { elems } => elems.iter().for_each(|elem| generalise_local(elem, free)),
+    }
+}
+
+pub(crate) fn collect_unbound(t: &Type, ids: &mut HashSet<u64>) {
+    match t {
+        Type::Var { tipo } => match tipo.borrow().deref() {
+            TypeVar::Unbound { id } => {
+                ids.insert(*id);
+            }
+            TypeVar::Link { tipo } => collect_unbound(tipo, ids),
+            TypeVar::Generic { .. } => (),
+        },
+
+        Type::App { args, .. } => args.iter().for_each(|arg| collect_unbound(arg, ids)),
+
+        Type::Fn { args, ret } => {
+            args.iter().for_each(|arg| collect_unbound(arg, ids));
+            collect_unbound(ret, ids);
+        }
+
+        Type::Tuple { elems } => elems.iter().for_each(|elem| collect_unbound(elem, ids)),
+    }
+}
+
 #[allow(clippy::only_used_in_recursion)]
 pub(crate) fn generalise(t: Arc<Type>, ctx_level: usize) -> Arc<Type> {
     match t.deref() {
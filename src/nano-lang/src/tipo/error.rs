@@ -1,6 +1,6 @@
 use super::Type;
 use crate::{
-    ast::{Annotation, BinOp, CallArg, Span, UntypedPattern},
+    ast::{Annotation, BinOp, CallArg, Purpose, Span, UntypedPattern},
     expr::{self, UntypedExpr},
     format::Formatter,
     levenshtein,
@@ -379,6 +379,23 @@ Perhaps, try the following:
         given: usize,
     },
 
+    #[error(
+        "I discovered an attempt to import an internal module of another package: '{}'\n",
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::import"))]
+    #[diagnostic(help(
+        "Modules living under an {} folder are private to the package '{}'. Only its other modules can import them.",
+        "internal".if_supports_color(Stdout, |s| s.purple()),
+        package.if_supports_color(Stdout, |s| s.bright_blue())
+    ))]
+    InternalModuleImported {
+        #[label]
+        location: Span,
+        name: String,
+        package: String,
+    },
+
     #[error(
       "Found the module '{}' contains the keyword '{}', which is forbidden.\n",
       name.if_supports_color(Stdout, |s| s.purple()),
@@ -421,14 +438,42 @@ If you really meant to return that last expression, try to replace it with the f
         name: String,
     },
 
-    #[error("found a multi-validator where both take the same number of arguments.\n")]
+    #[error(
+        "found a module '{}' that isn't exported by the package '{}'.\n",
+        name.if_supports_color(Stdout, |s| s.purple()),
+        package.if_supports_color(Stdout, |s| s.bright_blue())
+    )]
+    #[diagnostic(code("unknown::module"))]
+    #[diagnostic(help(
+        "This module is provided by '{}'. Did you mean to import it from there?",
+        actual_package.if_supports_color(Stdout, |s| s.bright_blue())
+    ))]
+    ModuleNotInPackage {
+        #[label]
+        location: Span,
+        name: String,
+        package: String,
+        actual_package: String,
+    },
+
+    #[error("found a multi-validator with two handlers for the same purpose.\n")]
     #[diagnostic(code("illegal::multi_validator"))]
-    #[diagnostic(help("Multi-validators cannot take the same number of arguments. One must take 3 arguments\nand the other must take 2 arguments. Both of these take {} arguments.", count.to_string().purple()))]
-    MultiValidatorEqualArgs {
-        #[label("{} here", count)]
+    #[diagnostic(help("Each function of a multi-validator handles a different purpose. Functions named spend, mint,\nwithdraw or certify handle that purpose; others handle spending when they take 3 arguments\nand minting when they take 2. Both of these handle {}.", purpose.to_string().purple()))]
+    MultiValidatorSamePurpose {
+        #[label("{} here", purpose)]
         location: Span,
-        #[label("and {} here", count)]
+        #[label("and {} here", purpose)]
         other_location: Span,
+        purpose: Purpose,
+    },
+
+    #[error("found a {} handler taking {} arguments.\n", purpose.to_string().purple(), count)]
+    #[diagnostic(code("illegal::multi_validator"))]
+    #[diagnostic(help("In a multi-validator, functions handling spending take a datum, a redeemer and the script\ncontext; the others only take a redeemer and the script context. This one should take {}\narguments.", purpose.arity().to_string().purple()))]
+    IncorrectHandlerArity {
+        #[label("{} arguments", count)]
+        location: Span,
+        purpose: Purpose,
         count: usize,
     },
 
@@ -887,6 +932,249 @@ The best thing to do from here is to remove it."#))]
         #[label("{} arguments", if *count < 2 { "not enough" } else { "too many" })]
         location: Span,
     },
+
+    #[error("I can't generate values for this test argument.\n")]
+    #[diagnostic(code("illegal::test_argument"))]
+    #[diagnostic(help(r#"Arguments of a test are generated from their type, which must be known and serialisable to data. I found:
+
+╰─▶ {signature}
+
+Annotate the argument with a concrete type that isn't a function."#
+        , signature = tipo.to_pretty(0).if_supports_color(Stdout, |s| s.red())
+    ))]
+    IllegalTestArgument {
+        #[label("cannot be generated")]
+        location: Span,
+        tipo: Arc<Type>,
+    },
+
+    #[error(
+        "found a reference to an unknown interface: '{}'.\n",
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("unknown::interface"))]
+    #[diagnostic(help(
+        "{}",
+        suggest_neighbor(name, interfaces.iter(), "Did you forget to import it?")
+    ))]
+    UnknownInterface {
+        #[label("unknown interface")]
+        location: Span,
+        name: String,
+        interfaces: Vec<String>,
+    },
+
+    #[error(
+        "'{}' isn't a method of the interface '{}'.\n",
+        name.if_supports_color(Stdout, |s| s.purple()),
+        interface.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("unknown::interface_method"))]
+    #[diagnostic(help(
+        "{}",
+        suggest_neighbor(name, methods.iter(), "Implementations may only define the methods of their interface.")
+    ))]
+    UnknownInterfaceMethod {
+        #[label("unknown method")]
+        location: Span,
+        name: String,
+        interface: String,
+        methods: Vec<String>,
+    },
+
+    #[error(
+        "This implementation of '{}' is missing some methods.\n",
+        interface.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("missing::interface_method"))]
+    #[diagnostic(help(
+        "Implementations must define every method of their interface. Missing:\n\n{}",
+        missing
+            .iter()
+            .map(|name| format!("─▶ {}", name.if_supports_color(Stdout, |s| s.yellow())))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))]
+    MissingInterfaceMethods {
+        #[label("incomplete implementation")]
+        location: Span,
+        interface: String,
+        missing: Vec<String>,
+    },
+
+    #[error(
+        "Found two implementations of '{}' for '{}'.\n",
+        interface.if_supports_color(Stdout, |s| s.purple()),
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("duplicate::implementation"))]
+    #[diagnostic(help("A type can implement an interface only once."))]
+    DuplicateImplementation {
+        #[label("implemented again here")]
+        location: Span,
+        #[label("implemented here")]
+        previous_location: Span,
+        interface: String,
+        name: String,
+    },
+
+    #[error(
+        "Can't implement '{}' for '{}' here.\n",
+        interface.if_supports_color(Stdout, |s| s.purple()),
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::implementation"))]
+    #[diagnostic(help(
+        "An interface can only be implemented in the module declaring the interface, or in the module declaring the type. That way, there's never more than one implementation to pick from."
+    ))]
+    OrphanImplementation {
+        #[label("implemented outside of both modules")]
+        location: Span,
+        interface: String,
+        name: String,
+    },
+
+    #[error("Can't implement an interface for this type.\n")]
+    #[diagnostic(code("illegal::implementation_type"))]
+    #[diagnostic(help(
+        "Interfaces are implemented for a data-type applied to distinct type variables, e.g. {} or {}.",
+        "Int".if_supports_color(Stdout, |s| s.green()),
+        "Option<a>".if_supports_color(Stdout, |s| s.green())
+    ))]
+    IllegalImplementationType {
+        #[label("expected a data-type")]
+        location: Span,
+    },
+
+    #[error(
+        "Couldn't find an implementation of '{}' for '{}'.\n",
+        interface.if_supports_color(Stdout, |s| s.purple()),
+        tipo.to_pretty_with_names(rigid_type_names.clone(), 0).if_supports_color(Stdout, |s| s.red())
+    )]
+    #[diagnostic(code("missing::implementation"))]
+    #[diagnostic(help("{}", suggest_implementation(interface, tipo, rigid_type_names)))]
+    MissingImplementation {
+        #[label("requires {interface}")]
+        location: Span,
+        interface: String,
+        tipo: Arc<Type>,
+        rigid_type_names: HashMap<u64, String>,
+    },
+
+    #[error(
+        "I don't know how to derive '{}'.\n",
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::derive"))]
+    #[diagnostic(help(
+        "Only the prelude interfaces {}, {} and {} can be derived. Other interfaces must be implemented by hand.",
+        "Eq".if_supports_color(Stdout, |s| s.green()),
+        "Ord".if_supports_color(Stdout, |s| s.green()),
+        "Serialise".if_supports_color(Stdout, |s| s.green())
+    ))]
+    IllegalDerive {
+        #[label("not derivable")]
+        location: Span,
+        name: String,
+    },
+
+    #[error(
+        "I can't derive '{}' for a type with parameters.\n",
+        "Serialise".if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::derive"))]
+    #[diagnostic(help(
+        "Values of a type parameter have no known representation as {}. Implement {} by hand for each instance of the type instead.",
+        "Data".if_supports_color(Stdout, |s| s.green()),
+        "Serialise".if_supports_color(Stdout, |s| s.green())
+    ))]
+    GenericSerialiseDerive {
+        #[label("parameterised")]
+        location: Span,
+    },
+
+    #[error("Found a constant whose value is a function.\n")]
+    #[diagnostic(code("illegal::function_in_constant"))]
+    #[diagnostic(help("Constants are evaluated at compile-time and end up as plain values in the generated code. Define a function instead."))]
+    FunctionTypeInConstant {
+        #[label("function")]
+        location: Span,
+    },
+
+    #[error(
+        "I can't use the computed constant '{}' in a clause guard.\n",
+        name.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::clause_guard"))]
+    #[diagnostic(help("Only constants defined by a literal can appear in a clause guard. Bind the value to a local variable before the {} and use that instead.", "when".if_supports_color(Stdout, |s| s.yellow())))]
+    ComputedConstantInClauseGuard {
+        #[label("computed")]
+        location: Span,
+        name: String,
+    },
+
+    #[error("Found a rational literal with a zero denominator.\n")]
+    #[diagnostic(code("illegal::rational"))]
+    #[diagnostic(help("A fraction such as {} needs a denominator other than zero.", "3/4r".if_supports_color(Stdout, |s| s.purple())))]
+    ZeroDenominator {
+        #[label("divides by zero")]
+        location: Span,
+    },
+
+    #[error(
+        "I found a range pattern that matches nothing: {}.\n",
+        format!("{start}..{end}").if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("illegal::range"))]
+    #[diagnostic(help("Ranges include both of their bounds, and start from the smaller one. Swap the bounds around."))]
+    EmptyIntRange {
+        #[label("empty")]
+        location: Span,
+        start: String,
+        end: String,
+    },
+
+    #[error(
+        "I found a literal pattern in {} assignment.\n",
+        if *is_let { "a let" } else { "an expect" }
+    )]
+    #[diagnostic(code("illegal::literal_pattern"))]
+    #[diagnostic(help(
+        "Literals can only be matched in the clauses of a {}. Compare the value with {} instead, for example {}.",
+        "when".if_supports_color(Stdout, |s| s.yellow()),
+        "==".if_supports_color(Stdout, |s| s.yellow()),
+        "expect True = s == @\"abc\"".if_supports_color(Stdout, |s| s.purple())
+    ))]
+    LiteralPatternInAssignment {
+        #[label("literal")]
+        location: Span,
+        is_let: bool,
+    },
+
+    #[error("I found explicit types applied to something other than a named value.\n")]
+    #[diagnostic(code("illegal::type_application"))]
+    #[diagnostic(help(
+        "Only functions and constants referred to by name, such as {}, can be given type arguments.",
+        "identity<Int>".if_supports_color(Stdout, |s| s.purple())
+    ))]
+    IllegalTypeApplication {
+        #[label("unnamed value")]
+        location: Span,
+    },
+
+    #[error(
+        "I found {} type arguments where at most {} were expected.\n",
+        given.if_supports_color(Stdout, |s| s.purple()),
+        expected.if_supports_color(Stdout, |s| s.purple())
+    )]
+    #[diagnostic(code("arity::type_application"))]
+    #[diagnostic(help("Type arguments are given in the order the type parameters are declared, followed by the other generic types in the order they appear in the value's type."))]
+    TooManyTypeArguments {
+        #[label("unexpected")]
+        location: Span,
+        expected: usize,
+        given: usize,
+    },
 }
 
 impl Error {
@@ -1196,6 +1484,37 @@ fn suggest_import_constructor() -> String {
     }
 }
 
+fn suggest_implementation(
+    interface: &str,
+    tipo: &Arc<Type>,
+    rigid_type_names: &HashMap<u64, String>,
+) -> String {
+    let signature = tipo.to_pretty_with_names(rigid_type_names.clone(), 0);
+
+    match tipo.get_generic() {
+        Some(_) => formatdoc! {
+            r#"Generic types are only known to implement the interfaces they're constrained with. Declare the constraint ahead of the function's arguments:
+
+                 {keyword_fn} foo<{signature}: {interface}>(..)
+            "#
+            , keyword_fn = "fn".if_supports_color(Stdout, |s| s.yellow())
+            , signature = signature.if_supports_color(Stdout, |s| s.bright_blue())
+            , interface = interface.if_supports_color(Stdout, |s| s.purple())
+        },
+        None => formatdoc! {
+            r#"Either annotate the expression with a type implementing '{interface}', or implement it:
+
+                 {keyword_impl} {interface}<{signature}> {{
+                   // ...
+                 }}
+            "#
+            , keyword_impl = "impl".if_supports_color(Stdout, |s| s.yellow())
+            , signature = signature.if_supports_color(Stdout, |s| s.bright_blue())
+            , interface = interface.if_supports_color(Stdout, |s| s.purple())
+        },
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, thiserror::Error, Diagnostic)]
 pub enum Warning {
     #[error("found a record update using all fields; thus redundant.\n")]
@@ -1289,6 +1608,16 @@ pub enum Warning {
         tipo: Arc<Type>,
     },
 
+    #[error("found an unreachable clause.\n")]
+    #[diagnostic(help(
+        "Every value this pattern matches is already matched by the clauses above it, so its body never runs. You can remove it, or move it up if it was meant to be matched first."
+    ))]
+    #[diagnostic(code("unreachable::clause"))]
+    UnreachableClause {
+        #[label("unreachable")]
+        location: Span,
+    },
+
     #[error(
         "I discovered an unused constructor: '{}'.\n",
         name.if_supports_color(Stderr, |s| s.purple())
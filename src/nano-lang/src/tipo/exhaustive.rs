@@ -0,0 +1,548 @@
+/*
+ * @file exhaustive.rs
+ * @author Krisna Pranav
+ * @brief Exhaustiveness and redundancy of patterns
+ * @version 0.1
+ * @date 2023-05-06
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+// Exhaustiveness and redundancy checking of patterns, after Maranget's
+// "Warnings for pattern matching". Clauses form the rows of a matrix whose
+// columns get specialised one constructor at a time. Types only matter to
+// know every constructor of a column, which is found from the constructor
+// patterns appearing in it: a column made only of wildcards needs no more.
+// Integers are ranges, split at the bounds of every range in their column so
+// that each piece is either inside or outside of any other range.
+
+use std::{collections::HashMap, sync::Arc};
+
+use itertools::Itertools;
+
+use crate::{
+    ast::{Span, TypedPattern},
+    tipo::{PatternConstructor, Type, TypeVar, ValueConstructorVariant},
+};
+
+use super::environment::Environment;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Constructor {
+    Record {
+        name: String,
+        /// The module and name of the type the constructor belongs to.
+        tipo: (String, String),
+    },
+    Tuple(usize),
+    Nil,
+    Cons,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    Wildcard,
+    /// Literals have infinitely many siblings, so they're never exhaustive
+    /// on their own.
+    Literal(String),
+    /// Integers from the first bound to the second, both included. Like
+    /// literals, they never cover every integer.
+    Range(i128, i128),
+    Constructor(Constructor, Vec<Pattern>),
+}
+
+type Row = Vec<Pattern>;
+
+/// Every constructor of the types seen in the patterns, with their arity.
+type Signatures = HashMap<(String, String), Vec<(String, usize)>>;
+
+/// Every constructor of a type, with their arity.
+type Siblings = Vec<(Constructor, usize)>;
+
+impl Environment<'_> {
+    /// Examples of the values none of the patterns match, if any.
+    pub fn check_exhaustiveness(
+        &mut self,
+        patterns: &[TypedPattern],
+        location: Span,
+    ) -> Result<(), Vec<String>> {
+        let rows = patterns
+            .iter()
+            .map(|pattern| vec![lower(pattern)])
+            .collect::<Vec<_>>();
+
+        let signatures = self.signatures(&rows, location);
+
+        let missing = missing(&signatures, &rows, 1);
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing
+                .into_iter()
+                .map(|mut witness| witness.remove(0).to_string())
+                .unique()
+                .collect())
+        }
+    }
+
+    /// The index of every clause that can't match anything the clauses
+    /// before it don't. Guarded clauses can be unreachable, but don't cover
+    /// anything for the clauses after them.
+    pub fn unreachable_clauses(
+        &mut self,
+        clauses: &[(&TypedPattern, bool)],
+        location: Span,
+    ) -> Vec<usize> {
+        let rows = clauses
+            .iter()
+            .map(|(pattern, _)| vec![lower(pattern)])
+            .collect::<Vec<_>>();
+
+        let signatures = self.signatures(&rows, location);
+
+        let mut covered: Vec<Row> = vec![];
+        let mut unreachable = vec![];
+
+        for (index, (row, (_, guarded))) in rows.into_iter().zip(clauses).enumerate() {
+            if !is_useful(&signatures, &covered, &row) {
+                unreachable.push(index);
+            }
+
+            if !guarded {
+                covered.push(row);
+            }
+        }
+
+        unreachable
+    }
+
+    fn signatures(&mut self, rows: &[Row], location: Span) -> Signatures {
+        let mut types = vec![];
+
+        for pattern in rows.iter().flatten() {
+            pattern.collect_types(&mut types);
+        }
+
+        types
+            .into_iter()
+            .unique()
+            .filter_map(|tipo| {
+                let constructors = self.constructors_of(&tipo, location)?;
+
+                Some((tipo, constructors))
+            })
+            .collect()
+    }
+
+    fn constructors_of(
+        &mut self,
+        (module, name): &(String, String),
+        location: Span,
+    ) -> Option<Vec<(String, usize)>> {
+        let full_module_name = if module.is_empty() || module == self.current_module {
+            None
+        } else {
+            Some(module.clone())
+        };
+
+        let constructors = self
+            .get_constructors_for_type(&full_module_name, name, location)
+            .ok()?
+            .clone();
+
+        constructors
+            .into_iter()
+            .map(|constructor| {
+                let value = if module.is_empty() {
+                    self.importable_modules
+                        .get("nano")
+                        .and_then(|prelude| prelude.values.get(&constructor))
+                } else if module == self.current_module {
+                    self.module_values
+                        .get(&constructor)
+                        .or_else(|| self.scope.get(&constructor))
+                } else {
+                    self.importable_modules
+                        .get(module)
+                        .and_then(|info| info.values.get(&constructor))
+                };
+
+                match value.map(|value| &value.variant) {
+                    Some(ValueConstructorVariant::Record { arity, .. }) => {
+                        Some((constructor, *arity))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn lower(pattern: &TypedPattern) -> Pattern {
+    match pattern {
+        TypedPattern::Var { .. } | TypedPattern::Discard { .. } => Pattern::Wildcard,
+
+        TypedPattern::Assign { pattern, .. } => lower(pattern),
+
+        TypedPattern::Int { value, .. } => range(value, value),
+
+        TypedPattern::IntRange { start, end, .. } => range(start, end),
+
+        TypedPattern::String { value, .. } => Pattern::Literal(format!("@{value:?}")),
+
+        TypedPattern::ByteArray { value, .. } => {
+            Pattern::Literal(format!("#\"{}\"", hex::encode(value)))
+        }
+
+        TypedPattern::Tuple { elems, .. } => Pattern::Constructor(
+            Constructor::Tuple(elems.len()),
+            elems.iter().map(lower).collect(),
+        ),
+
+        TypedPattern::List { elements, tail, .. } => {
+            let tail = match tail {
+                Some(tail) => lower(tail),
+                None => Pattern::Constructor(Constructor::Nil, vec![]),
+            };
+
+            elements.iter().rev().fold(tail, |tail, element| {
+                Pattern::Constructor(Constructor::Cons, vec![lower(element), tail])
+            })
+        }
+
+        TypedPattern::Constructor {
+            constructor: PatternConstructor::Record { name, .. },
+            arguments,
+            tipo,
+            ..
+        } => match type_name(tipo) {
+            Some(tipo) => Pattern::Constructor(
+                Constructor::Record {
+                    name: name.clone(),
+                    tipo,
+                },
+                arguments
+                    .iter()
+                    .map(|argument| lower(&argument.value))
+                    .collect(),
+            ),
+            // Without a type to find the other constructors from, the pattern
+            // can only be assumed to match anything.
+            None => Pattern::Wildcard,
+        },
+    }
+}
+
+/// Integers too large for a range are only ever equal to themselves.
+fn range(start: &str, end: &str) -> Pattern {
+    let start = start.replace('_', "");
+    let end = end.replace('_', "");
+
+    match (start.parse(), end.parse()) {
+        (Ok(start), Ok(end)) => Pattern::Range(start, end),
+        _ if start == end => Pattern::Literal(start),
+        _ => Pattern::Literal(format!("{start}..{end}")),
+    }
+}
+
+/// The module and name of the type built by a constructor.
+fn type_name(tipo: &Arc<Type>) -> Option<(String, String)> {
+    match tipo.as_ref() {
+        Type::App { module, name, .. } => Some((module.clone(), name.clone())),
+        Type::Fn { ret, .. } => type_name(ret),
+        Type::Var { tipo } => match &*tipo.borrow() {
+            TypeVar::Link { tipo } => type_name(tipo),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Pattern {
+    fn collect_types(&self, types: &mut Vec<(String, String)>) {
+        if let Pattern::Constructor(constructor, arguments) = self {
+            if let Constructor::Record { tipo, .. } = constructor {
+                types.push(tipo.clone());
+            }
+
+            for argument in arguments {
+                argument.collect_types(types);
+            }
+        }
+    }
+}
+
+impl Constructor {
+    /// Every constructor of the type this one belongs to, with their arity.
+    fn siblings(&self, signatures: &Signatures) -> Option<Siblings> {
+        match self {
+            Constructor::Tuple(arity) => Some(vec![(Constructor::Tuple(*arity), *arity)]),
+
+            Constructor::Nil | Constructor::Cons => {
+                Some(vec![(Constructor::Nil, 0), (Constructor::Cons, 2)])
+            }
+
+            Constructor::Record { tipo, .. } => signatures.get(tipo).map(|constructors| {
+                constructors
+                    .iter()
+                    .map(|(name, arity)| {
+                        let constructor = Constructor::Record {
+                            name: name.clone(),
+                            tipo: tipo.clone(),
+                        };
+
+                        (constructor, *arity)
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// The constructors heading the first column, along with every constructor of
+/// their type when known. Columns holding literals are left unknown.
+fn head_constructors(
+    signatures: &Signatures,
+    rows: &[Row],
+) -> Option<(Vec<Constructor>, Siblings)> {
+    let mut heads = vec![];
+
+    for row in rows {
+        match &row[0] {
+            Pattern::Wildcard => (),
+            Pattern::Literal(_) | Pattern::Range(..) => return None,
+            Pattern::Constructor(constructor, _) => {
+                if !heads.contains(constructor) {
+                    heads.push(constructor.clone());
+                }
+            }
+        }
+    }
+
+    let siblings = heads.first()?.siblings(signatures)?;
+
+    Some((heads, siblings))
+}
+
+fn specialise(rows: &[Row], constructor: &Constructor, arity: usize) -> Vec<Row> {
+    rows.iter()
+        .filter_map(|row| {
+            let head = match &row[0] {
+                Pattern::Wildcard => vec![Pattern::Wildcard; arity],
+                Pattern::Constructor(other, arguments) if other == constructor => arguments.clone(),
+                Pattern::Constructor(..) | Pattern::Literal(_) | Pattern::Range(..) => return None,
+            };
+
+            Some(head.into_iter().chain(row[1..].iter().cloned()).collect())
+        })
+        .collect()
+}
+
+fn specialise_literal(rows: &[Row], literal: &str) -> Vec<Row> {
+    rows.iter()
+        .filter(|row| match &row[0] {
+            Pattern::Wildcard => true,
+            Pattern::Literal(other) => other == literal,
+            Pattern::Constructor(..) | Pattern::Range(..) => false,
+        })
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// Rows matching every integer of the range, which is either inside or
+/// outside of each range in the column.
+fn specialise_range(rows: &[Row], (start, end): (i128, i128)) -> Vec<Row> {
+    rows.iter()
+        .filter(|row| match &row[0] {
+            Pattern::Wildcard => true,
+            Pattern::Range(other_start, other_end) => *other_start <= start && end <= *other_end,
+            Pattern::Constructor(..) | Pattern::Literal(_) => false,
+        })
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// The range cut at the bounds of every range of the column overlapping it.
+fn split_range(rows: &[Row], (start, end): (i128, i128)) -> Vec<(i128, i128)> {
+    let mut cuts = vec![start, end.saturating_add(1)];
+
+    for row in rows {
+        if let Pattern::Range(other_start, other_end) = row[0] {
+            cuts.extend(
+                [other_start, other_end.saturating_add(1)]
+                    .into_iter()
+                    .filter(|cut| start < *cut && *cut <= end),
+            );
+        }
+    }
+
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    cuts.windows(2)
+        .map(|window| (window[0], window[1] - 1))
+        .collect()
+}
+
+/// Rows whose first column matches anything, without that column.
+fn default(rows: &[Row]) -> Vec<Row> {
+    rows.iter()
+        .filter(|row| row[0] == Pattern::Wildcard)
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// Whether some value matched by the row isn't matched by any of the rows.
+fn is_useful(signatures: &Signatures, rows: &[Row], row: &[Pattern]) -> bool {
+    let (head, rest) = match row.split_first() {
+        Some(split) => split,
+        None => return rows.is_empty(),
+    };
+
+    match head {
+        Pattern::Constructor(constructor, arguments) => {
+            let row = arguments.iter().chain(rest).cloned().collect::<Vec<_>>();
+
+            is_useful(
+                signatures,
+                &specialise(rows, constructor, arguments.len()),
+                &row,
+            )
+        }
+
+        Pattern::Literal(literal) => {
+            is_useful(signatures, &specialise_literal(rows, literal), rest)
+        }
+
+        Pattern::Range(start, end) => split_range(rows, (*start, *end))
+            .into_iter()
+            .any(|range| is_useful(signatures, &specialise_range(rows, range), rest)),
+
+        Pattern::Wildcard => match head_constructors(signatures, rows) {
+            Some((heads, siblings)) if siblings.iter().all(|(c, _)| heads.contains(c)) => {
+                siblings.iter().any(|(constructor, arity)| {
+                    let row = vec![Pattern::Wildcard; *arity]
+                        .into_iter()
+                        .chain(rest.iter().cloned())
+                        .collect::<Vec<_>>();
+
+                    is_useful(signatures, &specialise(rows, constructor, *arity), &row)
+                })
+            }
+            _ => is_useful(signatures, &default(rows), rest),
+        },
+    }
+}
+
+/// Rows of `width` patterns matching values that none of the rows match.
+fn missing(signatures: &Signatures, rows: &[Row], width: usize) -> Vec<Row> {
+    if width == 0 {
+        return if rows.is_empty() {
+            vec![vec![]]
+        } else {
+            vec![]
+        };
+    }
+
+    match head_constructors(signatures, rows) {
+        Some((heads, siblings)) if siblings.iter().all(|(c, _)| heads.contains(c)) => siblings
+            .into_iter()
+            .flat_map(|(constructor, arity)| {
+                missing(
+                    signatures,
+                    &specialise(rows, &constructor, arity),
+                    arity + width - 1,
+                )
+                .into_iter()
+                .map(move |mut witness| {
+                    let rest = witness.split_off(arity);
+
+                    let mut row = vec![Pattern::Constructor(constructor.clone(), witness)];
+
+                    row.extend(rest);
+
+                    row
+                })
+                .collect::<Vec<_>>()
+            })
+            .collect(),
+
+        signature => {
+            let witnesses = missing(signatures, &default(rows), width - 1);
+
+            // Name the constructors left out when the type is known, or else
+            // let a wildcard stand for everything the column doesn't match.
+            let heads = match signature {
+                Some((heads, siblings)) => siblings
+                    .into_iter()
+                    .filter(|(constructor, _)| !heads.contains(constructor))
+                    .map(|(constructor, arity)| {
+                        Pattern::Constructor(constructor, vec![Pattern::Wildcard; arity])
+                    })
+                    .collect(),
+                None => vec![Pattern::Wildcard],
+            };
+
+            witnesses
+                .into_iter()
+                .flat_map(|witness| {
+                    heads.iter().map(move |head| {
+                        std::iter::once(head.clone())
+                            .chain(witness.iter().cloned())
+                            .collect()
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+
+            Pattern::Literal(literal) => write!(f, "{literal}"),
+
+            Pattern::Range(start, end) if start == end => write!(f, "{start}"),
+
+            Pattern::Range(start, end) => write!(f, "{start}..{end}"),
+
+            Pattern::Constructor(Constructor::Record { name, .. }, arguments) => {
+                if arguments.is_empty() {
+                    write!(f, "{name}")
+                } else {
+                    write!(f, "{name}({})", arguments.iter().join(", "))
+                }
+            }
+
+            Pattern::Constructor(Constructor::Tuple(_), elems) => {
+                write!(f, "({})", elems.iter().join(", "))
+            }
+
+            Pattern::Constructor(Constructor::Nil | Constructor::Cons, _) => {
+                let mut elements = vec![];
+                let mut list = self;
+
+                let tail = loop {
+                    match list {
+                        Pattern::Constructor(Constructor::Cons, arguments) => {
+                            elements.push(arguments[0].to_string());
+                            list = &arguments[1];
+                        }
+                        Pattern::Constructor(Constructor::Nil, _) => break None,
+                        other => break Some(other),
+                    }
+                };
+
+                match tail {
+                    None => write!(f, "[{}]", elements.join(", ")),
+                    Some(_) if elements.is_empty() => write!(f, "_"),
+                    Some(_) => write!(f, "[{}, ..]", elements.join(", ")),
+                }
+            }
+        }
+    }
+}
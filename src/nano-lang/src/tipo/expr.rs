@@ -1,29 +1,38 @@
 use crate::ast::TypedPattern;
-use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+};
 
 use vec1::Vec1;
 
 use crate::{
     ast::{
         Annotation, Arg, ArgName, AssignmentKind, BinOp, ByteArrayFormatPreference, CallArg,
-        ClauseGuard, Constant, IfBranch, RecordUpdateSpread, Span, TraceKind, Tracing, TypedArg,
-        TypedCallArg, TypedClause, TypedClauseGuard, TypedIfBranch, TypedRecordUpdateArg, UnOp,
-        UntypedArg, UntypedClause, UntypedClauseGuard, UntypedIfBranch, UntypedPattern,
-        UntypedRecordUpdateArg,
+        ClauseGuard, Constant, IfBranch, Pattern, RecordUpdateSpread, Span, TraceKind, Tracing,
+        TypedArg, TypedCallArg, TypedClause, TypedClauseGuard, TypedIfBranch, TypedRecordUpdateArg,
+        UnOp, UntypedArg, UntypedClause, UntypedClauseGuard, UntypedIfBranch, UntypedPattern,
+        UntypedRecordUpdateArg, BACKPASS_VARIABLE,
     },
-    builtins::{bool, byte_array, function, int, list, string, tuple},
+    builtins::{self, bool, byte_array, function, generic_var, int, list, string, tuple},
     expr::{TypedExpr, UntypedExpr},
     format,
     tipo::fields::FieldMap,
 };
 
 use super::{
-    environment::{assert_no_labeled_arguments, collapse_links, EntityKind, Environment},
+    environment::{
+        assert_no_labeled_arguments, collapse_links, collect_unbound, generalise_local, EntityKind,
+        Environment,
+    },
     error::{Error, Warning},
     hydrator::Hydrator,
     pattern::PatternTyper,
     pipe::PipeTyper,
-    RecordAccessor, Type, ValueConstructor, ValueConstructorVariant,
+    ImplementationKey, InterfaceKey, RecordAccessor, Type, TypeVar, ValueConstructor,
+    ValueConstructorVariant,
 };
 
 #[derive(Debug)]
@@ -32,32 +41,107 @@ pub(crate) struct ExprTyper<'a, 'b> {
     pub(crate) tracing: Tracing,
     pub(crate) hydrator: Hydrator,
 
+    /// Interfaces the type parameters of the function being inferred are
+    /// known to implement.
+    pub(crate) constraints: HashMap<String, Vec<InterfaceKey>>,
+
+    /// Types which must implement an interface, checked once the function
+    /// has been inferred and they are fully known.
+    wanted: Vec<(Arc<Type>, InterfaceKey, Span)>,
+
     pub(crate) ungeneralised_function_used: bool,
 }
 
 impl<'a, 'b> ExprTyper<'a, 'b> {
+    /// Checks that every type an interface was wanted for has an
+    /// implementation of it, or is a type parameter constrained to it.
+    pub fn check_constraints(&mut self) -> Result<(), Error> {
+        for (tipo, interface, location) in std::mem::take(&mut self.wanted) {
+            self.check_constraint(tipo, &interface, location)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_constraint(
+        &self,
+        tipo: Arc<Type>,
+        interface: &InterfaceKey,
+        location: Span,
+    ) -> Result<(), Error> {
+        let tipo = collapse_links(tipo);
+
+        match tipo.as_ref() {
+            Type::App {
+                module, name, args, ..
+            } => {
+                let key = ImplementationKey {
+                    interface: interface.clone(),
+                    module_name: module.clone(),
+                    type_name: name.clone(),
+                };
+
+                if let Some(implementation) = self.environment.get_implementation(&key) {
+                    for (arg, interfaces) in args.iter().zip(&implementation.arguments) {
+                        for interface in interfaces {
+                            self.check_constraint(arg.clone(), interface, location)?;
+                        }
+                    }
+
+                    return Ok(());
+                }
+            }
+
+            Type::Var { tipo: var } => {
+                if let TypeVar::Generic { id } = var.borrow().deref() {
+                    let constrained = self
+                        .hydrator
+                        .rigid_names()
+                        .get(id)
+                        .and_then(|name| self.constraints.get(name))
+                        .map(|interfaces| interfaces.contains(interface))
+                        .unwrap_or(false);
+
+                    if constrained {
+                        return Ok(());
+                    }
+                }
+            }
+
+            Type::Fn { .. } | Type::Tuple { .. } => (),
+        }
+
+        Err(Error::MissingImplementation {
+            location,
+            interface: interface.interface_name.clone(),
+            tipo,
+            rigid_type_names: self.hydrator.rigid_names(),
+        })
+    }
+
     fn check_when_exhaustiveness(
         &mut self,
-        subject: &Type,
         typed_clauses: &[TypedClause],
         location: Span,
     ) -> Result<(), Vec<String>> {
-        let value_typ = collapse_links(Arc::new(subject.clone()));
+        let clauses = typed_clauses
+            .iter()
+            .map(|clause| (&clause.pattern, clause.guard.is_some()))
+            .collect::<Vec<_>>();
 
-        let mut patterns = Vec::new();
-        for clause in typed_clauses {
-            if let TypedClause {
-                guard: None,
-                pattern,
-                ..
-            } = clause
-            {
-                patterns.push(pattern.clone())
-            }
+        for index in self.environment.unreachable_clauses(&clauses, location) {
+            self.environment.warnings.push(Warning::UnreachableClause {
+                location: typed_clauses[index].pattern.location(),
+            });
         }
 
-        self.environment
-            .check_exhaustiveness(patterns, value_typ, location)
+        let patterns = clauses
+            .into_iter()
+            .filter(|(_, guarded)| !guarded)
+            .map(|(pattern, _)| pattern.clone())
+            .collect::<Vec<_>>();
+
+        self.environment.check_exhaustiveness(&patterns, location)
     }
 
     pub fn do_infer_call(
@@ -181,6 +265,12 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 location, value, ..
             } => Ok(self.infer_int(value, location)),
 
+            UntypedExpr::Rational {
+                location,
+                numerator,
+                denominator,
+            } => self.infer_rational(numerator, denominator, location),
+
             UntypedExpr::Sequence {
                 expressions,
                 location,
@@ -211,6 +301,18 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 final_else,
             } => self.infer_if(branches, *final_else, location),
 
+            // With nothing left to give the callback, a backpassing
+            // assignment is the last expression of its sequence.
+            UntypedExpr::Assignment {
+                location,
+                value,
+                kind: AssignmentKind::Backpass,
+                ..
+            } => Err(Error::LastExpressionIsAssignment {
+                location,
+                expr: *value,
+            }),
+
             UntypedExpr::Assignment {
                 location,
                 pattern,
@@ -292,6 +394,12 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             UntypedExpr::TraceIfFalse { value, location } => {
                 self.infer_trace_if_false(*value, location)
             }
+
+            UntypedExpr::TypeApplication {
+                location,
+                value,
+                arguments,
+            } => self.infer_type_application(*value, &arguments, location),
         }
     }
 
@@ -603,7 +711,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             Err(err) => match container {
                 UntypedExpr::Var { name, location, .. } => {
                     let module_access =
-                        self.infer_module_access(&name, label, &location, access_location);
+                        self.infer_module_access(&name, label, &location, access_location, &[]);
 
                     if self.environment.scope.contains_key(&name) {
                         module_access.map_err(|_| err)
@@ -622,6 +730,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         label: String,
         module_location: &Span,
         select_location: Span,
+        type_arguments: &[Annotation],
     ) -> Result<TypedExpr, Error> {
         let (module_name, constructor) = {
             let (_, module) = self
@@ -658,7 +767,18 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             (module.name.clone(), constructor.clone())
         };
 
-        let tipo = self.instantiate(constructor.tipo, &mut HashMap::new());
+        let mut ids = HashMap::new();
+
+        let tipo = self.instantiate(constructor.tipo.clone(), &mut ids);
+
+        self.apply_type_arguments(
+            &constructor.variant,
+            &constructor.tipo,
+            &ids,
+            type_arguments,
+        )?;
+
+        self.want_constraints(&constructor.variant, &ids, select_location);
 
         let constructor = match &constructor.variant {
             variant @ ValueConstructorVariant::ModuleFn { name, module, .. } => {
@@ -682,6 +802,78 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         })
     }
 
+    /// Only named values, whose generic types are known, can be applied to
+    /// types.
+    fn infer_type_application(
+        &mut self,
+        value: UntypedExpr,
+        arguments: &[Annotation],
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        match value {
+            UntypedExpr::Var { name, .. } => {
+                let constructor =
+                    self.infer_value_constructor(&None, &name, &location, arguments)?;
+
+                Ok(TypedExpr::Var {
+                    constructor,
+                    location,
+                    name,
+                })
+            }
+
+            UntypedExpr::FieldAccess {
+                label, container, ..
+            } => match *container {
+                UntypedExpr::Var {
+                    name,
+                    location: module_location,
+                } if !self.environment.scope.contains_key(&name) => {
+                    self.infer_module_access(&name, label, &module_location, location, arguments)
+                }
+                _ => Err(Error::IllegalTypeApplication { location }),
+            },
+
+            _ => Err(Error::IllegalTypeApplication { location }),
+        }
+    }
+
+    /// Unifies the instances of a value's type parameters with the types it
+    /// is explicitly applied to.
+    fn apply_type_arguments(
+        &mut self,
+        variant: &ValueConstructorVariant,
+        tipo: &Type,
+        ids: &HashMap<u64, Arc<Type>>,
+        type_arguments: &[Annotation],
+    ) -> Result<(), Error> {
+        if type_arguments.is_empty() {
+            return Ok(());
+        }
+
+        let parameters = self.environment.type_parameters_of(variant, tipo);
+
+        if type_arguments.len() > parameters.len() {
+            return Err(Error::TooManyTypeArguments {
+                location: type_arguments[parameters.len()].location(),
+                expected: parameters.len(),
+                given: type_arguments.len(),
+            });
+        }
+
+        for (id, annotation) in parameters.iter().zip(type_arguments) {
+            let given = self
+                .type_from_annotation(annotation)
+                .map(|t| self.instantiate(t, &mut HashMap::new()))?;
+
+            if let Some(instance) = ids.get(id) {
+                self.unify(instance.clone(), given, annotation.location(), false)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn infer_record_access(
         &mut self,
         record: UntypedExpr,
@@ -852,13 +1044,28 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             )?
         };
 
+        // Literals are only matched by `when` clauses.
+        if let Some(literal) = pattern.find_literal() {
+            return Err(Error::LiteralPatternInAssignment {
+                location: literal.location(),
+                is_let: kind.is_let(),
+            });
+        }
+
+        // Functions bound by `let` are generalised so that, like functions
+        // defined at the top-level, each use can instantiate them afresh.
+        if let (AssignmentKind::Let, Pattern::Var { name, .. }, TypedExpr::Fn { .. }) =
+            (kind, &pattern, &typed_value)
+        {
+            self.generalise_local_function(name, &value_typ);
+        }
+
         match kind {
-            AssignmentKind::Let => {
-                if let Err(unmatched) = self.environment.check_exhaustiveness(
-                    vec![pattern.clone()],
-                    collapse_links(value_typ.clone()),
-                    location,
-                ) {
+            AssignmentKind::Let | AssignmentKind::Backpass => {
+                if let Err(unmatched) = self
+                    .environment
+                    .check_exhaustiveness(std::slice::from_ref(&pattern), location)
+                {
                     return Err(Error::NotExhaustivePatternMatch {
                         location,
                         unmatched,
@@ -870,11 +1077,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             AssignmentKind::Expect => {
                 let is_exaustive_pattern = self
                     .environment
-                    .check_exhaustiveness(
-                        vec![pattern.clone()],
-                        collapse_links(value_typ.clone()),
-                        location,
-                    )
+                    .check_exhaustiveness(std::slice::from_ref(&pattern), location)
                     .is_ok();
 
                 if !value_is_data && !value_typ.is_list() && is_exaustive_pattern {
@@ -908,6 +1111,24 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         })
     }
 
+    /// Makes generic the types of a local function which nothing else in
+    /// scope, nor any pending constraint, depends on.
+    fn generalise_local_function(&self, name: &str, tipo: &Type) {
+        let mut free = HashSet::new();
+
+        for (other, constructor) in &self.environment.scope {
+            if other != name {
+                collect_unbound(&constructor.tipo, &mut free);
+            }
+        }
+
+        for (wanted, _, _) in &self.wanted {
+            collect_unbound(wanted, &mut free);
+        }
+
+        generalise_local(tipo, &free);
+    }
+
     fn infer_call(
         &mut self,
         fun: UntypedExpr,
@@ -1002,7 +1223,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     fn infer_clause_guard(&mut self, guard: UntypedClauseGuard) -> Result<TypedClauseGuard, Error> {
         match guard {
             ClauseGuard::Var { location, name, .. } => {
-                let constructor = self.infer_value_constructor(&None, &name, &location)?;
+                let constructor = self.infer_value_constructor(&None, &name, &location, &[])?;
 
                 match &constructor.variant {
                     ValueConstructorVariant::LocalVariable { .. } => (),
@@ -1013,7 +1234,10 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                     }
 
                     ValueConstructorVariant::ModuleConstant { literal, .. } => {
-                        return Ok(ClauseGuard::Constant(literal.clone()))
+                        return match literal {
+                            Some(literal) => Ok(ClauseGuard::Constant(literal.clone())),
+                            None => Err(Error::ComputedConstantInClauseGuard { location, name }),
+                        };
                     }
                 };
 
@@ -1199,9 +1423,21 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 })
             }
 
-            ClauseGuard::Constant(constant) => {
-                self.infer_const(&None, constant).map(ClauseGuard::Constant)
+            ClauseGuard::Constant(Constant::ByteArray {
+                location,
+                bytes,
+                preferred_format,
+            }) => {
+                let _ = self.infer_bytearray(bytes.clone(), preferred_format, location)?;
+
+                Ok(ClauseGuard::Constant(Constant::ByteArray {
+                    location,
+                    bytes,
+                    preferred_format,
+                }))
             }
+
+            ClauseGuard::Constant(constant) => Ok(ClauseGuard::Constant(constant)),
         }
     }
 
@@ -1226,33 +1462,14 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         Ok(typed_patterns)
     }
 
+    /// Infers the value of a module constant, which can be anything but a
+    /// function since it gets evaluated at compile-time.
     pub fn infer_const(
         &mut self,
         annotation: &Option<Annotation>,
-        value: Constant,
-    ) -> Result<Constant, Error> {
-        let inferred = match value {
-            Constant::Int {
-                location, value, ..
-            } => Ok(Constant::Int { location, value }),
-
-            Constant::String {
-                location, value, ..
-            } => Ok(Constant::String { location, value }),
-
-            Constant::ByteArray {
-                location,
-                bytes,
-                preferred_format,
-            } => {
-                let _ = self.infer_bytearray(bytes.clone(), preferred_format, location)?;
-                Ok(Constant::ByteArray {
-                    location,
-                    bytes,
-                    preferred_format,
-                })
-            }
-        }?;
+        value: UntypedExpr,
+    ) -> Result<TypedExpr, Error> {
+        let inferred = self.infer(value)?;
 
         if let Some(ann) = annotation {
             let const_ann = self.type_from_annotation(ann)?;
@@ -1265,6 +1482,14 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             )?;
         };
 
+        self.check_constraints()?;
+
+        if inferred.tipo().is_function() {
+            return Err(Error::FunctionTypeInConstant {
+                location: inferred.location(),
+            });
+        }
+
         Ok(inferred)
     }
 
@@ -1440,6 +1665,40 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         }
     }
 
+    /// Literals small enough are put in lowest terms here, larger ones are
+    /// left for the prelude `rational` function to normalise when the program
+    /// runs.
+    fn infer_rational(
+        &mut self,
+        numerator: String,
+        denominator: String,
+        location: Span,
+    ) -> Result<TypedExpr, Error> {
+        if denominator.chars().all(|c| c == '0') {
+            return Err(Error::ZeroDenominator { location });
+        }
+
+        match (numerator.parse::<u128>(), denominator.parse::<u128>()) {
+            (Ok(n), Ok(d)) => {
+                let (mut a, mut b) = (n, d);
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+
+                Ok(builtins::rational_constructor(
+                    location,
+                    self.infer_int((n / a).to_string(), location),
+                    self.infer_int((d / a).to_string(), location),
+                ))
+            }
+            _ => Ok(builtins::rational_normalised(
+                location,
+                self.infer_int(numerator, location),
+                self.infer_int(denominator, location),
+            )),
+        }
+    }
+
     fn infer_list(
         &mut self,
         elements: Vec<UntypedExpr>,
@@ -1501,6 +1760,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     }
 
     fn infer_seq(&mut self, location: Span, untyped: Vec<UntypedExpr>) -> Result<TypedExpr, Error> {
+        let untyped = desugar_backpassing(untyped);
+
         let sequence = self.in_new_scope(|scope| {
             let count = untyped.len();
 
@@ -1650,6 +1911,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         module: &Option<String>,
         name: &str,
         location: &Span,
+        type_arguments: &[Annotation],
     ) -> Result<ValueConstructor, Error> {
         let constructor = match module {
             None => {
@@ -1713,7 +1975,15 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             tipo,
         } = constructor;
 
-        let tipo = self.instantiate(tipo, &mut HashMap::new());
+        let mut ids = HashMap::new();
+
+        let instance = self.instantiate(tipo.clone(), &mut ids);
+
+        self.apply_type_arguments(&variant, &tipo, &ids, type_arguments)?;
+
+        self.want_constraints(&variant, &ids, *location);
+
+        let tipo = instance;
 
         Ok(ValueConstructor {
             public,
@@ -1723,7 +1993,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     }
 
     fn infer_var(&mut self, name: String, location: Span) -> Result<TypedExpr, Error> {
-        let constructor = self.infer_value_constructor(&None, &name, &location)?;
+        let constructor = self.infer_value_constructor(&None, &name, &location, &[])?;
 
         Ok(TypedExpr::Var {
             constructor,
@@ -1770,9 +2040,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             }
         }
 
-        if let Err(unmatched) =
-            self.check_when_exhaustiveness(&subject_type, &typed_clauses, location)
-        {
+        if let Err(unmatched) = self.check_when_exhaustiveness(&typed_clauses, location) {
             return Err(Error::NotExhaustivePatternMatch {
                 location,
                 unmatched,
@@ -1797,6 +2065,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             hydrator: Hydrator::new(),
             environment,
             tracing,
+            constraints: HashMap::new(),
+            wanted: vec![],
             ungeneralised_function_used: false,
         }
     }
@@ -1819,6 +2089,168 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     ) -> Result<(), Error> {
         self.environment.unify(t1, t2, location, allow_cast)
     }
+    /// Records the interfaces the instantiated generic types of a value must
+    /// implement.
+    fn want_constraints(
+        &mut self,
+        variant: &ValueConstructorVariant,
+        ids: &HashMap<u64, Arc<Type>>,
+        location: Span,
+    ) {
+        for (id, interface) in self.environment.constraints_of(variant) {
+            let tipo = ids.get(&id).cloned().unwrap_or_else(|| generic_var(id));
+
+            self.wanted.push((tipo, interface, location));
+        }
+    }
+}
+
+/// Turns `let pattern <- f(args)` followed by the rest of a sequence into
+/// `f(args, fn(pattern) { rest })`. Backpassing in the rest is handled when
+/// the callback's body is inferred.
+fn desugar_backpassing(mut untyped: Vec<UntypedExpr>) -> Vec<UntypedExpr> {
+    let Some(index) = untyped.iter().position(|expression| {
+        matches!(
+            expression,
+            UntypedExpr::Assignment {
+                kind: AssignmentKind::Backpass,
+                ..
+            }
+        )
+    }) else {
+        return untyped;
+    };
+
+    // A backpassing assignment with nothing after it is reported as such.
+    if index + 1 == untyped.len() {
+        return untyped;
+    }
+
+    let rest = untyped.split_off(index + 1);
+
+    let Some(UntypedExpr::Assignment {
+        location,
+        value,
+        pattern,
+        annotation,
+        ..
+    }) = untyped.pop()
+    else {
+        unreachable!("A backpassing assignment was found at this index");
+    };
+
+    let rest_location = rest[0].location().union(rest[rest.len() - 1].location());
+
+    let (argument, mut body) = match pattern {
+        Pattern::Var {
+            name,
+            location: pattern_location,
+        } => (
+            Arg {
+                arg_name: ArgName::Named {
+                    label: name.clone(),
+                    name,
+                    location: pattern_location,
+                    is_validator_param: false,
+                },
+                location: pattern_location,
+                annotation,
+                tipo: (),
+            },
+            vec![],
+        ),
+
+        Pattern::Discard {
+            name,
+            location: pattern_location,
+        } => (
+            Arg {
+                arg_name: ArgName::Discarded {
+                    label: name.clone(),
+                    name,
+                    location: pattern_location,
+                },
+                location: pattern_location,
+                annotation,
+                tipo: (),
+            },
+            vec![],
+        ),
+
+        pattern => {
+            let pattern_location = pattern.location();
+            let name = format!("{BACKPASS_VARIABLE}_{index}");
+
+            (
+                Arg {
+                    arg_name: ArgName::Named {
+                        label: name.clone(),
+                        name: name.clone(),
+                        location: pattern_location,
+                        is_validator_param: false,
+                    },
+                    location: pattern_location,
+                    annotation: None,
+                    tipo: (),
+                },
+                vec![UntypedExpr::Assignment {
+                    location,
+                    value: Box::new(UntypedExpr::Var {
+                        location: pattern_location,
+                        name,
+                    }),
+                    pattern,
+                    kind: AssignmentKind::Let,
+                    annotation,
+                }],
+            )
+        }
+    };
+
+    body.extend(rest);
+
+    let callback = UntypedExpr::Fn {
+        location: location.union(rest_location),
+        is_capture: false,
+        arguments: vec![argument],
+        body: Box::new(UntypedExpr::Sequence {
+            location: rest_location,
+            expressions: body,
+        }),
+        return_annotation: None,
+    };
+
+    let callback = CallArg {
+        label: None,
+        location: callback.location(),
+        value: callback,
+    };
+
+    let call = match *value {
+        UntypedExpr::Call {
+            location: call_location,
+            fun,
+            mut arguments,
+        } => {
+            arguments.push(callback);
+
+            UntypedExpr::Call {
+                location: call_location.union(rest_location),
+                fun,
+                arguments,
+            }
+        }
+
+        value => UntypedExpr::Call {
+            location: value.location().union(rest_location),
+            fun: Box::new(value),
+            arguments: vec![callback],
+        },
+    };
+
+    untyped.push(call);
+
+    untyped
 }
 
 fn assert_no_assignment(expr: &UntypedExpr) -> Result<(), Error> {
@@ -1836,6 +2268,7 @@ fn assert_no_assignment(expr: &UntypedExpr) -> Result<(), Error> {
         | UntypedExpr::FieldAccess { .. }
         | UntypedExpr::If { .. }
         | UntypedExpr::Int { .. }
+        | UntypedExpr::Rational { .. }
         | UntypedExpr::List { .. }
         | UntypedExpr::PipeLine { .. }
         | UntypedExpr::RecordUpdate { .. }
@@ -1846,6 +2279,7 @@ fn assert_no_assignment(expr: &UntypedExpr) -> Result<(), Error> {
         | UntypedExpr::UnOp { .. }
         | UntypedExpr::Var { .. }
         | UntypedExpr::TraceIfFalse { .. }
+        | UntypedExpr::TypeApplication { .. }
         | UntypedExpr::When { .. } => Ok(()),
     }
 }
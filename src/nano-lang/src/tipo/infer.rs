@@ -13,9 +13,10 @@ use std::collections::HashMap;
 
 use crate::{
     ast::{
-        ArgName, DataType, Definition, Function, Layer, ModuleConstant, ModuleKind,
-        RecordConstructor, RecordConstructorArg, Span, Tracing, TypeAlias, TypedDefinition,
-        TypedFunction, TypedModule, UntypedDefinition, UntypedModule, Use, Validator,
+        ArgName, DataType, Definition, Function, Implementation, Layer, ModuleConstant, ModuleKind,
+        Purpose, RecordConstructor, RecordConstructorArg, Span, Tracing, TypeAlias, TypedArg,
+        TypedDefinition, TypedFunction, TypedModule, UntypedArg, UntypedDefinition,
+        UntypedFunction, UntypedModule, Use, Validator,
     },
     builtins,
     builtins::function,
@@ -23,6 +24,7 @@ use crate::{
 };
 
 use super::{
+    derive,
     environment::{generalise, EntityKind, Environment},
     error::{Error, Warning},
     expr::ExprTyper,
@@ -44,13 +46,28 @@ impl UntypedModule {
     ) -> Result<TypedModule, Error> {
         let name = self.name.clone();
         let docs = std::mem::take(&mut self.docs);
-        let mut environment = Environment::new(id_gen.clone(), &name, modules, warnings);
+        let mut environment = Environment::new(id_gen.clone(), &name, package, modules, warnings);
 
         let mut type_names = HashMap::with_capacity(self.definitions.len());
         let mut value_names = HashMap::with_capacity(self.definitions.len());
         let mut hydrators = HashMap::with_capacity(self.definitions.len());
 
-    
+        let derived = self
+            .definitions
+            .iter()
+            .filter_map(|def| match def {
+                Definition::DataType(data_type) => Some(derive::implementations(data_type)),
+                _ => None,
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.definitions.extend(
+            derived
+                .into_iter()
+                .flatten()
+                .map(Definition::Implementation),
+        );
+
         for def in self.definitions() {
             environment.register_import(def)?;
         }
@@ -62,6 +79,10 @@ impl UntypedModule {
             &mut type_names,
         )?;
 
+        for def in self.definitions() {
+            environment.register_interface(def, &name, &mut value_names)?;
+        }
+
         for def in self.definitions() {
             environment.register_values(def, &name, &mut hydrators, &mut value_names, kind)?;
         }
@@ -79,7 +100,9 @@ impl UntypedModule {
                 | Definition::Test { .. }
                 | Definition::TypeAlias { .. }
                 | Definition::DataType { .. }
-                | Definition::Use { .. } => not_consts.push(def),
+                | Definition::Use { .. }
+                | Definition::Interface { .. }
+                | Definition::Implementation { .. } => not_consts.push(def),
             }
         }
 
@@ -102,6 +125,10 @@ impl UntypedModule {
 
         environment.module_values.retain(|_, info| info.public);
 
+        environment
+            .module_interfaces
+            .retain(|_, interface| interface.module == name);
+
         environment
             .accessors
             .retain(|_, accessors| accessors.public);
@@ -120,6 +147,10 @@ impl UntypedModule {
             module_types_constructors: types_constructors,
             module_values: values,
             accessors,
+            module_interfaces: interfaces,
+            module_implementations: implementations,
+            module_constraints: constraints,
+            module_type_parameters: type_parameters,
             ..
         } = environment;
 
@@ -134,11 +165,69 @@ impl UntypedModule {
                 types_constructors,
                 values,
                 accessors,
+                interfaces,
+                implementations,
+                constraints,
+                type_parameters,
                 kind,
                 package: package.to_string(),
             },
         })
     }
+
+    /// Values in scope at the top-level of the module once its imports, types
+    /// and definitions are registered, without inferring any definition. This
+    /// is what editors complete against.
+    pub fn scope(
+        &self,
+        id_gen: &IdGenerator,
+        kind: ModuleKind,
+        package: &str,
+        modules: &HashMap<String, TypeInfo>,
+    ) -> Result<Vec<(String, ValueConstructor)>, Error> {
+        let mut warnings = vec![];
+        let mut environment =
+            Environment::new(id_gen.clone(), &self.name, package, modules, &mut warnings);
+
+        let mut type_names = HashMap::with_capacity(self.definitions.len());
+        let mut value_names = HashMap::with_capacity(self.definitions.len());
+        let mut hydrators = HashMap::with_capacity(self.definitions.len());
+
+        for def in self.definitions() {
+            environment.register_import(def)?;
+        }
+
+        environment.register_types(
+            self.definitions.iter().collect(),
+            &self.name,
+            &mut hydrators,
+            &mut type_names,
+        )?;
+
+        for def in self.definitions() {
+            environment.register_interface(def, &self.name, &mut value_names)?;
+        }
+
+        for def in self.definitions() {
+            environment.register_values(def, &self.name, &mut hydrators, &mut value_names, kind)?;
+        }
+
+        Ok(environment
+            .local_value_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = environment.get_variable(&name)?.clone();
+
+                // Methods of implementations are only ever called through
+                // their interface.
+                if value.variant.is_implementation_method() {
+                    return None;
+                }
+
+                Some((name, value))
+            })
+            .collect())
+    }
 }
 
 fn infer_definition(
@@ -158,6 +247,7 @@ fn infer_definition(
             arguments: args,
             body,
             return_annotation,
+            type_parameters,
             end_position,
             ..
         }) => {
@@ -190,15 +280,21 @@ fn infer_definition(
                         .map(|(arg_name, tipo)| arg_name.set_type(tipo.clone()))
                         .collect();
 
+                    let constraints = environment.type_parameter_constraints(&type_parameters)?;
+
                     let mut expr_typer = ExprTyper::new(environment, tracing);
 
                     expr_typer.hydrator = hydrators
                         .remove(&name)
                         .expect("Could not find hydrator for fn");
 
+                    expr_typer.constraints = constraints;
+
                     let (args, body) =
                         expr_typer.infer_fn_with_known_types(args, body, Some(return_type))?;
 
+                    expr_typer.check_constraints()?;
+
                     let args_types = args.iter().map(|a| a.tipo.clone()).collect();
 
                     let tipo = function(args_types, body.tipo());
@@ -241,6 +337,7 @@ fn infer_definition(
                 return_type: tipo
                     .return_type()
                     .expect("Could not find return type for fn"),
+                type_parameters,
                 body,
                 end_position,
             }))
@@ -250,14 +347,10 @@ fn infer_definition(
             doc,
             location,
             end_position,
-            mut fun,
-            other_fun,
+            fun,
+            other_funs,
             params,
         }) => {
-            let params_length = params.len();
-            let temp_params = params.iter().cloned().chain(fun.arguments);
-            fun.arguments = temp_params.collect();
-
             environment.in_new_scope(|environment| {
                 let preregistered_fn = environment
                     .get_variable(&fun.name)
@@ -295,89 +388,68 @@ fn infer_definition(
                     };
                 }
 
-                let Definition::Fn(mut typed_fun) = infer_definition(
-                    Definition::Fn(fun),
+                let (typed_params, typed_fun) = infer_handler(
+                    fun,
+                    &params,
                     module_name,
                     hydrators,
                     environment,
                     tracing,
                     kind,
-                )? else {
-                    unreachable!("validator definition inferred as something other than a function?")
-                };
-
-                if !typed_fun.return_type.is_bool() {
-                    return Err(Error::ValidatorMustReturnBool {
-                        return_type: typed_fun.return_type.clone(),
-                        location: typed_fun.location,
-                    });
-                }
-
-                let typed_params = typed_fun.arguments.drain(0..params_length).collect();
-
-                if typed_fun.arguments.len() < 2 || typed_fun.arguments.len() > 3 {
-                    return Err(Error::IncorrectValidatorArity {
-                        count: typed_fun.arguments.len() as u32,
-                        location: typed_fun.location,
-                    });
-                }
-
-                let typed_other_fun = other_fun
-                    .map(|mut other| -> Result<TypedFunction, Error> {
-                        let params = params.into_iter().chain(other.arguments);
-                        other.arguments = params.collect();
-
-                        let Definition::Fn(mut other_typed_fun) = infer_definition(
-                            Definition::Fn(other),
+                )?;
+
+                let typed_other_funs = other_funs
+                    .into_iter()
+                    .map(|other| {
+                        infer_handler(
+                            other,
+                            &params,
                             module_name,
                             hydrators,
                             environment,
                             tracing,
                             kind,
-                        )? else {
-                            unreachable!(
-                                "validator definition inferred as something other than a function?"
-                            )
-                        };
-
-                        if !other_typed_fun.return_type.is_bool() {
-                            return Err(Error::ValidatorMustReturnBool {
-                                return_type: other_typed_fun.return_type.clone(),
-                                location: other_typed_fun.location,
-                            });
-                        }
+                        )
+                        .map(|(_, other_typed_fun)| other_typed_fun)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
 
-                        other_typed_fun.arguments.drain(0..params_length);
+                let validator = Validator {
+                    doc,
+                    end_position,
+                    fun: typed_fun,
+                    other_funs: typed_other_funs,
+                    location,
+                    params: typed_params,
+                };
 
-                        if other_typed_fun.arguments.len() < 2
-                            || other_typed_fun.arguments.len() > 3
-                        {
-                            return Err(Error::IncorrectValidatorArity {
-                                count: other_typed_fun.arguments.len() as u32,
-                                location: other_typed_fun.location,
+                // A multi-validator runs the handler of the purpose it is
+                // run for, which there must be one of at most.
+                if !validator.other_funs.is_empty() {
+                    let mut handled: Vec<(Purpose, &TypedFunction)> = vec![];
+
+                    for (purpose, handler) in validator.handlers() {
+                        if handler.arguments.len() != purpose.arity() {
+                            return Err(Error::IncorrectHandlerArity {
+                                purpose,
+                                count: handler.arguments.len(),
+                                location: handler.location,
                             });
                         }
 
-                        if typed_fun.arguments.len() == other_typed_fun.arguments.len() {
-                            return Err(Error::MultiValidatorEqualArgs {
-                                location: typed_fun.location,
-                                other_location: other_typed_fun.location,
-                                count: other_typed_fun.arguments.len(),
+                        if let Some((_, other)) = handled.iter().find(|(p, _)| *p == purpose) {
+                            return Err(Error::MultiValidatorSamePurpose {
+                                location: other.location,
+                                other_location: handler.location,
+                                purpose,
                             });
                         }
 
-                        Ok(other_typed_fun)
-                    })
-                    .transpose();
+                        handled.push((purpose, handler));
+                    }
+                }
 
-                Ok(Definition::Validator(Validator {
-                    doc,
-                    end_position,
-                    fun: typed_fun,
-                    other_fun: typed_other_fun?,
-                    location,
-                    params: typed_params,
-                }))
+                Ok(Definition::Validator(validator))
             })
         }
 
@@ -392,12 +464,114 @@ fn infer_definition(
             )? {
                 environment.unify(f.return_type.clone(), builtins::bool(), f.location, false)?;
 
+                for arg in f.arguments.iter() {
+                    if arg.tipo.is_function() || arg.tipo.is_generic() {
+                        return Err(Error::IllegalTestArgument {
+                            location: arg.location,
+                            tipo: arg.tipo.clone(),
+                        });
+                    }
+                }
+
                 Ok(Definition::Test(f))
             } else {
                 unreachable!("test definition inferred as something other than a function?")
             }
         }
 
+        Definition::Interface(interface) => {
+            if interface.public && kind.is_validator() {
+                environment.warnings.push(Warning::PubInValidatorModule {
+                    location: Span {
+                        start: interface.location.start,
+                        end: interface.location.start + PUB_OFFSET,
+                    },
+                })
+            }
+
+            Ok(Definition::Interface(interface))
+        }
+
+        Definition::Implementation(Implementation {
+            annotation,
+            doc,
+            end_position,
+            interface,
+            location,
+            methods,
+            type_parameters,
+        }) => {
+            let interface_constructor = environment.get_interface(&interface)?.clone();
+
+            let key = environment.implementation_key(&interface_constructor, &annotation)?;
+
+            let mut self_hydrator = Hydrator::new();
+
+            let self_type = self_hydrator.type_from_annotation(&annotation, environment)?;
+
+            let mut typed_methods = Vec::with_capacity(methods.len());
+
+            for method in methods {
+                let expected = interface_constructor
+                    .methods
+                    .get(&method.name)
+                    .expect("Implementation methods are checked on registration")
+                    .clone();
+
+                let method = Function {
+                    name: key.method_name(&method.name),
+                    public: false,
+                    type_parameters: type_parameters
+                        .iter()
+                        .cloned()
+                        .chain(method.type_parameters)
+                        .collect(),
+                    ..method
+                };
+
+                let Definition::Fn(typed_method) = infer_definition(
+                    Definition::Fn(method),
+                    module_name,
+                    hydrators,
+                    environment,
+                    tracing,
+                    kind,
+                )? else {
+                    unreachable!("implementation method inferred as something other than a function?")
+                };
+
+                // The method must be at least as general as the interface
+                // says once its parameter is the implementing type.
+                let expected = environment.instantiate(
+                    expected,
+                    &mut HashMap::from([(interface_constructor.parameter, self_type.clone())]),
+                    &self_hydrator,
+                );
+
+                let given = environment
+                    .get_variable(&typed_method.name)
+                    .expect("Could not find preregistered type for method")
+                    .tipo
+                    .clone();
+
+                let given = environment.instantiate(given, &mut HashMap::new(), &Hydrator::new());
+
+                environment.unify(expected, given, typed_method.location, false)?;
+
+                typed_methods.push(typed_method);
+            }
+
+            Ok(Definition::Implementation(Implementation {
+                annotation,
+                doc,
+                end_position,
+                interface,
+                location,
+                methods: typed_methods,
+                type_parameters,
+            }))
+        }
+
         Definition::TypeAlias(TypeAlias {
             doc,
             location,
@@ -441,6 +615,7 @@ fn infer_definition(
             name,
             parameters,
             constructors: untyped_constructors,
+            derive,
             ..
         }) => {
             if public && kind.is_validator() {
@@ -516,6 +691,7 @@ fn infer_definition(
                 .clone();
 
             let typed_data = DataType {
+                derive,
                 doc,
                 location,
                 public,
@@ -546,17 +722,7 @@ fn infer_definition(
             mut unqualified,
             ..
         }) => {
-            let name = module.join("/");
-
-            let module_info =
-                environment
-                    .importable_modules
-                    .get(&name)
-                    .ok_or_else(|| Error::UnknownModule {
-                        location,
-                        name,
-                        imported_modules: environment.imported_modules.keys().cloned().collect(),
-                    })?;
+            let module_info = environment.find_module(&module, location)?;
 
             for import in unqualified.iter_mut() {
                 if environment.imported_types.contains(import.variable_name()) {
@@ -600,8 +766,9 @@ fn infer_definition(
                 public,
                 variant: ValueConstructorVariant::ModuleConstant {
                     location,
-                    literal: typed_expr.clone(),
                     module: module_name.to_owned(),
+                    name: name.clone(),
+                    literal: typed_expr.literal(),
                 },
                 tipo: tipo.clone(),
             };
@@ -625,4 +792,47 @@ fn infer_definition(
             }))
         }
     }
+}
+
+/// Infers a function of a validator, which takes the validator's parameters
+/// ahead of its own arguments. The parameters come back apart.
+fn infer_handler(
+    mut fun: UntypedFunction,
+    params: &[UntypedArg],
+    module_name: &String,
+    hydrators: &mut HashMap<String, Hydrator>,
+    environment: &mut Environment<'_>,
+    tracing: Tracing,
+    kind: ModuleKind,
+) -> Result<(Vec<TypedArg>, TypedFunction), Error> {
+    fun.arguments = params.iter().cloned().chain(fun.arguments).collect();
+
+    let Definition::Fn(mut typed_fun) = infer_definition(
+        Definition::Fn(fun),
+        module_name,
+        hydrators,
+        environment,
+        tracing,
+        kind,
+    )? else {
+        unreachable!("validator definition inferred as something other than a function?")
+    };
+
+    if !typed_fun.return_type.is_bool() {
+        return Err(Error::ValidatorMustReturnBool {
+            return_type: typed_fun.return_type.clone(),
+            location: typed_fun.location,
+        });
+    }
+
+    let typed_params = typed_fun.arguments.drain(0..params.len()).collect();
+
+    if typed_fun.arguments.len() < 2 || typed_fun.arguments.len() > 3 {
+        return Err(Error::IncorrectValidatorArity {
+            count: typed_fun.arguments.len() as u32,
+            location: typed_fun.location,
+        });
+    }
+
+    Ok((typed_params, typed_fun))
 }
\ No newline at end of file
@@ -24,7 +24,7 @@ use super::{
 };
 use crate::{
     ast::{CallArg, Pattern, Span, TypedPattern, UntypedPattern},
-    builtins::{int, list, tuple},
+    builtins::{byte_array, int, list, string, tuple},
 };
 
 pub struct PatternTyper<'a, 'b> {
@@ -182,6 +182,53 @@ impl<'a, 'b> PatternTyper<'a, 'b> {
                 Ok(Pattern::Int { location, value })
             }
 
+            Pattern::IntRange {
+                location,
+                start,
+                end,
+            } => {
+                self.environment.unify(tipo, int(), location, false)?;
+
+                let bound = |value: &str| value.replace('_', "").parse::<i128>();
+
+                if let (Ok(low), Ok(high)) = (bound(&start), bound(&end)) {
+                    if low > high {
+                        return Err(Error::EmptyIntRange {
+                            location,
+                            start,
+                            end,
+                        });
+                    }
+                }
+
+                Ok(Pattern::IntRange {
+                    location,
+                    start,
+                    end,
+                })
+            }
+
+            Pattern::String { location, value } => {
+                self.environment.unify(tipo, string(), location, false)?;
+
+                Ok(Pattern::String { location, value })
+            }
+
+            Pattern::ByteArray {
+                location,
+                value,
+                preferred_format,
+            } => {
+                self.environment
+                    .unify(tipo, byte_array(), location, false)?;
+
+                Ok(Pattern::ByteArray {
+                    location,
+                    value,
+                    preferred_format,
+                })
+            }
+
             Pattern::List {
                 location,
                 elements,
@@ -0,0 +1,55 @@
+use crossbeam_channel::SendError;
+use lsp_server::{ExtractError, Message, Notification, ProtocolError, Request};
+use miette::Diagnostic;
+use std::{io, path::PathBuf};
+
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum Error {
+    #[error("The client broke the protocol: {0}")]
+    Protocol(Box<ProtocolError>),
+
+    #[error("I couldn't send a message to the client.")]
+    Send(Box<SendError<Message>>),
+
+    #[error("I couldn't make sense of a request: {0}")]
+    ExtractRequest(Box<ExtractError<Request>>),
+
+    #[error("I couldn't make sense of a notification: {0}")]
+    ExtractNotification(Box<ExtractError<Notification>>),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("I can only work with files on disk, but got '{uri}'.")]
+    InvalidUri { uri: String },
+
+    #[error("I couldn't turn {} into a URI.", path.display())]
+    InvalidPath { path: PathBuf },
+}
+
+impl From<ProtocolError> for Error {
+    fn from(error: ProtocolError) -> Self {
+        Error::Protocol(Box::new(error))
+    }
+}
+
+impl From<SendError<Message>> for Error {
+    fn from(error: SendError<Message>) -> Self {
+        Error::Send(Box::new(error))
+    }
+}
+
+impl From<ExtractError<Request>> for Error {
+    fn from(error: ExtractError<Request>) -> Self {
+        Error::ExtractRequest(Box::new(error))
+    }
+}
+
+impl From<ExtractError<Notification>> for Error {
+    fn from(error: ExtractError<Notification>) -> Self {
+        Error::ExtractNotification(Box::new(error))
+    }
+}
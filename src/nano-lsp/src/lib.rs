@@ -0,0 +1,62 @@
+pub mod error;
+mod line_numbers;
+pub mod server;
+mod utils;
+
+#[cfg(test)]
+mod tests;
+
+use error::Error;
+use lsp_server::Connection;
+use lsp_types::{
+    CompletionOptions, HoverProviderCapability, InitializeParams, OneOf, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+use server::Server;
+use std::env;
+
+/// Runs the language server over stdin and stdout until the client asks it to
+/// shut down.
+pub fn start() -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+
+    run(&connection)?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
+
+/// Initializes the server with the client on the other end of the connection
+/// and serves it until it asks to shut down. Projects are looked up from the
+/// root the client was opened in, or else from the current directory.
+pub fn run(connection: &Connection) -> Result<(), Error> {
+    let capabilities = serde_json::to_value(capabilities())?;
+
+    let params = connection.initialize(capabilities)?;
+
+    let params: InitializeParams = serde_json::from_value(params)?;
+
+    let root = match params.root_uri.and_then(|uri| uri.to_file_path().ok()) {
+        Some(root) => root,
+        None => env::current_dir()?,
+    };
+
+    let mut server = Server::new(root);
+
+    server.listen(connection)
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
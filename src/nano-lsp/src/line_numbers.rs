@@ -0,0 +1,63 @@
+use lsp_types::{Position, Range};
+use nano_lang::ast::Span;
+
+/// Converts between the byte offsets spans are made of and the positions
+/// editors speak in, which count lines and UTF-16 code units within them.
+pub struct LineNumbers<'a> {
+    src: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineNumbers<'a> {
+    pub fn new(src: &'a str) -> Self {
+        LineNumbers {
+            src,
+            line_starts: std::iter::once(0)
+                .chain(src.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+        }
+    }
+
+    pub fn position(&self, byte_index: usize) -> Position {
+        let byte_index = byte_index.min(self.src.len());
+
+        let line = match self.line_starts.binary_search(&byte_index) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        let character = self.src[self.line_starts[line]..byte_index]
+            .encode_utf16()
+            .count();
+
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn byte_index(&self, position: Position) -> usize {
+        let start = match self.line_starts.get(position.line as usize) {
+            Some(start) => *start,
+            None => return self.src.len(),
+        };
+
+        let mut units = 0;
+
+        for (index, c) in self.src[start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return start + index;
+            }
+
+            units += c.len_utf16();
+        }
+
+        self.src.len()
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// The range covering the whole source.
+    pub fn full_range(&self) -> Range {
+        Range::new(Position::new(0, 0), self.position(self.src.len()))
+    }
+}
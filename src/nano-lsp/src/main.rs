@@ -0,0 +1,5 @@
+fn main() -> miette::Result<()> {
+    nano_lsp::start()?;
+
+    Ok(())
+}
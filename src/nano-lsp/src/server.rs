@@ -0,0 +1,453 @@
+mod completion;
+pub mod lsp_project;
+
+use crate::{
+    error::Error,
+    line_numbers::LineNumbers,
+    utils::{path_to_uri, to_lsp_diagnostic, uri_to_path},
+};
+use lsp_project::LspProject;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics, ShowMessage,
+    },
+    request::{Completion, Formatting, GotoDefinition, HoverRequest, Request as _},
+    CompletionParams, CompletionResponse, Diagnostic, DiagnosticSeverity, DocumentFormattingParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, Location,
+    MarkupContent, MarkupKind, MessageType, PublishDiagnosticsParams, ShowMessageParams, TextEdit,
+    Url,
+};
+use nano_lang::{
+    ast::{Located, ModuleKind},
+    format, parser,
+    tipo::ValueConstructor,
+    IdGenerator,
+};
+use nano_project::{error::Warning as ProjectWarning, module::CheckedModule, paths};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub struct Server {
+    root: PathBuf,
+    compiler: LspProject,
+    /// Documents open in the editor, as last sent by the client. They may
+    /// differ from what's on disk until saved.
+    edited: HashMap<PathBuf, String>,
+    /// Documents diagnostics were last published for, which are cleared once
+    /// they compile again.
+    published_diagnostics: HashSet<Url>,
+}
+
+impl Server {
+    pub fn new(root: PathBuf) -> Self {
+        Server {
+            compiler: LspProject::new(root.clone()),
+            root,
+            edited: HashMap::new(),
+            published_diagnostics: HashSet::new(),
+        }
+    }
+
+    pub fn listen(&mut self, connection: &Connection) -> Result<(), Error> {
+        self.compile(connection)?;
+
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request)?;
+
+                    connection.sender.send(Message::Response(response))?;
+                }
+
+                Message::Notification(notification) => {
+                    self.handle_notification(connection, notification)?
+                }
+
+                Message::Response(_) => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Result<Response, Error> {
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| self.hover(params)),
+
+            GotoDefinition::METHOD => {
+                respond::<GotoDefinition>(request, |params| self.definition(params))
+            }
+
+            Formatting::METHOD => respond::<Formatting>(request, |params| self.format(params)),
+
+            Completion::METHOD => respond::<Completion>(request, |params| self.completion(params)),
+
+            method => Ok(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {method}"),
+            )),
+        }
+    }
+
+    fn handle_notification(
+        &mut self,
+        connection: &Connection,
+        notification: Notification,
+    ) -> Result<(), Error> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = cast::<DidOpenTextDocument>(notification)?;
+
+                if let Ok(path) = params.text_document.uri.to_file_path() {
+                    self.edited.insert(path, params.text_document.text);
+                }
+
+                Ok(())
+            }
+
+            DidChangeTextDocument::METHOD => {
+                let mut params = cast::<DidChangeTextDocument>(notification)?;
+
+                // Documents are synchronised in full, the last change holds
+                // the whole text.
+                if let (Ok(path), Some(change)) = (
+                    params.text_document.uri.to_file_path(),
+                    params.content_changes.pop(),
+                ) {
+                    self.edited.insert(path, change.text);
+                }
+
+                Ok(())
+            }
+
+            DidSaveTextDocument::METHOD => self.compile(connection),
+
+            DidCloseTextDocument::METHOD => {
+                let params = cast::<DidCloseTextDocument>(notification)?;
+
+                if let Ok(path) = params.text_document.uri.to_file_path() {
+                    self.edited.remove(&path);
+                }
+
+                Ok(())
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    /// Type-checks the project and publishes its errors and warnings, or
+    /// shows those that aren't about any source file.
+    fn compile(&mut self, connection: &Connection) -> Result<(), Error> {
+        let (errors, warnings) = self.compiler.compile();
+
+        let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+
+        for error in errors {
+            match (error.path(), error.src()) {
+                (Some(path), Some(src)) => diagnostics
+                    .entry(path_to_uri(&path)?)
+                    .or_default()
+                    .push(to_lsp_diagnostic(&error, &src, DiagnosticSeverity::ERROR)),
+
+                _ => {
+                    let params = ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: error.to_string(),
+                    };
+
+                    notify::<ShowMessage>(connection, params)?;
+                }
+            }
+        }
+
+        for warning in warnings {
+            if let ProjectWarning::Type { path, src, .. } = &warning {
+                diagnostics
+                    .entry(path_to_uri(path)?)
+                    .or_default()
+                    .push(to_lsp_diagnostic(
+                        &warning,
+                        src,
+                        DiagnosticSeverity::WARNING,
+                    ));
+            }
+        }
+
+        for uri in self.published_diagnostics.drain() {
+            diagnostics.entry(uri).or_default();
+        }
+
+        for (uri, diagnostics) in diagnostics {
+            if !diagnostics.is_empty() {
+                self.published_diagnostics.insert(uri.clone());
+            }
+
+            let params = PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            };
+
+            notify::<PublishDiagnostics>(connection, params)?;
+        }
+
+        Ok(())
+    }
+
+    /// The type of the expression under the cursor, as of the last time its
+    /// module compiled.
+    fn hover(&self, params: HoverParams) -> Result<Option<Hover>, Error> {
+        let position = params.text_document_position_params;
+
+        let module = match self.module(&position.text_document.uri)? {
+            Some(module) => module,
+            None => return Ok(None),
+        };
+
+        let line_numbers = LineNumbers::new(&module.code);
+
+        let expression = match module
+            .ast
+            .find_node(line_numbers.byte_index(position.position))
+        {
+            Some(Located::Expression(expression)) => expression,
+            Some(Located::Definition(_)) | None => return Ok(None),
+        };
+
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```nano\n{}\n```", expression.tipo().to_pretty(0)),
+            }),
+            range: Some(line_numbers.range(expression.type_defining_location())),
+        }))
+    }
+
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>, Error> {
+        let position = params.text_document_position_params;
+
+        let module = match self.module(&position.text_document.uri)? {
+            Some(module) => module,
+            None => return Ok(None),
+        };
+
+        let byte_index = LineNumbers::new(&module.code).byte_index(position.position);
+
+        let node = match module.ast.find_node(byte_index) {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        let location = match node.definition_location() {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        let (uri, code) = match location.module {
+            None => (position.text_document.uri, &module.code),
+            // Prelude and builtins have no source to go to.
+            Some(name) => match self.compiler.modules.get(name) {
+                Some(target) => (path_to_uri(&target.input_path)?, &target.code),
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(GotoDefinitionResponse::Scalar(Location {
+            uri,
+            range: LineNumbers::new(code).range(location.span),
+        })))
+    }
+
+    /// Formats the document as currently edited. Documents that don't parse
+    /// are left as they are.
+    fn format(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>, Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+
+        let src = self.source(&path)?;
+
+        let (module, extra) = match parser::module(&src, self.module_kind(&path)) {
+            Ok(parsed) => parsed,
+            Err(_) => return Ok(None),
+        };
+
+        let mut formatted = String::new();
+
+        format::pretty(&mut formatted, module, extra, &src);
+
+        if formatted == src {
+            return Ok(Some(vec![]));
+        }
+
+        Ok(Some(vec![TextEdit {
+            range: LineNumbers::new(&src).full_range(),
+            new_text: formatted,
+        }]))
+    }
+
+    /// Completes values selected from an imported module after a `.`, or
+    /// else local variables, imported modules and every value in scope at the
+    /// top-level of the module.
+    fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>, Error> {
+        let position = params.text_document_position;
+
+        let path = uri_to_path(&position.text_document.uri)?;
+
+        let src = self.source(&path)?;
+
+        let byte_index = LineNumbers::new(&src).byte_index(position.position);
+
+        let checked = self.compiler.module_at(&path);
+
+        if let Some(alias) = completion::module_qualifier(&src, byte_index) {
+            let items = checked
+                .and_then(|module| completion::imported_module(&module.ast, alias))
+                .and_then(|name| self.compiler.importable_modules.get(&name))
+                .map(completion::module_values)
+                .unwrap_or_default();
+
+            return Ok(Some(CompletionResponse::Array(items)));
+        }
+
+        let mut items = vec![];
+
+        if let Some(module) = checked {
+            items.extend(completion::locals(&module.ast, byte_index));
+            items.extend(completion::imports(&module.ast));
+        }
+
+        items.extend(completion::scope(&self.scope(&path, &src, checked)));
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    /// Values in scope at the top-level of a module. A document being typed
+    /// in often doesn't parse, in which case the version that last compiled is
+    /// used instead.
+    fn scope(
+        &self,
+        path: &Path,
+        src: &str,
+        checked: Option<&CheckedModule>,
+    ) -> Vec<(String, ValueConstructor)> {
+        let kind = self.module_kind(path);
+
+        let parsed = parser::module(src, kind)
+            .ok()
+            .or_else(|| checked.and_then(|module| parser::module(&module.code, kind).ok()));
+
+        let mut module = match parsed {
+            Some((module, _)) => module,
+            None => return vec![],
+        };
+
+        let package = match checked {
+            Some(checked) => {
+                module.name = checked.name.clone();
+                checked.package.clone()
+            }
+            None => {
+                module.name = self.module_name(path, kind);
+                self.compiler.package.clone()
+            }
+        };
+
+        module
+            .scope(
+                &IdGenerator::new(),
+                kind,
+                &package,
+                &self.compiler.importable_modules,
+            )
+            .unwrap_or_default()
+    }
+
+    /// The module compiled from a document, unless the document was edited
+    /// since, in which case positions in the editor no longer match it.
+    fn module(&self, uri: &Url) -> Result<Option<&CheckedModule>, Error> {
+        let path = uri_to_path(uri)?;
+
+        let edited = self.edited.get(&path);
+
+        Ok(self
+            .compiler
+            .module_at(&path)
+            .filter(|module| edited.is_none_or(|src| src == &module.code)))
+    }
+
+    fn source(&self, path: &Path) -> Result<String, Error> {
+        match self.edited.get(path) {
+            Some(src) => Ok(src.clone()),
+            None => Ok(fs::read_to_string(path)?),
+        }
+    }
+
+    fn module_kind(&self, path: &Path) -> ModuleKind {
+        if path.starts_with(self.root.join(paths::validators())) {
+            ModuleKind::Validator
+        } else {
+            ModuleKind::Lib
+        }
+    }
+
+    fn module_name(&self, path: &Path, kind: ModuleKind) -> String {
+        let dir = match kind {
+            ModuleKind::Validator => self.root.join(paths::validators()),
+            ModuleKind::Lib => self.root.join(paths::lib()),
+        };
+
+        let mut name = path.strip_prefix(&dir).unwrap_or(path).to_path_buf();
+
+        name.set_extension("");
+
+        name.to_string_lossy().replace('\\', "/")
+    }
+}
+
+fn notify<N>(connection: &Connection, params: N::Params) -> Result<(), Error>
+where
+    N: lsp_types::notification::Notification,
+{
+    let notification = Notification::new(N::METHOD.to_string(), params);
+
+    Ok(connection
+        .sender
+        .send(Message::Notification(notification))?)
+}
+
+fn cast<N>(notification: Notification) -> Result<N::Params, Error>
+where
+    N: lsp_types::notification::Notification,
+{
+    Ok(notification.extract::<N::Params>(N::METHOD)?)
+}
+
+/// Answers a request with the given handler, reporting its failure to the
+/// client rather than stopping the server.
+fn respond<R>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> Result<R::Result, Error>,
+) -> Result<Response, Error>
+where
+    R: lsp_types::request::Request,
+{
+    let (id, params) = request.extract::<R::Params>(R::METHOD)?;
+
+    Ok(match handler(params) {
+        Ok(result) => Response::new_ok(id, result),
+        Err(error) => Response::new_err(id, ErrorCode::InternalError as i32, error.to_string()),
+    })
+}
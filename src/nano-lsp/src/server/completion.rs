@@ -0,0 +1,304 @@
+use lsp_types::{CompletionItem, CompletionItemKind};
+use nano_lang::{
+    ast::{Arg, Definition, Implementation, Pattern, TypedFunction, TypedModule, Use, Validator},
+    expr::TypedExpr,
+    tipo::{Type, TypeInfo, ValueConstructor, ValueConstructorVariant},
+};
+use std::sync::Arc;
+
+/// The module alias being selected from right before the cursor, as in
+/// `list.` or `list.fo`.
+pub fn module_qualifier(src: &str, byte_index: usize) -> Option<&str> {
+    let before = src[..byte_index].trim_end_matches(is_name_char);
+
+    let before = before.strip_suffix('.')?;
+
+    let alias = &before[before.trim_end_matches(is_name_char).len()..];
+
+    if alias.starts_with(|c: char| c.is_ascii_lowercase()) {
+        Some(alias)
+    } else {
+        None
+    }
+}
+
+/// The name of the module imported under the given alias.
+pub fn imported_module(module: &TypedModule, alias: &str) -> Option<String> {
+    module
+        .definitions()
+        .find_map(|definition| match definition {
+            Definition::Use(Use {
+                module, as_name, ..
+            }) => {
+                let name = as_name.as_ref().or_else(|| module.last())?;
+
+                (name == alias).then(|| module.join("/"))
+            }
+            _ => None,
+        })
+}
+
+pub fn imports(module: &TypedModule) -> Vec<CompletionItem> {
+    module
+        .definitions()
+        .filter_map(|definition| match definition {
+            Definition::Use(Use {
+                module, as_name, ..
+            }) => Some(CompletionItem {
+                label: as_name.as_ref().or_else(|| module.last())?.clone(),
+                kind: Some(CompletionItemKind::MODULE),
+                detail: Some(module.join("/")),
+                ..Default::default()
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn module_values(info: &TypeInfo) -> Vec<CompletionItem> {
+    info.values
+        .iter()
+        .filter(|(_, value)| value.public)
+        .map(|(name, value)| value_item(name, value))
+        .collect()
+}
+
+pub fn scope(values: &[(String, ValueConstructor)]) -> Vec<CompletionItem> {
+    values
+        .iter()
+        .map(|(name, value)| value_item(name, value))
+        .collect()
+}
+
+/// Arguments and variables bound by `let` or `when` that are visible from the
+/// given position, within the definition it falls in.
+pub fn locals(module: &TypedModule, byte_index: usize) -> Vec<CompletionItem> {
+    let mut names = vec![];
+
+    for definition in module.definitions() {
+        match definition {
+            Definition::Fn(fun) | Definition::Test(fun) => {
+                function_names(fun, byte_index, &mut names);
+            }
+
+            Definition::Implementation(Implementation { methods, .. }) => {
+                for fun in methods {
+                    function_names(fun, byte_index, &mut names);
+                }
+            }
+
+            Definition::Validator(Validator {
+                params,
+                fun,
+                other_funs,
+                ..
+            }) => {
+                for fun in std::iter::once(fun).chain(other_funs) {
+                    if encloses(fun, byte_index) {
+                        argument_names(params, &mut names);
+                    }
+
+                    function_names(fun, byte_index, &mut names);
+                }
+            }
+
+            Definition::TypeAlias(_)
+            | Definition::DataType(_)
+            | Definition::Use(_)
+            | Definition::ModuleConstant(_)
+            | Definition::Interface(_) => (),
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|(name, tipo)| CompletionItem {
+            label: name,
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: tipo.map(|tipo| tipo.to_pretty(0)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+type Local = (String, Option<Arc<Type>>);
+
+fn encloses(fun: &TypedFunction, byte_index: usize) -> bool {
+    (fun.location.start..=fun.end_position).contains(&byte_index)
+}
+
+fn function_names(fun: &TypedFunction, byte_index: usize, names: &mut Vec<Local>) {
+    if encloses(fun, byte_index) {
+        argument_names(&fun.arguments, names);
+        expression_names(&fun.body, byte_index, names);
+    }
+}
+
+fn argument_names(arguments: &[Arg<Arc<Type>>], names: &mut Vec<Local>) {
+    for argument in arguments {
+        if let Some(name) = argument.arg_name.get_variable_name() {
+            names.push((name.to_string(), Some(argument.tipo.clone())));
+        }
+    }
+}
+
+fn expression_names(expression: &TypedExpr, byte_index: usize, names: &mut Vec<Local>) {
+    if !expression.location().contains(byte_index) {
+        return;
+    }
+
+    match expression {
+        // Only assignments made before the cursor are in scope.
+        TypedExpr::Sequence { expressions, .. } | TypedExpr::Pipeline { expressions, .. } => {
+            for expression in expressions {
+                if expression.location().end > byte_index {
+                    expression_names(expression, byte_index, names);
+                    break;
+                }
+
+                if let TypedExpr::Assignment { pattern, .. } = expression {
+                    pattern_names(pattern, names);
+                }
+            }
+        }
+
+        TypedExpr::Fn { args, body, .. } => {
+            argument_names(args, names);
+            expression_names(body, byte_index, names);
+        }
+
+        TypedExpr::When {
+            subject, clauses, ..
+        } => {
+            expression_names(subject, byte_index, names);
+
+            for clause in clauses {
+                if clause.location().contains(byte_index) {
+                    pattern_names(&clause.pattern, names);
+                    expression_names(&clause.then, byte_index, names);
+                }
+            }
+        }
+
+        TypedExpr::If {
+            branches,
+            final_else,
+            ..
+        } => {
+            for branch in branches.iter() {
+                expression_names(&branch.condition, byte_index, names);
+                expression_names(&branch.body, byte_index, names);
+            }
+
+            expression_names(final_else, byte_index, names);
+        }
+
+        TypedExpr::List { elements, tail, .. } => {
+            for element in elements.iter().chain(tail.as_deref()) {
+                expression_names(element, byte_index, names);
+            }
+        }
+
+        TypedExpr::Call { fun, args, .. } => {
+            expression_names(fun, byte_index, names);
+
+            for arg in args {
+                expression_names(&arg.value, byte_index, names);
+            }
+        }
+
+        TypedExpr::RecordUpdate { spread, args, .. } => {
+            expression_names(spread, byte_index, names);
+
+            for arg in args {
+                expression_names(&arg.value, byte_index, names);
+            }
+        }
+
+        TypedExpr::BinOp { left, right, .. } => {
+            expression_names(left, byte_index, names);
+            expression_names(right, byte_index, names);
+        }
+
+        TypedExpr::Trace { then, text, .. } => {
+            expression_names(text, byte_index, names);
+            expression_names(then, byte_index, names);
+        }
+
+        TypedExpr::Tuple { elems, .. } => {
+            for elem in elems {
+                expression_names(elem, byte_index, names);
+            }
+        }
+
+        TypedExpr::Assignment { value, .. } => expression_names(value, byte_index, names),
+
+        TypedExpr::RecordAccess { record, .. } => expression_names(record, byte_index, names),
+
+        TypedExpr::TupleIndex { tuple, .. } => expression_names(tuple, byte_index, names),
+
+        TypedExpr::UnOp { value, .. } => expression_names(value, byte_index, names),
+
+        TypedExpr::Int { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::ByteArray { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::ModuleSelect { .. }
+        | TypedExpr::ErrorTerm { .. } => (),
+    }
+}
+
+fn pattern_names<A, B>(pattern: &Pattern<A, B>, names: &mut Vec<Local>) {
+    match pattern {
+        Pattern::Var { name, .. } => names.push((name.clone(), None)),
+
+        Pattern::Assign { name, pattern, .. } => {
+            names.push((name.clone(), None));
+            pattern_names(pattern, names);
+        }
+
+        Pattern::List { elements, tail, .. } => {
+            for element in elements.iter().chain(tail.as_deref()) {
+                pattern_names(element, names);
+            }
+        }
+
+        Pattern::Constructor { arguments, .. } => {
+            for argument in arguments {
+                pattern_names(&argument.value, names);
+            }
+        }
+
+        Pattern::Tuple { elems, .. } => {
+            for elem in elems {
+                pattern_names(elem, names);
+            }
+        }
+
+        Pattern::Int { .. }
+        | Pattern::IntRange { .. }
+        | Pattern::String { .. }
+        | Pattern::ByteArray { .. }
+        | Pattern::Discard { .. } => (),
+    }
+}
+
+fn value_item(name: &str, value: &ValueConstructor) -> CompletionItem {
+    let kind = match value.variant {
+        ValueConstructorVariant::LocalVariable { .. } => CompletionItemKind::VARIABLE,
+        ValueConstructorVariant::ModuleConstant { .. } => CompletionItemKind::CONSTANT,
+        ValueConstructorVariant::ModuleFn { .. } => CompletionItemKind::FUNCTION,
+        ValueConstructorVariant::Record { .. } => CompletionItemKind::CONSTRUCTOR,
+    };
+
+    CompletionItem {
+        label: name.to_string(),
+        kind: Some(kind),
+        detail: Some(value.tipo.to_pretty(0)),
+        ..Default::default()
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
@@ -0,0 +1,90 @@
+use nano_lang::{ast::Tracing, builtins, tipo::TypeInfo, IdGenerator};
+use nano_project::{
+    error::{Error as ProjectError, Warning as ProjectWarning},
+    module::CheckedModule,
+    telemetry::EventListener,
+    Project,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+pub struct LspProject {
+    root: PathBuf,
+    /// Every module as of the last compilation it type-checked in, so that
+    /// navigating a module keeps working while another one is broken.
+    pub modules: HashMap<String, CheckedModule>,
+    pub importable_modules: HashMap<String, TypeInfo>,
+    /// The name of the project's own package, once its manifest was read.
+    pub package: String,
+}
+
+/// Compilation progress isn't reported to the editor.
+struct Silent;
+
+impl EventListener for Silent {}
+
+impl LspProject {
+    pub fn new(root: PathBuf) -> Self {
+        let id_gen = IdGenerator::new();
+
+        let mut importable_modules = HashMap::new();
+
+        importable_modules.insert("nano".to_string(), builtins::prelude(&id_gen));
+        importable_modules.insert("nano/builtin".to_string(), builtins::plutus(&id_gen));
+
+        LspProject {
+            root,
+            modules: HashMap::new(),
+            importable_modules,
+            package: String::new(),
+        }
+    }
+
+    /// Type-checks the project as it is on disk. A project only compiles once,
+    /// so each compilation starts from a fresh one. Modules from a successful
+    /// compilation replace every previous one, whereas a failing compilation
+    /// only updates the modules it got to check.
+    pub fn compile(&mut self) -> (Vec<ProjectError>, Vec<ProjectWarning>) {
+        let mut project = match Project::new(self.root.clone(), Silent) {
+            Ok(project) => project,
+            Err(error) => return (vec![error], vec![]),
+        };
+
+        self.package = project.config().name.to_string();
+
+        let errors = match project.check(Tracing::KeepTraces) {
+            Ok(()) => vec![],
+            Err(errors) => errors,
+        };
+
+        let modules = project
+            .modules()
+            .into_iter()
+            .map(|module| (module.name.clone(), module));
+
+        let importable_modules = project
+            .importable_modules()
+            .iter()
+            .map(|(name, info)| (name.clone(), info.clone()));
+
+        if errors.is_empty() {
+            self.modules = modules.collect();
+            self.importable_modules = importable_modules.collect();
+        } else {
+            self.modules.extend(modules);
+            self.importable_modules.extend(importable_modules);
+        }
+
+        let warnings = std::mem::take(&mut project.warnings);
+
+        (errors, warnings)
+    }
+
+    pub fn module_at(&self, path: &Path) -> Option<&CheckedModule> {
+        self.modules
+            .values()
+            .find(|module| module.input_path == path)
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::{
+    notification::{Exit, Initialized, Notification as _, PublishDiagnostics},
+    request::{Initialize, Shutdown},
+    InitializeParams, InitializeResult, InitializedParams, PublishDiagnosticsParams, Url,
+};
+use nano_project::{config::Config, package_name::PackageName};
+use tempfile::TempDir;
+
+use crate::error::Error;
+
+mod server;
+
+/// How long to wait for the server before giving up on a message.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An editor talking to a server running in a thread of its own, on a project
+/// in a directory removed once the client is dropped.
+pub struct TestClient {
+    dir: TempDir,
+    connection: Connection,
+    server: Option<JoinHandle<Result<(), Error>>>,
+    next_id: i32,
+    /// Notifications received while waiting for a response.
+    notifications: Vec<Notification>,
+}
+
+impl TestClient {
+    /// A project holding the given library modules.
+    pub fn new(modules: &[(&str, &str)]) -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create project directory");
+
+        let name = PackageName {
+            owner: "nano".to_string(),
+            repo: "test".to_string(),
+        };
+
+        Config::default(&name)
+            .save(dir.path())
+            .expect("Failed to write nano.toml");
+
+        let (connection, server) = Connection::memory();
+
+        let client = TestClient {
+            dir,
+            connection,
+            server: Some(thread::spawn(move || crate::run(&server))),
+            next_id: 0,
+            notifications: vec![],
+        };
+
+        for (name, code) in modules {
+            client.write(name, code);
+        }
+
+        client
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.root().join("lib").join(format!("{name}.nl"))
+    }
+
+    pub fn uri(&self, name: &str) -> Url {
+        Url::from_file_path(self.path(name)).unwrap()
+    }
+
+    pub fn write(&self, name: &str, code: &str) {
+        let path = self.path(name);
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        fs::write(path, code).unwrap();
+    }
+
+    pub fn initialize(&mut self) -> InitializeResult {
+        let params = InitializeParams {
+            root_uri: Some(Url::from_file_path(self.root()).unwrap()),
+            ..InitializeParams::default()
+        };
+
+        let result = self.request::<Initialize>(params);
+
+        self.notify::<Initialized>(InitializedParams {});
+
+        result
+    }
+
+    pub fn request<R>(&mut self, params: R::Params) -> R::Result
+    where
+        R: lsp_types::request::Request,
+    {
+        self.next_id += 1;
+
+        let id = RequestId::from(self.next_id);
+
+        let request = Request::new(id.clone(), R::METHOD.to_string(), params);
+
+        self.connection
+            .sender
+            .send(Message::Request(request))
+            .unwrap();
+
+        loop {
+            match self.receive() {
+                Message::Response(response) if response.id == id => {
+                    if let Some(error) = response.error {
+                        panic!("{} failed: {}", R::METHOD, error.message);
+                    }
+
+                    return serde_json::from_value(response.result.unwrap_or_default()).unwrap();
+                }
+                Message::Notification(notification) => self.notifications.push(notification),
+                message => panic!("Unexpected message: {message:?}"),
+            }
+        }
+    }
+
+    pub fn notify<N>(&self, params: N::Params)
+    where
+        N: lsp_types::notification::Notification,
+    {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+
+        self.connection
+            .sender
+            .send(Message::Notification(notification))
+            .unwrap();
+    }
+
+    /// The next diagnostics published by the server.
+    pub fn diagnostics(&mut self) -> PublishDiagnosticsParams {
+        let notification = match self
+            .notifications
+            .iter()
+            .position(|n| n.method == PublishDiagnostics::METHOD)
+        {
+            Some(index) => self.notifications.remove(index),
+            None => loop {
+                match self.receive() {
+                    Message::Notification(n) if n.method == PublishDiagnostics::METHOD => break n,
+                    Message::Notification(n) => self.notifications.push(n),
+                    message => panic!("Unexpected message: {message:?}"),
+                }
+            },
+        };
+
+        serde_json::from_value(notification.params).unwrap()
+    }
+
+    fn receive(&self) -> Message {
+        self.connection
+            .receiver
+            .recv_timeout(TIMEOUT)
+            .expect("The server didn't answer")
+    }
+}
+
+/// Shuts the server down, once it was initialized.
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        if thread::panicking() || self.next_id == 0 {
+            return;
+        }
+
+        self.request::<Shutdown>(());
+
+        self.notify::<Exit>(());
+
+        if let Some(server) = self.server.take() {
+            server.join().unwrap().unwrap();
+        }
+    }
+}
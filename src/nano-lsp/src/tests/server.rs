@@ -0,0 +1,277 @@
+use std::fs;
+
+use indoc::indoc;
+use lsp_types::{
+    notification::{DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument},
+    request::{Completion, Formatting, GotoDefinition, HoverRequest},
+    CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, DocumentFormattingParams, FormattingOptions, GotoDefinitionParams,
+    GotoDefinitionResponse, HoverContents, HoverParams, OneOf, Position,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+};
+
+use super::TestClient;
+use crate::server::lsp_project::LspProject;
+
+const NUMBERS: &str = indoc! {r#"
+    pub fn double(n: Int) -> Int {
+      n * 2
+    }
+
+    pub fn quadruple(n: Int) -> Int {
+      double(double(n))
+    }
+"#};
+
+fn at(client: &TestClient, module: &str, line: u32, character: u32) -> TextDocumentPositionParams {
+    TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier {
+            uri: client.uri(module),
+        },
+        position: Position { line, character },
+    }
+}
+
+fn hover(client: &mut TestClient, line: u32, character: u32) -> Option<String> {
+    let params = HoverParams {
+        text_document_position_params: at(client, "numbers", line, character),
+        work_done_progress_params: Default::default(),
+    };
+
+    client
+        .request::<HoverRequest>(params)
+        .map(|hover| match hover.contents {
+            HoverContents::Markup(markup) => markup.value,
+            contents => panic!("Unexpected hover contents: {contents:?}"),
+        })
+}
+
+fn open(client: &TestClient, module: &str, text: &str) {
+    client.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: client.uri(module),
+            language_id: "nano".to_string(),
+            version: 1,
+            text: text.to_string(),
+        },
+    });
+}
+
+#[test]
+fn initialize_advertises_capabilities() {
+    let mut client = TestClient::new(&[("numbers", NUMBERS)]);
+
+    let capabilities = client.initialize().capabilities;
+
+    assert!(capabilities.hover_provider.is_some());
+    assert_eq!(capabilities.definition_provider, Some(OneOf::Left(true)));
+    assert_eq!(
+        capabilities.document_formatting_provider,
+        Some(OneOf::Left(true))
+    );
+    assert_eq!(
+        capabilities.completion_provider.unwrap().trigger_characters,
+        Some(vec![".".to_string()])
+    );
+}
+
+#[test]
+fn hover_shows_the_type_under_the_cursor() {
+    let mut client = TestClient::new(&[("numbers", NUMBERS)]);
+
+    client.initialize();
+
+    let contents = hover(&mut client, 5, 3).unwrap();
+
+    assert_eq!(contents, "```nano\nfn(Int) -> Int\n```");
+
+    assert_eq!(hover(&mut client, 3, 0), None);
+}
+
+#[test]
+fn hover_ignores_documents_edited_since_they_compiled() {
+    let mut client = TestClient::new(&[("numbers", NUMBERS)]);
+
+    client.initialize();
+
+    open(&client, "numbers", NUMBERS);
+
+    assert!(hover(&mut client, 5, 3).is_some());
+
+    client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: client.uri("numbers"),
+            version: 2,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: format!("// Edited\n{NUMBERS}"),
+        }],
+    });
+
+    assert_eq!(hover(&mut client, 5, 3), None);
+}
+
+#[test]
+fn definition_goes_to_the_function() {
+    let mut client = TestClient::new(&[
+        ("numbers", NUMBERS),
+        (
+            "main",
+            "use numbers\n\npub fn eight() {\n  numbers.quadruple(2)\n}\n",
+        ),
+    ]);
+
+    client.initialize();
+
+    let params = GotoDefinitionParams {
+        text_document_position_params: at(&client, "main", 3, 12),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let location = match client.request::<GotoDefinition>(params) {
+        Some(GotoDefinitionResponse::Scalar(location)) => location,
+        response => panic!("Unexpected definition: {response:?}"),
+    };
+
+    assert_eq!(location.uri, client.uri("numbers"));
+    assert_eq!(location.range.start.line, 4);
+
+    let params = GotoDefinitionParams {
+        text_document_position_params: at(&client, "numbers", 5, 3),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let location = match client.request::<GotoDefinition>(params) {
+        Some(GotoDefinitionResponse::Scalar(location)) => location,
+        response => panic!("Unexpected definition: {response:?}"),
+    };
+
+    assert_eq!(location.uri, client.uri("numbers"));
+    assert_eq!(location.range.start.line, 0);
+}
+
+#[test]
+fn completion_offers_values_in_scope() {
+    let mut client = TestClient::new(&[
+        ("numbers", NUMBERS),
+        (
+            "main",
+            "use numbers\n\npub fn eight(n: Int) {\n  numbers.quadruple(n)\n}\n",
+        ),
+    ]);
+
+    client.initialize();
+
+    let mut complete = |line, character| {
+        let params = CompletionParams {
+            text_document_position: at(&client, "main", line, character),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+
+        let mut labels: Vec<_> = match client.request::<Completion>(params) {
+            Some(CompletionResponse::Array(items)) => {
+                items.into_iter().map(|item| item.label).collect()
+            }
+            response => panic!("Unexpected completion: {response:?}"),
+        };
+
+        labels.sort();
+
+        labels
+    };
+
+    assert_eq!(complete(3, 10), ["double", "quadruple"]);
+
+    let labels = complete(3, 2);
+
+    for label in ["eight", "n", "numbers"] {
+        assert!(labels.contains(&label.to_string()), "{label}: {labels:?}");
+    }
+}
+
+#[test]
+fn formatting_rewrites_the_edited_document() {
+    let mut client = TestClient::new(&[("numbers", NUMBERS)]);
+
+    client.initialize();
+
+    let format = |client: &mut TestClient| {
+        let params = DocumentFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: client.uri("numbers"),
+            },
+            options: FormattingOptions::default(),
+            work_done_progress_params: Default::default(),
+        };
+
+        client.request::<Formatting>(params).unwrap()
+    };
+
+    assert!(format(&mut client).is_empty());
+
+    open(&client, "numbers", "pub fn   one() ->Int { 1 }");
+
+    let edits = format(&mut client);
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "pub fn one() -> Int {\n  1\n}\n");
+    assert_eq!(edits[0].range.start, Position::new(0, 0));
+}
+
+#[test]
+fn diagnostics_are_published_and_cleared() {
+    let mut client = TestClient::new(&[(
+        "numbers",
+        "pub fn double(n: Int) -> Int {\n  n * #\"02\"\n}\n",
+    )]);
+
+    client.initialize();
+
+    let published = client.diagnostics();
+
+    assert_eq!(published.uri, client.uri("numbers"));
+    assert_eq!(published.diagnostics.len(), 1);
+    assert_eq!(published.diagnostics[0].range.start.line, 1);
+
+    client.write("numbers", NUMBERS);
+
+    client.notify::<DidSaveTextDocument>(DidSaveTextDocumentParams {
+        text_document: TextDocumentIdentifier {
+            uri: client.uri("numbers"),
+        },
+        text: None,
+    });
+
+    let published = client.diagnostics();
+
+    assert_eq!(published.uri, client.uri("numbers"));
+    assert!(published.diagnostics.is_empty());
+}
+
+#[test]
+fn compile_forgets_removed_modules() {
+    let client = TestClient::new(&[("numbers", NUMBERS), ("main", "pub fn one() { 1 }\n")]);
+
+    let mut project = LspProject::new(client.root().to_path_buf());
+
+    let (errors, _) = project.compile();
+
+    assert!(errors.is_empty());
+    assert!(project.modules.contains_key("main"));
+
+    fs::remove_file(client.path("main")).unwrap();
+
+    let (errors, _) = project.compile();
+
+    assert!(errors.is_empty());
+    assert!(!project.modules.contains_key("main"));
+    assert!(!project.importable_modules.contains_key("main"));
+    assert!(project.importable_modules.contains_key("numbers"));
+}
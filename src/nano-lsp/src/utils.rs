@@ -0,0 +1,64 @@
+use crate::{error::Error, line_numbers::LineNumbers};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+use nano_lang::ast::Span;
+use std::path::{Path, PathBuf};
+
+pub fn uri_to_path(uri: &Url) -> Result<PathBuf, Error> {
+    uri.to_file_path().map_err(|_| Error::InvalidUri {
+        uri: uri.to_string(),
+    })
+}
+
+pub fn path_to_uri(path: &Path) -> Result<Url, Error> {
+    Url::from_file_path(path).map_err(|_| Error::InvalidPath {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Turns a compiler error or warning into an editor diagnostic, located at
+/// its first label or else at the start of the file.
+pub fn to_lsp_diagnostic<E>(error: &E, src: &str, severity: DiagnosticSeverity) -> Diagnostic
+where
+    E: miette::Diagnostic + ?Sized,
+{
+    let line_numbers = LineNumbers::new(src);
+
+    let labels = error
+        .labels()
+        .map(|labels| labels.collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let range = match labels.first() {
+        Some(label) => line_numbers.range(Span {
+            start: label.offset(),
+            end: label.offset() + label.len(),
+        }),
+        None => line_numbers.range(Span::empty()),
+    };
+
+    // Project errors only say which phase failed, the actual problem is their
+    // source.
+    let mut message = match error.source() {
+        Some(source) => source.to_string(),
+        None => error.to_string(),
+    };
+
+    for label in labels.iter().filter_map(|label| label.label()) {
+        message.push_str(&format!("\n\n{label}"));
+    }
+
+    if let Some(help) = error.help() {
+        message.push_str(&format!("\n\n{help}"));
+    }
+
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: error
+            .code()
+            .map(|code| NumberOrString::String(code.to_string())),
+        source: Some("nano".to_string()),
+        message,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,84 @@
+pub mod error;
+pub mod parameter;
+pub mod validator;
+
+pub use nano_lang::gen_uplc::schema;
+
+use crate::{config::Config, module::CheckedModules, script::Script};
+use error::Error;
+use indexmap::IndexMap;
+use nano_lang::{ast::TypedDataType, gen_uplc::builder::DataTypeKey};
+use schema::Definitions;
+use serde::Serialize;
+use validator::Validator;
+
+/// A CIP-57 Plutus contract blueprint, describing the interface of every
+/// validator in a project.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Blueprint {
+    pub preamble: Preamble,
+    pub validators: Vec<Validator>,
+    #[serde(skip_serializing_if = "Definitions::is_empty")]
+    pub definitions: Definitions,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preamble {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub version: String,
+    pub plutus_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+impl Blueprint {
+    pub fn new(
+        config: &Config,
+        modules: &CheckedModules,
+        data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+        scripts: &[Script],
+    ) -> Result<Self, Error> {
+        let mut definitions = Definitions::new();
+        let mut validators = vec![];
+
+        for (module, def) in modules.validators() {
+            let script = scripts
+                .iter()
+                .find(|script| script.module == module.name && script.name == def.fun.name)
+                .expect("Missing compiled script for validator");
+
+            validators.extend(Validator::from_checked_module(
+                module,
+                def,
+                &script.to_debruijn(),
+                data_types,
+                &mut definitions,
+            )?);
+        }
+
+        Ok(Blueprint {
+            preamble: config.into(),
+            validators,
+            definitions,
+        })
+    }
+}
+
+impl From<&Config> for Preamble {
+    fn from(config: &Config) -> Self {
+        Preamble {
+            title: config.name.to_string(),
+            description: if config.description.is_empty() {
+                None
+            } else {
+                Some(config.description.clone())
+            },
+            version: config.version.clone(),
+            plutus_version: "v2".to_string(),
+            license: config.license.clone(),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::schema;
+use miette::{Diagnostic, NamedSource};
+use nano_lang::ast::Span;
+use std::fmt;
+
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum Error {
+    #[error("{}", error)]
+    #[diagnostic(help("{}", error.help()))]
+    #[diagnostic(code("blueprint::interface"))]
+    Schema {
+        error: schema::Error,
+        #[label("invalid validator's boundary")]
+        location: Span,
+        #[source_code]
+        source_code: NamedSource,
+    },
+
+    #[error("I couldn't find any validator named '{title}'.")]
+    #[diagnostic(code("blueprint::apply::unknown_validator"))]
+    #[diagnostic(help("Validators are named after their module and function, e.g. 'module.function'. Known validators are:\n\n- {}", known.join("\n- ")))]
+    UnknownValidator { title: String, known: Vec<String> },
+
+    #[error("I was given {given} parameter(s) but '{title}' only takes {expected}.")]
+    #[diagnostic(code("blueprint::apply::arity"))]
+    TooManyParameters {
+        title: String,
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("I couldn't decode the value given for the parameter '{name}'.")]
+    #[diagnostic(code("blueprint::apply::malformed"))]
+    #[diagnostic(help("{hint}"))]
+    MalformedParameter { name: String, hint: String },
+
+    #[error("The value given for the parameter '{name}' doesn't match its type.")]
+    #[diagnostic(code("blueprint::apply::mismatch"))]
+    #[diagnostic(help("{mismatch}"))]
+    SchemaMismatch { name: String, mismatch: Mismatch },
+}
+
+/// Where, and how, a value departs from the schema it was checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub path: Vec<String>,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {} but found {}", self.expected, self.found)?;
+
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path.concat())?;
+        }
+
+        Ok(())
+    }
+}
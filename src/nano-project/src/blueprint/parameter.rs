@@ -0,0 +1,325 @@
+use super::{
+    error::{Error, Mismatch},
+    schema::{Annotated, Definitions, Items, Schema},
+};
+use crate::{module::CheckedModule, script::Script};
+use indexmap::IndexMap;
+use miette::NamedSource;
+use nano_lang::{ast::TypedDataType, gen_uplc::builder::DataTypeKey};
+use num_bigint::BigInt;
+use pallas::ledger::addresses::{Network, ShelleyAddress, ShelleyDelegationPart};
+use serde_json::Value;
+use untyped_plutus_core::{
+    ast::Data, machine::runtime::convert_tag_to_constr, plutus_data, Hash, PlutusData,
+};
+
+/// A value given for a validator parameter, either as JSON following the
+/// detailed schema of `cardano-cli` (e.g. `{ "constructor": 0, "fields": [{ "int": 42 }] }`)
+/// or as CBOR-serialised Plutus data.
+#[derive(Debug, Clone)]
+pub enum Parameter {
+    Json(Value),
+    Cbor(Vec<u8>),
+}
+
+impl Parameter {
+    pub fn to_plutus_data(&self) -> Result<PlutusData, String> {
+        match self {
+            Parameter::Json(value) => data_from_json(value),
+            Parameter::Cbor(bytes) => {
+                plutus_data(bytes).map_err(|e| format!("Invalid CBOR Plutus data: {e}"))
+            }
+        }
+    }
+}
+
+/// A validator once some of its parameters have been applied.
+#[derive(Debug, Clone)]
+pub struct AppliedScript {
+    pub script: Script,
+    pub hash: Hash<28>,
+    pub address: ShelleyAddress,
+}
+
+impl AppliedScript {
+    pub fn new(script: Script, network: Network) -> Self {
+        let program = script.to_debruijn();
+
+        AppliedScript {
+            hash: program.hash(),
+            address: program.address(network, ShelleyDelegationPart::Null),
+            script,
+        }
+    }
+}
+
+/// Checks each value against the type of the parameter it is given for, and
+/// applies them in order. Parameters left over remain in the returned script.
+pub fn apply(
+    module: &CheckedModule,
+    script: &Script,
+    values: &[Parameter],
+    data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+) -> Result<Script, Error> {
+    if values.len() > script.params.len() {
+        return Err(Error::TooManyParameters {
+            title: format!("{}.{}", script.module, script.name),
+            expected: script.params.len(),
+            given: values.len(),
+        });
+    }
+
+    let mut definitions = Definitions::new();
+    let mut program = script.program.clone();
+
+    for (param, value) in script.params.iter().zip(values) {
+        let name = param.arg_name.get_label();
+
+        let schema = Annotated::from_arg(param, data_types, &mut definitions).map_err(|error| {
+            Error::Schema {
+                error,
+                location: param.location,
+                source_code: NamedSource::new(
+                    module.input_path.display().to_string(),
+                    module.code.clone(),
+                ),
+            }
+        })?;
+
+        let data = value
+            .to_plutus_data()
+            .map_err(|hint| Error::MalformedParameter {
+                name: name.clone(),
+                hint,
+            })?;
+
+        validate(&schema.annotated, &definitions, &data, &mut vec![])
+            .map_err(|mismatch| Error::SchemaMismatch { name, mismatch })?;
+
+        program = program.apply_data(data);
+    }
+
+    Ok(Script {
+        program,
+        params: script.params[values.len()..].to_vec(),
+        ..script.clone()
+    })
+}
+
+fn validate(
+    schema: &Schema,
+    definitions: &Definitions,
+    data: &PlutusData,
+    path: &mut Vec<String>,
+) -> Result<(), Mismatch> {
+    let mismatch = |path: &Vec<String>| Mismatch {
+        path: path.clone(),
+        expected: describe_schema(schema),
+        found: describe_data(data),
+    };
+
+    match (schema, data) {
+        (Schema::Opaque, _) => Ok(()),
+
+        (Schema::Ref(key), _) => {
+            let schema = definitions
+                .lookup(key)
+                .expect("Missing definition for schema reference");
+
+            validate(&schema.annotated, definitions, data, path)
+        }
+
+        (Schema::Integer, PlutusData::BigInt(_)) => Ok(()),
+
+        (Schema::Bytes, PlutusData::BoundedBytes(_)) => Ok(()),
+
+        (Schema::List(Items::One(item)), PlutusData::Array(elems)) => {
+            for (index, elem) in elems.iter().enumerate() {
+                path.push(format!("[{index}]"));
+                validate(&item.annotated, definitions, elem, path)?;
+                path.pop();
+            }
+
+            Ok(())
+        }
+
+        (Schema::List(Items::Many(items)), PlutusData::Array(elems)) => {
+            if items.len() != elems.len() {
+                return Err(Mismatch {
+                    expected: format!("a list of {} elements", items.len()),
+                    found: format!("a list of {} elements", elems.len()),
+                    ..mismatch(path)
+                });
+            }
+
+            for (index, (item, elem)) in items.iter().zip(elems).enumerate() {
+                path.push(format!("[{index}]"));
+                validate(&item.annotated, definitions, elem, path)?;
+                path.pop();
+            }
+
+            Ok(())
+        }
+
+        (Schema::Map(keys, values), PlutusData::Map(pairs)) => {
+            for (index, (key, value)) in pairs.iter().enumerate() {
+                path.push(format!("[{index}].key"));
+                validate(&keys.annotated, definitions, key, path)?;
+                path.pop();
+
+                path.push(format!("[{index}].value"));
+                validate(&values.annotated, definitions, value, path)?;
+                path.pop();
+            }
+
+            Ok(())
+        }
+
+        (Schema::AnyOf(constructors), PlutusData::Constr(constr)) => {
+            let index = convert_tag_to_constr(constr.tag).or(constr.any_constructor);
+
+            let constructor = constructors
+                .iter()
+                .find(|constructor| Some(constructor.annotated.index as u64) == index)
+                .ok_or_else(|| mismatch(path))?;
+
+            if constructor.annotated.fields.len() != constr.fields.len() {
+                return Err(Mismatch {
+                    expected: format!(
+                        "{} fields for constructor {}",
+                        constructor.annotated.fields.len(),
+                        constructor.annotated.index
+                    ),
+                    found: format!("{} fields", constr.fields.len()),
+                    ..mismatch(path)
+                });
+            }
+
+            for (index, (field, elem)) in constructor
+                .annotated
+                .fields
+                .iter()
+                .zip(&constr.fields)
+                .enumerate()
+            {
+                path.push(format!(".fields[{index}]"));
+                validate(&field.annotated, definitions, elem, path)?;
+                path.pop();
+            }
+
+            Ok(())
+        }
+
+        _ => Err(mismatch(path)),
+    }
+}
+
+fn describe_schema(schema: &Schema) -> String {
+    match schema {
+        Schema::Integer => "an integer".to_string(),
+        Schema::Bytes => "bytes".to_string(),
+        Schema::List(_) => "a list".to_string(),
+        Schema::Map(..) => "a map".to_string(),
+        Schema::AnyOf(constructors) => format!(
+            "one of the constructors {}",
+            constructors
+                .iter()
+                .map(|constructor| match &constructor.title {
+                    Some(title) => format!("{title} ({})", constructor.annotated.index),
+                    None => constructor.annotated.index.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Schema::Ref(key) => key.clone(),
+        Schema::Opaque => "any data".to_string(),
+    }
+}
+
+fn describe_data(data: &PlutusData) -> String {
+    match data {
+        PlutusData::BigInt(_) => "an integer".to_string(),
+        PlutusData::BoundedBytes(_) => "bytes".to_string(),
+        PlutusData::Array(_) => "a list".to_string(),
+        PlutusData::Map(_) => "a map".to_string(),
+        PlutusData::Constr(constr) => match convert_tag_to_constr(constr.tag) {
+            Some(index) => format!("constructor {index}"),
+            None => match constr.any_constructor {
+                Some(index) => format!("constructor {index}"),
+                None => "a constructor".to_string(),
+            },
+        },
+    }
+}
+
+fn data_from_json(value: &Value) -> Result<PlutusData, String> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("Expected a JSON object but got: {value}"))?;
+
+    if let Some(int) = object.get("int") {
+        let int = match int {
+            Value::Number(n) => n.to_string(),
+            Value::String(s) => s.clone(),
+            _ => return Err(format!("Expected an integer but got: {int}")),
+        };
+
+        let int = int
+            .parse::<BigInt>()
+            .map_err(|_| format!("Expected an integer but got: {int}"))?;
+
+        Ok(Data::integer(int))
+    } else if let Some(bytes) = object.get("bytes") {
+        let bytes = bytes
+            .as_str()
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or_else(|| format!("Expected a base16-encoded string but got: {bytes}"))?;
+
+        Ok(Data::bytestring(bytes))
+    } else if let Some(list) = object.get("list") {
+        let list = list
+            .as_array()
+            .ok_or_else(|| format!("Expected an array but got: {list}"))?;
+
+        Ok(Data::list(
+            list.iter().map(data_from_json).collect::<Result<_, _>>()?,
+        ))
+    } else if let Some(map) = object.get("map") {
+        let map = map
+            .as_array()
+            .ok_or_else(|| format!("Expected an array but got: {map}"))?;
+
+        let pairs = map
+            .iter()
+            .map(|pair| match (pair.get("k"), pair.get("v")) {
+                (Some(k), Some(v)) => Ok((data_from_json(k)?, data_from_json(v)?)),
+                _ => Err(format!(
+                    "Expected an object with 'k' and 'v' but got: {pair}"
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Data::map(pairs))
+    } else if let Some(constructor) = object.get("constructor") {
+        let index = constructor
+            .as_u64()
+            .ok_or_else(|| format!("Expected a constructor index but got: {constructor}"))?;
+
+        let fields = object
+            .get("fields")
+            .and_then(|fields| fields.as_array())
+            .ok_or_else(|| format!("Expected 'fields' to be an array in: {value}"))?;
+
+        Ok(Data::constr(
+            index,
+            fields
+                .iter()
+                .map(data_from_json)
+                .collect::<Result<_, _>>()?,
+        ))
+    } else {
+        Err(format!(
+            "Expected one of 'int', 'bytes', 'list', 'map' or 'constructor' but got: {value}"
+        ))
+    }
+}
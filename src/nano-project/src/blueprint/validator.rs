@@ -0,0 +1,118 @@
+use super::{
+    error::Error,
+    schema::{Annotated, Definitions, Schema},
+};
+use crate::module::CheckedModule;
+use indexmap::IndexMap;
+use miette::NamedSource;
+use nano_lang::{
+    ast::{self, TypedArg, TypedDataType, TypedValidator},
+    gen_uplc::builder::DataTypeKey,
+};
+use serde::Serialize;
+use std::fmt::{self, Display};
+use untyped_plutus_core::ast::{DeBruijn, Program};
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Validator {
+    pub title: String,
+    pub purpose: Purpose,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datum: Option<Annotated<Schema>>,
+    pub redeemer: Annotated<Schema>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Annotated<Schema>>,
+    #[serde(flatten)]
+    pub program: Program<DeBruijn>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Purpose {
+    Spend,
+    Mint,
+    Withdraw,
+    Certify,
+}
+
+impl From<ast::Purpose> for Purpose {
+    fn from(purpose: ast::Purpose) -> Self {
+        match purpose {
+            ast::Purpose::Spend => Purpose::Spend,
+            ast::Purpose::Mint => Purpose::Mint,
+            ast::Purpose::Withdraw => Purpose::Withdraw,
+            ast::Purpose::Certify => Purpose::Certify,
+        }
+    }
+}
+
+impl Display for Purpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Purpose::Spend => "spend",
+            Purpose::Mint => "mint",
+            Purpose::Withdraw => "withdraw",
+            Purpose::Certify => "certify",
+        })
+    }
+}
+
+impl Validator {
+    /// Describes every entry point of a validator. Multi-validators yield one
+    /// item per function, all sharing the same compiled program.
+    pub fn from_checked_module(
+        module: &CheckedModule,
+        def: &TypedValidator,
+        program: &Program<DeBruijn>,
+        data_types: &IndexMap<DataTypeKey, &TypedDataType>,
+        definitions: &mut Definitions,
+    ) -> Result<Vec<Self>, Error> {
+        let schema_of = |arg: &TypedArg, definitions: &mut Definitions| {
+            Annotated::from_arg(arg, data_types, definitions).map_err(|error| Error::Schema {
+                error,
+                location: arg.location,
+                source_code: NamedSource::new(
+                    module.input_path.display().to_string(),
+                    module.code.clone(),
+                ),
+            })
+        };
+
+        let parameters = def
+            .params
+            .iter()
+            .map(|param| schema_of(param, definitions))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut validators = vec![];
+
+        for (purpose, fun) in def.handlers() {
+            let (datum, redeemer) = match &fun.arguments[..] {
+                [datum, redeemer, _context] => (
+                    Some(schema_of(datum, definitions)?),
+                    schema_of(redeemer, definitions)?,
+                ),
+                [redeemer, _context] => (None, schema_of(redeemer, definitions)?),
+                _ => unreachable!("validator arity is checked during type-checking"),
+            };
+
+            validators.push(Validator {
+                title: format!("{}.{}", module.name, fun.name),
+                purpose: purpose.into(),
+                description: fun
+                    .doc
+                    .as_ref()
+                    .or(def.doc.as_ref())
+                    .map(|doc| doc.trim().to_string()),
+                datum,
+                redeemer,
+                parameters: parameters.clone(),
+                program: program.clone(),
+            });
+        }
+
+        Ok(validators)
+    }
+}
@@ -0,0 +1,98 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{error::Error, package_name::PackageName, paths};
+use miette::NamedSource;
+use nano_lang::ast::Span;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Config {
+    pub name: PackageName,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Repository>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Repository {
+    pub user: String,
+    pub project: String,
+    pub platform: Platform,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Github,
+    Gitlab,
+    Bitbucket,
+    Cache,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct Dependency {
+    pub name: PackageName,
+    pub version: String,
+    pub source: Platform,
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), ::std::fmt::Error> {
+        match *self {
+            Platform::Github => f.write_str("github"),
+            Platform::Gitlab => f.write_str("gitlab"),
+            Platform::Bitbucket => f.write_str("bitbucket"),
+            Platform::Cache => f.write_str("cache"),
+        }
+    }
+}
+
+impl Config {
+    pub fn default(name: &PackageName) -> Self {
+        Config {
+            name: name.clone(),
+            version: "0.0.0".to_string(),
+            license: Some("Apache-2.0".to_string()),
+            description: format!("Nano contracts for project '{name}'"),
+            repository: Some(Repository {
+                user: name.owner.clone(),
+                project: name.repo.clone(),
+                platform: Platform::Github,
+            }),
+            dependencies: vec![],
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), io::Error> {
+        let nano_toml_path = dir.join(paths::project_config());
+        let nano_toml = toml::to_string_pretty(self).unwrap();
+        fs::write(nano_toml_path, nano_toml)
+    }
+
+    pub fn load(dir: &Path) -> Result<Config, Error> {
+        let config_path = dir.join(paths::project_config());
+
+        let raw_config = fs::read_to_string(&config_path).map_err(|_| Error::MissingManifest {
+            path: dir.to_path_buf(),
+        })?;
+
+        let result: Self = toml::from_str(&raw_config).map_err(|e| Error::TomlLoading {
+            path: config_path.clone(),
+            src: raw_config.clone(),
+            named: NamedSource::new(config_path.display().to_string(), raw_config).into(),
+            location: e.span().map(|range| Span {
+                start: range.start,
+                end: range.end,
+            }),
+            help: e.to_string(),
+        })?;
+
+        Ok(result)
+    }
+}
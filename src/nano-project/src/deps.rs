@@ -164,25 +164,34 @@ where
 
     let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio");
 
-    let (manifest, changed) = Manifest::load(
-        runtime.handle().clone(),
-        event_listener,
-        config,
-        use_manifest,
-        root_path,
-    )?;
+    let (mut manifest, mut changed) =
+        Manifest::load(event_listener, config, use_manifest, root_path)?;
 
-    let local = LocalPackages::load(root_path)?;
+    let mut local = LocalPackages::load(root_path)?;
 
     local.remove_extra_packages(&manifest, root_path)?;
 
-    runtime.block_on(fetch_missing_packages(
-        &manifest,
-        &local,
-        project_name,
-        root_path,
-        event_listener,
-    ))?;
+    loop {
+        runtime.block_on(fetch_missing_packages(
+            &manifest,
+            &local,
+            project_name.clone(),
+            root_path,
+            event_listener,
+        ))?;
+
+        local = LocalPackages::from(&manifest);
+
+        if manifest.discover_requirements(root_path)? == 0 {
+            break;
+        }
+
+        changed = true;
+    }
+
+    if manifest.verify(event_listener, root_path, &project_name)? {
+        changed = true;
+    }
 
     if changed {
         manifest.save(root_path)?;
@@ -0,0 +1,232 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use futures::future;
+use reqwest::Client;
+
+use crate::{config::Platform, error::Error, package_name::PackageName, paths};
+
+use super::manifest::Package;
+
+/// Where a package can be found in the local package cache.
+#[derive(Debug, Clone)]
+pub enum CachedPackage {
+    Directory(PathBuf),
+    Zip(PathBuf),
+    Tarball(PathBuf),
+}
+
+pub struct Downloader<'a> {
+    http: Client,
+    root_path: &'a Path,
+    cache_path: PathBuf,
+}
+
+impl<'a> Downloader<'a> {
+    pub fn new(root_path: &'a Path) -> Self {
+        Self {
+            http: Client::new(),
+            root_path,
+            cache_path: paths::packages_cache(),
+        }
+    }
+
+    pub async fn download_packages<'p, I>(
+        &self,
+        packages: I,
+        project_name: &PackageName,
+    ) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'p Package>,
+    {
+        let tasks = packages
+            .filter(|package| project_name != &package.name)
+            .map(|package| self.ensure_package_in_build_directory(package));
+
+        let _results = future::try_join_all(tasks).await?;
+
+        Ok(())
+    }
+
+    pub async fn ensure_package_in_build_directory(&self, package: &Package) -> Result<(), Error> {
+        let cached = self.ensure_package_in_cache(package).await?;
+
+        self.extract_package_from_cache(&package.name, &cached)
+    }
+
+    pub async fn ensure_package_in_cache(&self, package: &Package) -> Result<CachedPackage, Error> {
+        if let Some(cached) = self.lookup_cache(package) {
+            return Ok(cached);
+        }
+
+        let url = match package.source {
+            Platform::Cache => {
+                return Err(Error::MissingCachedPackage {
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    cache: self.cache_path.clone(),
+                });
+            }
+            Platform::Github => format!(
+                "https://api.github.com/repos/{}/{}/zipball/{}",
+                package.name.owner, package.name.repo, package.version
+            ),
+            Platform::Gitlab => format!(
+                "https://gitlab.com/{owner}/{repo}/-/archive/{version}/{repo}-{version}.zip",
+                owner = package.name.owner,
+                repo = package.name.repo,
+                version = package.version
+            ),
+            Platform::Bitbucket => format!(
+                "https://bitbucket.org/{}/{}/get/{}.zip",
+                package.name.owner, package.name.repo, package.version
+            ),
+        };
+
+        let response = self
+            .http
+            .get(url)
+            .header("User-Agent", "nano-lang")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let bytes = response.bytes().await?;
+
+        fs::create_dir_all(&self.cache_path)?;
+
+        let zipball_path = self.cache_path.join(format!("{}.zip", cache_key(package)));
+
+        fs::write(&zipball_path, bytes)?;
+
+        Ok(CachedPackage::Zip(zipball_path))
+    }
+
+    fn lookup_cache(&self, package: &Package) -> Option<CachedPackage> {
+        let key = cache_key(package);
+
+        let directory = self.cache_path.join(&key);
+
+        if directory.is_dir() {
+            return Some(CachedPackage::Directory(directory));
+        }
+
+        let zipball = self.cache_path.join(format!("{key}.zip"));
+
+        if zipball.is_file() {
+            return Some(CachedPackage::Zip(zipball));
+        }
+
+        ["tar.gz", "tgz"]
+            .iter()
+            .map(|ext| self.cache_path.join(format!("{key}.{ext}")))
+            .find(|tarball| tarball.is_file())
+            .map(CachedPackage::Tarball)
+    }
+
+    fn extract_package_from_cache(
+        &self,
+        name: &PackageName,
+        cached: &CachedPackage,
+    ) -> Result<(), Error> {
+        let destination = self.root_path.join(paths::build_deps_package(name));
+
+        if destination.exists() {
+            fs::remove_dir_all(&destination)?;
+        }
+
+        match cached {
+            CachedPackage::Directory(source) => copy_dir(source, &destination)?,
+            CachedPackage::Zip(zipball) => {
+                let staging = staging_dir(&destination)?;
+
+                let mut archive = zip::ZipArchive::new(File::open(zipball)?)?;
+
+                archive.extract(&staging)?;
+
+                unwrap_single_root(&staging, &destination)?;
+            }
+            CachedPackage::Tarball(tarball) => {
+                let staging = staging_dir(&destination)?;
+
+                let mut archive = tar::Archive::new(GzDecoder::new(File::open(tarball)?));
+
+                archive.unpack(&staging)?;
+
+                unwrap_single_root(&staging, &destination)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn cache_key(package: &Package) -> String {
+    format!(
+        "{}-{}-{}",
+        package.name.owner,
+        package.name.repo,
+        package.version.replace('/', "_")
+    )
+}
+
+fn staging_dir(destination: &Path) -> Result<PathBuf, Error> {
+    let mut name = destination
+        .file_name()
+        .expect("Package destination without a file name")
+        .to_os_string();
+
+    name.push(".staging");
+
+    let staging = destination.with_file_name(name);
+
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    Ok(staging)
+}
+
+/// Archives produced by source hosting platforms wrap everything inside a
+/// single top-level folder (e.g. `owner-repo-<sha>/`). When that is the case
+/// the folder's content becomes the package root.
+fn unwrap_single_root(staging: &Path, destination: &Path) -> Result<(), Error> {
+    let entries = fs::read_dir(staging)?.collect::<Result<Vec<_>, io::Error>>()?;
+
+    match entries.as_slice() {
+        [single] if single.file_type()?.is_dir() => {
+            fs::rename(single.path(), destination)?;
+
+            fs::remove_dir_all(staging)?;
+        }
+        _ => fs::rename(staging, destination)?,
+    }
+
+    Ok(())
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> Result<(), Error> {
+    for entry in walkdir::WalkDir::new(source)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .expect("Stripping cache prefix from package path");
+
+        let target = destination.join(relative);
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
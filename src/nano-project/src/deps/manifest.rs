@@ -0,0 +1,273 @@
+use std::{fs, path::Path};
+
+use miette::NamedSource;
+use nano_lang::ast::Span;
+use pallas::crypto::hash::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, Dependency, Platform},
+    error::Error,
+    package_name::PackageName,
+    paths,
+    telemetry::{Event, EventListener},
+};
+
+use super::UseManifest;
+
+#[derive(Deserialize, Serialize, Default)]
+pub struct Manifest {
+    pub requirements: Vec<Dependency>,
+    pub packages: Vec<Package>,
+    /// The packages of the manifest this one was resolved again from, whose
+    /// hashes still hold for the versions that didn't change.
+    #[serde(skip)]
+    pub(crate) previous: Vec<Package>,
+}
+
+impl Manifest {
+    pub fn load<T>(
+        event_listener: &T,
+        config: &Config,
+        use_manifest: UseManifest,
+        root_path: &Path,
+    ) -> Result<(Self, bool), Error>
+    where
+        T: EventListener,
+    {
+        let manifest_path = root_path.join(paths::manifest());
+
+        let should_resolve = match use_manifest {
+            UseManifest::No => true,
+            UseManifest::Yes => !manifest_path.exists(),
+        };
+
+        if should_resolve {
+            let manifest = resolve_versions(config, event_listener);
+
+            return Ok((manifest, true));
+        }
+
+        let toml = fs::read_to_string(&manifest_path)?;
+
+        let manifest: Self = toml::from_str(&toml).map_err(|e| Error::TomlLoading {
+            path: manifest_path.clone(),
+            src: toml.clone(),
+            named: NamedSource::new(manifest_path.display().to_string(), toml).into(),
+            location: e.span().map(|range| Span {
+                start: range.start,
+                end: range.end,
+            }),
+            help: e.to_string(),
+        })?;
+
+        if manifest.requirements != config.dependencies {
+            let mut resolved = resolve_versions(config, event_listener);
+
+            resolved.previous = manifest.packages;
+
+            for package in resolved.packages.iter_mut() {
+                package.hash = previous_hash(&resolved.previous, package);
+            }
+
+            return Ok((resolved, true));
+        }
+
+        Ok((manifest, false))
+    }
+
+    pub fn save(&self, root_path: &Path) -> Result<(), Error> {
+        let manifest_path = root_path.join(paths::manifest());
+
+        let mut toml = toml::to_string(&self).expect("nano.lock serialization");
+
+        toml.insert_str(
+            0,
+            "# This file was generated by Nano\n# You typically do not need to edit this file\n\n",
+        );
+
+        fs::write(manifest_path, toml)?;
+
+        Ok(())
+    }
+
+    /// Reads the configuration of every installed package and appends the
+    /// requirements not yet pinned by this manifest. Returns how many new
+    /// packages were discovered.
+    pub fn discover_requirements(&mut self, root_path: &Path) -> Result<usize, Error> {
+        let mut discovered = Vec::new();
+
+        for package in self.packages.iter_mut() {
+            let package_path = root_path.join(paths::build_deps_package(&package.name));
+
+            let config = Config::load(&package_path)?;
+
+            package.requirements = config
+                .dependencies
+                .iter()
+                .map(|dep| dep.name.to_string())
+                .collect();
+
+            discovered.extend(config.dependencies);
+        }
+
+        let mut count = 0;
+
+        for dependency in discovered {
+            match self.packages.iter().find(|p| p.name == dependency.name) {
+                Some(existing) if existing.version != dependency.version => {
+                    return Err(Error::PackageVersionConflict {
+                        package: dependency.name,
+                        versions: vec![existing.version.clone(), dependency.version],
+                    });
+                }
+                Some(_) => (),
+                None => {
+                    let mut package = Package::from(dependency);
+
+                    package.hash = previous_hash(&self.previous, &package);
+
+                    self.packages.push(package);
+
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Checks the content hash of every installed package against the one
+    /// pinned in the manifest. Packages installed for the first time get
+    /// their hash pinned, in which case this returns `true`.
+    pub fn verify<T>(
+        &mut self,
+        event_listener: &T,
+        root_path: &Path,
+        root: &PackageName,
+    ) -> Result<bool, Error>
+    where
+        T: EventListener,
+    {
+        let mut changed = false;
+
+        for package in self.packages.iter_mut().filter(|p| &p.name != root) {
+            let package_path = root_path.join(paths::build_deps_package(&package.name));
+
+            let found = content_hash(&package_path)?;
+
+            match &package.hash {
+                Some(expected) if expected != &found => {
+                    fs::remove_dir_all(&package_path)?;
+
+                    return Err(Error::PackageHashMismatch {
+                        package: package.name.clone(),
+                        version: package.version.clone(),
+                        expected: expected.clone(),
+                        found,
+                    });
+                }
+                Some(_) => (),
+                None => {
+                    event_listener.handle_event(Event::PinningPackageHash {
+                        name: package.name.to_string(),
+                        version: package.version.clone(),
+                        hash: found.clone(),
+                    });
+
+                    package.hash = Some(found);
+
+                    changed = true;
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct Package {
+    pub name: PackageName,
+    pub version: String,
+    pub requirements: Vec<String>,
+    pub source: Platform,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl From<Dependency> for Package {
+    fn from(dependency: Dependency) -> Self {
+        Package {
+            name: dependency.name,
+            version: dependency.version,
+            requirements: vec![],
+            source: dependency.source,
+            hash: None,
+        }
+    }
+}
+
+fn resolve_versions<T>(config: &Config, event_listener: &T) -> Manifest
+where
+    T: EventListener,
+{
+    event_listener.handle_event(Event::ResolvingVersions);
+
+    Manifest {
+        requirements: config.dependencies.clone(),
+        packages: config
+            .dependencies
+            .iter()
+            .cloned()
+            .map(Package::from)
+            .collect(),
+        previous: vec![],
+    }
+}
+
+/// The hash pinned for the same version of a package, if any.
+fn previous_hash(previous: &[Package], package: &Package) -> Option<String> {
+    previous
+        .iter()
+        .find(|p| {
+            p.name == package.name && p.version == package.version && p.source == package.source
+        })
+        .and_then(|p| p.hash.clone())
+}
+
+/// Computes a blake2b-256 digest over the relative path and contents of every
+/// file in a package, visited in a stable order. Build artifacts and VCS
+/// metadata are left out so that the hash only depends on the sources.
+pub fn content_hash(package_path: &Path) -> Result<String, Error> {
+    let mut hasher = Hasher::<256>::new();
+
+    let files = walkdir::WalkDir::new(package_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+
+            e.depth() != 1 || (name != "build" && name != ".git")
+        })
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file());
+
+    for file in files {
+        let relative = file
+            .path()
+            .strip_prefix(package_path)
+            .expect("Stripping package prefix from file path")
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let contents = fs::read(file.path())?;
+
+        hasher.input(relative.as_bytes());
+        hasher.input(&[0]);
+        hasher.input(&(contents.len() as u64).to_be_bytes());
+        hasher.input(&contents);
+    }
+
+    Ok(hasher.finalize().to_string())
+}
@@ -0,0 +1,636 @@
+use crate::{config::Config, module::CheckedModule};
+use askama::Template;
+use nano_lang::{
+    ast::{Definition, DefinitionLocation, ModuleConstant, Span, TypeAlias, TypedDefinition},
+    format,
+    tipo::{Type, TypeInfo, TypeVar},
+};
+use pulldown_cmark::{html, Options, Parser};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const MAX_COLUMNS: isize = 80;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A file of the documentation site, relative to its root.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DocFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+#[derive(Template)]
+#[template(path = "module.html")]
+struct ModuleTemplate<'a> {
+    nano_version: &'a str,
+    breadcrumbs: String,
+    page_title: String,
+    project_name: &'a str,
+    project_version: &'a str,
+    modules: &'a [DocLink],
+    module_name: String,
+    documentation: String,
+    types: Vec<DocType>,
+    functions: Vec<DocFunction>,
+    constants: Vec<DocConstant>,
+}
+
+#[derive(Template)]
+#[template(path = "page.html")]
+struct PageTemplate<'a> {
+    nano_version: &'a str,
+    breadcrumbs: String,
+    page_title: String,
+    project_name: &'a str,
+    project_version: &'a str,
+    modules: &'a [DocLink],
+    content: String,
+}
+
+struct DocLink {
+    name: String,
+    path: String,
+}
+
+struct DocFunction {
+    name: String,
+    signature: String,
+    documentation: String,
+    raw_documentation: String,
+}
+
+struct DocConstant {
+    name: String,
+    definition: String,
+    documentation: String,
+    raw_documentation: String,
+}
+
+struct DocType {
+    name: String,
+    definition: String,
+    documentation: String,
+    raw_documentation: String,
+    constructors: Vec<DocConstructor>,
+}
+
+struct DocConstructor {
+    name: String,
+    definition: String,
+    documentation: String,
+}
+
+#[derive(Serialize)]
+struct SearchEntry {
+    doc: String,
+    title: String,
+    content: String,
+    url: String,
+}
+
+/// Generates the documentation site of the given modules: one page per
+/// module, an index page built from the project's README, static assets and
+/// a search index.
+pub fn generate_all(
+    root: &Path,
+    config: &Config,
+    modules: Vec<&CheckedModule>,
+    module_types: &HashMap<String, TypeInfo>,
+) -> Vec<DocFile> {
+    let mut modules = modules;
+
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let links = modules
+        .iter()
+        .map(|module| DocLink {
+            name: module.name.clone(),
+            path: module_path(&module.name),
+        })
+        .collect::<Vec<_>>();
+
+    let index = Links::new(&modules, module_types);
+
+    let mut files = vec![];
+    let mut search_entries = vec![];
+
+    for module in &modules {
+        let (file, entries) = generate_module(config, module, &links, &index);
+
+        files.push(file);
+        search_entries.extend(entries);
+    }
+
+    files.push(generate_index(root, config, &links));
+
+    files.extend(generate_static_assets(search_entries));
+
+    files
+}
+
+fn generate_module(
+    config: &Config,
+    module: &CheckedModule,
+    links: &[DocLink],
+    index: &Links,
+) -> (DocFile, Vec<SearchEntry>) {
+    let breadcrumbs = breadcrumbs(&module.name);
+    let page = module_path(&module.name);
+    let scope = index.scope(module, &breadcrumbs);
+
+    let mut types = vec![];
+    let mut functions = vec![];
+    let mut constants = vec![];
+
+    for def in module.ast.definitions() {
+        match def {
+            Definition::Fn(func) if func.public => {
+                let signature = format::Formatter::new()
+                    .docs_fn_signature(
+                        &func.name,
+                        &func.arguments,
+                        &func.return_annotation,
+                        func.return_type.clone(),
+                    )
+                    .to_pretty_string(MAX_COLUMNS);
+
+                let referenced = func
+                    .arguments
+                    .iter()
+                    .map(|arg| &arg.tipo)
+                    .chain(std::iter::once(&func.return_type));
+
+                functions.push(DocFunction {
+                    name: func.name.clone(),
+                    signature: scope.linkify(&format!("fn {signature}"), referenced),
+                    documentation: to_html(&func.doc),
+                    raw_documentation: func.doc.clone().unwrap_or_default(),
+                });
+            }
+
+            Definition::DataType(data_type) if data_type.public => {
+                let referenced = data_type
+                    .constructors
+                    .iter()
+                    .flat_map(|constructor| constructor.arguments.iter().map(|arg| &arg.tipo))
+                    .collect::<Vec<_>>();
+
+                let (definition, constructors) = if data_type.opaque {
+                    let definition = format::Formatter::new()
+                        .docs_opaque_data_type(
+                            &data_type.name,
+                            &data_type.parameters,
+                            &data_type.location,
+                        )
+                        .to_pretty_string(MAX_COLUMNS);
+
+                    (format!("opaque type {definition}"), vec![])
+                } else {
+                    let definition = format::Formatter::new()
+                        .docs_data_type(
+                            &data_type.name,
+                            &data_type.parameters,
+                            &data_type.constructors,
+                            &data_type.location,
+                        )
+                        .to_pretty_string(MAX_COLUMNS);
+
+                    let constructors = data_type
+                        .constructors
+                        .iter()
+                        .map(|constructor| DocConstructor {
+                            name: constructor.name.clone(),
+                            definition: scope.linkify(
+                                &format::Formatter::new()
+                                    .docs_record_constructor(constructor)
+                                    .to_pretty_string(MAX_COLUMNS),
+                                constructor.arguments.iter().map(|arg| &arg.tipo),
+                            ),
+                            documentation: to_html(&constructor.doc),
+                        })
+                        .collect();
+
+                    (format!("type {definition}"), constructors)
+                };
+
+                types.push(DocType {
+                    name: data_type.name.clone(),
+                    definition: scope.linkify(&definition, referenced),
+                    documentation: to_html(&data_type.doc),
+                    raw_documentation: data_type.doc.clone().unwrap_or_default(),
+                    constructors,
+                });
+            }
+
+            Definition::TypeAlias(TypeAlias {
+                alias,
+                annotation,
+                doc,
+                parameters,
+                public: true,
+                tipo,
+                ..
+            }) => {
+                let definition = format::Formatter::new()
+                    .docs_type_alias(alias, parameters, annotation)
+                    .to_pretty_string(MAX_COLUMNS);
+
+                types.push(DocType {
+                    name: alias.clone(),
+                    definition: scope.linkify(
+                        &format!("type {definition}"),
+                        std::iter::once(tipo),
+                    ),
+                    documentation: to_html(doc),
+                    raw_documentation: doc.clone().unwrap_or_default(),
+                    constructors: vec![],
+                });
+            }
+
+            Definition::ModuleConstant(ModuleConstant {
+                name,
+                value,
+                doc,
+                public: true,
+                ..
+            }) => {
+                let literal = value.literal();
+
+                let definition = format::Formatter::new()
+                    .docs_const_expr(name, &value.tipo(), literal.as_ref())
+                    .to_pretty_string(MAX_COLUMNS);
+
+                constants.push(DocConstant {
+                    name: name.clone(),
+                    definition: scope.linkify(
+                        &format!("const {definition}"),
+                        std::iter::once(&value.tipo()),
+                    ),
+                    documentation: to_html(doc),
+                    raw_documentation: doc.clone().unwrap_or_default(),
+                });
+            }
+
+            _ => {}
+        }
+    }
+
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+    functions.sort_by(|a, b| a.name.cmp(&b.name));
+    constants.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let module_documentation = module.ast.docs.join("\n");
+
+    let mut search_entries = vec![SearchEntry {
+        doc: module.name.clone(),
+        title: module.name.clone(),
+        content: module_documentation.clone(),
+        url: page.clone(),
+    }];
+
+    search_entries.extend(types.iter().map(|doc_type| SearchEntry {
+        doc: module.name.clone(),
+        title: doc_type.name.clone(),
+        content: doc_type.raw_documentation.clone(),
+        url: format!("{page}#{}", doc_type.name),
+    }));
+
+    search_entries.extend(functions.iter().map(|function| SearchEntry {
+        doc: module.name.clone(),
+        title: function.name.clone(),
+        content: function.raw_documentation.clone(),
+        url: format!("{page}#{}", function.name),
+    }));
+
+    search_entries.extend(constants.iter().map(|constant| SearchEntry {
+        doc: module.name.clone(),
+        title: constant.name.clone(),
+        content: constant.raw_documentation.clone(),
+        url: format!("{page}#{}", constant.name),
+    }));
+
+    let template = ModuleTemplate {
+        nano_version: VERSION,
+        breadcrumbs,
+        page_title: format!("{} - {}", module.name, config.name),
+        project_name: &config.name.repo,
+        project_version: &config.version,
+        modules: links,
+        module_name: module.name.clone(),
+        documentation: to_html(&Some(module_documentation)),
+        types,
+        functions,
+        constants,
+    };
+
+    let file = DocFile {
+        path: PathBuf::from(page),
+        content: template
+            .render()
+            .expect("Module documentation template rendering"),
+    };
+
+    (file, search_entries)
+}
+
+fn generate_index(root: &Path, config: &Config, links: &[DocLink]) -> DocFile {
+    let readme = fs::read_to_string(root.join("README.md"))
+        .unwrap_or_else(|_| format!("# {}\n\n{}", config.name, config.description));
+
+    let template = PageTemplate {
+        nano_version: VERSION,
+        breadcrumbs: ".".to_string(),
+        page_title: config.name.to_string(),
+        project_name: &config.name.repo,
+        project_version: &config.version,
+        modules: links,
+        content: to_html(&Some(readme)),
+    };
+
+    DocFile {
+        path: PathBuf::from("index.html"),
+        content: template.render().expect("Index page template rendering"),
+    }
+}
+
+fn generate_static_assets(search_entries: Vec<SearchEntry>) -> Vec<DocFile> {
+    let search_data =
+        serde_json::to_string(&search_entries).expect("Failed to serialize search index");
+
+    vec![
+        DocFile {
+            path: PathBuf::from("css/index.css"),
+            content: std::include_str!("../templates/css/index.css").to_string(),
+        },
+        DocFile {
+            path: PathBuf::from("js/index.js"),
+            content: std::include_str!("../templates/js/index.js").to_string(),
+        },
+        DocFile {
+            path: PathBuf::from("search-data.js"),
+            content: format!("window.Nano.initSearch({search_data});"),
+        },
+    ]
+}
+
+/// Where the public types of the documented modules are described, indexed
+/// by the location of their definition.
+struct Links<'a> {
+    anchors: HashMap<(String, usize), String>,
+    module_types: &'a HashMap<String, TypeInfo>,
+    type_name: Regex,
+}
+
+impl<'a> Links<'a> {
+    fn new(modules: &[&CheckedModule], module_types: &'a HashMap<String, TypeInfo>) -> Self {
+        let mut anchors = HashMap::new();
+
+        for module in modules {
+            let page = module_path(&module.name);
+
+            for def in module.ast.definitions() {
+                if let Some((name, location)) = public_type(def) {
+                    anchors.insert(
+                        (module.name.clone(), location.start),
+                        format!("{page}#{name}"),
+                    );
+                }
+            }
+        }
+
+        Links {
+            anchors,
+            module_types,
+            type_name: Regex::new(
+                r"\b(?:(?P<module>[a-z_][a-z0-9_/]*)\.)?(?P<name>[A-Z][A-Za-z0-9_]*)\b",
+            )
+            .expect("Type name regex"),
+        }
+    }
+
+    fn resolve(&self, location: DefinitionLocation<'_>) -> Option<&String> {
+        self.anchors
+            .get(&(location.module?.to_string(), location.span.start))
+    }
+
+    /// The names under which the given module refers to types, from its
+    /// imports.
+    fn scope<'m>(&'m self, module: &'m CheckedModule, breadcrumbs: &'m str) -> Scope<'m> {
+        let mut modules = HashMap::new();
+        let mut types = HashMap::new();
+
+        for def in module.ast.definitions() {
+            let Definition::Use(import) = def else {
+                continue;
+            };
+
+            // Imports may be prefixed with the package exporting the module.
+            let name = import.module.join("/");
+
+            let name = if self.module_types.contains_key(&name) || import.module.len() <= 2 {
+                name
+            } else {
+                import.module[2..].join("/")
+            };
+
+            for unqualified in &import.unqualified {
+                types.insert(
+                    unqualified.variable_name(),
+                    (name.clone(), unqualified.name.as_str()),
+                );
+            }
+
+            if let Some(alias) = import.as_name.as_ref().or(import.module.last()) {
+                modules.insert(alias.as_str(), name);
+            }
+        }
+
+        Scope {
+            links: self,
+            module: &module.name,
+            breadcrumbs,
+            modules,
+            types,
+        }
+    }
+
+    fn collect_urls(&self, tipo: &Type, urls: &mut HashMap<(String, String), String>) {
+        match tipo {
+            Type::App {
+                module, name, args, ..
+            } => {
+                let url = self
+                    .module_types
+                    .get(module)
+                    .and_then(|info| info.types.get(name))
+                    .and_then(|constructor| self.resolve(constructor.definition_location()));
+
+                if let Some(url) = url {
+                    urls.insert((module.clone(), name.clone()), url.clone());
+                }
+
+                for arg in args {
+                    self.collect_urls(arg, urls);
+                }
+            }
+
+            Type::Fn { args, ret } => {
+                for arg in args {
+                    self.collect_urls(arg, urls);
+                }
+
+                self.collect_urls(ret, urls);
+            }
+
+            Type::Var { tipo } => {
+                if let TypeVar::Link { tipo } = &*tipo.borrow() {
+                    self.collect_urls(tipo, urls);
+                }
+            }
+
+            Type::Tuple { elems } => {
+                for elem in elems {
+                    self.collect_urls(elem, urls);
+                }
+            }
+        }
+    }
+}
+
+/// The types a documented module refers to, either through the modules it
+/// imports, by their alias, or by name.
+struct Scope<'a> {
+    links: &'a Links<'a>,
+    module: &'a str,
+    breadcrumbs: &'a str,
+    modules: HashMap<&'a str, String>,
+    types: HashMap<&'a str, (String, &'a str)>,
+}
+
+impl Scope<'_> {
+    /// Escapes a rendered signature, linking the names of the documented
+    /// types it refers to.
+    fn linkify<'t>(
+        &self,
+        signature: &str,
+        referenced: impl IntoIterator<Item = &'t Arc<Type>>,
+    ) -> String {
+        let mut urls = HashMap::new();
+
+        for tipo in referenced {
+            self.links.collect_urls(tipo, &mut urls);
+        }
+
+        self.links
+            .type_name
+            .replace_all(&escape(signature), |captures: &Captures| {
+                let name = &captures["name"];
+
+                let url = match captures.name("module") {
+                    Some(module) => {
+                        let module = module.as_str();
+
+                        let module = self.modules.get(module).map_or(module, String::as_str);
+
+                        urls.get(&(module.to_string(), name.to_string()))
+                    }
+                    None => self.unqualified(name, &urls),
+                };
+
+                match url {
+                    Some(url) => {
+                        let qualifier = &captures[0][..captures[0].len() - name.len()];
+
+                        format!(
+                            "{qualifier}<a href=\"{}/{url}\">{name}</a>",
+                            self.breadcrumbs
+                        )
+                    }
+                    None => captures[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    /// Resolves a type referred to by name alone: imported unqualified,
+    /// defined by the module itself or, as printed from an inferred type,
+    /// the only referenced type by that name.
+    fn unqualified<'u>(
+        &self,
+        name: &str,
+        urls: &'u HashMap<(String, String), String>,
+    ) -> Option<&'u String> {
+        let key = match self.types.get(name) {
+            Some((module, name)) => (module.clone(), name.to_string()),
+            None => (self.module.to_string(), name.to_string()),
+        };
+
+        urls.get(&key).or_else(|| {
+            let mut candidates = urls
+                .iter()
+                .filter(|((_, candidate), _)| candidate == name)
+                .map(|(_, url)| url);
+
+            match (candidates.next(), candidates.next()) {
+                (Some(url), None) => Some(url),
+                _ => None,
+            }
+        })
+    }
+}
+
+fn public_type(def: &TypedDefinition) -> Option<(&str, Span)> {
+    match def {
+        Definition::DataType(data_type) if data_type.public => {
+            Some((&data_type.name, data_type.location))
+        }
+        Definition::TypeAlias(alias) if alias.public => Some((&alias.alias, alias.location)),
+        _ => None,
+    }
+}
+
+fn module_path(module: &str) -> String {
+    format!("{module}.html")
+}
+
+/// The relative path from a module page back to the root of the site.
+fn breadcrumbs(module: &str) -> String {
+    let depth = module.matches('/').count();
+
+    if depth == 0 {
+        ".".to_string()
+    } else {
+        vec![".."; depth].join("/")
+    }
+}
+
+fn to_html(markdown: &Option<String>) -> String {
+    let markdown = match markdown {
+        Some(markdown) => markdown,
+        None => return String::new(),
+    };
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut html = String::new();
+
+    html::push_html(&mut html, Parser::new_ext(markdown, options));
+
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,464 @@
+use crate::{
+    blueprint,
+    package_name::{self, PackageName},
+};
+use miette::{
+    Diagnostic, EyreContext, LabeledSpan, MietteHandlerOpts, NamedSource, RgbColors, SourceCode,
+};
+use nano_lang::{ast, gen_uplc::ConstantEvaluationError, parser::error::ParseError, tipo};
+use std::{
+    fmt::{Debug, Display},
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+#[derive(thiserror::Error)]
+pub enum Error {
+    #[error("I just found two modules with the same name: '{module}'")]
+    DuplicateModule {
+        module: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+
+    #[error("I found a module named '{module}' in two different packages: {first} and {second}.")]
+    DuplicatePackageModule {
+        module: String,
+        first: PackageName,
+        second: PackageName,
+    },
+
+    #[error("Some operation on the file-system did fail.")]
+    FileIo { error: io::Error, path: PathBuf },
+
+    #[error(transparent)]
+    StandardIo(#[from] io::Error),
+
+    #[error("I couldn't find a 'nano.toml' in {}.", path.display())]
+    MissingManifest { path: PathBuf },
+
+    #[error("{help}")]
+    TomlLoading {
+        path: PathBuf,
+        src: String,
+        named: Box<NamedSource>,
+        location: Option<ast::Span>,
+        help: String,
+    },
+
+    #[error("I just found a cycle in module hierarchy!")]
+    ImportCycle { modules: Vec<String> },
+
+    #[error("While parsing files...")]
+    Parse {
+        path: PathBuf,
+        src: String,
+        named: Box<NamedSource>,
+        #[source]
+        error: Box<ParseError>,
+    },
+
+    #[error("While trying to make sense of your code...")]
+    Type {
+        path: PathBuf,
+        src: String,
+        named: Box<NamedSource>,
+        #[source]
+        error: tipo::error::Error,
+    },
+
+    #[error("{error}")]
+    InvalidModuleName { path: PathBuf, error: ast::Error },
+
+    #[error(transparent)]
+    InvalidProjectName(#[from] package_name::Error),
+
+    #[error(transparent)]
+    Blueprint(#[from] blueprint::error::Error),
+
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error(transparent)]
+    ZipExtract(#[from] zip::result::ZipError),
+
+    #[error("I couldn't find {package} {version} in the package cache.")]
+    MissingCachedPackage {
+        package: PackageName,
+        version: String,
+        cache: PathBuf,
+    },
+
+    #[error("The content of {package} {version} doesn't match the hash recorded in 'nano.lock'.")]
+    PackageHashMismatch {
+        package: PackageName,
+        version: String,
+        expected: String,
+        found: String,
+    },
+
+    #[error("I found conflicting versions of {package} in the dependency tree.")]
+    PackageVersionConflict {
+        package: PackageName,
+        versions: Vec<String>,
+    },
+
+    #[error(
+        "{name} failed{}",
+        if *verbose { format!("\n{src}") } else { String::new() }
+    )]
+    TestFailure {
+        name: String,
+        path: PathBuf,
+        verbose: bool,
+        src: String,
+        evaluation_hint: Option<String>,
+        counterexample: Option<String>,
+    },
+
+    #[error("I couldn't evaluate the constant '{name}' at compile-time.")]
+    ConstantEvaluation {
+        name: String,
+        path: PathBuf,
+        src: String,
+        named: Box<NamedSource>,
+        location: ast::Span,
+        error: Rc<ConstantEvaluationError>,
+    },
+}
+
+impl Error {
+    pub fn report(&self) {
+        eprintln!("Error: {self:?}")
+    }
+
+    pub fn from_parse_errors(errs: Vec<ParseError>, path: &Path, src: &str) -> Vec<Self> {
+        let mut errors = Vec::with_capacity(errs.len());
+
+        for error in errs {
+            errors.push(Error::Parse {
+                path: path.into(),
+                src: src.to_string(),
+                named: NamedSource::new(path.display().to_string(), src.to_string()).into(),
+                error: error.into(),
+            });
+        }
+
+        errors
+    }
+
+    pub fn path(&self) -> Option<PathBuf> {
+        match self {
+            Error::DuplicateModule { second, .. } => Some(second.to_path_buf()),
+            Error::DuplicatePackageModule { .. } => None,
+            Error::FileIo { .. } => None,
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { path } => Some(path.to_path_buf()),
+            Error::TomlLoading { path, .. } => Some(path.to_path_buf()),
+            Error::ImportCycle { .. } => None,
+            Error::Parse { path, .. } => Some(path.to_path_buf()),
+            Error::Type { path, .. } => Some(path.to_path_buf()),
+            Error::InvalidModuleName { path, .. } => Some(path.to_path_buf()),
+            Error::InvalidProjectName(_) => None,
+            Error::Blueprint(_) => None,
+            Error::Http(_) => None,
+            Error::ZipExtract(_) => None,
+            Error::MissingCachedPackage { cache, .. } => Some(cache.to_path_buf()),
+            Error::PackageHashMismatch { .. } => None,
+            Error::PackageVersionConflict { .. } => None,
+            Error::TestFailure { path, .. } => Some(path.to_path_buf()),
+            Error::ConstantEvaluation { path, .. } => Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn src(&self) -> Option<String> {
+        match self {
+            Error::DuplicateModule { .. } => None,
+            Error::DuplicatePackageModule { .. } => None,
+            Error::FileIo { .. } => None,
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { .. } => None,
+            Error::TomlLoading { src, .. } => Some(src.to_string()),
+            Error::ImportCycle { .. } => None,
+            Error::Parse { src, .. } => Some(src.to_string()),
+            Error::Type { src, .. } => Some(src.to_string()),
+            Error::InvalidModuleName { .. } => None,
+            Error::InvalidProjectName(_) => None,
+            Error::Blueprint(_) => None,
+            Error::Http(_) => None,
+            Error::ZipExtract(_) => None,
+            Error::MissingCachedPackage { .. } => None,
+            Error::PackageHashMismatch { .. } => None,
+            Error::PackageVersionConflict { .. } => None,
+            Error::TestFailure { src, .. } => Some(src.to_string()),
+            Error::ConstantEvaluation { src, .. } => Some(src.to_string()),
+        }
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let miette_handler = MietteHandlerOpts::new()
+            .rgb_colors(RgbColors::Never)
+            .color(true)
+            .unicode(true)
+            .terminal_links(true)
+            .build();
+
+        let _ = miette_handler.debug(self, f);
+
+        Ok(())
+    }
+}
+
+impl From<Error> for Vec<Error> {
+    fn from(value: Error) -> Self {
+        vec![value]
+    }
+}
+
+impl Diagnostic for Error {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Error)
+    }
+
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Error::DuplicateModule { .. } => Some(Box::new("nano::module::duplicate")),
+            Error::DuplicatePackageModule { .. } => {
+                Some(Box::new("nano::packages::duplicate_module"))
+            }
+            Error::FileIo { .. } => None,
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { .. } => Some(Box::new("nano::project::config")),
+            Error::TomlLoading { .. } => Some(Box::new("nano::loading::toml")),
+            Error::ImportCycle { .. } => Some(Box::new("nano::module::cyclical")),
+            Error::Parse { .. } => Some(Box::new("nano::parser")),
+            Error::Type { error, .. } => Some(Box::new(format!(
+                "nano::check{}",
+                error.code().map(|s| format!("::{s}")).unwrap_or_default()
+            ))),
+            Error::InvalidModuleName { error, .. } => error.code(),
+            Error::InvalidProjectName(_) => Some(Box::new("nano::project::name")),
+            Error::Blueprint(error) => error.code(),
+            Error::Http(_) => Some(Box::new("nano::packages::download")),
+            Error::ZipExtract(_) => Some(Box::new("nano::packages::extract")),
+            Error::MissingCachedPackage { .. } => Some(Box::new("nano::packages::cache")),
+            Error::PackageHashMismatch { .. } => Some(Box::new("nano::packages::hash")),
+            Error::PackageVersionConflict { .. } => Some(Box::new("nano::packages::resolve")),
+            Error::TestFailure { .. } => Some(Box::new("nano::test::failure")),
+            Error::ConstantEvaluation { .. } => Some(Box::new("nano::constant::evaluation")),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Error::DuplicateModule { first, second, .. } => Some(Box::new(format!(
+                "Rename either {} or {}",
+                first.display(),
+                second.display()
+            ))),
+            Error::DuplicatePackageModule { module, .. } => Some(Box::new(format!(
+                "Modules share a single namespace across packages, so only one package can define '{module}'. Remove one of the conflicting dependencies or rename the module in one of them."
+            ))),
+            Error::FileIo { error, .. } => Some(Box::new(format!("{error}"))),
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { .. } => Some(Box::new(
+                "Try running `nano new <REPOSITORY/PROJECT>` to initialise a project with an example manifest.",
+            )),
+            Error::TomlLoading { .. } => None,
+            Error::ImportCycle { modules } => Some(Box::new(format!(
+                "Try moving the shared code to a separate module that the others can depend on\n- {}",
+                modules.join("\n- ")
+            ))),
+            Error::Parse { error, .. } => error.kind.help(),
+            Error::Type { error, .. } => error.help(),
+            Error::InvalidModuleName { error, .. } => error.help(),
+            Error::InvalidProjectName(error) => error.help(),
+            Error::Blueprint(error) => error.help(),
+            Error::Http(_) => None,
+            Error::ZipExtract(_) => None,
+            Error::MissingCachedPackage {
+                package,
+                version,
+                cache,
+            } => Some(Box::new(format!(
+                "Packages with a 'cache' source are never downloaded. Place a directory, a .zip or a .tar.gz archive named '{}-{}-{}' in:\n\n    {}\n\nYou can point to another cache location with the NANO_PACKAGES_CACHE environment variable.",
+                package.owner,
+                package.repo,
+                version.replace('/', "_"),
+                cache.display()
+            ))),
+            Error::PackageHashMismatch {
+                expected, found, ..
+            } => Some(Box::new(format!(
+                "expected: {expected}\n   found: {found}\n\nIf this change is expected, remove the package from 'nano.lock' so that its hash gets pinned again."
+            ))),
+            Error::PackageVersionConflict { versions, .. } => Some(Box::new(format!(
+                "Requested versions: {}\n\nPin a single version of this package in your 'nano.toml'.",
+                versions.join(", ")
+            ))),
+            Error::TestFailure {
+                evaluation_hint,
+                counterexample,
+                ..
+            } => match (counterexample, evaluation_hint) {
+                (None, None) => None,
+                (None, Some(hint)) => Some(Box::new(hint.to_string())),
+                (Some(counterexample), None) => Some(Box::new(counterexample.to_string())),
+                (Some(counterexample), Some(hint)) => {
+                    Some(Box::new(format!("{counterexample}\n\n{hint}")))
+                }
+            },
+            Error::ConstantEvaluation { error, .. } => Some(Box::new(error.to_string())),
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            Error::DuplicateModule { .. } => None,
+            Error::DuplicatePackageModule { .. } => None,
+            Error::FileIo { .. } => None,
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { .. } => None,
+            Error::TomlLoading { location, .. } => {
+                if let Some(location) = location {
+                    Some(Box::new(
+                        vec![LabeledSpan::new_with_span(None, *location)].into_iter(),
+                    ))
+                } else {
+                    None
+                }
+            }
+            Error::ImportCycle { .. } => None,
+            Error::Parse { error, .. } => error.labels(),
+            Error::Type { error, .. } => error.labels(),
+            Error::InvalidModuleName { .. } => None,
+            Error::InvalidProjectName(_) => None,
+            Error::Blueprint(error) => error.labels(),
+            Error::Http(_) => None,
+            Error::ZipExtract(_) => None,
+            Error::MissingCachedPackage { .. } => None,
+            Error::PackageHashMismatch { .. } => None,
+            Error::PackageVersionConflict { .. } => None,
+            Error::TestFailure { .. } => None,
+            Error::ConstantEvaluation { location, .. } => Some(Box::new(
+                vec![LabeledSpan::new_with_span(None, *location)].into_iter(),
+            )),
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self {
+            Error::DuplicateModule { .. } => None,
+            Error::DuplicatePackageModule { .. } => None,
+            Error::FileIo { .. } => None,
+            Error::StandardIo(_) => None,
+            Error::MissingManifest { .. } => None,
+            Error::TomlLoading { named, .. } => Some(named.as_ref()),
+            Error::ImportCycle { .. } => None,
+            Error::Parse { named, .. } => Some(named.as_ref()),
+            Error::Type { named, .. } => Some(named.as_ref()),
+            Error::InvalidModuleName { .. } => None,
+            Error::InvalidProjectName(_) => None,
+            Error::Blueprint(error) => error.source_code(),
+            Error::Http(_) => None,
+            Error::ZipExtract(_) => None,
+            Error::MissingCachedPackage { .. } => None,
+            Error::PackageHashMismatch { .. } => None,
+            Error::PackageVersionConflict { .. } => None,
+            Error::TestFailure { .. } => None,
+            Error::ConstantEvaluation { named, .. } => Some(named.as_ref()),
+        }
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Error::Type { error, .. } => error.url(),
+            Error::InvalidModuleName { error, .. } => error.url(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum Warning {
+    #[error("You do not have any validators to build!")]
+    NoValidators,
+    #[error("While trying to make sense of your code...")]
+    Type {
+        path: PathBuf,
+        src: String,
+        named: NamedSource,
+        #[source]
+        warning: tipo::error::Warning,
+    },
+}
+
+impl Diagnostic for Warning {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Warning)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self {
+            Warning::Type { named, .. } => Some(named),
+            Warning::NoValidators => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            Warning::Type { warning, .. } => warning.labels(),
+            Warning::NoValidators => None,
+        }
+    }
+
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Warning::Type { warning, .. } => Some(Box::new(format!(
+                "nano::check{}",
+                warning.code().map(|s| format!("::{s}")).unwrap_or_default()
+            ))),
+            Warning::NoValidators => Some(Box::new("nano::check")),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            Warning::Type { warning, .. } => warning.help(),
+            Warning::NoValidators => None,
+        }
+    }
+}
+
+impl Warning {
+    pub fn from_type_warning(warning: tipo::error::Warning, path: PathBuf, src: String) -> Warning {
+        Warning::Type {
+            path: path.clone(),
+            warning,
+            src: src.clone(),
+            named: NamedSource::new(path.display().to_string(), src),
+        }
+    }
+
+    pub fn report(&self) {
+        eprintln!("Warning: {self:?}")
+    }
+}
+
+impl Debug for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let miette_handler = MietteHandlerOpts::new()
+            .rgb_colors(RgbColors::Never)
+            .color(true)
+            .unicode(true)
+            .terminal_links(true)
+            .build();
+
+        let _ = miette_handler.debug(self, f);
+
+        Ok(())
+    }
+}
@@ -1 +1,887 @@
-pub mod options;
\ No newline at end of file
+pub mod blueprint;
+pub mod config;
+pub mod deps;
+pub mod docs;
+pub mod error;
+pub mod module;
+pub mod options;
+pub mod package_name;
+pub mod paths;
+pub mod script;
+pub mod telemetry;
+pub mod test_framework;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    blueprint::{
+        parameter::{self, AppliedScript, Parameter},
+        Blueprint,
+    },
+    config::Config,
+    deps::UseManifest,
+    error::{Error, Warning},
+    module::{CheckedModule, CheckedModules, ParsedModule, ParsedModules},
+    package_name::PackageName,
+    script::Script,
+    telemetry::Event,
+    test_framework::{coverage::Coverage, EvalHint, Recording, UnitTest},
+};
+use indexmap::IndexMap;
+use miette::NamedSource;
+use nano_lang::{
+    ast::{Definition, ModuleKind, Tracing, TypedDataType, TypedFunction},
+    builtins,
+    gen_uplc::{
+        builder::{DataTypeKey, FunctionAccessKey},
+        schema::{Annotated, Definitions},
+    },
+    tipo::TypeInfo,
+    IdGenerator,
+};
+use options::{CodeGenMode, Options, TestOptions};
+use pallas::ledger::addresses::Network;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use telemetry::EventListener;
+use untyped_plutus_core::{
+    ast::{DeBruijn, Name, Program},
+    machine::profiler::Profile,
+};
+
+#[derive(Debug)]
+pub struct Source {
+    pub path: PathBuf,
+    pub name: String,
+    pub code: String,
+    pub kind: ModuleKind,
+}
+
+pub struct Project<T>
+where
+    T: EventListener,
+{
+    config: Config,
+    defined_modules: HashMap<String, (PackageName, PathBuf)>,
+    checked_modules: CheckedModules,
+    id_gen: IdGenerator,
+    module_types: HashMap<String, TypeInfo>,
+    root: PathBuf,
+    sources: Vec<Source>,
+    pub warnings: Vec<Warning>,
+    event_listener: T,
+    functions: IndexMap<FunctionAccessKey, TypedFunction>,
+    data_types: IndexMap<DataTypeKey, TypedDataType>,
+}
+
+impl<T> Project<T>
+where
+    T: EventListener,
+{
+    pub fn new(root: PathBuf, event_listener: T) -> Result<Project<T>, Error> {
+        let config = Config::load(&root)?;
+
+        Ok(Project::new_with_config(config, root, event_listener))
+    }
+
+    pub fn new_with_config(config: Config, root: PathBuf, event_listener: T) -> Project<T> {
+        let id_gen = IdGenerator::new();
+
+        let mut module_types = HashMap::new();
+
+        module_types.insert("nano".to_string(), builtins::prelude(&id_gen));
+        module_types.insert("nano/builtin".to_string(), builtins::plutus(&id_gen));
+
+        let functions = builtins::prelude_functions(&id_gen);
+
+        let data_types = builtins::prelude_data_types(&id_gen);
+
+        Project {
+            config,
+            checked_modules: CheckedModules::default(),
+            defined_modules: HashMap::new(),
+            id_gen,
+            module_types,
+            root,
+            sources: vec![],
+            warnings: vec![],
+            event_listener,
+            functions,
+            data_types,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn build(&mut self, uplc: bool, tracing: Tracing) -> Result<(), Vec<Error>> {
+        let options = Options {
+            code_gen_mode: CodeGenMode::Build(uplc),
+            tracing,
+        };
+
+        self.compile(options)
+    }
+
+    pub fn check(&mut self, tracing: Tracing) -> Result<(), Vec<Error>> {
+        let options = Options {
+            code_gen_mode: CodeGenMode::NoOp,
+            tracing,
+        };
+
+        self.compile(options)
+    }
+
+    pub fn test(&mut self, options: TestOptions, tracing: Tracing) -> Result<(), Vec<Error>> {
+        let options = Options {
+            code_gen_mode: CodeGenMode::Test(options),
+            tracing,
+        };
+
+        self.compile(options)
+    }
+
+    /// Generates the documentation of the project's library modules, in the
+    /// given directory or in `docs/` by default.
+    pub fn docs(&mut self, destination: Option<PathBuf>) -> Result<(), Vec<Error>> {
+        self.event_listener
+            .handle_event(Event::BuildingDocumentation {
+                root: self.root.clone(),
+                name: self.config.name.to_string(),
+                version: self.config.version.clone(),
+            });
+
+        self.compile_deps(Tracing::NoTraces)?;
+
+        self.read_source_files()?;
+
+        let destination = destination.unwrap_or_else(|| self.root.join(paths::docs()));
+
+        let parsed_modules = self.parse_sources(self.config.name.clone())?;
+
+        self.type_check(parsed_modules, Tracing::NoTraces, true)?;
+
+        self.event_listener.handle_event(Event::GeneratingDocFiles {
+            output_path: destination.clone(),
+        });
+
+        let package = self.config.name.to_string();
+
+        let modules = self
+            .checked_modules
+            .values()
+            .filter(|module| {
+                module.package == package
+                    && module.kind.is_lib()
+                    && !module.name.split('/').any(|segment| segment == "internal")
+            })
+            .collect();
+
+        let doc_files = docs::generate_all(&self.root, &self.config, modules, &self.module_types);
+
+        for file in doc_files {
+            let path = destination.join(file.path);
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(Error::from)?;
+            }
+
+            fs::write(&path, file.content).map_err(Error::from)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the given values to the parameters of the validator titled
+    /// `module.function`, after checking them against the parameters' types.
+    pub fn apply_parameters(
+        &mut self,
+        title: &str,
+        values: &[Parameter],
+        network: Network,
+        tracing: Tracing,
+    ) -> Result<AppliedScript, Vec<Error>> {
+        self.check(tracing)?;
+
+        let scripts = self.code_gen();
+
+        let script = scripts
+            .iter()
+            .find(|script| format!("{}.{}", script.module, script.name) == title)
+            .ok_or_else(|| {
+                Error::from(blueprint::error::Error::UnknownValidator {
+                    title: title.to_string(),
+                    known: scripts
+                        .iter()
+                        .map(|script| format!("{}.{}", script.module, script.name))
+                        .collect(),
+                })
+            })?;
+
+        let module = self
+            .checked_modules
+            .get(&script.module)
+            .expect("Missing checked module for compiled script");
+
+        let data_types = self.checked_modules.data_types(&self.data_types);
+
+        let applied =
+            parameter::apply(module, script, values, &data_types).map_err(Error::from)?;
+
+        Ok(AppliedScript::new(applied, network))
+    }
+
+    pub fn compile(&mut self, options: Options) -> Result<(), Vec<Error>> {
+        self.event_listener
+            .handle_event(Event::StartingCompilation {
+                root: self.root.clone(),
+                name: self.config.name.to_string(),
+                version: self.config.version.clone(),
+            });
+
+        self.compile_deps(options.tracing)?;
+
+        self.read_source_files()?;
+
+        let parsed_modules = self.parse_sources(self.config.name.clone())?;
+
+        self.type_check(parsed_modules, options.tracing, true)?;
+
+        self.evaluate_constants()?;
+
+        match options.code_gen_mode {
+            CodeGenMode::Build(uplc_dump) => {
+                if self.checked_modules.validators().next().is_none() {
+                    self.warnings.push(Warning::NoValidators);
+
+                    return Ok(());
+                }
+
+                let scripts = self.code_gen();
+
+                self.write_build_outputs(&scripts, uplc_dump)?;
+
+                self.write_blueprint(&scripts)?;
+
+                Ok(())
+            }
+            CodeGenMode::Test(TestOptions {
+                match_tests,
+                verbose,
+                exact_match,
+                budget,
+                property,
+                profile,
+                coverage,
+            }) => {
+                let tests = self.collect_tests(match_tests, exact_match, profile || coverage);
+
+                if !tests.is_empty() {
+                    self.event_listener.handle_event(Event::RunningTests);
+                }
+
+                let recording = Recording { profile, coverage };
+
+                let results = test_framework::run(tests, budget, property, recording);
+
+                if profile {
+                    self.write_profile(&test_framework::profile(&results))?;
+                }
+
+                if coverage {
+                    self.write_coverage(&test_framework::coverage(&results))?;
+                }
+
+                let errors: Vec<Error> = results
+                    .iter()
+                    .filter(|result| !result.success)
+                    .map(|result| Error::TestFailure {
+                        name: result.test.name.clone(),
+                        path: result.test.input_path.clone(),
+                        verbose,
+                        src: result.test.program.to_pretty(),
+                        evaluation_hint: result.test.evaluation_hint.as_ref().map(|hint| {
+                            match &result.counterexample {
+                                Some(values) => hint.apply_data(values).to_string(),
+                                None => hint.to_string(),
+                            }
+                        }),
+                        counterexample: result.counterexample.as_ref().map(|values| {
+                            format!(
+                                "Counterexample found after {} run(s) with seed {}:\n\n{}",
+                                result.iterations,
+                                property.seed,
+                                result.test.display_inputs(values)
+                            )
+                        }),
+                    })
+                    .collect();
+
+                self.event_listener
+                    .handle_event(Event::FinishedTests { tests: results });
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+            CodeGenMode::NoOp => Ok(()),
+        }
+    }
+
+    fn compile_deps(&mut self, tracing: Tracing) -> Result<(), Vec<Error>> {
+        let manifest = deps::download(
+            &self.event_listener,
+            UseManifest::Yes,
+            &self.root,
+            &self.config,
+        )?;
+
+        let mut parsed_packages = ParsedModules::new();
+
+        for package in manifest.packages {
+            let lib = self
+                .root
+                .join(paths::build_deps_package(&package.name))
+                .join(paths::lib());
+
+            self.nano_files(&lib, ModuleKind::Lib)?;
+
+            let parsed_modules: HashMap<_, _> = self.parse_sources(package.name)?.into();
+
+            parsed_packages.extend(parsed_modules);
+        }
+
+        self.type_check(parsed_packages, tracing, false)?;
+
+        Ok(())
+    }
+
+    pub fn modules(&self) -> Vec<CheckedModule> {
+        self.checked_modules.values().cloned().collect()
+    }
+
+    pub fn importable_modules(&self) -> &HashMap<String, TypeInfo> {
+        &self.module_types
+    }
+
+    pub fn id_gen(&self) -> &IdGenerator {
+        &self.id_gen
+    }
+
+    pub fn code_gen(&self) -> Vec<Script> {
+        let mut generator = self.checked_modules.new_generator(
+            &self.functions,
+            &self.data_types,
+            &self.module_types,
+        );
+
+        let mut scripts = Vec::new();
+
+        for (module, validator) in self.checked_modules.validators() {
+            self.event_listener.handle_event(Event::GeneratingUPLCFor {
+                name: format!("{}.{}", module.name, validator.fun.name),
+                path: module.input_path.clone(),
+            });
+
+            let program = generator.generate(validator);
+
+            scripts.push(Script::new(
+                module.input_path.clone(),
+                module.name.clone(),
+                validator.fun.name.clone(),
+                program,
+                validator.params.clone(),
+            ));
+        }
+
+        scripts
+    }
+
+    /// Evaluates every constant computed from an expression, so that code
+    /// generation can rely on them having a value.
+    fn evaluate_constants(&self) -> Result<(), Vec<Error>> {
+        let mut generator = self.checked_modules.new_generator(
+            &self.functions,
+            &self.data_types,
+            &self.module_types,
+        );
+
+        let mut errors = Vec::new();
+
+        for module in self.checked_modules.values() {
+            for def in module.ast.definitions() {
+                let Definition::ModuleConstant(constant) = def else {
+                    continue;
+                };
+
+                if constant.value.literal().is_some() {
+                    continue;
+                }
+
+                if let Err(error) = generator.evaluate_constant(&module.name, &constant.name) {
+                    errors.push(Error::ConstantEvaluation {
+                        name: constant.name.clone(),
+                        path: module.input_path.clone(),
+                        src: module.code.clone(),
+                        named: NamedSource::new(
+                            module.input_path.display().to_string(),
+                            module.code.clone(),
+                        )
+                        .into(),
+                        location: constant.location,
+                        error,
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Compiles the tests of the project's own modules. Each pattern either
+    /// selects modules by name (`module`) or tests within a module
+    /// (`module.test` or `module.{test_a,test_b}`); names are matched as
+    /// substrings unless `exact_match` is set. Tests come with a source map
+    /// when `source_maps` is set.
+    fn collect_tests(
+        &self,
+        match_tests: Option<Vec<String>>,
+        exact_match: bool,
+        source_maps: bool,
+    ) -> Vec<UnitTest> {
+        let patterns = match_tests.map(|patterns| {
+            patterns
+                .iter()
+                .map(|pattern| match pattern.split_once('.') {
+                    Some((module, names)) => (
+                        module.to_string(),
+                        Some(
+                            names
+                                .trim_matches(|c| c == '{' || c == '}')
+                                .split(',')
+                                .map(|name| name.trim().to_string())
+                                .collect::<Vec<_>>(),
+                        ),
+                    ),
+                    None => (pattern.to_string(), None),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let is_match = |expected: &str, actual: &str| {
+            if exact_match {
+                expected == actual
+            } else {
+                actual.contains(expected)
+            }
+        };
+
+        let package = self.config.name.to_string();
+
+        let data_types = self.checked_modules.data_types(&self.data_types);
+
+        let mut generator = self.checked_modules.new_generator(
+            &self.functions,
+            &self.data_types,
+            &self.module_types,
+        );
+
+        let mut tests = Vec::new();
+
+        for module in self.checked_modules.values() {
+            if module.package != package {
+                continue;
+            }
+
+            let funcs = module.ast.definitions().filter_map(|def| match def {
+                Definition::Test(func) => Some(func),
+                _ => None,
+            });
+
+            for func in funcs {
+                let selected = match &patterns {
+                    None => true,
+                    Some(patterns) => patterns.iter().any(|(module_pattern, names)| {
+                        is_match(module_pattern, &module.name)
+                            && names.as_ref().is_none_or(|names| {
+                                names.iter().any(|name| is_match(name, &func.name))
+                            })
+                    }),
+                };
+
+                if !selected {
+                    continue;
+                }
+
+                let mut definitions = Definitions::new();
+
+                let inputs = func
+                    .arguments
+                    .iter()
+                    .map(|arg| {
+                        let schema = Annotated::from_arg(arg, &data_types, &mut definitions)
+                            .expect("Test arguments are checked to be serialisable");
+
+                        (arg.arg_name.get_label(), schema)
+                    })
+                    .collect();
+
+                let evaluation_hint = func.test_hint().map(|(bin_op, left, right)| EvalHint {
+                    bin_op,
+                    left: to_debruijn(generator.generate_test(&left, &func.arguments)),
+                    right: to_debruijn(generator.generate_test(&right, &func.arguments)),
+                });
+
+                let (program, source_map) = if source_maps {
+                    let (program, source_map) = generator.generate_test_with_source_map(
+                        &module.name,
+                        &func.name,
+                        &func.body,
+                        &func.arguments,
+                    );
+
+                    (program, Some(source_map))
+                } else {
+                    (generator.generate_test(&func.body, &func.arguments), None)
+                };
+
+                tests.push(UnitTest {
+                    input_path: module.input_path.clone(),
+                    module: module.name.clone(),
+                    name: func.name.clone(),
+                    program: to_debruijn(program),
+                    evaluation_hint,
+                    inputs,
+                    definitions,
+                    source_map,
+                });
+            }
+        }
+
+        tests
+    }
+
+    /// Writes the cpu and memory spent by the tests as folded stacks, from
+    /// which flamegraph tools draw their graphs.
+    fn write_profile(&self, profile: &Profile) -> Result<(), Error> {
+        let output_path = self.root.join(paths::build_profile());
+
+        fs::create_dir_all(&output_path)?;
+
+        self.event_listener.handle_event(Event::GeneratingProfile {
+            output_path: output_path.clone(),
+        });
+
+        fs::write(output_path.join("cpu.folded"), profile.folded_cpu())?;
+        fs::write(output_path.join("mem.folded"), profile.folded_mem())?;
+
+        Ok(())
+    }
+
+    /// Writes an lcov report on the functions and validators of the project's
+    /// own modules.
+    fn write_coverage(&self, coverage: &Coverage) -> Result<(), Error> {
+        let output_path = self.root.join(paths::build_coverage());
+
+        self.event_listener.handle_event(Event::GeneratingCoverage {
+            output_path: output_path.clone(),
+        });
+
+        let package = self.config.name.to_string();
+
+        let mut modules: Vec<_> = self
+            .checked_modules
+            .values()
+            .filter(|module| module.package == package)
+            .collect();
+
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let report = coverage.lcov(modules);
+
+        fs::create_dir_all(self.root.join(paths::build()))?;
+
+        fs::write(output_path, report)?;
+
+        Ok(())
+    }
+
+    fn write_build_outputs(&self, scripts: &[Script], uplc_dump: bool) -> Result<(), Error> {
+        for script in scripts {
+            let script_output_dir = self
+                .root
+                .join(paths::build_validator(&script.module, &script.name));
+
+            fs::create_dir_all(&script_output_dir)?;
+
+            self.event_listener.handle_event(Event::GeneratingUPLC {
+                output_path: script_output_dir.clone(),
+            });
+
+            if uplc_dump {
+                let uplc_path = script_output_dir.join("raw.uplc");
+
+                fs::write(uplc_path, script.program.to_pretty())?;
+            }
+
+            let program = script.to_debruijn();
+
+            let cbor_hex = program.to_hex().expect("Failed to encode compiled program");
+
+            fs::write(script_output_dir.join("script.cbor"), cbor_hex)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_blueprint(&self, scripts: &[Script]) -> Result<(), Error> {
+        let blueprint_path = self.root.join(paths::blueprint());
+
+        self.event_listener.handle_event(Event::GeneratingBlueprint {
+            path: blueprint_path.clone(),
+        });
+
+        let data_types = self.checked_modules.data_types(&self.data_types);
+
+        let blueprint = Blueprint::new(&self.config, &self.checked_modules, &data_types, scripts)?;
+
+        let json = serde_json::to_string_pretty(&blueprint).expect("Failed to serialize blueprint");
+
+        fs::write(blueprint_path, json)?;
+
+        Ok(())
+    }
+
+    fn read_source_files(&mut self) -> Result<(), Error> {
+        let lib = self.root.join(paths::lib());
+        let validators = self.root.join(paths::validators());
+
+        self.nano_files(&validators, ModuleKind::Validator)?;
+        self.nano_files(&lib, ModuleKind::Lib)?;
+
+        Ok(())
+    }
+
+    fn parse_sources(&mut self, package_name: PackageName) -> Result<ParsedModules, Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut parsed_modules = HashMap::with_capacity(self.sources.len());
+
+        for Source {
+            path,
+            name,
+            code,
+            kind,
+        } in self.sources.drain(0..)
+        {
+            match nano_lang::parser::module(&code, kind) {
+                Ok((mut ast, extra)) => {
+                    ast.name = name.clone();
+
+                    let module = ParsedModule {
+                        kind,
+                        ast,
+                        code,
+                        name,
+                        path,
+                        extra,
+                        package: package_name.to_string(),
+                    };
+
+                    if let Some((first_package, first)) = self.defined_modules.insert(
+                        module.name.clone(),
+                        (package_name.clone(), module.path.clone()),
+                    ) {
+                        if first_package != package_name {
+                            return Err(Error::DuplicatePackageModule {
+                                module: module.name.clone(),
+                                first: first_package,
+                                second: package_name,
+                            }
+                            .into());
+                        }
+
+                        return Err(Error::DuplicateModule {
+                            module: module.name.clone(),
+                            first,
+                            second: module.path,
+                        }
+                        .into());
+                    }
+
+                    parsed_modules.insert(module.name.clone(), module);
+                }
+                Err(errs) => {
+                    errors.extend(Error::from_parse_errors(errs, &path, &code));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(parsed_modules.into())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn type_check(
+        &mut self,
+        mut parsed_modules: ParsedModules,
+        tracing: Tracing,
+        validate_module_name: bool,
+    ) -> Result<(), Error> {
+        let processing_sequence = parsed_modules.sequence()?;
+
+        for name in processing_sequence {
+            if let Some(ParsedModule {
+                name,
+                path,
+                code,
+                kind,
+                extra,
+                package,
+                ast,
+            }) = parsed_modules.remove(&name)
+            {
+                let mut type_warnings = Vec::new();
+
+                let ast = ast
+                    .infer(
+                        &self.id_gen,
+                        kind,
+                        &package,
+                        &self.module_types,
+                        tracing,
+                        &mut type_warnings,
+                    )
+                    .map_err(|error| Error::Type {
+                        path: path.clone(),
+                        src: code.clone(),
+                        named: NamedSource::new(path.display().to_string(), code.clone()).into(),
+                        error,
+                    })?;
+
+                if validate_module_name {
+                    ast.validate_module_name()
+                        .map_err(|error| Error::InvalidModuleName {
+                            path: path.clone(),
+                            error,
+                        })?;
+                }
+
+                let type_warnings = type_warnings
+                    .into_iter()
+                    .map(|w| Warning::from_type_warning(w, path.clone(), code.clone()));
+
+                self.warnings.extend(type_warnings);
+
+                self.module_types
+                    .insert(name.clone(), ast.type_info.clone());
+
+                let mut checked_module = CheckedModule {
+                    kind,
+                    extra,
+                    name: name.clone(),
+                    code,
+                    ast,
+                    package,
+                    input_path: path,
+                };
+
+                checked_module.attach_doc_and_module_comments();
+
+                self.checked_modules.insert(name, checked_module);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn nano_files(&mut self, dir: &Path, kind: ModuleKind) -> Result<(), Error> {
+        let paths = walkdir::WalkDir::new(dir)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .map(|d| d.into_path())
+            .filter(move |d| is_nano_path(d, dir));
+
+        for path in paths {
+            self.add_module(path, dir, kind)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_module(&mut self, path: PathBuf, dir: &Path, kind: ModuleKind) -> Result<(), Error> {
+        let name = self.module_name(dir, &path);
+
+        let code = fs::read_to_string(&path).map_err(|error| Error::FileIo {
+            path: path.clone(),
+            error,
+        })?;
+
+        self.sources.push(Source {
+            name,
+            code,
+            kind,
+            path,
+        });
+
+        Ok(())
+    }
+
+    fn module_name(&self, package_path: &Path, full_module_path: &Path) -> String {
+        let mut module_path = full_module_path
+            .strip_prefix(package_path)
+            .expect("Stripping package prefix from module path")
+            .to_path_buf();
+
+        module_path.set_extension("");
+
+        let name = module_path
+            .to_str()
+            .expect("Module name path to str")
+            .to_string();
+
+        name.replace('\\', "/")
+    }
+}
+
+fn to_debruijn(program: Program<Name>) -> Program<DeBruijn> {
+    program
+        .try_into()
+        .expect("Failed to convert program to De Bruijn indices")
+}
+
+fn is_nano_path(path: &Path, dir: impl AsRef<Path>) -> bool {
+    use regex::Regex;
+
+    let re = Regex::new(&format!(
+        "^({module}{slash})*{module}\\.nl$",
+        module = "[a-z][_a-z0-9]*",
+        slash = "(/|\\\\)",
+    ))
+    .expect("is_nano_path() RE regex");
+
+    re.is_match(
+        path.strip_prefix(dir)
+            .expect("is_nano_path(): strip_prefix")
+            .to_str()
+            .expect("is_nano_path(): to_str"),
+    )
+}
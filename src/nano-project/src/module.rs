@@ -0,0 +1,396 @@
+use crate::error::Error;
+use indexmap::IndexMap;
+use nano_lang::{
+    ast::{
+        DataType, Definition, ModuleKind, TypedDataType, TypedFunction, TypedModule,
+        TypedValidator, UntypedModule,
+    },
+    gen_uplc::{
+        builder::{ConstantAccessKey, DataTypeKey, FunctionAccessKey},
+        CodeGenerator,
+    },
+    parser::extra::{comments_before, Comment, ModuleExtra},
+    tipo::TypeInfo,
+};
+use petgraph::{algo, graph::NodeIndex, Direction, Graph};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+};
+
+#[derive(Debug)]
+pub struct ParsedModule {
+    pub path: PathBuf,
+    pub name: String,
+    pub code: String,
+    pub kind: ModuleKind,
+    pub package: String,
+    pub ast: UntypedModule,
+    pub extra: ModuleExtra,
+}
+
+impl ParsedModule {
+    /// The module's name along with the modules it imports. Imports may be
+    /// prefixed with the package they come from (e.g. `owner/repo/module`);
+    /// any prefix naming one of the given packages is stripped so that the
+    /// dependency refers to the module itself.
+    pub fn deps_for_graph(&self, packages: &HashSet<&str>) -> (String, Vec<String>) {
+        let name = self.name.clone();
+
+        let deps: Vec<_> = self
+            .ast
+            .dependencies()
+            .into_iter()
+            .map(|(dep, _span)| {
+                let local = dep
+                    .match_indices('/')
+                    .nth(1)
+                    .map(|(index, _)| dep.split_at(index))
+                    .filter(|(package, _)| packages.contains(package))
+                    .map(|(_, local)| local[1..].to_string());
+
+                local.unwrap_or(dep)
+            })
+            .collect();
+
+        (name, deps)
+    }
+}
+
+pub struct ParsedModules(HashMap<String, ParsedModule>);
+
+impl ParsedModules {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    pub fn sequence(&self) -> Result<Vec<String>, Error> {
+        let packages = self.0.values().map(|m| m.package.as_str()).collect();
+
+        let inputs = self
+            .0
+            .values()
+            .map(|m| m.deps_for_graph(&packages))
+            .collect::<Vec<(String, Vec<String>)>>();
+
+        let capacity = inputs.len();
+
+        let mut graph = Graph::<(), ()>::with_capacity(capacity, capacity * 5);
+
+        let mut indices = HashMap::with_capacity(capacity);
+
+        let mut values = HashMap::with_capacity(capacity);
+
+        for (value, _) in &inputs {
+            let index = graph.add_node(());
+
+            indices.insert(value.clone(), index);
+
+            values.insert(index, value.clone());
+        }
+
+        for (value, deps) in inputs {
+            if let Some(from_index) = indices.get(&value) {
+                let deps = deps.into_iter().filter_map(|dep| indices.get(&dep));
+
+                for to_index in deps {
+                    graph.add_edge(*from_index, *to_index, ());
+                }
+            }
+        }
+
+        match algo::toposort(&graph, None) {
+            Ok(sequence) => {
+                let sequence = sequence
+                    .iter()
+                    .filter_map(|i| values.remove(i))
+                    .rev()
+                    .collect();
+
+                Ok(sequence)
+            }
+            Err(cycle) => {
+                let origin = cycle.node_id();
+
+                let mut path = vec![];
+
+                find_cycle(origin, origin, &graph, &mut path, &mut HashSet::new());
+
+                let modules = path
+                    .iter()
+                    .filter_map(|index| values.remove(index))
+                    .collect();
+
+                Err(Error::ImportCycle { modules })
+            }
+        }
+    }
+}
+
+impl Default for ParsedModules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<HashMap<String, ParsedModule>> for ParsedModules {
+    fn from(parsed_modules: HashMap<String, ParsedModule>) -> Self {
+        ParsedModules(parsed_modules)
+    }
+}
+
+impl From<ParsedModules> for HashMap<String, ParsedModule> {
+    fn from(parsed_modules: ParsedModules) -> Self {
+        parsed_modules.0
+    }
+}
+
+impl Deref for ParsedModules {
+    type Target = HashMap<String, ParsedModule>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ParsedModules {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+fn find_cycle(
+    origin: NodeIndex,
+    parent: NodeIndex,
+    graph: &petgraph::Graph<(), ()>,
+    path: &mut Vec<NodeIndex>,
+    seen: &mut HashSet<NodeIndex>,
+) -> bool {
+    seen.insert(parent);
+
+    for node in graph.neighbors_directed(parent, Direction::Outgoing) {
+        if node == origin {
+            path.push(node);
+
+            return true;
+        }
+
+        if seen.contains(&node) {
+            continue;
+        }
+
+        if find_cycle(origin, node, graph, path, seen) {
+            path.push(node);
+
+            return true;
+        }
+    }
+
+    false
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckedModule {
+    pub name: String,
+    pub code: String,
+    pub input_path: PathBuf,
+    pub kind: ModuleKind,
+    pub package: String,
+    pub ast: TypedModule,
+    pub extra: ModuleExtra,
+}
+
+impl CheckedModule {
+    pub fn attach_doc_and_module_comments(&mut self) {
+        self.ast.docs = self
+            .extra
+            .module_comments
+            .iter()
+            .map(|span| Comment::from((span, self.code.as_str())).content.to_string())
+            .collect();
+
+        let mut definitions: Vec<_> = self.ast.definitions.iter_mut().collect();
+
+        definitions.sort_by_key(|def| def.location().start);
+
+        let mut doc_comments = self.extra.doc_comments.iter().peekable();
+
+        for def in &mut definitions {
+            let docs: Vec<&str> =
+                comments_before(&mut doc_comments, def.location().start, &self.code);
+
+            if !docs.is_empty() {
+                let doc = docs.join("\n");
+
+                def.put_doc(doc);
+            }
+
+            if let Definition::DataType(DataType { constructors, .. }) = def {
+                for constructor in constructors {
+                    let docs: Vec<&str> =
+                        comments_before(&mut doc_comments, constructor.location.start, &self.code);
+
+                    if !docs.is_empty() {
+                        let doc = docs.join("\n");
+
+                        constructor.put_doc(doc);
+                    }
+
+                    for argument in constructor.arguments.iter_mut() {
+                        let docs: Vec<&str> =
+                            comments_before(&mut doc_comments, argument.location.start, &self.code);
+
+                        if !docs.is_empty() {
+                            let doc = docs.join("\n");
+
+                            argument.put_doc(doc);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckedModules(HashMap<String, CheckedModule>);
+
+impl From<HashMap<String, CheckedModule>> for CheckedModules {
+    fn from(checked_modules: HashMap<String, CheckedModule>) -> Self {
+        CheckedModules(checked_modules)
+    }
+}
+
+impl From<CheckedModules> for HashMap<String, CheckedModule> {
+    fn from(checked_modules: CheckedModules) -> Self {
+        checked_modules.0
+    }
+}
+
+impl CheckedModules {
+    pub fn validators(&self) -> impl Iterator<Item = (&CheckedModule, &TypedValidator)> {
+        let mut items = vec![];
+
+        for validator_module in self.0.values().filter(|module| module.kind.is_validator()) {
+            for some_definition in validator_module.ast.definitions() {
+                if let Definition::Validator(def) = some_definition {
+                    items.push((validator_module, def));
+                }
+            }
+        }
+
+        items.sort_by(|left, right| {
+            (
+                left.0.package.to_string(),
+                left.0.name.to_string(),
+                left.1.fun.name.to_string(),
+            )
+                .cmp(&(
+                    right.0.package.to_string(),
+                    right.0.name.to_string(),
+                    right.1.fun.name.to_string(),
+                ))
+        });
+
+        items.into_iter()
+    }
+
+    pub fn into_validators(self) -> impl Iterator<Item = CheckedModule> {
+        self.0
+            .into_values()
+            .filter(|module| module.kind.is_validator())
+    }
+
+    pub fn data_types<'a>(
+        &'a self,
+        builtin_data_types: &'a IndexMap<DataTypeKey, TypedDataType>,
+    ) -> IndexMap<DataTypeKey, &'a TypedDataType> {
+        let mut data_types = IndexMap::new();
+        for (k, v) in builtin_data_types {
+            data_types.insert(k.clone(), v);
+        }
+
+        for module in self.values() {
+            for def in module.ast.definitions() {
+                if let Definition::DataType(dt) = def {
+                    data_types.insert(
+                        DataTypeKey {
+                            module_name: module.name.clone(),
+                            defined_type: dt.name.clone(),
+                        },
+                        dt,
+                    );
+                }
+            }
+        }
+
+        data_types
+    }
+
+    pub fn new_generator<'a>(
+        &'a self,
+        builtin_functions: &'a IndexMap<FunctionAccessKey, TypedFunction>,
+        builtin_data_types: &'a IndexMap<DataTypeKey, TypedDataType>,
+        module_types: &'a HashMap<String, TypeInfo>,
+    ) -> CodeGenerator<'a> {
+        let mut functions = IndexMap::new();
+        for (k, v) in builtin_functions {
+            functions.insert(k.clone(), v);
+        }
+
+        let mut constants = IndexMap::new();
+
+        for module in self.values() {
+            for def in module.ast.definitions() {
+                let funcs = match def {
+                    Definition::Fn(func) => std::slice::from_ref(func),
+                    Definition::Implementation(implementation) => &implementation.methods[..],
+                    Definition::ModuleConstant(constant) => {
+                        constants.insert(
+                            ConstantAccessKey {
+                                module_name: module.name.clone(),
+                                constant_name: constant.name.clone(),
+                            },
+                            constant,
+                        );
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                for func in funcs {
+                    functions.insert(
+                        FunctionAccessKey {
+                            module_name: module.name.clone(),
+                            function_name: func.name.clone(),
+                            variant_name: String::new(),
+                        },
+                        func,
+                    );
+                }
+            }
+        }
+
+        let data_types = self.data_types(builtin_data_types);
+
+        let mut module_types_index = IndexMap::new();
+        module_types_index.extend(module_types);
+
+        CodeGenerator::new(functions, data_types, module_types_index, constants)
+    }
+}
+
+impl Deref for CheckedModules {
+    type Target = HashMap<String, CheckedModule>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for CheckedModules {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -1,4 +1,6 @@
+use crate::test_framework::property::PropertyConfig;
 use nano_lang::ast::Tracing;
+use untyped_plutus_core::machine::cost_model::ExBudget;
 
 pub struct Options {
     pub code_gen_mode: CodeGenMode,
@@ -6,11 +8,21 @@ pub struct Options {
 }
 
 pub enum CodeGenMode {
-    Test {
-        match_tests: Option<Vec<String>>,
-        verbose: bool,
-        exact_match: bool,
-    },
+    Test(TestOptions),
     Build(bool),
     NoOp,
+}
+
+/// Which tests to run, and how.
+#[derive(Debug, Clone, Default)]
+pub struct TestOptions {
+    pub match_tests: Option<Vec<String>>,
+    pub verbose: bool,
+    pub exact_match: bool,
+    pub budget: ExBudget,
+    pub property: PropertyConfig,
+    /// Whether to write out where the tests spend their budget.
+    pub profile: bool,
+    /// Whether to write out what code the tests run, as an lcov report.
+    pub coverage: bool,
 }
\ No newline at end of file
@@ -6,7 +6,7 @@ use std::{
 };
 use thiserror::Error;
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct PackageName {
     pub owner: String,
     pub repo: String,
@@ -0,0 +1,71 @@
+use crate::package_name::PackageName;
+use std::{env, path::PathBuf};
+
+pub fn project_config() -> PathBuf {
+    PathBuf::from("nano.toml")
+}
+
+pub fn manifest() -> PathBuf {
+    PathBuf::from("nano.lock")
+}
+
+pub fn blueprint() -> PathBuf {
+    PathBuf::from("plutus.json")
+}
+
+pub fn docs() -> PathBuf {
+    PathBuf::from("docs")
+}
+
+pub fn lib() -> PathBuf {
+    PathBuf::from("lib")
+}
+
+pub fn validators() -> PathBuf {
+    PathBuf::from("validators")
+}
+
+pub fn build() -> PathBuf {
+    PathBuf::from("build")
+}
+
+pub fn packages() -> PathBuf {
+    build().join("packages")
+}
+
+pub fn packages_toml() -> PathBuf {
+    packages().join("packages.toml")
+}
+
+pub fn build_deps_package(package_name: &PackageName) -> PathBuf {
+    packages().join(format!("{}-{}", package_name.owner, package_name.repo))
+}
+
+pub fn packages_cache() -> PathBuf {
+    match env::var_os("NANO_PACKAGES_CACHE") {
+        Some(path) => PathBuf::from(path),
+        None => default_nano_cache().join("packages"),
+    }
+}
+
+pub fn default_nano_cache() -> PathBuf {
+    dirs::cache_dir()
+        .expect("Failed to determine user cache directory")
+        .join("nano")
+}
+
+pub fn build_profile() -> PathBuf {
+    build().join("profile")
+}
+
+pub fn build_coverage() -> PathBuf {
+    build().join("lcov.info")
+}
+
+pub fn build_validators() -> PathBuf {
+    build().join("validators")
+}
+
+pub fn build_validator(module: &str, name: &str) -> PathBuf {
+    build_validators().join(module).join(name)
+}
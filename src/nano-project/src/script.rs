@@ -0,0 +1,37 @@
+use nano_lang::ast::TypedArg;
+use std::path::PathBuf;
+use untyped_plutus_core::ast::{DeBruijn, Name, Program};
+
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub input_path: PathBuf,
+    pub module: String,
+    pub name: String,
+    pub program: Program<Name>,
+    pub params: Vec<TypedArg>,
+}
+
+impl Script {
+    pub fn new(
+        input_path: PathBuf,
+        module: String,
+        name: String,
+        program: Program<Name>,
+        params: Vec<TypedArg>,
+    ) -> Script {
+        Script {
+            input_path,
+            module,
+            name,
+            program,
+            params,
+        }
+    }
+
+    pub fn to_debruijn(&self) -> Program<DeBruijn> {
+        self.program
+            .clone()
+            .try_into()
+            .expect("Failed to convert compiled program to DeBruijn indices")
+    }
+}
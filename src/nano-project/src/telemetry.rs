@@ -0,0 +1,57 @@
+use crate::test_framework::TestResult;
+use std::path::PathBuf;
+use tokio::time::Instant;
+
+pub trait EventListener {
+    fn handle_event(&self, _event: Event) {}
+}
+
+pub enum Event {
+    StartingCompilation {
+        name: String,
+        version: String,
+        root: PathBuf,
+    },
+    GeneratingUPLC {
+        output_path: PathBuf,
+    },
+    GeneratingUPLCFor {
+        name: String,
+        path: PathBuf,
+    },
+    GeneratingBlueprint {
+        path: PathBuf,
+    },
+    BuildingDocumentation {
+        name: String,
+        version: String,
+        root: PathBuf,
+    },
+    GeneratingDocFiles {
+        output_path: PathBuf,
+    },
+    RunningTests,
+    FinishedTests {
+        tests: Vec<TestResult>,
+    },
+    GeneratingProfile {
+        output_path: PathBuf,
+    },
+    GeneratingCoverage {
+        output_path: PathBuf,
+    },
+    WaitingForBuildDirLock,
+    ResolvingVersions,
+    PinningPackageHash {
+        name: String,
+        version: String,
+        hash: String,
+    },
+    DownloadingPackage {
+        name: String,
+    },
+    PackagesDownloaded {
+        start: Instant,
+        count: usize,
+    },
+}
@@ -0,0 +1,376 @@
+pub mod coverage;
+pub mod property;
+
+use coverage::Coverage;
+use nano_lang::{
+    ast::BinOp,
+    gen_uplc::{
+        schema::{Annotated, Definitions, Schema},
+        source_map::SourceMap,
+    },
+};
+use property::PropertyConfig;
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+};
+use untyped_plutus_core::{
+    ast::{DeBruijn, NamedDeBruijn, Program, Term},
+    machine::{
+        self,
+        cost_model::ExBudget,
+        coverage::Hits,
+        debugger::Observer,
+        profiler::{Profile, Profiler},
+    },
+    PlutusData,
+};
+
+/// A compiled `test` definition, ready to be evaluated.
+#[derive(Debug, Clone)]
+pub struct UnitTest {
+    pub input_path: PathBuf,
+    pub module: String,
+    pub name: String,
+    pub program: Program<DeBruijn>,
+    pub evaluation_hint: Option<EvalHint>,
+    /// Names and schemas of the test's arguments, from which inputs are
+    /// generated. Tests without arguments are evaluated once, as they are.
+    pub inputs: Vec<(String, Annotated<Schema>)>,
+    pub definitions: Definitions,
+    /// Where the terms of the program come from, when asked for.
+    pub source_map: Option<SourceMap>,
+}
+
+/// The two sides of a test ending with a binary operator, compiled separately
+/// so that a failure can show what each side evaluated to.
+#[derive(Debug, Clone)]
+pub struct EvalHint {
+    pub bin_op: BinOp,
+    pub left: Program<DeBruijn>,
+    pub right: Program<DeBruijn>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub test: UnitTest,
+    pub success: bool,
+    /// For property tests, the budget of the most expensive run.
+    pub spent_budget: ExBudget,
+    pub logs: Vec<String>,
+    pub output: Option<String>,
+    /// The number of inputs a property test was run with before it failed,
+    /// or all of them.
+    pub iterations: usize,
+    /// The inputs a property test failed with, once shrunk.
+    pub counterexample: Option<Vec<PlutusData>>,
+    /// What the test spent under each function, when recorded.
+    pub profile: Profile,
+    /// How many times the test computed each span of code, when recorded.
+    pub coverage: Coverage,
+}
+
+/// What to record about a test, on top of its result, as it runs. Only tests
+/// compiled with a source map can be recorded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Recording {
+    pub profile: bool,
+    pub coverage: bool,
+}
+
+impl UnitTest {
+    pub fn title(&self) -> String {
+        format!("{}.{}", self.module, self.name)
+    }
+
+    pub fn is_property(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+
+    /// Renders values given for the test's arguments, one per line.
+    pub fn display_inputs(&self, values: &[PlutusData]) -> String {
+        self.inputs
+            .iter()
+            .zip(values)
+            .map(|((name, schema), value)| {
+                format!(
+                    "{name} = {}",
+                    property::display_annotated(schema, &self.definitions, value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl EvalHint {
+    pub fn apply_data(&self, values: &[PlutusData]) -> Self {
+        EvalHint {
+            bin_op: self.bin_op,
+            left: apply_data(&self.left, values),
+            right: apply_data(&self.right, values),
+        }
+    }
+}
+
+struct Evaluation {
+    success: bool,
+    spent_budget: ExBudget,
+    logs: Vec<String>,
+    output: Option<String>,
+}
+
+/// Evaluates every test under the given budget, in parallel. Terms share their
+/// nodes through `Rc` and can't cross threads, so each program travels to its
+/// worker in its flat encoding.
+///
+/// Recorded tests are observed as they run with their generated inputs, not
+/// while a counterexample is shrunk. Frames are named after the function
+/// their code is part of.
+pub fn run(
+    tests: Vec<UnitTest>,
+    budget: ExBudget,
+    config: PropertyConfig,
+    recording: Recording,
+) -> Vec<TestResult> {
+    let jobs = tests
+        .iter()
+        .map(|test| {
+            let bytes = test
+                .program
+                .to_flat()
+                .expect("Failed to encode test program");
+
+            let inputs = test
+                .inputs
+                .iter()
+                .map(|(_, schema)| schema.clone())
+                .collect::<Vec<_>>();
+
+            (
+                bytes,
+                inputs,
+                test.definitions.clone(),
+                test.title(),
+                test.source_map.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let outcomes = jobs
+        .into_par_iter()
+        .map(|(bytes, inputs, definitions, title, source_map)| {
+            let program =
+                Program::<DeBruijn>::from_flat(&bytes).expect("Failed to decode test program");
+
+            // Each input is applied around the program, which comes right
+            // after the applications.
+            let offset = inputs.len();
+
+            let mut profiler = Profiler::new(&title, |position| {
+                let location = source_map
+                    .as_ref()?
+                    .enclosing(position.checked_sub(offset)?)?;
+
+                Some(format!("{}.{}", location.module, location.function))
+            });
+
+            let mut hits = Hits::new();
+
+            let mut observers: Vec<&mut dyn Observer> = vec![];
+
+            if source_map.is_some() {
+                if recording.profile {
+                    observers.push(&mut profiler);
+                }
+
+                if recording.coverage {
+                    observers.push(&mut hits);
+                }
+            }
+
+            let (evaluation, iterations, counterexample) = if inputs.is_empty() {
+                (evaluate(&program, &[], budget, &mut observers), 1, None)
+            } else {
+                check(
+                    &program,
+                    &inputs,
+                    &definitions,
+                    budget,
+                    config,
+                    &mut observers,
+                )
+            };
+
+            let mut coverage = Coverage::default();
+
+            if let (Some(source_map), true) = (&source_map, recording.coverage) {
+                coverage.record(source_map, &hits, offset);
+            }
+
+            (
+                evaluation,
+                iterations,
+                counterexample,
+                profiler.profile,
+                coverage,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    tests
+        .into_iter()
+        .zip(outcomes)
+        .map(
+            |(test, (evaluation, iterations, counterexample, profile, coverage))| TestResult {
+                test,
+                success: evaluation.success,
+                spent_budget: evaluation.spent_budget,
+                logs: evaluation.logs,
+                output: evaluation.output,
+                iterations,
+                counterexample,
+                profile,
+                coverage,
+            },
+        )
+        .collect()
+}
+
+/// What the tests spent under each function, all together.
+pub fn profile(results: &[TestResult]) -> Profile {
+    let mut profile = Profile::default();
+
+    for result in results {
+        profile.merge(result.profile.clone());
+    }
+
+    profile
+}
+
+/// How many times the tests computed each span of code, all together.
+pub fn coverage(results: &[TestResult]) -> Coverage {
+    let mut coverage = Coverage::default();
+
+    for result in results {
+        coverage.merge(&result.coverage);
+    }
+
+    coverage
+}
+
+/// Runs a property test with generated inputs until it fails, in which case
+/// the failing inputs are shrunk, or until enough inputs were tried.
+fn check(
+    program: &Program<DeBruijn>,
+    inputs: &[Annotated<Schema>],
+    definitions: &Definitions,
+    budget: ExBudget,
+    config: PropertyConfig,
+    observers: &mut [&mut dyn Observer],
+) -> (Evaluation, usize, Option<Vec<PlutusData>>) {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut spent_budget = ExBudget { mem: 0, cpu: 0 };
+
+    for iteration in 0..config.max_success {
+        let size = property::size(iteration, &config);
+
+        let values = inputs
+            .iter()
+            .map(|input| property::generate(input, definitions, size, &mut rng))
+            .collect::<Vec<_>>();
+
+        let evaluation = evaluate(program, &values, budget, observers);
+
+        if !evaluation.success {
+            let (values, evaluation, _) =
+                property::shrink(inputs, definitions, values, evaluation, |values| {
+                    let evaluation = evaluate(program, values, budget, &mut []);
+
+                    (!evaluation.success).then_some(evaluation)
+                });
+
+            return (evaluation, iteration + 1, Some(values));
+        }
+
+        spent_budget = ExBudget {
+            mem: spent_budget.mem.max(evaluation.spent_budget.mem),
+            cpu: spent_budget.cpu.max(evaluation.spent_budget.cpu),
+        };
+    }
+
+    let evaluation = Evaluation {
+        success: true,
+        spent_budget,
+        logs: vec![],
+        output: None,
+    };
+
+    (evaluation, config.max_success, None)
+}
+
+/// Evaluates a program with the given inputs. Observers, if any, follow the
+/// evaluation a step at a time.
+fn evaluate(
+    program: &Program<DeBruijn>,
+    values: &[PlutusData],
+    budget: ExBudget,
+    observers: &mut [&mut dyn Observer],
+) -> Evaluation {
+    let program = apply_data(program, values);
+
+    let mut eval_result = if observers.is_empty() {
+        program.eval(budget)
+    } else {
+        program.eval_observed(budget, observers)
+    };
+
+    Evaluation {
+        success: !eval_result.failed(),
+        spent_budget: eval_result.cost(),
+        logs: eval_result.logs(),
+        output: eval_result.result().ok().map(|term| term.to_string()),
+    }
+}
+
+fn apply_data(program: &Program<DeBruijn>, values: &[PlutusData]) -> Program<DeBruijn> {
+    values.iter().fold(program.clone(), |program, value| {
+        program.apply_data(value.clone())
+    })
+}
+
+impl Display for EvalHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let budget = ExBudget {
+            mem: i64::MAX,
+            cpu: i64::MAX,
+        };
+
+        let left = display_result(self.left.eval(budget).result());
+        let right = display_result(self.right.eval(budget).result());
+
+        match self.bin_op {
+            BinOp::And => write!(f, "{left}\n\nand\n\n{right}\n\nshould both be true."),
+            BinOp::Or => write!(f, "{left}\n\nor\n\n{right}\n\nshould be true."),
+            BinOp::Eq => write!(f, "{left}\n\nshould be equal to\n\n{right}"),
+            BinOp::NotEq => write!(f, "{left}\n\nshould not be equal to\n\n{right}"),
+            BinOp::LtInt => write!(f, "{left}\n\nshould be lower than\n\n{right}"),
+            BinOp::LtEqInt => write!(f, "{left}\n\nshould be lower than or equal to\n\n{right}"),
+            BinOp::GtEqInt => {
+                write!(f, "{left}\n\nshould be greater than or equal to\n\n{right}")
+            }
+            BinOp::GtInt => write!(f, "{left}\n\nshould be greater than\n\n{right}"),
+            _ => write!(f, "{left}\n\n{right}"),
+        }
+    }
+}
+
+fn display_result(result: Result<Term<NamedDeBruijn>, machine::Error>) -> String {
+    match result {
+        Ok(term) => term.to_string(),
+        Err(error) => format!("<error: {error}>"),
+    }
+}
@@ -0,0 +1,195 @@
+use nano_lang::{
+    ast::{Definition, Span, TypedFunction},
+    gen_uplc::source_map::SourceMap,
+};
+use std::collections::{BTreeMap, HashMap};
+use untyped_plutus_core::machine::coverage::Hits;
+
+use crate::module::CheckedModule;
+
+/// How many times the tests computed the terms coming from each span of
+/// source code, by module.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    modules: HashMap<String, BTreeMap<(usize, usize), usize>>,
+}
+
+impl Coverage {
+    /// Adds up the hits of a run, where the program of the source map starts
+    /// `offset` positions into the program that was run.
+    pub fn record(&mut self, source_map: &SourceMap, hits: &Hits, offset: usize) {
+        for (position, location) in source_map.iter() {
+            let count = self
+                .modules
+                .entry(location.module.clone())
+                .or_default()
+                .entry((location.span.start, location.span.end))
+                .or_insert(0);
+
+            *count += hits.get(&(position + offset)).copied().unwrap_or(0);
+        }
+    }
+
+    /// Adds up the counts of another coverage.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (module, spans) in &other.modules {
+            let counts = self.modules.entry(module.clone()).or_default();
+
+            for (span, count) in spans {
+                *counts.entry(*span).or_insert(0) += count;
+            }
+        }
+    }
+
+    /// How many times the code of a span was entered, which is as many times
+    /// as the terms it starts with were computed.
+    fn entries(&self, module: &str, span: Span) -> usize {
+        self.modules.get(module).map_or(0, |spans| {
+            spans
+                .range((span.start, span.start)..=(span.start, span.end))
+                .map(|(_, count)| *count)
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    /// An lcov report on the functions and validators of the modules, with
+    /// the branches taken by each `when` and `if` in them.
+    pub fn lcov<'a>(&self, modules: impl IntoIterator<Item = &'a CheckedModule>) -> String {
+        modules
+            .into_iter()
+            .map(|module| self.lcov_record(module))
+            .collect()
+    }
+
+    fn lcov_record(&self, module: &CheckedModule) -> String {
+        let line = |offset: usize| {
+            module
+                .code
+                .bytes()
+                .take(offset)
+                .filter(|byte| *byte == b'\n')
+                .count()
+                + 1
+        };
+
+        let mut functions: Vec<(String, &TypedFunction)> = vec![];
+
+        for definition in module.ast.definitions() {
+            match definition {
+                Definition::Fn(function) => functions.push((function.name.clone(), function)),
+                Definition::Validator(validator) => {
+                    for (_, function) in validator.handlers() {
+                        functions.push((function.name.clone(), function));
+                    }
+                }
+                // Methods of different implementations share their names.
+                Definition::Implementation(implementation) => {
+                    for method in &implementation.methods {
+                        let name = format!(
+                            "{}.{}@{}",
+                            implementation.interface,
+                            method.name,
+                            line(method.location.start)
+                        );
+
+                        functions.push((name, method));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut record = vec![];
+
+        record.push("TN:".to_string());
+        record.push(format!("SF:{}", module.input_path.display()));
+
+        let mut lines = BTreeMap::new();
+        let mut functions_hit = 0;
+
+        for (name, function) in &functions {
+            let entries = self.entries(&module.name, function.body.location());
+
+            record.push(format!("FN:{},{}", line(function.location.start), name));
+            record.push(format!("FNDA:{},{}", entries, name));
+
+            let count = lines.entry(line(function.location.start)).or_insert(0);
+            *count = entries.max(*count);
+
+            if entries > 0 {
+                functions_hit += 1;
+            }
+        }
+
+        record.push(format!("FNF:{}", functions.len()));
+        record.push(format!("FNH:{}", functions_hit));
+
+        let mut points = vec![];
+
+        for (_, function) in &functions {
+            function.body.branch_points(&mut points);
+        }
+
+        let mut branches = 0;
+        let mut branches_hit = 0;
+
+        for (block, (point, bodies)) in points.into_iter().enumerate() {
+            let entered = self.entries(&module.name, point) > 0;
+
+            for (branch, body) in bodies.into_iter().enumerate() {
+                // Branches of a `when` or `if` that was never reached weren't
+                // so much not taken as never considered.
+                let taken = if entered {
+                    let taken = self.entries(&module.name, body);
+
+                    if taken > 0 {
+                        branches_hit += 1;
+                    }
+
+                    taken.to_string()
+                } else {
+                    "-".to_string()
+                };
+
+                record.push(format!(
+                    "BRDA:{},{},{},{}",
+                    line(point.start),
+                    block,
+                    branch,
+                    taken
+                ));
+
+                branches += 1;
+            }
+        }
+
+        record.push(format!("BRF:{}", branches));
+        record.push(format!("BRH:{}", branches_hit));
+
+        // Only the code of functions counts, leaving out that of tests.
+        for ((start, _), hits) in self.modules.get(&module.name).into_iter().flatten() {
+            let in_function = functions.iter().any(|(_, function)| {
+                (function.location.start..function.end_position).contains(start)
+            });
+
+            if in_function {
+                let count = lines.entry(line(*start)).or_insert(0);
+                *count = (*hits).max(*count);
+            }
+        }
+
+        for (line, count) in &lines {
+            record.push(format!("DA:{},{}", line, count));
+        }
+
+        record.push(format!("LF:{}", lines.len()));
+        record.push(format!(
+            "LH:{}",
+            lines.values().filter(|count| **count > 0).count()
+        ));
+        record.push("end_of_record\n".to_string());
+
+        record.join("\n")
+    }
+}
@@ -0,0 +1,616 @@
+use nano_lang::gen_uplc::schema::{Annotated, Definitions, Items, Schema};
+use num_bigint::BigInt;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use untyped_plutus_core::{
+    ast::Data,
+    machine::{runtime::convert_tag_to_constr, value::from_pallas_bigint},
+    PlutusData,
+};
+
+/// How property tests are run.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyConfig {
+    /// Seed of the input generator; runs sharing a seed generate the same inputs.
+    pub seed: u64,
+    /// Number of generated inputs a property must hold for.
+    pub max_success: usize,
+}
+
+impl Default for PropertyConfig {
+    fn default() -> Self {
+        PropertyConfig {
+            seed: rand::random(),
+            max_success: 100,
+        }
+    }
+}
+
+/// Upper bound of the size inputs grow to. The size bounds the magnitude of
+/// integers and the length of lists and bytes, and halves at every level of
+/// nesting.
+const MAX_SIZE: usize = 32;
+
+/// Upper bound of successful shrinking steps, in case shrinking doesn't
+/// converge.
+const MAX_SHRINKS: usize = 1000;
+
+/// The size of the inputs generated at a given iteration, growing linearly
+/// from 0 to [`MAX_SIZE`] over a run.
+pub fn size(iteration: usize, config: &PropertyConfig) -> usize {
+    iteration * MAX_SIZE / config.max_success.max(1)
+}
+
+/// Generates a random value laid out as described by the schema. `String`s
+/// are generated as UTF-8 text, any other bytes are arbitrary.
+pub fn generate(
+    schema: &Annotated<Schema>,
+    definitions: &Definitions,
+    size: usize,
+    rng: &mut StdRng,
+) -> PlutusData {
+    match &schema.annotated {
+        Schema::Integer => Data::integer(generate_integer(size, rng)),
+
+        Schema::Bytes if is_text(schema) => Data::bytestring(generate_text(size, rng).into_bytes()),
+
+        Schema::Bytes => Data::bytestring(generate_bytes(size, rng)),
+
+        Schema::List(Items::One(item)) => {
+            let len = rng.gen_range(0..=size);
+
+            Data::list(
+                (0..len)
+                    .map(|_| generate(item, definitions, size / 2, rng))
+                    .collect(),
+            )
+        }
+
+        Schema::List(Items::Many(items)) => Data::list(
+            items
+                .iter()
+                .map(|item| generate(item, definitions, size / 2, rng))
+                .collect(),
+        ),
+
+        Schema::Map(keys, values) => {
+            let len = rng.gen_range(0..=size);
+
+            Data::map(
+                (0..len)
+                    .map(|_| {
+                        (
+                            generate(keys, definitions, size / 2, rng),
+                            generate(values, definitions, size / 2, rng),
+                        )
+                    })
+                    .collect(),
+            )
+        }
+
+        Schema::AnyOf(constructors) => {
+            // Once out of size, the constructor with the fewest fields is
+            // picked, which is the base case of recursive types.
+            let constructor = if size == 0 {
+                constructors
+                    .iter()
+                    .min_by_key(|constructor| constructor.annotated.fields.len())
+            } else {
+                constructors.choose(rng)
+            }
+            .expect("Data type without constructors");
+
+            Data::constr(
+                constructor.annotated.index as u64,
+                constructor
+                    .annotated
+                    .fields
+                    .iter()
+                    .map(|field| generate(field, definitions, size / 2, rng))
+                    .collect(),
+            )
+        }
+
+        Schema::Ref(key) => generate(lookup(definitions, key), definitions, size, rng),
+
+        Schema::Opaque => {
+            let kind = if size == 0 { 0 } else { rng.gen_range(0..5) };
+
+            let opaque = || Box::new(Schema::Opaque.into());
+
+            match kind {
+                0 => generate(&Schema::Integer.into(), definitions, size, rng),
+                1 => generate(&Schema::Bytes.into(), definitions, size, rng),
+                2 => generate(
+                    &Schema::List(Items::One(opaque())).into(),
+                    definitions,
+                    size,
+                    rng,
+                ),
+                3 => generate(
+                    &Schema::Map(opaque(), opaque()).into(),
+                    definitions,
+                    size,
+                    rng,
+                ),
+                _ => {
+                    let len = rng.gen_range(0..=size);
+
+                    Data::constr(
+                        rng.gen_range(0..8),
+                        (0..len)
+                            .map(|_| generate(&Schema::Opaque.into(), definitions, size / 2, rng))
+                            .collect(),
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Whether the schema is the one of a `String`, whose bytes must remain valid
+/// UTF-8.
+fn is_text(schema: &Annotated<Schema>) -> bool {
+    matches!(
+        (&schema.title, &schema.annotated),
+        (Some(title), Schema::Bytes) if title == "String"
+    )
+}
+
+fn generate_integer(size: usize, rng: &mut StdRng) -> BigInt {
+    match rng.gen_range(0..10) {
+        0 => [0, 1, -1, i64::MAX, i64::MIN]
+            .choose(rng)
+            .copied()
+            .map(BigInt::from)
+            .unwrap_or_default(),
+        1 => BigInt::from(rng.gen::<i128>()),
+        _ => {
+            let bound = (size * size) as i64 + 1;
+
+            BigInt::from(rng.gen_range(-bound..=bound))
+        }
+    }
+}
+
+fn generate_text(size: usize, rng: &mut StdRng) -> String {
+    let len = rng.gen_range(0..=size);
+
+    (0..len)
+        .map(|_| {
+            if rng.gen_bool(0.75) {
+                rng.gen_range(0x20u8..0x7f) as char
+            } else {
+                rng.gen::<char>()
+            }
+        })
+        .collect()
+}
+
+/// Arbitrary bytes. Every so often, the bytes are one of the edge cases
+/// properties on bytes tend to overlook: the empty bytes, bytes made of 0x00
+/// or 0xff only, and sequences that aren't valid UTF-8.
+fn generate_bytes(size: usize, rng: &mut StdRng) -> Vec<u8> {
+    let len = rng.gen_range(0..=size);
+
+    match rng.gen_range(0..10) {
+        0 => vec![0x00; len],
+        1 => vec![0xff; len],
+        2 => [
+            vec![],
+            vec![0x80],
+            vec![0xc3, 0x28],
+            vec![0xe2, 0x28, 0xa1],
+            vec![0xf0, 0x28, 0x8c, 0x28],
+        ]
+        .choose(rng)
+        .cloned()
+        .unwrap_or_default(),
+        _ => (0..len).map(|_| rng.gen()).collect(),
+    }
+}
+
+/// Repeatedly replaces one of the values by a simpler one, for as long as the
+/// property keeps failing with it. `fails` evaluates the property, returning
+/// the outcome of a failure. Returns the simplest failing values found, along
+/// with the outcome of their failure and the number of steps it took.
+pub fn shrink<T>(
+    inputs: &[Annotated<Schema>],
+    definitions: &Definitions,
+    mut values: Vec<PlutusData>,
+    mut failure: T,
+    fails: impl Fn(&[PlutusData]) -> Option<T>,
+) -> (Vec<PlutusData>, T, usize) {
+    let mut steps = 0;
+
+    'search: while steps < MAX_SHRINKS {
+        for (index, input) in inputs.iter().enumerate() {
+            for candidate in shrink_value(&input.annotated, definitions, &values[index]) {
+                let mut next = values.clone();
+
+                next[index] = candidate;
+
+                if let Some(next_failure) = fails(&next) {
+                    values = next;
+                    failure = next_failure;
+                    steps += 1;
+
+                    continue 'search;
+                }
+            }
+        }
+
+        break;
+    }
+
+    (values, failure, steps)
+}
+
+/// Simpler values of the same schema, simplest first.
+fn shrink_value(schema: &Schema, definitions: &Definitions, data: &PlutusData) -> Vec<PlutusData> {
+    match (schema, data) {
+        (Schema::Ref(key), _) => {
+            shrink_value(&lookup(definitions, key).annotated, definitions, data)
+        }
+
+        (Schema::Opaque, _) => shrink_opaque(definitions, data),
+
+        (Schema::Integer, PlutusData::BigInt(n)) => shrink_integer(from_pallas_bigint(n)),
+
+        (Schema::Bytes, PlutusData::BoundedBytes(bytes)) => shrink_bytes(bytes),
+
+        (Schema::List(Items::One(item)), PlutusData::Array(elems)) => shrink_list(elems, |elem| {
+            shrink_value(&item.annotated, definitions, elem)
+        })
+        .into_iter()
+        .map(Data::list)
+        .collect(),
+
+        (Schema::List(Items::Many(items)), PlutusData::Array(elems)) => {
+            shrink_each(elems, |index, elem| {
+                shrink_value(&items[index].annotated, definitions, elem)
+            })
+            .into_iter()
+            .map(Data::list)
+            .collect()
+        }
+
+        (Schema::Map(keys, values), PlutusData::Map(pairs)) => {
+            shrink_list(pairs, |(key, value)| {
+                shrink_pair(keys, values, definitions, key, value)
+            })
+            .into_iter()
+            .map(Data::map)
+            .collect()
+        }
+
+        (Schema::AnyOf(constructors), PlutusData::Constr(constr)) => {
+            let index = convert_tag_to_constr(constr.tag)
+                .or(constr.any_constructor)
+                .unwrap_or_default();
+
+            let mut candidates: Vec<PlutusData> = constructors
+                .iter()
+                .filter(|constructor| {
+                    (constructor.annotated.index as u64) < index
+                        && constructor.annotated.fields.is_empty()
+                })
+                .map(|constructor| Data::constr(constructor.annotated.index as u64, vec![]))
+                .collect();
+
+            if let Some(constructor) = constructors
+                .iter()
+                .find(|constructor| constructor.annotated.index as u64 == index)
+            {
+                candidates.extend(
+                    shrink_each(&constr.fields, |field, elem| {
+                        match constructor.annotated.fields.get(field) {
+                            Some(schema) => shrink_value(&schema.annotated, definitions, elem),
+                            None => vec![],
+                        }
+                    })
+                    .into_iter()
+                    .map(|fields| Data::constr(index, fields)),
+                );
+            }
+
+            candidates
+        }
+
+        _ => vec![],
+    }
+}
+
+fn shrink_opaque(definitions: &Definitions, data: &PlutusData) -> Vec<PlutusData> {
+    let mut candidates = match data {
+        PlutusData::BigInt(n) => return shrink_integer(from_pallas_bigint(n)),
+        _ => vec![Data::integer(0.into())],
+    };
+
+    match data {
+        PlutusData::BoundedBytes(bytes) => candidates.extend(shrink_bytes(bytes)),
+
+        PlutusData::Array(elems) => candidates.extend(
+            shrink_list(elems, |elem| shrink_opaque(definitions, elem))
+                .into_iter()
+                .map(Data::list),
+        ),
+
+        PlutusData::Map(pairs) => candidates.extend(
+            shrink_list(pairs, |(key, value)| {
+                shrink_pair(
+                    &Schema::Opaque.into(),
+                    &Schema::Opaque.into(),
+                    definitions,
+                    key,
+                    value,
+                )
+            })
+            .into_iter()
+            .map(Data::map),
+        ),
+
+        PlutusData::Constr(constr) => {
+            let index = convert_tag_to_constr(constr.tag)
+                .or(constr.any_constructor)
+                .unwrap_or_default();
+
+            candidates.extend(
+                shrink_list(&constr.fields, |elem| shrink_opaque(definitions, elem))
+                    .into_iter()
+                    .map(|fields| Data::constr(index, fields)),
+            )
+        }
+
+        PlutusData::BigInt(_) => {}
+    }
+
+    candidates
+}
+
+fn shrink_integer(n: BigInt) -> Vec<PlutusData> {
+    let zero = BigInt::from(0);
+
+    if n == zero {
+        return vec![];
+    }
+
+    let simpler = [
+        (n < zero).then(|| -n.clone()),
+        Some(&n / 2),
+        Some(if n < zero { &n + 1 } else { &n - 1 }),
+    ];
+
+    let mut candidates = vec![zero];
+
+    for candidate in simpler.into_iter().flatten() {
+        if candidate != n && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+
+    candidates.into_iter().map(Data::integer).collect()
+}
+
+/// Shrinks text character by character, so that shrinking never breaks the
+/// encoding of a `String`.
+fn shrink_bytes(bytes: &[u8]) -> Vec<PlutusData> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => shrink_list(&text.chars().collect::<Vec<_>>(), |_| vec![])
+            .into_iter()
+            .map(|chars| Data::bytestring(chars.into_iter().collect::<String>().into_bytes()))
+            .collect(),
+        Err(_) => shrink_list(bytes, |_| vec![])
+            .into_iter()
+            .map(Data::bytestring)
+            .collect(),
+    }
+}
+
+fn shrink_pair(
+    keys: &Annotated<Schema>,
+    values: &Annotated<Schema>,
+    definitions: &Definitions,
+    key: &PlutusData,
+    value: &PlutusData,
+) -> Vec<(PlutusData, PlutusData)> {
+    shrink_value(&keys.annotated, definitions, key)
+        .into_iter()
+        .map(|key| (key, value.clone()))
+        .chain(
+            shrink_value(&values.annotated, definitions, value)
+                .into_iter()
+                .map(|value| (key.clone(), value)),
+        )
+        .collect()
+}
+
+/// Shorter lists first (the empty list, then the list without each of its
+/// elements), followed by lists where a single element got shrunk.
+fn shrink_list<T: Clone>(elems: &[T], shrink_elem: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    if elems.is_empty() {
+        return vec![];
+    }
+
+    let mut candidates = vec![vec![]];
+
+    if elems.len() > 1 {
+        for index in 0..elems.len() {
+            let mut shorter = elems.to_vec();
+
+            shorter.remove(index);
+
+            candidates.push(shorter);
+        }
+    }
+
+    candidates.extend(shrink_each(elems, |_, elem| shrink_elem(elem)));
+
+    candidates
+}
+
+fn shrink_each<T: Clone>(elems: &[T], shrink_elem: impl Fn(usize, &T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut candidates = vec![];
+
+    for (index, elem) in elems.iter().enumerate() {
+        for shrunk in shrink_elem(index, elem) {
+            let mut next = elems.to_vec();
+
+            next[index] = shrunk;
+
+            candidates.push(next);
+        }
+    }
+
+    candidates
+}
+
+/// Renders a value the way it would be written in source code, using the
+/// names of constructors found in the schema.
+pub fn display(schema: &Schema, definitions: &Definitions, data: &PlutusData) -> String {
+    let join = |items: Vec<String>| items.join(", ");
+
+    match (schema, data) {
+        (Schema::Ref(key), _) => display(&lookup(definitions, key).annotated, definitions, data),
+
+        (Schema::Integer | Schema::Opaque, PlutusData::BigInt(n)) => {
+            from_pallas_bigint(n).to_string()
+        }
+
+        (Schema::Bytes | Schema::Opaque, PlutusData::BoundedBytes(bytes)) => {
+            display_bytes(bytes, false)
+        }
+
+        (Schema::List(Items::One(item)), PlutusData::Array(elems)) => format!(
+            "[{}]",
+            join(
+                elems
+                    .iter()
+                    .map(|elem| display_annotated(item, definitions, elem))
+                    .collect()
+            )
+        ),
+
+        (Schema::List(Items::Many(items)), PlutusData::Array(elems)) => format!(
+            "({})",
+            join(
+                items
+                    .iter()
+                    .zip(elems)
+                    .map(|(item, elem)| display_annotated(item, definitions, elem))
+                    .collect()
+            )
+        ),
+
+        (Schema::Map(keys, values), PlutusData::Map(pairs)) => format!(
+            "[{}]",
+            join(
+                pairs
+                    .iter()
+                    .map(|(key, value)| format!(
+                        "({}, {})",
+                        display_annotated(keys, definitions, key),
+                        display_annotated(values, definitions, value)
+                    ))
+                    .collect()
+            )
+        ),
+
+        (Schema::AnyOf(constructors), PlutusData::Constr(constr)) => {
+            let index = convert_tag_to_constr(constr.tag)
+                .or(constr.any_constructor)
+                .unwrap_or_default();
+
+            let constructor = constructors
+                .iter()
+                .find(|constructor| constructor.annotated.index as u64 == index);
+
+            match constructor {
+                Some(constructor) => {
+                    let name = constructor
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| format!("Constr{index}"));
+
+                    if constr.fields.is_empty() {
+                        name
+                    } else {
+                        format!(
+                            "{name}({})",
+                            join(
+                                constructor
+                                    .annotated
+                                    .fields
+                                    .iter()
+                                    .zip(&constr.fields)
+                                    .map(|(field, elem)| display_annotated(
+                                        field,
+                                        definitions,
+                                        elem
+                                    ))
+                                    .collect()
+                            )
+                        )
+                    }
+                }
+                None => display(&Schema::Opaque, definitions, data),
+            }
+        }
+
+        (_, PlutusData::BigInt(_) | PlutusData::BoundedBytes(_)) => {
+            display(&Schema::Opaque, definitions, data)
+        }
+
+        (_, PlutusData::Array(_)) => display(
+            &Schema::List(Items::One(Box::new(Schema::Opaque.into()))),
+            definitions,
+            data,
+        ),
+
+        (_, PlutusData::Map(_)) => display(
+            &Schema::Map(
+                Box::new(Schema::Opaque.into()),
+                Box::new(Schema::Opaque.into()),
+            ),
+            definitions,
+            data,
+        ),
+
+        (_, PlutusData::Constr(constr)) => format!(
+            "Constr{}({})",
+            convert_tag_to_constr(constr.tag)
+                .or(constr.any_constructor)
+                .unwrap_or_default(),
+            join(
+                constr
+                    .fields
+                    .iter()
+                    .map(|elem| display(&Schema::Opaque, definitions, elem))
+                    .collect()
+            )
+        ),
+    }
+}
+
+pub fn display_annotated(
+    schema: &Annotated<Schema>,
+    definitions: &Definitions,
+    data: &PlutusData,
+) -> String {
+    match data {
+        PlutusData::BoundedBytes(bytes) if is_text(schema) => display_bytes(bytes, true),
+        _ => display(&schema.annotated, definitions, data),
+    }
+}
+
+fn display_bytes(bytes: &[u8], is_text: bool) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if is_text => format!("{text:?}"),
+        _ => format!("#\"{}\"", hex::encode(bytes)),
+    }
+}
+
+fn lookup<'a>(definitions: &'a Definitions, key: &str) -> &'a Annotated<Schema> {
+    definitions
+        .lookup(key)
+        .expect("Missing definition for schema reference")
+}
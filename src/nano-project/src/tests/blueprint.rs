@@ -0,0 +1,223 @@
+use indoc::indoc;
+use nano_lang::ast::Tracing;
+use pallas::ledger::addresses::Network;
+use serde_json::{json, Value};
+use untyped_plutus_core::{ast::Data, plutus_data_to_bytes};
+
+use super::TestProject;
+use crate::{
+    blueprint::{
+        error::{Error as BlueprintError, Mismatch},
+        parameter::{AppliedScript, Parameter},
+    },
+    error::Error,
+};
+
+/// A validator parameterised by an owner, a name and a custom type.
+fn locked() -> TestProject {
+    let project = TestProject::new();
+
+    project.validator(
+        "locker",
+        indoc! {r#"
+            pub type Lock {
+              Until(Int)
+              Forever
+            }
+
+            validator(owner: ByteArray, name: String, lock: Lock) {
+              fn spend(_datum: Data, _redeemer: Data, _ctx: Data) -> Bool {
+                when lock is {
+                  Until(deadline) -> deadline > 0 && owner != #"" && name != @""
+                  Forever -> False
+                }
+              }
+            }
+        "#},
+    );
+
+    project
+}
+
+fn apply(project: &TestProject, values: &[Parameter]) -> Result<AppliedScript, Vec<Error>> {
+    project
+        .project()
+        .apply_parameters("locker.spend", values, Network::Testnet, Tracing::NoTraces)
+}
+
+fn json(values: &[Value]) -> Vec<Parameter> {
+    values.iter().cloned().map(Parameter::Json).collect()
+}
+
+fn blueprint_error(result: Result<AppliedScript, Vec<Error>>) -> BlueprintError {
+    match result.map_err(<[Error; 1]>::try_from) {
+        Err(Ok([Error::Blueprint(error)])) => error,
+        Err(errors) => panic!("expected a single blueprint error, got {errors:?}"),
+        Ok(_) => panic!("expected the parameters to be rejected"),
+    }
+}
+
+/// The mismatch reported for the given owner, name and lock.
+fn mismatch(project: &TestProject, owner: Value, name: Value, lock: Value) -> (String, Mismatch) {
+    match blueprint_error(apply(project, &json(&[owner, name, lock]))) {
+        BlueprintError::SchemaMismatch { name, mismatch } => (name, mismatch),
+        error => panic!("expected a schema mismatch, got {error:?}"),
+    }
+}
+
+#[test]
+fn apply_json_and_cbor_parameters() {
+    let project = locked();
+
+    let values = [
+        json!({ "bytes": "00ff" }),
+        json!({ "bytes": "6e616e6f" }),
+        json!({ "constructor": 0, "fields": [{ "int": 42 }] }),
+    ];
+
+    let partial = apply(&project, &json(&values[..1])).unwrap();
+
+    let remaining: Vec<_> = partial
+        .script
+        .params
+        .iter()
+        .map(|param| param.arg_name.get_label())
+        .collect();
+
+    assert_eq!(remaining, ["name", "lock"]);
+
+    let from_json = apply(&project, &json(&values)).unwrap();
+
+    assert!(from_json.script.params.is_empty());
+    assert_ne!(from_json.hash, partial.hash);
+
+    let cbor: Vec<_> = [
+        Data::bytestring(vec![0x00, 0xff]),
+        Data::bytestring(b"nano".to_vec()),
+        Data::constr(0, vec![Data::integer(42.into())]),
+    ]
+    .iter()
+    .map(|data| Parameter::Cbor(plutus_data_to_bytes(data).unwrap()))
+    .collect();
+
+    let from_cbor = apply(&project, &cbor).unwrap();
+
+    assert_eq!(from_cbor.hash, from_json.hash);
+    assert_eq!(from_cbor.address, from_json.address);
+}
+
+#[test]
+fn apply_rejects_mistyped_parameters() {
+    let project = locked();
+
+    let owner = || json!({ "bytes": "00ff" });
+    let name = || json!({ "bytes": "6e616e6f" });
+
+    let (param, found) = mismatch(
+        &project,
+        owner(),
+        name(),
+        json!({ "constructor": 2, "fields": [] }),
+    );
+
+    assert_eq!(param, "lock");
+    assert_eq!(
+        found,
+        Mismatch {
+            path: vec![],
+            expected: "one of the constructors Until (0), Forever (1)".to_string(),
+            found: "constructor 2".to_string(),
+        }
+    );
+
+    let (_, found) = mismatch(
+        &project,
+        owner(),
+        name(),
+        json!({ "constructor": 0, "fields": [] }),
+    );
+
+    assert_eq!(found.expected, "1 fields for constructor 0");
+    assert_eq!(found.found, "0 fields");
+
+    let (_, found) = mismatch(
+        &project,
+        owner(),
+        name(),
+        json!({ "constructor": 0, "fields": [{ "bytes": "2a" }] }),
+    );
+
+    assert_eq!(
+        found,
+        Mismatch {
+            path: vec![".fields[0]".to_string()],
+            expected: "an integer".to_string(),
+            found: "bytes".to_string(),
+        }
+    );
+
+    let (param, found) = mismatch(
+        &project,
+        owner(),
+        json!({ "int": 42 }),
+        json!({ "constructor": 1, "fields": [] }),
+    );
+
+    assert_eq!(param, "name");
+    assert_eq!(found.expected, "bytes");
+    assert_eq!(found.found, "an integer");
+}
+
+#[test]
+fn apply_rejects_malformed_parameters() {
+    let project = locked();
+
+    for (value, hint) in [
+        (json!("00ff"), "Expected a JSON object"),
+        (
+            json!({ "bytes": "nano" }),
+            "Expected a base16-encoded string",
+        ),
+        (json!({ "string": "00ff" }), "Expected one of"),
+    ] {
+        match blueprint_error(apply(&project, &json(&[value]))) {
+            BlueprintError::MalformedParameter { name, hint: found } => {
+                assert_eq!(name, "owner");
+                assert!(found.starts_with(hint), "{found}");
+            }
+            error => panic!("expected a malformed parameter, got {error:?}"),
+        }
+    }
+
+    match blueprint_error(apply(&project, &[Parameter::Cbor(vec![0xff])])) {
+        BlueprintError::MalformedParameter { hint, .. } => {
+            assert!(hint.starts_with("Invalid CBOR"), "{hint}");
+        }
+        error => panic!("expected a malformed parameter, got {error:?}"),
+    }
+}
+
+#[test]
+fn apply_rejects_too_many_parameters() {
+    let project = locked();
+
+    let values = json(&[
+        json!({ "bytes": "00ff" }),
+        json!({ "bytes": "6e616e6f" }),
+        json!({ "constructor": 1, "fields": [] }),
+        json!({ "int": 1 }),
+    ]);
+
+    match blueprint_error(apply(&project, &values)) {
+        BlueprintError::TooManyParameters {
+            title,
+            expected,
+            given,
+        } => {
+            assert_eq!(title, "locker.spend");
+            assert_eq!(expected, 3);
+            assert_eq!(given, 4);
+        }
+        error => panic!("expected too many parameters, got {error:?}"),
+    }
+}
@@ -0,0 +1,525 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+use nano_lang::{
+    ast::{ModuleKind, Tracing},
+    tipo,
+};
+
+use tempfile::TempDir;
+
+use super::{Silent, TestProject};
+use crate::{
+    config::{Config, Dependency, Platform},
+    deps::{
+        manifest::{content_hash, Manifest, Package},
+        LocalPackages, UseManifest,
+    },
+    error::Error,
+    module::{ParsedModule, ParsedModules},
+    package_name::PackageName,
+    paths,
+};
+
+fn name(name: &str) -> PackageName {
+    name.parse().unwrap()
+}
+
+fn dependency(package: &str, version: &str) -> Dependency {
+    Dependency {
+        name: name(package),
+        version: version.to_string(),
+        source: Platform::Github,
+    }
+}
+
+fn pinned(package: &str, version: &str, hash: &str) -> Package {
+    Package {
+        hash: Some(hash.to_string()),
+        ..Package::from(dependency(package, version))
+    }
+}
+
+fn hash_of<'a>(manifest: &'a Manifest, package: &str) -> Option<&'a str> {
+    manifest
+        .packages
+        .iter()
+        .find(|p| p.name == name(package))
+        .and_then(|p| p.hash.as_deref())
+}
+
+/// Installs a package under the build directory, with the given
+/// dependencies.
+fn install(project: &TestProject, package: &str, dependencies: Vec<Dependency>) {
+    let path = project
+        .root()
+        .join(paths::build_deps_package(&name(package)));
+
+    fs::create_dir_all(path.join("lib")).unwrap();
+
+    Config {
+        dependencies,
+        ..Config::default(&name(package))
+    }
+    .save(&path)
+    .unwrap();
+
+    fs::write(path.join("lib").join("util.nl"), "pub fn one() { 1 }\n").unwrap();
+}
+
+#[test]
+fn changed_requirements_keep_unchanged_pins() {
+    let project = TestProject::new();
+
+    Manifest {
+        requirements: vec![dependency("nano/a", "1.0.0"), dependency("nano/b", "1.0.0")],
+        packages: vec![
+            pinned("nano/a", "1.0.0", "aaaa"),
+            pinned("nano/b", "1.0.0", "bbbb"),
+            pinned("nano/d", "1.0.0", "dddd"),
+        ],
+        ..Manifest::default()
+    }
+    .save(project.root())
+    .unwrap();
+
+    let config = Config {
+        dependencies: vec![
+            dependency("nano/a", "1.0.0"),
+            dependency("nano/b", "2.0.0"),
+            dependency("nano/c", "1.0.0"),
+        ],
+        ..Config::default(&name("nano/test"))
+    };
+
+    let (mut manifest, changed) =
+        Manifest::load(&Silent, &config, UseManifest::Yes, project.root()).unwrap();
+
+    assert!(changed);
+    assert_eq!(hash_of(&manifest, "nano/a"), Some("aaaa"));
+    assert_eq!(hash_of(&manifest, "nano/b"), None);
+    assert_eq!(hash_of(&manifest, "nano/c"), None);
+
+    // nano/d is only found again once the packages requiring it are
+    // installed.
+    install(&project, "nano/a", vec![dependency("nano/d", "1.0.0")]);
+    install(&project, "nano/b", vec![]);
+    install(&project, "nano/c", vec![]);
+
+    assert_eq!(manifest.discover_requirements(project.root()).unwrap(), 1);
+    assert_eq!(hash_of(&manifest, "nano/d"), Some("dddd"));
+}
+
+#[test]
+fn verify_pins_new_packages() {
+    let project = TestProject::new();
+
+    install(&project, "nano/a", vec![]);
+
+    let mut manifest = Manifest {
+        requirements: vec![dependency("nano/a", "1.0.0")],
+        packages: vec![Package::from(dependency("nano/a", "1.0.0"))],
+        ..Manifest::default()
+    };
+
+    assert!(manifest
+        .verify(&Silent, project.root(), &name("nano/test"))
+        .unwrap());
+
+    let path = project
+        .root()
+        .join(paths::build_deps_package(&name("nano/a")));
+
+    assert_eq!(
+        hash_of(&manifest, "nano/a"),
+        Some(content_hash(&path).unwrap().as_str())
+    );
+
+    assert!(!manifest
+        .verify(&Silent, project.root(), &name("nano/test"))
+        .unwrap());
+}
+
+#[test]
+fn verify_rejects_changed_packages() {
+    let project = TestProject::new();
+
+    install(&project, "nano/a", vec![]);
+
+    let path = project
+        .root()
+        .join(paths::build_deps_package(&name("nano/a")));
+
+    let expected = content_hash(&path).unwrap();
+
+    fs::write(path.join("lib").join("util.nl"), "pub fn one() { 2 }\n").unwrap();
+
+    let changed = content_hash(&path).unwrap();
+
+    let mut manifest = Manifest {
+        requirements: vec![dependency("nano/a", "1.0.0")],
+        packages: vec![pinned("nano/a", "1.0.0", &expected)],
+        ..Manifest::default()
+    };
+
+    match manifest.verify(&Silent, project.root(), &name("nano/test")) {
+        Err(Error::PackageHashMismatch {
+            package,
+            expected: reported,
+            found,
+            ..
+        }) => {
+            assert_eq!(package, name("nano/a"));
+            assert_eq!(reported, expected);
+            assert_eq!(found, changed);
+        }
+        _ => panic!("expected a hash mismatch"),
+    }
+
+    assert!(!path.exists());
+}
+
+/// Makes the project depend on the given packages, already installed with
+/// their library modules so that nothing needs to be downloaded.
+fn depend_on(project: &TestProject, packages: &[(&str, &[(&str, &str)])]) {
+    let dependencies: Vec<_> = packages
+        .iter()
+        .map(|(package, _)| dependency(package, "1.0.0"))
+        .collect();
+
+    for (package, modules) in packages {
+        let path = project
+            .root()
+            .join(paths::build_deps_package(&name(package)));
+
+        fs::create_dir_all(&path).unwrap();
+
+        Config::default(&name(package)).save(&path).unwrap();
+
+        for (module, code) in modules.iter() {
+            let module_path = path.join("lib").join(format!("{module}.nl"));
+
+            fs::create_dir_all(module_path.parent().unwrap()).unwrap();
+
+            fs::write(module_path, code).unwrap();
+        }
+    }
+
+    let manifest = Manifest {
+        requirements: dependencies.clone(),
+        packages: dependencies.iter().cloned().map(Package::from).collect(),
+        ..Manifest::default()
+    };
+
+    manifest.save(project.root()).unwrap();
+
+    LocalPackages::from(&manifest).save(project.root()).unwrap();
+
+    Config {
+        dependencies,
+        ..Config::default(&name("nano/test"))
+    }
+    .save(project.root())
+    .unwrap();
+}
+
+fn type_error(errors: &[Error]) -> &tipo::error::Error {
+    match errors {
+        [Error::Type { error, .. }] => error,
+        _ => panic!("expected a single type error, got {errors:?}"),
+    }
+}
+
+fn parsed(package: &str, module: &str, code: &str) -> (String, ParsedModule) {
+    let (ast, extra) = nano_lang::parser::module(code, ModuleKind::Lib).unwrap();
+
+    let parsed = ParsedModule {
+        path: PathBuf::from(format!("{module}.nl")),
+        name: module.to_string(),
+        code: code.to_string(),
+        kind: ModuleKind::Lib,
+        package: package.to_string(),
+        ast,
+        extra,
+    };
+
+    (module.to_string(), parsed)
+}
+
+#[test]
+fn sequence_follows_package_qualified_imports() {
+    let modules: ParsedModules = HashMap::from([
+        parsed("ownera/repoa", "users", "use ownerb/repob/numbers\n"),
+        parsed("ownera/repoa", "local", "use ownera/repoa/users\n"),
+        parsed("ownerb/repob", "numbers", "use unknown/repo/other\n"),
+    ])
+    .into();
+
+    assert_eq!(modules.sequence().unwrap(), ["numbers", "users", "local"]);
+}
+
+#[test]
+fn check_resolves_package_qualified_imports() {
+    let project = TestProject::new();
+
+    // Several modules of the first package import the second one, so that
+    // they are only checked in the right order when the package prefix is
+    // resolved.
+    let users: Vec<_> = (0..8)
+        .map(|i| {
+            (
+                format!("users/user_{i}"),
+                "use ownerb/repob/numbers\n\npub fn two() { numbers.one() + numbers.one() }\n",
+            )
+        })
+        .collect();
+
+    let users: Vec<_> = users
+        .iter()
+        .map(|(module, code)| (module.as_str(), *code))
+        .collect();
+
+    depend_on(
+        &project,
+        &[
+            ("ownera/repoa", &users),
+            ("ownerb/repob", &[("numbers", "pub fn one() { 1 }\n")]),
+        ],
+    );
+
+    project.lib(
+        "main",
+        "use ownera/repoa/users/user_0\nuse numbers\n\npub fn three() { user_0.two() + numbers.one() }\n",
+    );
+
+    project.project().check(Tracing::KeepTraces).unwrap();
+}
+
+#[test]
+fn check_rejects_modules_from_another_package() {
+    let project = TestProject::new();
+
+    depend_on(
+        &project,
+        &[
+            ("ownera/repoa", &[("letters", "pub fn a() { #\"61\" }\n")]),
+            ("ownerb/repob", &[("numbers", "pub fn one() { 1 }\n")]),
+        ],
+    );
+
+    project.lib(
+        "main",
+        "use ownera/repoa/numbers\n\npub fn one() { numbers.one() }\n",
+    );
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    match type_error(&errors) {
+        tipo::error::Error::ModuleNotInPackage {
+            name,
+            package,
+            actual_package,
+            ..
+        } => {
+            assert_eq!(name, "numbers");
+            assert_eq!(package, "ownera/repoa");
+            assert_eq!(actual_package, "ownerb/repob");
+        }
+        error => panic!("expected ModuleNotInPackage, got {error:?}"),
+    }
+}
+
+#[test]
+fn check_rejects_internal_modules_of_other_packages() {
+    let project = TestProject::new();
+
+    depend_on(
+        &project,
+        &[(
+            "ownera/repoa",
+            &[
+                ("internal/secret", "pub fn secret() { 42 }\n"),
+                (
+                    "public",
+                    "use internal/secret\n\npub fn answer() { secret.secret() }\n",
+                ),
+            ],
+        )],
+    );
+
+    project.lib(
+        "main",
+        "use public\n\npub fn answer() { public.answer() }\n",
+    );
+
+    project.project().check(Tracing::KeepTraces).unwrap();
+
+    project.lib(
+        "main",
+        "use ownera/repoa/internal/secret\n\npub fn answer() { secret.secret() }\n",
+    );
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    match type_error(&errors) {
+        tipo::error::Error::InternalModuleImported { name, package, .. } => {
+            assert_eq!(name, "internal/secret");
+            assert_eq!(package, "ownera/repoa");
+        }
+        error => panic!("expected InternalModuleImported, got {error:?}"),
+    }
+}
+
+#[test]
+fn check_rejects_modules_defined_by_two_packages() {
+    let project = TestProject::new();
+
+    depend_on(
+        &project,
+        &[
+            ("ownera/repoa", &[("util", "pub fn one() { 1 }\n")]),
+            ("ownerb/repob", &[("util", "pub fn two() { 2 }\n")]),
+        ],
+    );
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    assert!(matches!(
+        errors.as_slice(),
+        [Error::DuplicatePackageModule { module, .. }] if module == "util"
+    ));
+}
+
+/// The offline package cache, shared by every test as the environment is.
+fn packages_cache() -> &'static Path {
+    static CACHE: OnceLock<TempDir> = OnceLock::new();
+
+    CACHE
+        .get_or_init(|| {
+            let cache = tempfile::tempdir().expect("Failed to create package cache");
+
+            env::set_var("NANO_PACKAGES_CACHE", cache.path());
+
+            cache
+        })
+        .path()
+}
+
+/// Writes a package with a single library module to the given directory.
+fn write_package(path: &Path, package: &str, module: &str, code: &str) {
+    fs::create_dir_all(path.join("lib")).unwrap();
+
+    Config::default(&name(package)).save(path).unwrap();
+
+    fs::write(path.join("lib").join(format!("{module}.nl")), code).unwrap();
+}
+
+fn depend_on_cached(project: &TestProject, packages: &[&str]) {
+    Config {
+        dependencies: packages
+            .iter()
+            .map(|package| Dependency {
+                source: Platform::Cache,
+                ..dependency(package, "1.0.0")
+            })
+            .collect(),
+        ..Config::default(&name("nano/test"))
+    }
+    .save(project.root())
+    .unwrap();
+}
+
+#[test]
+fn check_installs_packages_from_the_cache() {
+    let cache = packages_cache();
+
+    assert_eq!(paths::packages_cache(), cache);
+
+    write_package(
+        &cache.join("cached-numbers-1.0.0"),
+        "cached/numbers",
+        "numbers",
+        "pub fn one() { 1 }\n",
+    );
+
+    // Archives wrap the package in a single folder, as hosting platforms do.
+    let tarball = tempfile::tempdir().unwrap();
+
+    write_package(
+        &tarball.path().join("cached-letters-c0ffee"),
+        "cached/letters",
+        "letters",
+        "pub fn a() { #\"61\" }\n",
+    );
+
+    let mut archive = tar::Builder::new(GzEncoder::new(
+        File::create(cache.join("cached-letters-1.0.0.tar.gz")).unwrap(),
+        Compression::default(),
+    ));
+
+    archive
+        .append_dir_all(
+            "cached-letters-c0ffee",
+            tarball.path().join("cached-letters-c0ffee"),
+        )
+        .unwrap();
+
+    archive.into_inner().unwrap().finish().unwrap();
+
+    let project = TestProject::new();
+
+    depend_on_cached(&project, &["cached/numbers", "cached/letters"]);
+
+    project.lib(
+        "main",
+        "use numbers\nuse letters\n\npub fn one() { numbers.one() }\n\npub fn a() { letters.a() }\n",
+    );
+
+    project.project().check(Tracing::KeepTraces).unwrap();
+
+    for (package, module) in [("cached/numbers", "numbers"), ("cached/letters", "letters")] {
+        let installed = project
+            .root()
+            .join(paths::build_deps_package(&name(package)))
+            .join("lib")
+            .join(format!("{module}.nl"));
+
+        assert!(installed.is_file(), "{}", installed.display());
+    }
+
+    let manifest: Manifest = toml::from_str(&project.read(paths::manifest())).unwrap();
+
+    assert!(hash_of(&manifest, "cached/numbers").is_some());
+    assert!(hash_of(&manifest, "cached/letters").is_some());
+}
+
+#[test]
+fn check_rejects_packages_missing_from_the_cache() {
+    let cache = packages_cache();
+
+    let project = TestProject::new();
+
+    depend_on_cached(&project, &["cached/missing"]);
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    match errors.as_slice() {
+        [Error::MissingCachedPackage {
+            package,
+            version,
+            cache: reported,
+        }] => {
+            assert_eq!(package, &name("cached/missing"));
+            assert_eq!(version, "1.0.0");
+            assert_eq!(reported, cache);
+        }
+        _ => panic!("expected a missing cached package, got {errors:?}"),
+    }
+}
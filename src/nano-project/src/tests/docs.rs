@@ -0,0 +1,158 @@
+use indoc::indoc;
+use serde_json::{json, Value};
+
+use super::TestProject;
+use crate::paths;
+
+/// Two modules defining a type of the same name, both referred to by a third
+/// one.
+fn things() -> TestProject {
+    let project = TestProject::new();
+
+    project
+        .lib(
+            "alpha",
+            indoc! {r#"
+                /// A thing counted.
+                pub type Thing {
+                  Thing(Int)
+                }
+            "#},
+        )
+        .lib(
+            "nested/beta",
+            indoc! {r#"
+                /// A thing named.
+                pub type Thing {
+                  Thing(ByteArray)
+                }
+            "#},
+        )
+        .lib(
+            "convert",
+            indoc! {r#"
+                //// Conversions between things.
+
+                use alpha
+                use nested/beta.{Thing as Named}
+
+                /// Names a counted thing.
+                pub fn name(counted: alpha.Thing, default: Named) -> Named {
+                  when counted is {
+                    alpha.Thing(0) -> default
+                    _ -> Named(#"6e616e6f")
+                  }
+                }
+            "#},
+        );
+
+    project
+}
+
+fn generate(project: &TestProject) {
+    project.project().docs(None).unwrap();
+}
+
+#[test]
+fn docs_link_types_to_their_own_module() {
+    let project = things();
+
+    generate(&project);
+
+    let page = project.read(paths::docs().join("convert.html"));
+
+    assert!(
+        page.contains(r#"alpha.<a href="./alpha.html#Thing">Thing</a>"#),
+        "{page}"
+    );
+    assert!(
+        page.contains(r#"<a href="./nested/beta.html#Thing">Named</a>"#),
+        "{page}"
+    );
+    assert!(page.contains("Names a counted thing."), "{page}");
+
+    let nested = project.read(paths::docs().join("nested/beta.html"));
+
+    assert!(nested.contains(r#"href="../css/index.css"#), "{nested}");
+}
+
+#[test]
+fn docs_are_reproducible() {
+    let project = things();
+
+    let read = |project: &TestProject| {
+        [
+            "index.html",
+            "convert.html",
+            "nested/beta.html",
+            "search-data.js",
+        ]
+        .map(|file| project.read(paths::docs().join(file)))
+    };
+
+    generate(&project);
+
+    let first = read(&project);
+
+    generate(&project);
+
+    assert_eq!(read(&project), first);
+
+    // Assets are only versioned after the project, not the time of
+    // generation.
+    assert!(first[1].contains(r#"src="./search-data.js?v=0.0.0""#));
+}
+
+#[test]
+fn docs_index_public_definitions_for_search() {
+    let project = things();
+
+    generate(&project);
+
+    let search = project.read(paths::docs().join("search-data.js"));
+
+    let entries: Value = serde_json::from_str(
+        search
+            .strip_prefix("window.Nano.initSearch(")
+            .and_then(|search| search.strip_suffix(");"))
+            .unwrap(),
+    )
+    .unwrap();
+
+    // Documentation comments are indexed as written, after the slashes.
+    let entries: Vec<_> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let mut entry = entry.clone();
+            entry["content"] = entry["content"].as_str().unwrap().trim().into();
+            entry
+        })
+        .collect();
+
+    for entry in [
+        json!({
+            "doc": "convert",
+            "title": "convert",
+            "content": "Conversions between things.",
+            "url": "convert.html",
+        }),
+        json!({
+            "doc": "convert",
+            "title": "name",
+            "content": "Names a counted thing.",
+            "url": "convert.html#name",
+        }),
+        json!({
+            "doc": "nested/beta",
+            "title": "Thing",
+            "content": "A thing named.",
+            "url": "nested/beta.html#Thing",
+        }),
+    ] {
+        assert!(entries.contains(&entry), "{entry} in {entries:?}");
+    }
+
+    assert_eq!(entries.len(), 6);
+}
@@ -0,0 +1,82 @@
+use std::{fs, path::Path};
+
+use nano_lang::ast::Tracing;
+use tempfile::TempDir;
+use crate::{
+    config::Config, error::Error, options::TestOptions, package_name::PackageName,
+    telemetry::EventListener, Project,
+};
+
+mod blueprint;
+mod deps;
+mod docs;
+mod project;
+mod property;
+mod test_framework;
+
+pub struct Silent;
+
+impl EventListener for Silent {}
+
+/// A project in a directory of its own, removed once the project is dropped.
+pub struct TestProject {
+    dir: TempDir,
+}
+
+impl TestProject {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create project directory");
+
+        let name = PackageName {
+            owner: "nano".to_string(),
+            repo: "test".to_string(),
+        };
+
+        Config::default(&name)
+            .save(dir.path())
+            .expect("Failed to write nano.toml");
+
+        TestProject { dir }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn lib(&self, name: &str, code: &str) -> &Self {
+        self.write("lib", name, code)
+    }
+
+    pub fn validator(&self, name: &str, code: &str) -> &Self {
+        self.write("validators", name, code)
+    }
+
+    fn write(&self, dir: &str, name: &str, code: &str) -> &Self {
+        let path = self.root().join(dir).join(format!("{name}.nl"));
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        fs::write(path, code).unwrap();
+
+        self
+    }
+
+    pub fn project(&self) -> Project<Silent> {
+        Project::new(self.root().to_path_buf(), Silent).expect("Failed to load project")
+    }
+
+    /// Runs the tests of the project, recording what is asked for.
+    pub fn test(&self, profile: bool, coverage: bool) -> Result<(), Vec<Error>> {
+        let options = TestOptions {
+            profile,
+            coverage,
+            ..TestOptions::default()
+        };
+
+        self.project().test(options, Tracing::KeepTraces)
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> String {
+        fs::read_to_string(self.root().join(path)).expect("Failed to read output")
+    }
+}
@@ -0,0 +1,159 @@
+use indoc::indoc;
+use nano_lang::ast::Tracing;
+
+use super::TestProject;
+use crate::{error::Error, paths};
+
+/// A library module in a nested directory, used by a validator through
+/// another library module.
+fn vesting() -> TestProject {
+    let project = TestProject::new();
+
+    project
+        .lib(
+            "time/window",
+            indoc! {r#"
+                pub fn contains(start: Int, end: Int, at: Int) -> Bool {
+                  start <= at && at <= end
+                }
+            "#},
+        )
+        .lib(
+            "rules",
+            indoc! {r#"
+                use time/window
+
+                pub fn unlocked(deadline: Int, now: Int) -> Bool {
+                  !window.contains(0, deadline, now)
+                }
+            "#},
+        )
+        .validator(
+            "vesting",
+            indoc! {r#"
+                use rules
+
+                validator {
+                  fn spend(deadline: Int, now: Int, _ctx: Data) -> Bool {
+                    rules.unlocked(deadline, now)
+                  }
+                }
+            "#},
+        );
+
+    project
+}
+
+#[test]
+fn check_modules_in_dependency_order() {
+    let project = vesting();
+
+    let mut checked = project.project();
+
+    checked.check(Tracing::KeepTraces).unwrap();
+
+    assert!(checked.warnings.is_empty());
+}
+
+#[test]
+fn check_reports_type_errors_with_their_module() {
+    let project = vesting();
+
+    project.lib(
+        "rules",
+        indoc! {r#"
+            use time/window
+
+            pub fn unlocked(deadline: Int, now: Int) -> Bool {
+              window.contains(0, deadline, #"00")
+            }
+        "#},
+    );
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    assert!(matches!(
+        errors.as_slice(),
+        [Error::Type { path, .. }] if path.ends_with("lib/rules.nl")
+    ));
+}
+
+#[test]
+fn check_reports_parse_errors() {
+    let project = vesting();
+
+    project.lib("broken", "pub fn (x: Int) -> Int { x }\n");
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    assert!(!errors.is_empty());
+    assert!(errors.iter().all(
+        |error| matches!(error, Error::Parse { path, .. } if path.ends_with("lib/broken.nl"))
+    ));
+}
+
+#[test]
+fn check_rejects_import_cycles() {
+    let project = TestProject::new();
+
+    project
+        .lib("ping", "use pong\n\npub fn ping() { pong.pong() }\n")
+        .lib("pong", "use ping\n\npub fn pong() { ping.ping() }\n");
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    assert!(matches!(errors.as_slice(), [Error::ImportCycle { .. }]));
+}
+
+#[test]
+fn check_reports_failing_constants_and_their_dependents() {
+    let project = TestProject::new();
+
+    project.lib(
+        "consts",
+        indoc! {r#"
+            pub const a: Int = 1 / 0
+
+            pub const b: Int = a + 1
+        "#},
+    );
+
+    let errors = project.project().check(Tracing::KeepTraces).unwrap_err();
+
+    let names: Vec<&str> = errors
+        .iter()
+        .map(|error| match error {
+            Error::ConstantEvaluation { name, .. } => name.as_str(),
+            _ => panic!("Unexpected error {error:?}"),
+        })
+        .collect();
+
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn build_writes_scripts_and_blueprint() {
+    let project = vesting();
+
+    project.project().build(true, Tracing::NoTraces).unwrap();
+
+    let output = paths::build_validator("vesting", "spend");
+
+    let cbor = project.read(output.join("script.cbor"));
+
+    assert!(!cbor.is_empty());
+    assert!(hex::decode(cbor.trim()).is_ok());
+
+    assert!(project
+        .read(output.join("raw.uplc"))
+        .starts_with("(program"));
+
+    let blueprint: serde_json::Value =
+        serde_json::from_str(&project.read(paths::blueprint())).unwrap();
+
+    let validators = blueprint["validators"].as_array().unwrap();
+
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators[0]["title"], "vesting.spend");
+    assert_eq!(validators[0]["compiledCode"], cbor.trim());
+}
@@ -0,0 +1,126 @@
+use nano_lang::gen_uplc::schema::{Annotated, Definitions, Items, Schema};
+use rand::{rngs::StdRng, SeedableRng};
+use untyped_plutus_core::{ast::Data, machine::value::from_pallas_bigint, PlutusData};
+
+use crate::test_framework::property;
+
+fn string() -> Annotated<Schema> {
+    Annotated {
+        title: Some("String".to_string()),
+        description: None,
+        annotated: Schema::Bytes,
+    }
+}
+
+fn generate_all(schema: &Annotated<Schema>, seed: u64, count: usize) -> Vec<PlutusData> {
+    let definitions = Definitions::new();
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..count)
+        .map(|iteration| property::generate(schema, &definitions, iteration % 32, &mut rng))
+        .collect()
+}
+
+fn bytes(data: &PlutusData) -> Vec<u8> {
+    match data {
+        PlutusData::BoundedBytes(bytes) => bytes.to_vec(),
+        _ => panic!("Expected bytes, got {data:?}"),
+    }
+}
+
+#[test]
+fn same_seed_generates_same_values() {
+    let schemas: Vec<Annotated<Schema>> = vec![
+        Schema::Integer.into(),
+        Schema::Bytes.into(),
+        string(),
+        Schema::List(Items::One(Box::new(string()))).into(),
+        Schema::Opaque.into(),
+    ];
+
+    for schema in &schemas {
+        assert_eq!(generate_all(schema, 42, 100), generate_all(schema, 42, 100));
+        assert_ne!(generate_all(schema, 42, 100), generate_all(schema, 7, 100));
+    }
+}
+
+#[test]
+fn bytes_are_raw_and_strings_are_text() {
+    let generated: Vec<Vec<u8>> = generate_all(&Schema::Bytes.into(), 42, 500)
+        .iter()
+        .map(bytes)
+        .collect();
+
+    assert!(generated.iter().any(|bytes| bytes.contains(&0x00)));
+    assert!(generated.iter().any(|bytes| bytes.contains(&0xff)));
+    assert!(generated
+        .iter()
+        .any(|bytes| std::str::from_utf8(bytes).is_err()));
+
+    let strings = Schema::List(Items::One(Box::new(string()))).into();
+
+    for list in generate_all(&strings, 42, 500) {
+        let PlutusData::Array(elems) = list else {
+            panic!("Expected a list, got {list:?}");
+        };
+
+        for elem in elems {
+            assert!(std::str::from_utf8(&bytes(&elem)).is_ok());
+        }
+    }
+}
+
+#[test]
+fn shrinks_to_minimal_counterexample() {
+    let definitions = Definitions::new();
+
+    let inputs = vec![
+        Schema::Integer.into(),
+        Schema::List(Items::One(Box::new(Schema::Integer.into()))).into(),
+        Schema::Bytes.into(),
+    ];
+
+    let integer = |data: &PlutusData| match data {
+        PlutusData::BigInt(n) => from_pallas_bigint(n),
+        _ => panic!("Expected an integer, got {data:?}"),
+    };
+
+    // Fails for any integer of at least 10, any list holding an element of at
+    // least 5 and any bytes holding 0xff.
+    let fails = |values: &[PlutusData]| {
+        let PlutusData::Array(elems) = &values[1] else {
+            return None;
+        };
+
+        let failing = integer(&values[0]) >= 10.into()
+            && elems.iter().any(|elem| integer(elem) >= 5.into())
+            && bytes(&values[2]).contains(&0xff);
+
+        failing.then_some(())
+    };
+
+    let values = vec![
+        Data::integer(1234.into()),
+        Data::list(vec![
+            Data::integer((-3).into()),
+            Data::integer(42.into()),
+            Data::integer(17.into()),
+        ]),
+        Data::bytestring(vec![0x00, 0xc3, 0xff, 0x28]),
+    ];
+
+    assert!(fails(&values).is_some());
+
+    let (shrunk, (), steps) = property::shrink(&inputs, &definitions, values, (), fails);
+
+    assert_eq!(
+        shrunk,
+        vec![
+            Data::integer(10.into()),
+            Data::list(vec![Data::integer(5.into())]),
+            Data::bytestring(vec![0xff]),
+        ]
+    );
+    assert!(steps > 0);
+}
@@ -0,0 +1,120 @@
+use indoc::indoc;
+
+use super::TestProject;
+use crate::paths;
+
+fn math() -> TestProject {
+    let project = TestProject::new();
+
+    project.lib(
+        "math",
+        indoc! {r#"
+            pub fn double(n: Int) -> Int {
+              n * 2
+            }
+
+            pub fn sign(n: Int) -> Int {
+              if n < 0 {
+                -1
+              } else {
+                1
+              }
+            }
+
+            test double_is_even(n: Int) {
+              double(n) % 2 == 0
+            }
+
+            test sign_of_three() {
+              sign(3) == 1
+            }
+        "#},
+    );
+
+    project
+}
+
+#[test]
+fn profile_folds_stacks_by_function() {
+    let project = math();
+
+    project.test(true, false).unwrap();
+
+    let cpu = project.read(paths::build_profile().join("cpu.folded"));
+
+    let stacks: Vec<&str> = cpu
+        .lines()
+        .map(|line| {
+            let (stack, amount) = line.rsplit_once(' ').unwrap();
+
+            assert!(amount.parse::<i64>().unwrap() > 0, "{line}");
+
+            stack
+        })
+        .collect();
+
+    assert!(stacks.iter().all(|stack| {
+        stack.starts_with("math.double_is_even") || stack.starts_with("math.sign_of_three")
+    }));
+
+    for stack in [
+        "math.double_is_even",
+        "math.double_is_even;math.double",
+        "math.double_is_even;math.double;multiplyInteger",
+        "math.double_is_even;modInteger",
+        "math.sign_of_three;math.sign",
+        "math.sign_of_three;math.sign;ifThenElse",
+    ] {
+        assert!(stacks.contains(&stack), "{stack} missing from\n{cpu}");
+    }
+
+    let mem = project.read(paths::build_profile().join("mem.folded"));
+
+    assert!(mem
+        .lines()
+        .any(|line| line.starts_with("math.sign_of_three;math.sign ")));
+
+    assert!(!project.root().join(paths::build_coverage()).exists());
+}
+
+#[test]
+fn coverage_reports_functions_branches_and_lines() {
+    let project = math();
+
+    project.test(false, true).unwrap();
+
+    let lcov = project.read(paths::build_coverage());
+
+    let records: Vec<&str> = lcov
+        .lines()
+        .filter(|line| {
+            ["FN:", "FNDA:", "BRDA:", "DA:"]
+                .iter()
+                .any(|tag| line.starts_with(tag))
+        })
+        .collect();
+
+    // The property test runs double with each of its 100 inputs, the unit
+    // test only takes the else branch of sign.
+    assert_eq!(
+        records,
+        [
+            "FN:1,double",
+            "FNDA:100,double",
+            "FN:5,sign",
+            "FNDA:1,sign",
+            "BRDA:6,0,0,0",
+            "BRDA:6,0,1,1",
+            "DA:1,100",
+            "DA:2,100",
+            "DA:5,1",
+            "DA:6,1",
+            "DA:7,0",
+            "DA:9,1",
+        ]
+    );
+
+    assert!(lcov.ends_with("end_of_record\n"));
+
+    assert!(!project.root().join(paths::build_profile()).exists());
+}
@@ -0,0 +1,78 @@
+(function () {
+  const root = document.body.dataset.root;
+  const input = document.getElementById("search-input");
+  const results = document.getElementById("search-results");
+  const content = document.getElementById("content");
+
+  let entries = [];
+
+  function matches(entry, terms) {
+    const haystack = `${entry.title} ${entry.doc} ${entry.content}`.toLowerCase();
+
+    return terms.every((term) => haystack.includes(term));
+  }
+
+  function rank(entry, terms) {
+    const title = entry.title.toLowerCase();
+
+    if (terms.some((term) => title === term)) {
+      return 0;
+    }
+
+    if (terms.some((term) => title.includes(term))) {
+      return 1;
+    }
+
+    return 2;
+  }
+
+  function render(query) {
+    const terms = query.toLowerCase().split(/\s+/).filter((term) => term.length > 0);
+
+    if (terms.length === 0) {
+      results.hidden = true;
+      content.hidden = false;
+      return;
+    }
+
+    const found = entries
+      .filter((entry) => matches(entry, terms))
+      .sort((a, b) => rank(a, terms) - rank(b, terms))
+      .slice(0, 50);
+
+    results.replaceChildren(
+      ...found.map((entry) => {
+        const item = document.createElement("li");
+        const link = document.createElement("a");
+        const doc = document.createElement("span");
+
+        link.href = `${root}/${entry.url}`;
+        link.textContent = entry.title;
+        doc.className = "doc";
+        doc.textContent = entry.doc;
+
+        item.append(link, doc);
+
+        return item;
+      })
+    );
+
+    if (found.length === 0) {
+      const item = document.createElement("li");
+      item.textContent = "No results.";
+      results.append(item);
+    }
+
+    results.hidden = false;
+    content.hidden = true;
+  }
+
+  input.addEventListener("input", () => render(input.value));
+
+  window.Nano = {
+    initSearch(data) {
+      entries = data;
+      render(input.value);
+    },
+  };
+})();
@@ -15,7 +15,10 @@ use crate::{
     flat::Binder,
     machine::{
         cost_model::{initialize_cost_model, CostModel, ExBudget},
+        coverage::Hits,
+        debugger::{Debugger, Observer},
         eval_result::EvalResult,
+        profiler::{Profile, Profiler},
         Machine,
     },
 };
@@ -100,7 +103,7 @@ impl Serialize for Program<DeBruijn> {
         let cbor = self.to_cbor().unwrap();
         let mut s = serializer.serialize_struct("Program<DeBruijn>", 2)?;
         s.serialize_field("compiledCode", &hex::encode(&cbor))?;
-        s.serialize_field("hash", &cardano::PlutusV2Script(cbor.into()).compute_hash())?;
+        s.serialize_field("hash", &self.hash())?;
         s.end()
     }
 }
@@ -159,12 +162,15 @@ impl<'a> Deserialize<'a> for Program<DeBruijn> {
 }
 
 impl Program<DeBruijn> {
-    pub fn address(&self, network: Network, delegation: ShelleyDelegationPart) -> ShelleyAddress {
+    pub fn hash(&self) -> pallas_crypto::hash::Hash<28> {
         let cbor = self.to_cbor().unwrap();
-        let validator_hash = cardano::PlutusV2Script(cbor.into()).compute_hash();
+        cardano::PlutusV2Script(cbor.into()).compute_hash()
+    }
+
+    pub fn address(&self, network: Network, delegation: ShelleyDelegationPart) -> ShelleyAddress {
         ShelleyAddress::new(
             network,
-            ShelleyPaymentPart::Script(validator_hash),
+            ShelleyPaymentPart::Script(self.hash()),
             delegation,
         )
     }
@@ -196,6 +202,33 @@ impl<T> Term<T> {
     pub fn is_int(&self) -> bool {
         matches!(self, Term::Constant(c) if matches!(c.as_ref(), &Constant::Integer(_)))
     }
+
+    /// Every sub-term in pre-order, along with the position of its parent.
+    /// Positions index into this list, and are how source maps and the
+    /// debugger refer to the terms of a program.
+    pub fn positions(&self) -> Vec<(&Term<T>, Option<usize>)> {
+        let mut positions = vec![];
+        let mut stack = vec![(self, None)];
+
+        while let Some((term, parent)) = stack.pop() {
+            let position = Some(positions.len());
+
+            positions.push((term, parent));
+
+            match term {
+                Term::Delay(body) | Term::Lambda { body, .. } | Term::Force(body) => {
+                    stack.push((body.as_ref(), position));
+                }
+                Term::Apply { function, argument } => {
+                    stack.push((argument.as_ref(), position));
+                    stack.push((function.as_ref(), position));
+                }
+                Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => {}
+            }
+        }
+
+        positions
+    }
 }
 
 impl<'a, T> Display for Term<T>
@@ -647,6 +680,65 @@ impl Program<NamedDeBruijn> {
 
         EvalResult::new(term, machine.ex_budget, budget, machine.logs)
     }
+
+    /// Evaluates the program like `eval`, a step at a time, letting every
+    /// observer follow each step. Starting the machine is spent before the
+    /// first one. Steps are spent as they are taken, without slippage.
+    pub fn eval_observed(
+        self,
+        initial_budget: ExBudget,
+        observers: &mut [&mut dyn Observer],
+    ) -> EvalResult {
+        let machine = Machine::new(Language::PlutusV2, CostModel::default(), initial_budget, 1);
+
+        let mut debugger = match Debugger::new(machine, self) {
+            Ok(debugger) => debugger,
+            Err(error) => {
+                return EvalResult::new(Err(error), initial_budget, initial_budget, vec![]);
+            }
+        };
+
+        for observer in observers.iter_mut() {
+            observer.after_step(&debugger, initial_budget - debugger.machine().ex_budget);
+        }
+
+        let term = debugger.run(observers);
+
+        let machine = debugger.machine();
+
+        EvalResult::new(
+            term,
+            machine.ex_budget,
+            initial_budget,
+            machine.logs.clone(),
+        )
+    }
+
+    /// Evaluates the program like `eval`, recording what is spent under each
+    /// function as a `Profiler` does. Starting the machine is spent at the
+    /// root.
+    pub fn profile(
+        self,
+        initial_budget: ExBudget,
+        root: &str,
+        frame: impl Fn(usize) -> Option<String>,
+    ) -> (EvalResult, Profile) {
+        let mut profiler = Profiler::new(root, frame);
+
+        let result = self.eval_observed(initial_budget, &mut [&mut profiler]);
+
+        (result, profiler.profile)
+    }
+
+    /// Evaluates the program like `eval`, counting how many times each of its
+    /// terms is computed.
+    pub fn coverage(self, initial_budget: ExBudget) -> (EvalResult, Hits) {
+        let mut hits = Hits::new();
+
+        let result = self.eval_observed(initial_budget, &mut [&mut hits]);
+
+        (result, hits)
+    }
 }
 
 impl Program<DeBruijn> {
@@ -655,6 +747,33 @@ impl Program<DeBruijn> {
 
         program.eval(initial_budget)
     }
+
+    pub fn eval_observed(
+        &self,
+        initial_budget: ExBudget,
+        observers: &mut [&mut dyn Observer],
+    ) -> EvalResult {
+        let program: Program<NamedDeBruijn> = self.clone().into();
+
+        program.eval_observed(initial_budget, observers)
+    }
+
+    pub fn profile(
+        &self,
+        initial_budget: ExBudget,
+        root: &str,
+        frame: impl Fn(usize) -> Option<String>,
+    ) -> (EvalResult, Profile) {
+        let program: Program<NamedDeBruijn> = self.clone().into();
+
+        program.profile(initial_budget, root, frame)
+    }
+
+    pub fn coverage(&self, initial_budget: ExBudget) -> (EvalResult, Hits) {
+        let program: Program<NamedDeBruijn> = self.clone().into();
+
+        program.coverage(initial_budget)
+    }
 }
 
 impl Term<NamedDeBruijn> {
@@ -14,9 +14,12 @@ use std::rc::Rc;
 use crate::ast::{Constant, NamedDeBruijn, Term, Type};
 
 pub mod cost_model;
+pub mod coverage;
+pub mod debugger;
 mod discharge;
 mod error;
 pub mod eval_result;
+pub mod profiler;
 pub mod runtime;
 pub mod value;
 
@@ -30,20 +33,35 @@ use self::{
     value::{Env, Value},
 };
 
-enum MachineState {
+/// Where the machine is between two steps. Terms being computed are shared
+/// with the program, so that they can be told apart by their address.
+pub enum MachineState {
     Return(Context, Value),
-    Compute(Context, Env, Term<NamedDeBruijn>),
+    Compute(Context, Env, Rc<Term<NamedDeBruijn>>),
     Done(Term<NamedDeBruijn>),
 }
 
+/// What is left to do with the value being computed, innermost frame first.
 #[derive(Clone)]
-enum Context {
+pub enum Context {
     FrameApplyFun(Value, Box<Context>),
-    FrameApplyArg(Env, Term<NamedDeBruijn>, Box<Context>),
+    FrameApplyArg(Env, Rc<Term<NamedDeBruijn>>, Box<Context>),
     FrameForce(Box<Context>),
     NoFrame,
 }
 
+impl Context {
+    /// The frame this one returns to.
+    pub fn parent(&self) -> Option<&Context> {
+        match self {
+            Context::FrameApplyFun(_, parent)
+            | Context::FrameApplyArg(_, _, parent)
+            | Context::FrameForce(parent) => Some(parent),
+            Context::NoFrame => None,
+        }
+    }
+}
+
 pub struct Machine {
     costs: CostModel,
     pub ex_budget: ExBudget,
@@ -71,32 +89,48 @@ impl Machine {
     }
 
     pub fn run(&mut self, term: Term<NamedDeBruijn>) -> Result<Term<NamedDeBruijn>, Error> {
-        use MachineState::*;
-
-        let startup_budget = self.costs.machine_costs.get(StepKind::StartUp);
-
-        self.spend_budget(startup_budget)?;
-
-        let mut state = Compute(Context::NoFrame, Rc::new(vec![]), term);
+        let mut state = self.start(Rc::new(term))?;
 
         loop {
             state = match state {
-                Compute(context, env, t) => self.compute(context, env, t)?,
-                Return(context, value) => self.return_compute(context, value)?,
-                Done(t) => {
+                MachineState::Done(t) => {
                     return Ok(t);
                 }
+                state => self.step(state)?,
             };
         }
     }
 
+    /// Spends the startup budget and gets ready to compute the term.
+    pub fn start(&mut self, term: Rc<Term<NamedDeBruijn>>) -> Result<MachineState, Error> {
+        let startup_budget = self.costs.machine_costs.get(StepKind::StartUp);
+
+        self.spend_budget(startup_budget)?;
+
+        Ok(MachineState::Compute(
+            Context::NoFrame,
+            Rc::new(vec![]),
+            term,
+        ))
+    }
+
+    /// Moves the machine on by a single transition. A machine that is done
+    /// stays done.
+    pub fn step(&mut self, state: MachineState) -> Result<MachineState, Error> {
+        match state {
+            MachineState::Compute(context, env, t) => self.compute(context, env, t),
+            MachineState::Return(context, value) => self.return_compute(context, value),
+            done @ MachineState::Done(_) => Ok(done),
+        }
+    }
+
     fn compute(
         &mut self,
         context: Context,
         env: Env,
-        term: Term<NamedDeBruijn>,
+        term: Rc<Term<NamedDeBruijn>>,
     ) -> Result<MachineState, Error> {
-        match term {
+        match term.as_ref() {
             Term::Var(name) => {
                 self.step_and_maybe_spend(StepKind::Var)?;
 
@@ -107,7 +141,10 @@ impl Machine {
             Term::Delay(body) => {
                 self.step_and_maybe_spend(StepKind::Delay)?;
 
-                Ok(MachineState::Return(context, Value::Delay(body, env)))
+                Ok(MachineState::Return(
+                    context,
+                    Value::Delay(body.clone(), env),
+                ))
             }
             Term::Lambda {
                 parameter_name,
@@ -118,8 +155,8 @@ impl Machine {
                 Ok(MachineState::Return(
                     context,
                     Value::Lambda {
-                        parameter_name,
-                        body,
+                        parameter_name: parameter_name.clone(),
+                        body: body.clone(),
                         env,
                     },
                 ))
@@ -128,15 +165,15 @@ impl Machine {
                 self.step_and_maybe_spend(StepKind::Apply)?;
 
                 Ok(MachineState::Compute(
-                    Context::FrameApplyArg(env.clone(), argument.as_ref().clone(), context.into()),
+                    Context::FrameApplyArg(env.clone(), argument.clone(), context.into()),
                     env,
-                    function.as_ref().clone(),
+                    function.clone(),
                 ))
             }
             Term::Constant(x) => {
                 self.step_and_maybe_spend(StepKind::Constant)?;
 
-                Ok(MachineState::Return(context, Value::Con(x)))
+                Ok(MachineState::Return(context, Value::Con(x.clone())))
             }
             Term::Force(body) => {
                 self.step_and_maybe_spend(StepKind::Force)?;
@@ -144,18 +181,18 @@ impl Machine {
                 Ok(MachineState::Compute(
                     Context::FrameForce(context.into()),
                     env,
-                    body.as_ref().clone(),
+                    body.clone(),
                 ))
             }
             Term::Error => Err(Error::EvaluationFailure),
             Term::Builtin(fun) => {
                 self.step_and_maybe_spend(StepKind::Builtin)?;
 
-                let runtime: BuiltinRuntime = fun.into();
+                let runtime: BuiltinRuntime = (*fun).into();
 
                 Ok(MachineState::Return(
                     context,
-                    Value::Builtin { fun, runtime },
+                    Value::Builtin { fun: *fun, runtime },
                 ))
             }
         }
@@ -184,9 +221,7 @@ impl Machine {
 
     fn force_evaluate(&mut self, context: Context, value: Value) -> Result<MachineState, Error> {
         match value {
-            Value::Delay(body, env) => {
-                Ok(MachineState::Compute(context, env, body.as_ref().clone()))
-            }
+            Value::Delay(body, env) => Ok(MachineState::Compute(context, env, body)),
             Value::Builtin { fun, mut runtime } => {
                 if runtime.needs_force() {
                     runtime.consume_force();
@@ -220,11 +255,7 @@ impl Machine {
 
                 e.push(argument);
 
-                Ok(MachineState::Compute(
-                    context,
-                    Rc::new(e.clone()),
-                    body.as_ref().clone(),
-                ))
+                Ok(MachineState::Compute(context, Rc::new(e.clone()), body))
             }
             Value::Builtin { fun, runtime } => {
                 if runtime.is_arrow() && !runtime.needs_force() {
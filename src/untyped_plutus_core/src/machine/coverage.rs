@@ -0,0 +1,30 @@
+/**
+ * @file coverage.rs
+ * @author Krisna Pranav
+ * @version 0.1
+ * @date 2023-05-15
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+use std::collections::BTreeMap;
+
+use super::{
+    debugger::{Debugger, Observer},
+    MachineState,
+};
+
+/// How many times each term of a program was computed, by position. Terms
+/// that never were are left out.
+pub type Hits = BTreeMap<usize, usize>;
+
+impl Observer for Hits {
+    fn before_step(&mut self, debugger: &Debugger) {
+        if let (Some(MachineState::Compute(..)), Some(position)) =
+            (debugger.state(), debugger.position())
+        {
+            *self.entry(position).or_insert(0) += 1;
+        }
+    }
+}
@@ -0,0 +1,331 @@
+/*
+ * @file debugger.rs
+ * @author Krisna Pranav
+ * @version 0.1
+ * @date 2023-05-07
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use crate::ast::{NamedDeBruijn, Program, Term};
+
+use super::{cost_model::ExBudget, discharge, value::Env, Context, Error, Machine, MachineState};
+
+/// Where a debugger stopped.
+pub enum Status {
+    Paused,
+    Done(Term<NamedDeBruijn>),
+}
+
+/// Follows an evaluation as a debugger takes it, a step at a time.
+pub trait Observer {
+    /// Called ahead of each step, with the debugger about to take it.
+    fn before_step(&mut self, _debugger: &Debugger) {}
+
+    /// Called once a step was taken, with what it spent.
+    fn after_step(&mut self, _debugger: &Debugger, _spent: ExBudget) {}
+}
+
+/// Evaluates a program a step at a time, pausing on breakpoints. Terms are
+/// referred to by their position in the program, as given by
+/// `Term::positions`.
+pub struct Debugger {
+    machine: Machine,
+    program: Rc<Term<NamedDeBruijn>>,
+    /// `None` once the evaluation failed.
+    state: Option<MachineState>,
+    positions: HashMap<*const Term<NamedDeBruijn>, usize>,
+    parents: Vec<Option<usize>>,
+    binders: Vec<Option<Rc<NamedDeBruijn>>>,
+    pub breakpoints: BTreeSet<usize>,
+    /// The term computed last and the environment it was computed in.
+    position: Option<usize>,
+    env: Env,
+}
+
+impl Debugger {
+    pub fn new(mut machine: Machine, program: Program<NamedDeBruijn>) -> Result<Self, Error> {
+        let program = Rc::new(program.term);
+
+        let mut positions = HashMap::new();
+        let mut parents = vec![];
+        let mut binders = vec![];
+
+        for (position, (term, parent)) in program.positions().into_iter().enumerate() {
+            positions.insert(term as *const Term<NamedDeBruijn>, position);
+            parents.push(parent);
+            binders.push(match term {
+                Term::Lambda { parameter_name, .. } => Some(parameter_name.clone()),
+                _ => None,
+            });
+        }
+
+        let state = machine.start(program.clone())?;
+
+        let mut debugger = Debugger {
+            machine,
+            program,
+            state: None,
+            positions,
+            parents,
+            binders,
+            breakpoints: BTreeSet::new(),
+            position: None,
+            env: Rc::new(vec![]),
+        };
+
+        debugger.enter(state);
+
+        Ok(debugger)
+    }
+
+    /// Moves the machine on by a single transition.
+    pub fn step(&mut self) -> Result<Status, Error> {
+        let state = self.state.take().ok_or(Error::MachineNeverReachedDone)?;
+
+        let state = self.machine.step(state)?;
+
+        let status = match &state {
+            MachineState::Done(term) => Status::Done(term.clone()),
+            _ => Status::Paused,
+        };
+
+        self.enter(state);
+
+        Ok(status)
+    }
+
+    /// Steps until the machine is about to compute a term on a breakpoint, or
+    /// is done.
+    pub fn resume(&mut self) -> Result<Status, Error> {
+        loop {
+            if let Status::Done(term) = self.step()? {
+                return Ok(Status::Done(term));
+            }
+
+            if matches!(self.state, Some(MachineState::Compute(..)))
+                && self
+                    .position
+                    .is_some_and(|position| self.breakpoints.contains(&position))
+            {
+                return Ok(Status::Paused);
+            }
+        }
+    }
+
+    /// Steps until the machine is done or failed, letting every observer
+    /// follow each step.
+    pub fn run(
+        &mut self,
+        observers: &mut [&mut dyn Observer],
+    ) -> Result<Term<NamedDeBruijn>, Error> {
+        loop {
+            for observer in observers.iter_mut() {
+                observer.before_step(self);
+            }
+
+            let before = self.machine.ex_budget;
+
+            let status = self.step();
+
+            for observer in observers.iter_mut() {
+                observer.after_step(self, before - self.machine.ex_budget);
+            }
+
+            if let Status::Done(term) = status? {
+                return Ok(term);
+            }
+        }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    pub fn program(&self) -> &Term<NamedDeBruijn> {
+        &self.program
+    }
+
+    pub fn state(&self) -> Option<&MachineState> {
+        self.state.as_ref()
+    }
+
+    /// The position of the term computed last.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// The position of a term of the program, such as one held by a frame.
+    pub fn position_of(&self, term: &Rc<Term<NamedDeBruijn>>) -> Option<usize> {
+        self.positions.get(&Rc::as_ptr(term)).copied()
+    }
+
+    /// The frames of the current context, innermost first.
+    pub fn frames(&self) -> Vec<&Context> {
+        let mut frames = vec![];
+
+        let mut context = match &self.state {
+            Some(MachineState::Compute(context, ..) | MachineState::Return(context, _)) => {
+                Some(context)
+            }
+            Some(MachineState::Done(_)) | None => None,
+        };
+
+        while let Some(frame) = context {
+            frames.push(frame);
+            context = frame.parent();
+        }
+
+        frames
+    }
+
+    /// The variables in scope of the term computed last, outermost first. The
+    /// environment of a term holds a value for each lambda around it.
+    pub fn locals(&self) -> Vec<(String, Term<NamedDeBruijn>)> {
+        let mut names = vec![];
+
+        let mut parent = self.position.and_then(|position| self.parents[position]);
+
+        while let Some(position) = parent {
+            if let Some(name) = &self.binders[position] {
+                names.push(name.text.clone());
+            }
+
+            parent = self.parents[position];
+        }
+
+        let mut locals: Vec<_> = names
+            .into_iter()
+            .zip(self.env.iter().rev())
+            .map(|(name, value)| (name, discharge::value_as_term(value.clone())))
+            .collect();
+
+        locals.reverse();
+
+        locals
+    }
+
+    fn enter(&mut self, state: MachineState) {
+        if let MachineState::Compute(_, env, term) = &state {
+            self.position = self.position_of(term);
+            self.env = env.clone();
+        }
+
+        self.state = Some(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{machine::cost_model::CostModel, parser};
+    use pallas_primitives::babbage::Language;
+    use pretty_assertions::assert_eq;
+
+    /// `(\x -> (\y -> x + y) 2) 1`, whose terms are at the positions:
+    ///
+    /// 0 `[(lam x ...) (con integer 1)]`, 1 `(lam x ...)`,
+    /// 2 `[(lam y ...) (con integer 2)]`, 3 `(lam y ...)`, 4 `[[add x] y]`,
+    /// 5 `[add x]`, 6 `add`, 7 `x`, 8 `y`, 9 `(con integer 2)`,
+    /// 10 `(con integer 1)`.
+    const SRC: &str = "(program 1.0.0 [(lam x [(lam y [[(builtin addInteger) x] y]) (con integer 2)]) (con integer 1)])";
+
+    fn debugger() -> Debugger {
+        let program: Program<NamedDeBruijn> = parser::program(SRC)
+            .expect("Failed to parse program")
+            .try_into()
+            .unwrap();
+
+        let machine = Machine::new(
+            Language::PlutusV2,
+            CostModel::default(),
+            ExBudget::default(),
+            1,
+        );
+
+        Debugger::new(machine, program).unwrap()
+    }
+
+    fn done(status: Status) -> Term<NamedDeBruijn> {
+        match status {
+            Status::Done(term) => term,
+            Status::Paused => panic!("Expected the debugger to be done"),
+        }
+    }
+
+    #[test]
+    fn step_one_transition_at_a_time() {
+        let mut debugger = debugger();
+
+        assert_eq!(debugger.position(), Some(0));
+
+        let mut positions = vec![];
+
+        let term = loop {
+            match debugger.step().unwrap() {
+                Status::Paused => positions.push(debugger.position()),
+                Status::Done(term) => break term,
+            }
+        };
+
+        assert_eq!(term, Term::integer(3.into()));
+        assert_eq!(positions.first(), Some(&Some(1)));
+        assert!(positions.contains(&Some(8)));
+        assert!(debugger.frames().is_empty());
+    }
+
+    #[test]
+    fn resume_pauses_on_breakpoints() {
+        let mut debugger = debugger();
+
+        debugger.breakpoints.extend([7, 8]);
+
+        assert!(matches!(debugger.resume().unwrap(), Status::Paused));
+        assert_eq!(debugger.position(), Some(7));
+
+        assert!(matches!(debugger.resume().unwrap(), Status::Paused));
+        assert_eq!(debugger.position(), Some(8));
+
+        assert_eq!(done(debugger.resume().unwrap()), Term::integer(3.into()));
+    }
+
+    #[test]
+    fn frames_innermost_first() {
+        let mut debugger = debugger();
+
+        debugger.breakpoints.insert(8);
+
+        debugger.resume().unwrap();
+
+        let frames = debugger.frames();
+
+        // `y` is the argument of `[add x]`, which was already computed.
+        assert!(matches!(frames.first(), Some(Context::FrameApplyFun(..))));
+        assert!(matches!(frames.last(), Some(Context::NoFrame)));
+    }
+
+    #[test]
+    fn locals_outermost_first() {
+        let mut debugger = debugger();
+
+        assert_eq!(debugger.locals(), vec![]);
+
+        debugger.breakpoints.insert(8);
+
+        debugger.resume().unwrap();
+
+        assert_eq!(
+            debugger.locals(),
+            vec![
+                ("x".to_string(), Term::integer(1.into())),
+                ("y".to_string(), Term::integer(2.into())),
+            ]
+        );
+    }
+}
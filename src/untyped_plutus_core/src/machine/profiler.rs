@@ -0,0 +1,147 @@
+/**
+ * @file profiler.rs
+ * @author Krisna Pranav
+ * @version 0.1
+ * @date 2023-05-14
+ *
+ * @copyright Copyright (c) 2023 Krisna Pranav, NanoBlocksDevelopers
+ *
+*/
+
+use std::collections::BTreeMap;
+
+use super::{
+    cost_model::ExBudget,
+    debugger::{Debugger, Observer},
+    value::Value,
+    Context, MachineState,
+};
+
+/// The budget spent under each stack of frames, outermost frame first.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    stacks: BTreeMap<Vec<String>, ExBudget>,
+}
+
+impl Profile {
+    pub fn record(&mut self, stack: Vec<String>, spent: ExBudget) {
+        if spent.cpu == 0 && spent.mem == 0 {
+            return;
+        }
+
+        let total = self
+            .stacks
+            .entry(stack)
+            .or_insert(ExBudget { mem: 0, cpu: 0 });
+
+        total.mem += spent.mem;
+        total.cpu += spent.cpu;
+    }
+
+    pub fn merge(&mut self, other: Profile) {
+        for (stack, spent) in other.stacks {
+            self.record(stack, spent);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[String], &ExBudget)> {
+        self.stacks
+            .iter()
+            .map(|(stack, spent)| (stack.as_slice(), spent))
+    }
+
+    /// The cpu spent, in the folded format read by flamegraph tools.
+    pub fn folded_cpu(&self) -> String {
+        self.folded(|spent| spent.cpu)
+    }
+
+    /// The memory spent, in the folded format read by flamegraph tools.
+    pub fn folded_mem(&self) -> String {
+        self.folded(|spent| spent.mem)
+    }
+
+    /// A line per stack: its frames separated by semicolons, then the amount.
+    fn folded(&self, amount: fn(&ExBudget) -> i64) -> String {
+        self.iter()
+            .filter(|(_, spent)| amount(spent) > 0)
+            .map(|(stack, spent)| format!("{} {}\n", stack.join(";"), amount(spent)))
+            .collect()
+    }
+}
+
+/// Records what each step spends under the stack of functions it is spent
+/// in, below `root`. `frame` names the function a term is part of, given its
+/// position. Builtins get a frame of their own, under the function that
+/// called them.
+///
+/// The stack is made of the functions that are waiting on the value being
+/// computed, so a function that calls another as its very last step no
+/// longer shows up under it. Steps are only spent as they are taken by a
+/// machine without slippage.
+pub struct Profiler<F> {
+    root: String,
+    frame: F,
+    stack: Vec<String>,
+    pub profile: Profile,
+}
+
+impl<F> Profiler<F>
+where
+    F: Fn(usize) -> Option<String>,
+{
+    pub fn new(root: &str, frame: F) -> Self {
+        Profiler {
+            root: root.to_string(),
+            frame,
+            stack: vec![root.to_string()],
+            profile: Profile::default(),
+        }
+    }
+}
+
+impl<F> Observer for Profiler<F>
+where
+    F: Fn(usize) -> Option<String>,
+{
+    fn before_step(&mut self, debugger: &Debugger) {
+        self.stack = stack(debugger, &self.root, &self.frame);
+    }
+
+    fn after_step(&mut self, _debugger: &Debugger, spent: ExBudget) {
+        self.profile.record(self.stack.clone(), spent);
+    }
+}
+
+fn stack(debugger: &Debugger, root: &str, frame: &impl Fn(usize) -> Option<String>) -> Vec<String> {
+    let mut positions: Vec<usize> = debugger
+        .frames()
+        .into_iter()
+        .rev()
+        .filter_map(|context| match context {
+            Context::FrameApplyArg(_, term, _) => debugger.position_of(term),
+            _ => None,
+        })
+        .collect();
+
+    positions.extend(debugger.position());
+
+    let mut stack = vec![root.to_string()];
+
+    for name in positions.into_iter().filter_map(frame) {
+        if stack.last() != Some(&name) {
+            stack.push(name);
+        }
+    }
+
+    let builtin = match debugger.state() {
+        Some(MachineState::Return(Context::FrameApplyFun(Value::Builtin { fun, .. }, _), _))
+        | Some(MachineState::Return(Context::FrameForce(_), Value::Builtin { fun, .. })) => {
+            Some(fun)
+        }
+        _ => None,
+    };
+
+    stack.extend(builtin.map(|fun| fun.to_string()));
+
+    stack
+}
@@ -21,7 +21,7 @@ use crate::{
 
 use super::{runtime::BuiltinRuntime, Error};
 
-pub(super) type Env = Rc<Vec<Value>>;
+pub type Env = Rc<Vec<Value>>;
 
 #[derive(Clone, Debug)]
 pub enum Value {
@@ -16,8 +16,19 @@ use crate::{
 
 pub mod shrinker;
 
+use shrinker::Labels;
+
 pub fn nano_optimize_and_intern(program: Program<Name>) -> Program<Name> {
-    let mut program = program.builtin_force_reduce();
+    nano_optimize_and_intern_labelled(program, &mut Labels::new())
+}
+
+/// Optimises a program whose terms are labelled, so that the labels point at
+/// the same terms in the optimised program.
+pub fn nano_optimize_and_intern_labelled(
+    program: Program<Name>,
+    labels: &mut Labels,
+) -> Program<Name> {
+    let mut program = program.builtin_force_reduce(labels);
 
     let mut interner = Interner::new();
 
@@ -27,13 +38,15 @@ pub fn nano_optimize_and_intern(program: Program<Name>) -> Program<Name> {
 
     let program: Program<Name> = program_named.try_into().unwrap();
 
+    // Interning and converting back and forth keep the terms where they are.
     program
-        .lambda_reduce()
-        .inline_reduce()
-        .lambda_reduce()
-        .inline_reduce()
-        .force_delay_reduce()
-        .wrap_data_reduce()
-        .lambda_reduce()
-        .inline_reduce()
+        .lambda_reduce(labels)
+        .inline_reduce(labels)
+        .lambda_reduce(labels)
+        .inline_reduce(labels)
+        .force_delay_reduce(labels)
+        .wrap_data_reduce(labels)
+        .constant_fold_reduce(labels)
+        .lambda_reduce(labels)
+        .inline_reduce(labels)
 }
\ No newline at end of file
@@ -8,13 +8,17 @@
  *
 */
 
-use std::rc::Rc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::{
-    ast::{Name, Program, Term},
+    ast::{Constant, Name, Program, Term},
     builtins::DefaultFunction,
+    machine::{runtime::BuiltinRuntime, value::Value},
 };
 
 #[derive(Eq, Hash, PartialEq, Clone)]
@@ -23,20 +27,22 @@ pub struct Occurrence {
     lambda_count: usize,
 }
 
+/// Labels on the terms of a program, by position. Optimisations move them
+/// along with the terms, so that a term carries the label of what it replaces.
+pub type Labels = BTreeMap<usize, usize>;
+
 impl Program<Name> {
-    pub fn lambda_reduce(self) -> Program<Name> {
-        let mut term = self.term;
-        lambda_reduce(&mut term);
-        Program {
-            version: self.version,
-            term,
-        }
+    pub fn lambda_reduce(self, labels: &mut Labels) -> Program<Name> {
+        self.traverse(labels, lambda_reduce)
     }
 
-    pub fn builtin_force_reduce(self) -> Program<Name> {
+    pub fn builtin_force_reduce(self, labels: &mut Labels) -> Program<Name> {
         let mut term = self.term;
         let mut builtin_map = IndexMap::new();
-        builtin_force_reduce(&mut term, &mut builtin_map);
+
+        let mut tracker = Tracker::new(&term, labels);
+        builtin_force_reduce(&mut term, &mut builtin_map, &mut tracker);
+        *labels = tracker.labels(&term);
 
         for default_func_index in builtin_map.keys().sorted().cloned() {
             let default_func: DefaultFunction = default_func_index.try_into().unwrap();
@@ -48,6 +54,12 @@ impl Program<Name> {
                 } else {
                     Term::Builtin(default_func).force().force()
                 });
+
+            // The program moved under a lambda and its application.
+            *labels = labels
+                .iter()
+                .map(|(position, label)| (position + 2, *label))
+                .collect();
         }
 
         Program {
@@ -56,137 +68,265 @@ impl Program<Name> {
         }
     }
 
-    pub fn inline_reduce(self) -> Program<Name> {
+    pub fn inline_reduce(self, labels: &mut Labels) -> Program<Name> {
+        self.traverse(labels, |term, tracker| {
+            inline_basic_reduce(term, tracker);
+            inline_direct_reduce(term, tracker);
+        })
+    }
+
+    pub fn force_delay_reduce(self, labels: &mut Labels) -> Program<Name> {
+        self.traverse(labels, force_delay_reduce)
+    }
+
+    pub fn wrap_data_reduce(self, labels: &mut Labels) -> Program<Name> {
+        self.traverse(labels, wrap_data_reduce)
+    }
+
+    pub fn constant_fold_reduce(self, labels: &mut Labels) -> Program<Name> {
+        self.traverse(labels, constant_fold_reduce)
+    }
+
+    fn traverse(
+        self,
+        labels: &mut Labels,
+        pass: fn(&mut Term<Name>, &mut Tracker),
+    ) -> Program<Name> {
         let mut term = self.term;
-        inline_basic_reduce(&mut term);
-        inline_direct_reduce(&mut term);
+
+        let mut tracker = Tracker::new(&term, labels);
+        pass(&mut term, &mut tracker);
+        *labels = tracker.labels(&term);
 
         Program {
             version: self.version,
             term,
         }
     }
+}
 
-    pub fn force_delay_reduce(self) -> Program<Name> {
-        let mut term = self.term;
-        force_delay_reduce(&mut term);
-        Program {
-            version: self.version,
-            term,
+/// Labels by the address of their term while a pass runs. Terms stay where
+/// they are when mutated in place, so only copies and replacements need
+/// taking care of. Labels of the terms freed by a replacement are dropped,
+/// lest a new term be allocated at the same address.
+#[derive(Default)]
+struct Tracker {
+    labels: HashMap<*const Term<Name>, usize>,
+}
+
+impl Tracker {
+    fn new(term: &Term<Name>, labels: &Labels) -> Self {
+        if labels.is_empty() {
+            return Tracker::default();
+        }
+
+        let positions = term.positions();
+
+        Tracker {
+            labels: labels
+                .iter()
+                .filter_map(|(position, label)| {
+                    let (term, _) = positions.get(*position)?;
+
+                    Some((*term as *const Term<Name>, *label))
+                })
+                .collect(),
         }
     }
 
-    pub fn wrap_data_reduce(self) -> Program<Name> {
-        let mut term = self.term;
-        wrap_data_reduce(&mut term);
-        Program {
-            version: self.version,
-            term,
+    fn labels(&self, term: &Term<Name>) -> Labels {
+        if self.labels.is_empty() {
+            return Labels::new();
+        }
+
+        term.positions()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(position, (term, _))| {
+                let label = self.labels.get(&(term as *const Term<Name>))?;
+
+                Some((position, *label))
+            })
+            .collect()
+    }
+
+    /// `Rc::make_mut`, labelling the copy of a shared term like the original.
+    fn make_mut<'a>(&mut self, term: &'a mut Rc<Term<Name>>) -> &'a mut Term<Name> {
+        let original = Rc::as_ptr(term);
+
+        let term = Rc::make_mut(term);
+
+        if !std::ptr::eq(original, &*term) {
+            self.copy(original, &*term);
+        }
+
+        term
+    }
+
+    /// Wraps a new term, labelled like the one it comes from.
+    fn new_rc(&mut self, term: Term<Name>, origin: &Term<Name>) -> Rc<Term<Name>> {
+        let term = Rc::new(term);
+
+        self.copy(origin, term.as_ref());
+
+        term
+    }
+
+    /// Replaces a term, which takes the label of `origin` when there is one
+    /// and keeps its own otherwise.
+    fn replace(
+        &mut self,
+        term: &mut Term<Name>,
+        replacement: Term<Name>,
+        origin: Option<*const Term<Name>>,
+    ) {
+        if !self.labels.is_empty() {
+            let label = origin.and_then(|origin| self.labels.get(&origin).copied());
+
+            self.forget(term);
+
+            if let Some(label) = label {
+                self.labels.insert(&*term, label);
+            }
+        }
+
+        *term = replacement;
+    }
+
+    fn copy(&mut self, from: *const Term<Name>, to: *const Term<Name>) {
+        match self.labels.get(&from).copied() {
+            Some(label) => self.labels.insert(to, label),
+            None => self.labels.remove(&to),
+        };
+    }
+
+    /// Drops the labels of the terms below `term` which nothing else holds on
+    /// to, and which are freed once it is replaced.
+    fn forget(&mut self, term: &Term<Name>) {
+        let children = match term {
+            Term::Delay(body) | Term::Lambda { body, .. } | Term::Force(body) => vec![body],
+            Term::Apply { function, argument } => vec![function, argument],
+            Term::Var(_) | Term::Constant(_) | Term::Error | Term::Builtin(_) => vec![],
+        };
+
+        for child in children {
+            if Rc::strong_count(child) == 1 {
+                self.labels.remove(&Rc::as_ptr(child));
+                self.forget(child);
+            }
         }
     }
 }
 
-fn builtin_force_reduce(term: &mut Term<Name>, builtin_map: &mut IndexMap<u8, ()>) {
+fn builtin_force_reduce(
+    term: &mut Term<Name>,
+    builtin_map: &mut IndexMap<u8, ()>,
+    tracker: &mut Tracker,
+) {
     match term {
         Term::Force(f) => {
-            let f = Rc::make_mut(f);
+            let f = tracker.make_mut(f);
 
             match f {
                 Term::Force(inner_f) => {
                     if let Term::Builtin(func) = inner_f.as_ref() {
                         builtin_map.insert(*func as u8, ());
-                        *term = Term::Var(
+                        let wrapped = Term::Var(
                             Name {
                                 text: format!("__{}_wrapped", func.nano_name()),
                                 unique: 0.into(),
                             }
                             .into(),
                         );
+                        tracker.replace(term, wrapped, None);
                         return;
                     }
                 }
                 Term::Builtin(func) => {
                     builtin_map.insert(*func as u8, ());
-                    *term = Term::Var(
+                    let wrapped = Term::Var(
                         Name {
                             text: format!("__{}_wrapped", func.nano_name()),
                             unique: 0.into(),
                         }
                         .into(),
                     );
+                    tracker.replace(term, wrapped, None);
 
                     return;
                 }
                 _ => {}
             }
-            builtin_force_reduce(f, builtin_map);
+            builtin_force_reduce(f, builtin_map, tracker);
         }
         Term::Delay(d) => {
-            let d = Rc::make_mut(d);
-            builtin_force_reduce(d, builtin_map);
+            let d = tracker.make_mut(d);
+            builtin_force_reduce(d, builtin_map, tracker);
         }
         Term::Lambda { body, .. } => {
-            let body = Rc::make_mut(body);
-            builtin_force_reduce(body, builtin_map);
+            let body = tracker.make_mut(body);
+            builtin_force_reduce(body, builtin_map, tracker);
         }
         Term::Apply { function, argument } => {
-            let func = Rc::make_mut(function);
-            builtin_force_reduce(func, builtin_map);
+            let func = tracker.make_mut(function);
+            builtin_force_reduce(func, builtin_map, tracker);
 
-            let arg = Rc::make_mut(argument);
-            builtin_force_reduce(arg, builtin_map);
+            let arg = tracker.make_mut(argument);
+            builtin_force_reduce(arg, builtin_map, tracker);
         }
         _ => {}
     }
 }
 
-fn force_delay_reduce(term: &mut Term<Name>) {
+fn force_delay_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
     match term {
         Term::Force(f) => {
-            let f = Rc::make_mut(f);
+            let f = tracker.make_mut(f);
 
             if let Term::Delay(body) = f {
-                *term = body.as_ref().clone();
-                force_delay_reduce(term);
+                let origin = Rc::as_ptr(body);
+                let body = body.as_ref().clone();
+                tracker.replace(term, body, Some(origin));
+                force_delay_reduce(term, tracker);
             } else {
-                force_delay_reduce(f);
+                force_delay_reduce(f, tracker);
             }
         }
         Term::Delay(d) => {
-            let d = Rc::make_mut(d);
-            force_delay_reduce(d);
+            let d = tracker.make_mut(d);
+            force_delay_reduce(d, tracker);
         }
         Term::Lambda { body, .. } => {
-            let body = Rc::make_mut(body);
-            force_delay_reduce(body);
+            let body = tracker.make_mut(body);
+            force_delay_reduce(body, tracker);
         }
         Term::Apply { function, argument } => {
-            let func = Rc::make_mut(function);
-            force_delay_reduce(func);
+            let func = tracker.make_mut(function);
+            force_delay_reduce(func, tracker);
 
-            let arg = Rc::make_mut(argument);
-            force_delay_reduce(arg);
+            let arg = tracker.make_mut(argument);
+            force_delay_reduce(arg, tracker);
         }
         _ => {}
     }
 }
 
-fn inline_direct_reduce(term: &mut Term<Name>) {
+fn inline_direct_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
     match term {
         Term::Delay(d) => {
-            let d = Rc::make_mut(d);
-            inline_direct_reduce(d);
+            let d = tracker.make_mut(d);
+            inline_direct_reduce(d, tracker);
         }
         Term::Lambda { body, .. } => {
-            let body = Rc::make_mut(body);
-            inline_direct_reduce(body);
+            let body = tracker.make_mut(body);
+            inline_direct_reduce(body, tracker);
         }
         Term::Apply { function, argument } => {
-            let func = Rc::make_mut(function);
-            let arg = Rc::make_mut(argument);
+            let func = tracker.make_mut(function);
+            let arg = tracker.make_mut(argument);
 
-            inline_direct_reduce(func);
-            inline_direct_reduce(arg);
+            inline_direct_reduce(func, tracker);
+            inline_direct_reduce(arg, tracker);
 
             let Term::Lambda { parameter_name, body } = func
             else{
@@ -199,33 +339,35 @@ fn inline_direct_reduce(term: &mut Term<Name>) {
             };
 
             if name.as_ref() == parameter_name.as_ref() {
-                *term = arg.clone();
+                let origin: *const Term<Name> = arg;
+                let arg = arg.clone();
+                tracker.replace(term, arg, Some(origin));
             }
         }
         Term::Force(f) => {
-            let f = Rc::make_mut(f);
-            inline_direct_reduce(f);
+            let f = tracker.make_mut(f);
+            inline_direct_reduce(f, tracker);
         }
         _ => {}
     }
 }
 
-fn inline_basic_reduce(term: &mut Term<Name>) {
+fn inline_basic_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
     match term {
         Term::Delay(d) => {
-            let d = Rc::make_mut(d);
-            inline_basic_reduce(d);
+            let d = tracker.make_mut(d);
+            inline_basic_reduce(d, tracker);
         }
         Term::Lambda { body, .. } => {
-            let body = Rc::make_mut(body);
-            inline_basic_reduce(body);
+            let body = tracker.make_mut(body);
+            inline_basic_reduce(body, tracker);
         }
         Term::Apply { function, argument } => {
-            let arg = Rc::make_mut(argument);
-            inline_basic_reduce(arg);
+            let arg = tracker.make_mut(argument);
+            inline_basic_reduce(arg, tracker);
 
-            let func = Rc::make_mut(function);
-            inline_basic_reduce(func);
+            let func = tracker.make_mut(function);
+            inline_basic_reduce(func, tracker);
 
             if let Term::Lambda {
                 parameter_name,
@@ -241,47 +383,53 @@ fn inline_basic_reduce(term: &mut Term<Name>) {
                     | Term::Lambda { .. }
                     | Term::Builtin(_)) = arg
                     {
-                        *term =
-                            substitute_term(body.as_ref(), parameter_name.clone(), replace_term);
+                        let origin = Rc::as_ptr(body);
+                        let substituted = substitute_term(
+                            body.as_ref(),
+                            parameter_name.clone(),
+                            replace_term,
+                            tracker,
+                        );
+                        tracker.replace(term, substituted, Some(origin));
                     }
                 }
             }
         }
         Term::Force(f) => {
-            let f = Rc::make_mut(f);
-            inline_basic_reduce(f);
+            let f = tracker.make_mut(f);
+            inline_basic_reduce(f, tracker);
         }
         _ => {}
     }
 }
 
-fn wrap_data_reduce(term: &mut Term<Name>) {
+fn wrap_data_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
     match term {
         Term::Delay(d) => {
-            wrap_data_reduce(Rc::make_mut(d));
+            wrap_data_reduce(tracker.make_mut(d), tracker);
         }
         Term::Lambda { body, .. } => {
-            wrap_data_reduce(Rc::make_mut(body));
+            wrap_data_reduce(tracker.make_mut(body), tracker);
         }
         Term::Apply { function, argument } => {
             let Term::Builtin(
                 first_action
             ) = function.as_ref()
             else {
-                wrap_data_reduce(Rc::make_mut(function));
-                wrap_data_reduce(Rc::make_mut(argument));
+                wrap_data_reduce(tracker.make_mut(function), tracker);
+                wrap_data_reduce(tracker.make_mut(argument), tracker);
                 return;
             };
 
-            let Term::Apply { function: inner_func, argument: inner_arg } = Rc::make_mut(argument)
+            let Term::Apply { function: inner_func, argument: inner_arg } = tracker.make_mut(argument)
             else {
-                wrap_data_reduce(Rc::make_mut(argument));
+                wrap_data_reduce(tracker.make_mut(argument), tracker);
                 return;
             };
 
             let Term::Builtin(second_action) = inner_func.as_ref()
             else {
-                wrap_data_reduce(Rc::make_mut(argument));
+                wrap_data_reduce(tracker.make_mut(argument), tracker);
                 return;
             };
 
@@ -296,21 +444,98 @@ fn wrap_data_reduce(term: &mut Term<Name>) {
                 | (DefaultFunction::UnMapData, DefaultFunction::MapData)
                 | (DefaultFunction::UnConstrData, DefaultFunction::ConstrData)
                 | (DefaultFunction::ConstrData, DefaultFunction::UnConstrData) => {
-                    wrap_data_reduce(Rc::make_mut(inner_arg));
-                    *term = inner_arg.as_ref().clone();
+                    wrap_data_reduce(tracker.make_mut(inner_arg), tracker);
+                    let origin = Rc::as_ptr(inner_arg);
+                    let inner_arg = inner_arg.as_ref().clone();
+                    tracker.replace(term, inner_arg, Some(origin));
                 }
                 _ => {
-                    wrap_data_reduce(Rc::make_mut(argument));
+                    wrap_data_reduce(tracker.make_mut(argument), tracker);
                 }
             }
         }
         Term::Force(f) => {
-            wrap_data_reduce(Rc::make_mut(f));
+            wrap_data_reduce(tracker.make_mut(f), tracker);
         }
         _ => {}
     }
 }
 
+/// Replaces builtins applied to constants by their result, from the innermost
+/// applications outwards. A folded term costs nothing to evaluate, but is only
+/// kept when its encoding is no larger than the application it replaces, since
+/// hashes and concatenations can be bigger than their arguments.
+fn constant_fold_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
+    match term {
+        Term::Delay(d) => {
+            constant_fold_reduce(tracker.make_mut(d), tracker);
+        }
+        Term::Lambda { body, .. } => {
+            constant_fold_reduce(tracker.make_mut(body), tracker);
+        }
+        Term::Apply { function, argument } => {
+            constant_fold_reduce(tracker.make_mut(function), tracker);
+            constant_fold_reduce(tracker.make_mut(argument), tracker);
+
+            let Some(value) = eval_saturated_builtin(term) else {
+                return;
+            };
+
+            let folded = Term::Constant(value);
+
+            if let (Ok(before), Ok(after)) = (flat::encode(&*term), flat::encode(&folded)) {
+                if after.len() <= before.len() {
+                    tracker.replace(term, folded, None);
+                }
+            }
+        }
+        Term::Force(f) => {
+            constant_fold_reduce(tracker.make_mut(f), tracker);
+        }
+        _ => {}
+    }
+}
+
+/// Runs a builtin applied to exactly as many constants as it takes. Builtins
+/// that must be forced are left alone, as are calls that fail at runtime so
+/// that the script still fails where it would have.
+fn eval_saturated_builtin(term: &Term<Name>) -> Option<Rc<Constant>> {
+    let mut args = vec![];
+    let mut function = term;
+
+    while let Term::Apply {
+        function: inner_func,
+        argument,
+    } = function
+    {
+        let Term::Constant(arg) = argument.as_ref() else {
+            return None;
+        };
+
+        args.push(arg.clone());
+        function = inner_func.as_ref();
+    }
+
+    let Term::Builtin(func) = function else {
+        return None;
+    };
+
+    if func.force_count() > 0 || func.arity() != args.len() {
+        return None;
+    }
+
+    let mut runtime = BuiltinRuntime::new(*func);
+
+    for arg in args.into_iter().rev() {
+        runtime.push(Value::Con(arg)).ok()?;
+    }
+
+    match runtime.call(&mut vec![]).ok()? {
+        Value::Con(constant) => Some(constant),
+        _ => None,
+    }
+}
+
 fn var_occurrences(term: &Term<Name>, search_for: Rc<Name>) -> usize {
     match term {
         Term::Var(name) => {
@@ -340,14 +565,14 @@ fn var_occurrences(term: &Term<Name>, search_for: Rc<Name>) -> usize {
     }
 }
 
-fn lambda_reduce(term: &mut Term<Name>) {
+fn lambda_reduce(term: &mut Term<Name>, tracker: &mut Tracker) {
     match term {
         Term::Apply { function, argument } => {
-            let func = Rc::make_mut(function);
-            lambda_reduce(func);
+            let func = tracker.make_mut(function);
+            lambda_reduce(func, tracker);
 
-            let arg = Rc::make_mut(argument);
-            lambda_reduce(arg);
+            let arg = tracker.make_mut(argument);
+            lambda_reduce(arg, tracker);
 
             if let Term::Lambda {
                 parameter_name,
@@ -355,28 +580,36 @@ fn lambda_reduce(term: &mut Term<Name>) {
             } = func
             {
                 if let replace_term @ (Term::Var(_) | Term::Constant(_) | Term::Builtin(_)) = arg {
-                    let body = Rc::make_mut(body);
-                    *term = substitute_term(body, parameter_name.clone(), replace_term);
+                    let body = tracker.make_mut(body);
+                    let origin: *const Term<Name> = body;
+                    let substituted =
+                        substitute_term(body, parameter_name.clone(), replace_term, tracker);
+                    tracker.replace(term, substituted, Some(origin));
                 }
             }
         }
         Term::Delay(d) => {
-            let d = Rc::make_mut(d);
-            lambda_reduce(d);
+            let d = tracker.make_mut(d);
+            lambda_reduce(d, tracker);
         }
         Term::Lambda { body, .. } => {
-            let body = Rc::make_mut(body);
-            lambda_reduce(body);
+            let body = tracker.make_mut(body);
+            lambda_reduce(body, tracker);
         }
         Term::Force(f) => {
-            let f = Rc::make_mut(f);
-            lambda_reduce(f);
+            let f = tracker.make_mut(f);
+            lambda_reduce(f, tracker);
         }
         _ => {}
     }
 }
 
-fn substitute_term(term: &Term<Name>, original: Rc<Name>, replace_with: &Term<Name>) -> Term<Name> {
+fn substitute_term(
+    term: &Term<Name>,
+    original: Rc<Name>,
+    replace_with: &Term<Name>,
+    tracker: &mut Tracker,
+) -> Term<Name> {
     match term {
         Term::Var(name) => {
             if name.as_ref() == original.as_ref() {
@@ -385,9 +618,7 @@ fn substitute_term(term: &Term<Name>, original: Rc<Name>, replace_with: &Term<Na
                 Term::Var(name.clone())
             }
         }
-        Term::Delay(body) => {
-            Term::Delay(substitute_term(body.as_ref(), original, replace_with).into())
-        }
+        Term::Delay(body) => Term::Delay(substitute_child(body, original, replace_with, tracker)),
         Term::Lambda {
             parameter_name,
             body,
@@ -395,7 +626,7 @@ fn substitute_term(term: &Term<Name>, original: Rc<Name>, replace_with: &Term<Na
             if parameter_name.as_ref() != original.as_ref() {
                 Term::Lambda {
                     parameter_name: parameter_name.clone(),
-                    body: Rc::new(substitute_term(body.as_ref(), original, replace_with)),
+                    body: substitute_child(body, original, replace_with, tracker),
                 }
             } else {
                 Term::Lambda {
@@ -405,15 +636,75 @@ fn substitute_term(term: &Term<Name>, original: Rc<Name>, replace_with: &Term<Na
             }
         }
         Term::Apply { function, argument } => Term::Apply {
-            function: Rc::new(substitute_term(
-                function.as_ref(),
-                original.clone(),
-                replace_with,
-            )),
-            argument: Rc::new(substitute_term(argument.as_ref(), original, replace_with)),
+            function: substitute_child(function, original.clone(), replace_with, tracker),
+            argument: substitute_child(argument, original, replace_with, tracker),
         },
-        Term::Force(x) => Term::Force(Rc::new(substitute_term(x.as_ref(), original, replace_with))),
+        Term::Force(x) => Term::Force(substitute_child(x, original, replace_with, tracker)),
         x => x.clone(),
     }
 }
 
+/// A substituted term is labelled like the term it was, or like the term it
+/// was replaced with.
+fn substitute_child(
+    term: &Rc<Term<Name>>,
+    original: Rc<Name>,
+    replace_with: &Term<Name>,
+    tracker: &mut Tracker,
+) -> Rc<Term<Name>> {
+    let substituted = substitute_term(term, original.clone(), replace_with, tracker);
+
+    let origin = match term.as_ref() {
+        Term::Var(name) if name.as_ref() == original.as_ref() => replace_with,
+        term => term,
+    };
+
+    tracker.new_rc(substituted, origin)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser;
+    use pretty_assertions::assert_eq;
+
+    fn constant_fold(src: &str) -> Term<Name> {
+        let program = parser::program(src).expect("Failed to parse program");
+
+        program.constant_fold_reduce(&mut Labels::new()).term
+    }
+
+    fn term(src: &str) -> Term<Name> {
+        parser::term(src).expect("Failed to parse term")
+    }
+
+    #[test]
+    fn constant_fold_builtin() {
+        let folded = constant_fold(
+            "(program 1.0.0 [(lam x x) [[(builtin addInteger) (con integer 1)] [[(builtin multiplyInteger) (con integer 2)] (con integer 3)]]])",
+        );
+
+        assert_eq!(folded, term("[(lam x x) (con integer 7)]"));
+    }
+
+    #[test]
+    fn constant_fold_keeps_larger_results() {
+        let src = "[(builtin sha2_256) (con bytestring #00)]";
+
+        assert_eq!(constant_fold(&format!("(program 1.0.0 {src})")), term(src));
+    }
+
+    #[test]
+    fn constant_fold_keeps_failing_builtins() {
+        let src = "[[(builtin divideInteger) (con integer 1)] (con integer 0)]";
+
+        assert_eq!(constant_fold(&format!("(program 1.0.0 {src})")), term(src));
+    }
+
+    #[test]
+    fn constant_fold_skips_partial_applications() {
+        let src = "[(builtin addInteger) (con integer 1)]";
+
+        assert_eq!(constant_fold(&format!("(program 1.0.0 {src})")), term(src));
+    }
+}